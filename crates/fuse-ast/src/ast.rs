@@ -38,8 +38,14 @@ pub enum Statement {
     EnumDeclaration(Box<EnumDeclaration>),
     /// A struct declaration using struct keyword.
     StructDeclaration(Box<StructDeclaration>),
-    /// A struct declaration using struct keyword.
+    /// An impl statement using impl keyword.
     ImplStatement(Box<ImplStatement>),
+    /// A type alias declaration using type keyword.
+    TypeAliasDeclaration(Box<TypeAliasDeclaration>),
+    /// A tagged union declaration using union keyword.
+    UnionDeclaration(Box<UnionDeclaration>),
+    /// A declaration of a host API using extern keyword.
+    ExternDeclaration(Box<ExternDeclaration>),
 }
 
 #[serializable]
@@ -58,7 +64,7 @@ pub struct VariableDeclaration {
 }
 
 #[serializable]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VariableDeclarationKind {
     Let,
     Const,
@@ -91,8 +97,48 @@ pub struct BindingIdentifier {
 #[serializable]
 #[derive(Debug, PartialEq)]
 pub struct TypeAnnotation {
-    // TODO: at the moment we treat type annotation like identifiers.
+    pub span: Span,
+    pub kind: TypeAnnotationKind,
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub enum TypeAnnotationKind {
+    /// A named type, e.g. `number` or `Point`.
+    Identifier(Identifier),
+    /// A tuple type, e.g. `(number, string)`.
+    Tuple(Vec<TypeAnnotation>),
+    /// An array type, e.g. `[number]`.
+    Array(Box<TypeAnnotation>),
+    /// A function type, e.g. `fn(number, number) -> number`.
+    Function(Box<FunctionTypeAnnotation>),
+    /// An optional type, e.g. `number?`.
+    Optional(Box<TypeAnnotation>),
+    /// A generic type with its type arguments, e.g. `Stack<number>`.
+    Generic(Box<GenericTypeAnnotation>),
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct FunctionTypeAnnotation {
+    pub params: Vec<TypeAnnotation>,
+    pub return_type: Option<TypeAnnotation>,
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct GenericTypeAnnotation {
     pub identifier: Identifier,
+    pub arguments: Vec<TypeAnnotation>,
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct TypeParameter {
+    pub span: Span,
+    pub identifier: Identifier,
+    /// Traits that the type arguments should implement, e.g. `T: Display + Eq`.
+    pub bounds: Vec<TypeAnnotation>,
 }
 
 #[serializable]
@@ -100,7 +146,7 @@ pub struct TypeAnnotation {
 pub struct Atom(pub Rc<str>);
 
 impl Atom {
-    pub fn as_str<'a>(&'a self) -> &'a str {
+    pub fn as_str(&self) -> &str {
         &self.0
     }
 }
@@ -108,12 +154,14 @@ impl Atom {
 #[serializable]
 #[derive(Debug, PartialEq)]
 pub enum Expression {
+    NilLiteral(Box<NilLiteral>),
     NumberLiteral(Box<NumberLiteral>),
     StringLiteral(Box<StringLiteral>),
     BooleanLiteral(Box<BooleanLiteral>),
     Identifier(Box<Identifier>),
     Function(Box<Function>),
     If(Box<If>),
+    Match(Box<Match>),
     UnaryOperator(Box<UnaryOperator>),
     BinaryOperator(Box<BinaryOperator>),
     ArrayExpression(Box<ArrayExpression>),
@@ -129,12 +177,14 @@ impl Expression {
     pub fn span(&self) -> Span {
         use Expression::*;
         match self {
+            NilLiteral(expr) => expr.span,
             NumberLiteral(expr) => expr.span,
             StringLiteral(expr) => expr.span,
             BooleanLiteral(expr) => expr.span,
             Identifier(expr) => expr.span,
             Function(expr) => expr.span,
             If(expr) => expr.span,
+            Match(expr) => expr.span,
             UnaryOperator(expr) => expr.span(),
             BinaryOperator(expr) => expr.span(),
             ArrayExpression(expr) => expr.span,
@@ -148,6 +198,12 @@ impl Expression {
     }
 }
 
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct NilLiteral {
+    pub span: Span,
+}
+
 #[serializable]
 #[derive(Debug, PartialEq)]
 pub struct BooleanLiteral {
@@ -233,6 +289,7 @@ pub struct Function {
 pub struct FunctionSignature {
     pub span: Span,
     pub identifier: Option<Identifier>,
+    pub type_parameters: Vec<TypeParameter>,
     pub params: FunctionParameters,
     pub return_type: Option<TypeAnnotation>,
 }
@@ -283,6 +340,56 @@ pub enum Else {
     Block(Box<Block>),
 }
 
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct Match {
+    pub span: Span,
+    pub expression: Expression,
+    pub arms: Vec<MatchArm>,
+    pub r#else: Option<Block>,
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct MatchArm {
+    pub span: Span,
+    pub pattern: Pattern,
+    pub body: Block,
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub enum Pattern {
+    /// The `_` pattern, matches anything without binding it.
+    Wildcard(Span),
+    /// A literal pattern, e.g. `1`, `"str"` or `true`.
+    Literal(Expression),
+    /// Binds the matched value to a new identifier.
+    Binding(BindingIdentifier),
+    /// Matches a variant of an enum or union, e.g. `Shape.Circle(r)`.
+    Variant(VariantPattern),
+}
+
+impl GetSpan for Pattern {
+    fn span(&self) -> Span {
+        match self {
+            Self::Wildcard(span) => *span,
+            Self::Literal(expr) => expr.span(),
+            Self::Binding(binding) => binding.span,
+            Self::Variant(variant) => variant.span,
+        }
+    }
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct VariantPattern {
+    pub span: Span,
+    pub target: Identifier,
+    pub variant: Identifier,
+    pub fields: Vec<Pattern>,
+}
+
 #[serializable]
 #[derive(Debug, PartialEq)]
 pub struct UnaryOperator {
@@ -333,6 +440,8 @@ impl GetSpan for BinaryOperator {
 #[derive(Debug, PartialEq)]
 pub enum BinaryOperatorKind {
     Assignment(Span),
+    /// The nil-coalescing operator `??`.
+    Coalesce(Span),
     LogicalOr(Span),
     LogicalAnd(Span),
     BitwiseOr(Span),
@@ -359,6 +468,7 @@ impl GetSpan for BinaryOperatorKind {
     fn span(&self) -> Span {
         let span = match self {
             Self::Assignment(span) => span,
+            Self::Coalesce(span) => span,
             Self::LogicalOr(span) => span,
             Self::LogicalAnd(span) => span,
             Self::BitwiseOr(span) => span,
@@ -447,6 +557,7 @@ pub struct EnumVariant {
 pub struct StructDeclaration {
     pub span: Span,
     pub identifier: Identifier,
+    pub type_parameters: Vec<TypeParameter>,
     pub fields: Vec<StructField>,
 }
 
@@ -458,6 +569,77 @@ pub struct StructField {
     pub type_annotation: TypeAnnotation,
 }
 
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct TypeAliasDeclaration {
+    pub span: Span,
+    pub identifier: Identifier,
+    pub annotation: TypeAnnotation,
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct UnionDeclaration {
+    pub span: Span,
+    pub identifier: Identifier,
+    pub variants: Vec<UnionVariant>,
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct UnionVariant {
+    pub span: Span,
+    pub identifier: Identifier,
+    pub fields: Vec<TypeAnnotation>,
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct ExternDeclaration {
+    pub span: Span,
+    pub item: ExternItem,
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub enum ExternItem {
+    /// A foreign function, e.g. `extern fn print(...values: unknown)`.
+    Function(FunctionSignature),
+    /// A foreign value, e.g. `extern _VERSION: string`.
+    Global(ExternGlobal),
+    /// A foreign table and its members, e.g. `extern math pi: number end`.
+    Table(ExternTable),
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct ExternGlobal {
+    pub span: Span,
+    pub identifier: Identifier,
+    pub type_annotation: TypeAnnotation,
+}
+
+#[serializable]
+#[derive(Debug, PartialEq)]
+pub struct ExternTable {
+    pub span: Span,
+    pub identifier: Identifier,
+    pub items: Vec<ExternItem>,
+}
+
+impl ExternItem {
+    pub fn identifier(&self) -> &Identifier {
+        match self {
+            Self::Function(signature) => signature
+                .identifier
+                .as_ref()
+                .expect("All extern functions need an identifier."),
+            Self::Global(global) => &global.identifier,
+            Self::Table(table) => &table.identifier,
+        }
+    }
+}
+
 #[serializable]
 #[derive(Debug, PartialEq)]
 pub enum VisibilityModifier {
@@ -486,6 +668,8 @@ pub struct MemberExpression {
     pub span: Span,
     pub lhs: Box<MemberExpressionLHS>,
     pub rhs: Box<MemberExpressionRHS>,
+    /// Optional chaining using `?.`, Evaluates to nil if the `lhs` is nil.
+    pub optional: bool,
 }
 
 #[serializable]
@@ -497,6 +681,17 @@ pub enum MemberExpressionLHS {
     Call(CallExpression),
 }
 
+impl GetSpan for MemberExpressionLHS {
+    fn span(&self) -> Span {
+        match self {
+            Self::Identifier(ident) => ident.span,
+            Self::Expression(expr) => expr.span(),
+            Self::Member(member) => member.span,
+            Self::Call(call) => call.span,
+        }
+    }
+}
+
 impl From<MemberExpressionRHS> for MemberExpressionLHS {
     fn from(value: MemberExpressionRHS) -> Self {
         match value {
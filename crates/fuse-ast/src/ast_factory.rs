@@ -3,16 +3,13 @@ use std::rc::Rc;
 use crate::ast::*;
 use fuse_common::Span;
 
+#[derive(Default)]
 pub struct AstFactory();
 impl AstFactory {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn default() -> Self {
-        Self()
-    }
-
     pub fn chunk(&self, span: Span, body: Block) -> Chunk {
         Chunk { span, body }
     }
@@ -51,6 +48,18 @@ impl AstFactory {
         Statement::ImplStatement(Box::from(r#impl))
     }
 
+    pub fn type_alias_declaration_statement(&self, decl: TypeAliasDeclaration) -> Statement {
+        Statement::TypeAliasDeclaration(Box::from(decl))
+    }
+
+    pub fn union_declaration_statement(&self, decl: UnionDeclaration) -> Statement {
+        Statement::UnionDeclaration(Box::from(decl))
+    }
+
+    pub fn extern_declaration_statement(&self, decl: ExternDeclaration) -> Statement {
+        Statement::ExternDeclaration(Box::from(decl))
+    }
+
     pub fn expression_statement(&self, expr: Expression) -> Statement {
         Statement::Expression(Box::from(expr))
     }
@@ -121,6 +130,10 @@ impl AstFactory {
         Expression::BooleanLiteral(Box::from(literal))
     }
 
+    pub fn nil_literal_expression(&self, literal: NilLiteral) -> Expression {
+        Expression::NilLiteral(Box::from(literal))
+    }
+
     pub fn number_literal_expression(&self, literal: NumberLiteral) -> Expression {
         Expression::NumberLiteral(Box::from(literal))
     }
@@ -141,6 +154,10 @@ impl AstFactory {
         Expression::If(Box::from(expr))
     }
 
+    pub fn match_expression(&self, expr: Match) -> Expression {
+        Expression::Match(Box::from(expr))
+    }
+
     pub fn unary_operator_expression(&self, op: UnaryOperator) -> Expression {
         Expression::UnaryOperator(Box::from(op))
     }
@@ -187,11 +204,13 @@ impl AstFactory {
         span: Span,
         lhs: MemberExpressionLHS,
         rhs: MemberExpressionRHS,
+        optional: bool,
     ) -> Expression {
         Expression::MemberExpression(Box::from(MemberExpression {
             span,
             lhs: Box::from(lhs),
             rhs: Box::from(rhs),
+            optional,
         }))
     }
 
@@ -9,21 +9,28 @@ pub enum AstNode<'a> {
     // Statement related
     EmptyStatement(&'a EmptyStatement),
     ImplStatement(&'a ImplStatement),
+    ImplMethod(&'a ImplMethod),
     EnumDeclaration(&'a EnumDeclaration),
     StructDeclaration(&'a StructDeclaration),
     FunctionDeclaration(&'a Function),
     VariableDeclaration(&'a VariableDeclaration),
+    TypeAliasDeclaration(&'a TypeAliasDeclaration),
+    UnionDeclaration(&'a UnionDeclaration),
+    ExternDeclaration(&'a ExternDeclaration),
 
     // expression related
     FunctionExpression(&'a Function),
     CallExpression(&'a CallExpression),
     ArrayExpression(&'a ArrayExpression),
+    TupleExpression(&'a TupleExpression),
     ParenthesizedExpression(&'a ParenthesizedExpression),
     ConstructionExpression(&'a ConstructionExpression),
     TableConstructionExpression(&'a ConstructionExpression),
     StructConstructionExpression(&'a StructConstructionExpression),
     If(&'a If),
     Else(&'a Else),
+    Match(&'a Match),
+    MatchArm(&'a MatchArm),
 
     // function inner nodes
     FunctionSignature(&'a FunctionSignature),
@@ -33,9 +40,12 @@ pub enum AstNode<'a> {
 
     // Misc
     EnumVariant(&'a EnumVariant),
+    UnionVariant(&'a UnionVariant),
     StructField(&'a StructField),
+    ExternItem(&'a ExternItem),
     ConstructionField(&'a ConstructionField),
     ArrayExpressionElement(&'a ArrayExpressionElement),
+    TupleExpressionElement(&'a TupleExpressionElement),
 
     VisibilityModifier(&'a VisibilityModifier),
 
@@ -43,6 +53,7 @@ pub enum AstNode<'a> {
     NumberLiteral(&'a NumberLiteral),
     StringLiteral(&'a StringLiteral),
     BooleanLiteral(&'a BooleanLiteral),
+    NilLiteral(&'a NilLiteral),
 
     Identifier(&'a Identifier),
     BindingPattern(&'a BindingPattern),
@@ -59,4 +70,9 @@ pub enum AstNode<'a> {
     MemberExpressionRHS(&'a MemberExpressionRHS),
 
     TypeAnnotation(&'a TypeAnnotation),
+    TypeParameter(&'a TypeParameter),
+
+    // patterns
+    Pattern(&'a Pattern),
+    VariantPattern(&'a VariantPattern),
 }
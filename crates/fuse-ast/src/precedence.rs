@@ -7,6 +7,7 @@ pub enum Precedence {
     /// used as 0 value of enum.
     Expression,
     Assignment,
+    Coalesce,
     LogicalOr,
     LogicalAnd,
     BitwiseOr,
@@ -29,6 +30,7 @@ impl Precedence {
     pub fn is_left_associative(&self) -> bool {
         matches! {
             self,
+            | Self::Coalesce
             | Self::LogicalOr
             | Self::LogicalAnd
             | Self::BitwiseOr
@@ -0,0 +1,108 @@
+use std::collections::BTreeSet;
+
+use fuse_common::Span;
+use fuse_ir::{Constant, InstructionKind, Module, Operand, Rvalue};
+
+use crate::{
+    minify::strip_whitespace,
+    printer::{string, Printer},
+    runtime::{self, Runtime},
+    CodegenOptions,
+};
+
+/// Name of the function loading the modules of a bundle.
+pub(crate) const REQUIRE_NAME: &str = "_fuse_require";
+/// Name of the table holding the loader of each module.
+pub(crate) const LOADERS_NAME: &str = "_fuse_loaders";
+/// Name of the table holding the loaded modules.
+pub(crate) const MODULES_NAME: &str = "_fuse_modules";
+
+/// Load a module once like `require`, Modules returning nothing are stored as `true`.
+const REQUIRE: &str = r#"local _fuse_loaders = {}
+local _fuse_modules = {}
+local function _fuse_require(name)
+    local module = _fuse_modules[name]
+    if module == nil then
+        module = _fuse_loaders[name](name)
+        if module == nil then
+            module = true
+        end
+        _fuse_modules[name] = module
+    end
+    return module
+end
+"#;
+
+/// Module of a bundle, `name` is the name other modules import it with.
+pub struct BundleModule<'a> {
+    pub name: &'a str,
+    pub module: &'a Module,
+}
+
+/// Import of a module, e.g. `require("utils")`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    pub name: String,
+    pub span: Span,
+}
+
+/// Imports of a module, They are the calls to the global `require` with a constant name.
+pub fn imports(module: &Module) -> Vec<Import> {
+    let mut imports = Vec::new();
+    for function in &module.functions {
+        for block in &function.blocks {
+            for instruction in &block.instructions {
+                let (InstructionKind::Call(callee, args)
+                | InstructionKind::Assign(_, Rvalue::Call(callee, args))) = &instruction.kind
+                else {
+                    continue;
+                };
+                if let (Operand::Global(callee), [Operand::Constant(Constant::String(name))]) =
+                    (callee, args.as_slice())
+                {
+                    if callee.as_str() == "require" {
+                        imports.push(Import {
+                            name: name.as_str().to_string(),
+                            span: instruction.span,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    imports
+}
+
+/// Generate a single Lua chunk running the first module, Each module is wrapped in a loader
+/// function and their imports load the other modules of the bundle instead of calling
+/// `require`. The runtime helpers used by the modules are printed once at the top, Whatever
+/// the `options.runtime` is.
+pub fn generate_bundle(modules: &[BundleModule], options: CodegenOptions) -> String {
+    let options = CodegenOptions {
+        runtime: Runtime::Inline,
+        ..options
+    };
+    let mut helpers = BTreeSet::new();
+    let mut loaders = String::new();
+    for BundleModule { name, module } in modules {
+        let (body, used) = Printer::new(module, options).print_bundled();
+        helpers.extend(used);
+        loaders.push_str(&format!(
+            "{LOADERS_NAME}[{}] = function(...)\n",
+            string(name)
+        ));
+        loaders.push_str(&body);
+        loaders.push_str("end\n");
+    }
+
+    let mut out = runtime::prelude(Runtime::Inline, helpers);
+    out.push_str(REQUIRE);
+    out.push_str(&loaders);
+    if let Some(entry) = modules.first() {
+        out.push_str(&format!("return {REQUIRE_NAME}({})\n", string(entry.name)));
+    }
+    if options.minify {
+        out = strip_whitespace(&out);
+    }
+    out
+}
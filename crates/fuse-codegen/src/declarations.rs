@@ -0,0 +1,173 @@
+use std::collections::{HashMap, HashSet};
+
+use fuse_ir::{Function, LocalId};
+
+use crate::{
+    structure::{Expr, Stmt, StmtKind, Target},
+    usage::Usage,
+};
+
+/// Position of a statement, The index of its list in preorder and its index in that list.
+pub(crate) type Position = (usize, usize);
+
+/// Where the locals of a function are declared. Each local is declared in the innermost
+/// list containing all of its mentions, Before the first statement mentioning it.
+#[derive(Default)]
+pub(crate) struct Declarations {
+    /// Assignments declaring their local, e.g. `local x = 1`.
+    pub(crate) inline: HashSet<Position>,
+    /// Locals declared without a value before the statement, e.g. `local x`.
+    pub(crate) before: HashMap<Position, Vec<LocalId>>,
+}
+
+impl Declarations {
+    pub(crate) fn new(function: &Function, usage: &Usage, statements: &[Stmt]) -> Self {
+        let owned: HashSet<LocalId> = function.locals.iter().copied().collect();
+        let mut collector = Mentions {
+            usage,
+            owned: &owned,
+            path: Vec::new(),
+            lists: 0,
+            mentions: HashMap::new(),
+        };
+        collector.list(statements);
+
+        let mut declarations = Self::default();
+        // Declare locals in the order of the function, So the output is deterministic.
+        for local in &function.locals {
+            let Some(mentions) = collector.mentions.get(local) else {
+                continue;
+            };
+            let (first, assigned) = &mentions[0];
+            let depth = mentions[1..]
+                .iter()
+                .map(|(path, _)| common_depth(first, path))
+                .min()
+                .unwrap_or(first.len() - 1);
+            let position = first[depth];
+            if *assigned && depth == first.len() - 1 {
+                declarations.inline.insert(position);
+            } else {
+                declarations
+                    .before
+                    .entry(position)
+                    .or_default()
+                    .push(*local);
+            }
+        }
+        declarations
+    }
+}
+
+/// Depth of the innermost list containing both of the paths.
+fn common_depth(lhs: &[Position], rhs: &[Position]) -> usize {
+    let mut depth = 0;
+    for (index, (lhs, rhs)) in lhs.iter().zip(rhs).enumerate() {
+        if lhs.0 != rhs.0 {
+            break;
+        }
+        depth = index;
+        if lhs.1 != rhs.1 {
+            break;
+        }
+    }
+    depth
+}
+
+/// Paths of the statements mentioning each local, And whether the mention is an assignment declaring it.
+struct Mentions<'a> {
+    usage: &'a Usage,
+    owned: &'a HashSet<LocalId>,
+    path: Vec<Position>,
+    lists: usize,
+    mentions: HashMap<LocalId, Vec<(Vec<Position>, bool)>>,
+}
+
+impl<'a> Mentions<'a> {
+    fn list(&mut self, statements: &[Stmt]) {
+        let list = self.lists;
+        self.lists += 1;
+        for (index, statement) in statements.iter().enumerate() {
+            self.path.push((list, index));
+            self.statement(statement);
+            self.path.pop();
+        }
+    }
+
+    fn statement(&mut self, statement: &Stmt) {
+        match &statement.kind {
+            StmtKind::Assign(target, value) => match target {
+                // Functions can refer to themselves, e.g. `local function f() f() end`.
+                Target::Local(local) if matches!(value, Expr::Function(_)) => {
+                    self.mention(*local, true);
+                    self.expr(value);
+                }
+                Target::Local(local) => {
+                    self.expr(value);
+                    self.mention(*local, true);
+                }
+                Target::Global(_) => self.expr(value),
+                Target::Index(table, key) => {
+                    self.expr(table);
+                    self.expr(key);
+                    self.expr(value);
+                }
+            },
+            StmtKind::Call(expr) | StmtKind::Return(expr) => self.expr(expr),
+            StmtKind::Spread { target, source, .. } => {
+                self.expr(target);
+                self.expr(source);
+            }
+            StmtKind::If {
+                condition,
+                then,
+                r#else,
+            } => {
+                self.expr(condition);
+                self.list(then);
+                self.list(r#else);
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Local(local) => self.mention(*local, false),
+            Expr::Global(_) | Expr::Constant(_) | Expr::VarArgs => {}
+            Expr::Function(function) => {
+                let mentions: Vec<LocalId> =
+                    self.usage.mentions(*function).iter().copied().collect();
+                for local in mentions {
+                    self.mention(local, false);
+                }
+            }
+            Expr::Unary(_, value) | Expr::Marked(_, value) => self.expr(value),
+            Expr::Binary(_, lhs, rhs) | Expr::Index(lhs, rhs) => {
+                self.expr(lhs);
+                self.expr(rhs);
+            }
+            Expr::Concat(values) => values.iter().for_each(|value| self.expr(value)),
+            Expr::Call(callee, args) => {
+                self.expr(callee);
+                args.iter().for_each(|arg| self.expr(arg));
+            }
+            Expr::Table(entries) => {
+                for (key, value) in entries {
+                    if let Some(key) = key {
+                        self.expr(key);
+                    }
+                    self.expr(value);
+                }
+            }
+        }
+    }
+
+    fn mention(&mut self, local: LocalId, assigned: bool) {
+        if self.owned.contains(&local) {
+            self.mentions
+                .entry(local)
+                .or_default()
+                .push((self.path.clone(), assigned));
+        }
+    }
+}
@@ -1 +1,84 @@
-// Not implemented yet!
+mod bundle;
+mod declarations;
+mod minify;
+mod printer;
+mod runtime;
+mod source_map;
+mod structure;
+mod usage;
+
+use fuse_ir::Module;
+
+use printer::Printer;
+
+pub use bundle::{generate_bundle, imports, BundleModule, Import};
+pub use runtime::{runtime_module, Runtime, RUNTIME_MODULE, RUNTIME_VERSION};
+pub use source_map::{Mapping, Position, SourceMap, SourceMapError};
+
+/// Options of the code generation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CodegenOptions {
+    /// How the chunk gets the runtime helpers it uses.
+    pub runtime: Runtime,
+    /// Shorten the names of the locals and remove the optional whitespace.
+    pub minify: bool,
+    pub target: LuaTarget,
+}
+
+/// Lua dialect of the generated code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LuaTarget {
+    Lua53,
+    #[default]
+    Lua54,
+    /// LuaJIT, It lacks the floor division and the bitwise operators of Lua 5.3. They are
+    /// generated as calls to `math.floor` and to its `bit` library.
+    LuaJit,
+}
+
+impl LuaTarget {
+    pub const ALL: [LuaTarget; 3] = [LuaTarget::Lua53, LuaTarget::Lua54, LuaTarget::LuaJit];
+
+    /// Name of the target, e.g. `lua54`.
+    pub fn name(self) -> &'static str {
+        match self {
+            LuaTarget::Lua53 => "lua53",
+            LuaTarget::Lua54 => "lua54",
+            LuaTarget::LuaJit => "luajit",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|target| target.name() == name)
+    }
+}
+
+/// Generate the Lua code of a lowered module.
+pub fn generate(module: &Module) -> String {
+    generate_with_options(module, CodegenOptions::default())
+}
+
+/// Generate the Lua code of a lowered module with the given options.
+pub fn generate_with_options(module: &Module, options: CodegenOptions) -> String {
+    Printer::new(module, options).print()
+}
+
+/// Generate the Lua code of a lowered module along with its source map. `source` is the code
+/// the module was lowered from, `source_name` and `file` are the names of the source and the
+/// generated files.
+pub fn generate_with_source_map(
+    module: &Module,
+    options: CodegenOptions,
+    source: &str,
+    source_name: &str,
+    file: &str,
+) -> (String, SourceMap) {
+    let (text, marks) = Printer::new(module, options).print_marked();
+    let (code, mappings) = marks.strip(&text, source);
+    let map = SourceMap {
+        file: file.to_string(),
+        sources: vec![source_name.to_string()],
+        mappings,
+    };
+    (code, map)
+}
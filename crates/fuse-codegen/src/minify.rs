@@ -0,0 +1,88 @@
+//! Minification of the generated code, Locals get short names while printing and the
+//! optional whitespace is removed from the printed text.
+
+use crate::source_map::{MARK_END, MARK_START};
+
+const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_";
+
+/// The `index`th shortest identifier, e.g. `a`, `b`, ..., `Z`, `aa`, `ab`.
+pub(crate) fn short_name(mut index: usize) -> String {
+    let mut name = vec![FIRST[index % FIRST.len()]];
+    index /= FIRST.len();
+    while index > 0 {
+        index -= 1;
+        name.push(REST[index % REST.len()]);
+        index /= REST.len();
+    }
+    String::from_utf8(name).unwrap()
+}
+
+/// Remove the indentation and the spaces that don't separate tokens, Statements stay on their
+/// own lines since Lua tracebacks only report lines. The marks of the source maps are kept
+/// in place.
+pub(crate) fn strip_whitespace(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    // Last character of the last token on the line, Marks don't count as tokens.
+    let mut last: Option<char> = None;
+    let mut space = false;
+    let mut newline = false;
+    // Marks are moved after the whitespace, So they point at the token following them.
+    let mut marks = String::new();
+    let mut chars = text.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '\n' => newline = true,
+            char if char.is_whitespace() => space = true,
+            MARK_START => {
+                marks.push(char);
+                for char in chars.by_ref() {
+                    marks.push(char);
+                    if char == MARK_END {
+                        break;
+                    }
+                }
+            }
+            char => {
+                if newline && !out.is_empty() {
+                    out.push('\n');
+                } else if space && last.is_some_and(|last| needs_space(last, char)) {
+                    out.push(' ');
+                }
+                newline = false;
+                space = false;
+                out.push_str(&marks);
+                marks.clear();
+                out.push(char);
+                last = Some(char);
+                if char == '"' || char == '\'' {
+                    // Copy the string as is, Escaped quotes don't end it.
+                    while let Some(next) = chars.next() {
+                        out.push(next);
+                        if next == '\\' {
+                            out.extend(chars.next());
+                        } else if next == char {
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    out.push_str(&marks);
+    if newline {
+        out.push('\n');
+    }
+    out
+}
+
+/// Returns true if two tokens ending and starting with these characters would merge without
+/// a space between them, e.g. `local x`, `- -1`, `1 ..`.
+fn needs_space(last: char, next: char) -> bool {
+    let is_word = |char: char| char.is_ascii_alphanumeric() || char == '_';
+    (is_word(last) && is_word(next))
+        || (last == '-' && next == '-')
+        || (last == '.' && (next == '.' || next.is_ascii_digit()))
+        || (last.is_ascii_digit() && next == '.')
+        || (last == '[' && (next == '[' || next == '='))
+}
@@ -0,0 +1,678 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use fuse_common::Span;
+use fuse_ir::{
+    BinaryOp, Constant, FunctionId, LocalId, LocalKind, Module, Number, SpreadKind, UnaryOp,
+};
+
+use crate::{
+    bundle,
+    declarations::Declarations,
+    minify::{short_name, strip_whitespace},
+    runtime::{self, Helper},
+    source_map::Marks,
+    structure::{structure, Expr, Stmt, StmtKind, Target},
+    usage::Usage,
+    CodegenOptions, LuaTarget,
+};
+
+const INDENT: &str = "    ";
+
+const KEYWORDS: [&str; 22] = [
+    "and", "break", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in",
+    "local", "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+/// Globals and names used by the generated code itself, The names of the helpers are reserved too.
+const RESERVED: [&str; 9] = [
+    "bit",
+    "math",
+    "table",
+    "tostring",
+    "type",
+    runtime::MODULE_NAME,
+    bundle::REQUIRE_NAME,
+    bundle::LOADERS_NAME,
+    bundle::MODULES_NAME,
+];
+
+/// Concatenations with more parts use `table.concat`, Lua parses `..` chains recursively and
+/// limits their depth.
+const CONCAT_LIMIT: usize = 64;
+
+/// Precedence of unary operators, Only `^` binds tighter.
+const UNARY_PRECEDENCE: u8 = 11;
+/// Precedence of atoms and prefix expressions.
+const ATOM_PRECEDENCE: u8 = 13;
+
+/// Printer, It writes the Lua code of a module.
+pub(crate) struct Printer<'a> {
+    module: &'a Module,
+    options: CodegenOptions,
+    usage: Usage,
+    out: String,
+    indent: usize,
+    names: HashMap<LocalId, String>,
+    taken: HashSet<String>,
+    temporaries: usize,
+    /// Spans of the marked statements and values, Only collected when printing a source map.
+    marks: Option<Marks>,
+    /// Whether the module is part of a bundle, Its imports load the bundled modules.
+    bundled: bool,
+    /// Functions whose locals got their minified names.
+    minified: HashSet<FunctionId>,
+}
+
+/// Declarations of the function being printed and the number of its lists printed so far.
+struct Scope {
+    declarations: Declarations,
+    lists: usize,
+}
+
+impl<'a> Printer<'a> {
+    pub(crate) fn new(module: &'a Module, options: CodegenOptions) -> Self {
+        let usage = Usage::new(module);
+        let helpers = Helper::ALL.map(Helper::name);
+        let mut taken: HashSet<String> = KEYWORDS
+            .iter()
+            .chain(RESERVED.iter())
+            .chain(helpers.iter())
+            .map(|name| name.to_string())
+            .collect();
+        taken.extend(usage.globals.iter().map(|name| name.as_str().to_string()));
+        Self {
+            module,
+            options,
+            usage,
+            out: String::new(),
+            indent: 0,
+            names: HashMap::new(),
+            taken,
+            temporaries: 0,
+            marks: None,
+            bundled: false,
+            minified: HashSet::new(),
+        }
+    }
+
+    pub(crate) fn print(mut self) -> String {
+        self.module();
+        self.out
+    }
+
+    /// Print the module with a mark at the start of each statement and inlined value, See
+    /// `Marks::strip`.
+    pub(crate) fn print_marked(mut self) -> (String, Marks) {
+        self.marks = Some(Marks::default());
+        self.module();
+        (self.out, self.marks.unwrap())
+    }
+
+    /// Print the module as the body of its loader in a bundle, See `generate_bundle`. It returns
+    /// the helpers used by the module instead of printing them.
+    pub(crate) fn print_bundled(mut self) -> (String, BTreeSet<Helper>) {
+        self.bundled = true;
+        self.indent = 1;
+        self.function_body(Module::MAIN);
+        (self.out, self.usage.helpers)
+    }
+
+    fn module(&mut self) {
+        let prelude = runtime::prelude(self.options.runtime, self.usage.helpers.iter().copied());
+        self.out.push_str(&prelude);
+        self.function_body(Module::MAIN);
+        if self.options.minify {
+            self.out = strip_whitespace(&self.out);
+        }
+    }
+
+    /// Mark to print at the start of a statement or an inlined value, Empty unless marks
+    /// are collected.
+    fn mark(&mut self, span: Span) -> String {
+        match &mut self.marks {
+            Some(marks) => marks.push(span),
+            None => String::new(),
+        }
+    }
+
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    /// Unique name of a local, Names are allocated the first time they get printed.
+    fn name(&mut self, local: LocalId) -> String {
+        if let Some(name) = self.names.get(&local) {
+            return name.clone();
+        }
+        if self.options.minify {
+            let owner = self.usage.owner(local).expect("Local without a function.");
+            self.minify_names(owner);
+            return self.names[&local].clone();
+        }
+        let info = self.module.local(local);
+        let name = match info.kind {
+            LocalKind::Temporary => loop {
+                self.temporaries += 1;
+                let name = format!("_t{}", self.temporaries);
+                if !self.taken.contains(&name) {
+                    break name;
+                }
+            },
+            LocalKind::Variable | LocalKind::Parameter => {
+                let base = info.name.as_str();
+                let mut name = base.to_string();
+                let mut suffix = 0;
+                while self.taken.contains(&name) {
+                    suffix += 1;
+                    name = format!("{base}_{suffix}");
+                }
+                name
+            }
+        };
+        self.taken.insert(name.clone());
+        self.names.insert(local, name.clone());
+        name
+    }
+
+    /// Give the shortest available names to the locals of a function, Names are reused by
+    /// the other functions unless they mention the local, e.g. a closure capturing it.
+    fn minify_names(&mut self, id: FunctionId) {
+        if !self.minified.insert(id) {
+            return;
+        }
+        let function = self.module.function(id);
+        let mut own: Vec<LocalId> = function
+            .params
+            .iter()
+            .chain(&function.rest)
+            .chain(&function.locals)
+            .copied()
+            .collect();
+        // Temporaries are often inlined, The shortest names go to the other locals.
+        own.sort_by_key(|local| self.module.local(*local).kind == LocalKind::Temporary);
+        // The mentions include the locals of the nested functions, Which come after their
+        // parents. So the captured locals are the ones declared by an earlier function.
+        let mut captured: Vec<LocalId> = self
+            .usage
+            .mentions(id)
+            .iter()
+            .filter(|local| self.usage.owner(**local).is_some_and(|owner| owner < id))
+            .copied()
+            .collect();
+        captured.sort_by_key(|local| local.0);
+        let mut avoided = HashSet::new();
+        for local in captured {
+            avoided.insert(self.name(local));
+        }
+
+        let mut index = 0;
+        for local in own {
+            let name = loop {
+                let name = short_name(index);
+                index += 1;
+                if !self.taken.contains(&name) && !avoided.contains(&name) {
+                    break name;
+                }
+            };
+            self.names.insert(local, name);
+        }
+    }
+
+    fn function_body(&mut self, id: FunctionId) {
+        let function = self.module.function(id);
+        let statements = structure(self.module, &self.usage, function);
+        let mut scope = Scope {
+            declarations: Declarations::new(function, &self.usage, &statements),
+            lists: 0,
+        };
+        self.list(&statements, &mut scope);
+    }
+
+    /// Parameters of a function, e.g. `(a, b, ...)`.
+    fn parameters(&mut self, id: FunctionId) -> String {
+        let function = self.module.function(id);
+        let mut params: Vec<String> = function
+            .params
+            .iter()
+            .map(|param| self.name(*param))
+            .collect();
+        if function.rest.is_some() {
+            params.push("...".to_string());
+        }
+        format!("({})", params.join(", "))
+    }
+
+    fn list(&mut self, statements: &[Stmt], scope: &mut Scope) {
+        let list = scope.lists;
+        scope.lists += 1;
+        for (index, statement) in statements.iter().enumerate() {
+            if let Some(locals) = scope.declarations.before.get(&(list, index)) {
+                let names: Vec<String> = locals.iter().map(|local| self.name(*local)).collect();
+                self.line(&format!("local {}", names.join(", ")));
+            }
+            let inline = scope.declarations.inline.contains(&(list, index));
+            self.statement(statement, inline, scope);
+        }
+    }
+
+    fn statement(&mut self, statement: &Stmt, inline: bool, scope: &mut Scope) {
+        let mark = self.mark(statement.span);
+        match &statement.kind {
+            StmtKind::Assign(Target::Local(local), Expr::Function(function)) if inline => {
+                let name = self.name(*local);
+                let params = self.parameters(*function);
+                self.line(&format!("{mark}local function {name}{params}"));
+                self.indent += 1;
+                self.function_body(*function);
+                self.indent -= 1;
+                self.line("end");
+            }
+            StmtKind::Assign(target, value) => {
+                let target = match target {
+                    Target::Local(local) => {
+                        let name = self.name(*local);
+                        if inline {
+                            format!("local {name}")
+                        } else {
+                            name
+                        }
+                    }
+                    Target::Global(name) => name.as_str().to_string(),
+                    Target::Index(table, key) => self.index(table, key),
+                };
+                let value = self.expr(value, 0);
+                self.line(&format!("{mark}{target} = {value}"));
+            }
+            StmtKind::Call(call) => {
+                let call = self.expr(call, 0);
+                self.line(&format!("{mark}{call}"));
+            }
+            StmtKind::Spread {
+                target,
+                source,
+                kind,
+            } => {
+                let target = self.expr(target, 0);
+                let source = self.expr(source, 0);
+                let helper = match kind {
+                    SpreadKind::Elements => Helper::SpreadElements,
+                    SpreadKind::Fields => Helper::SpreadFields,
+                };
+                self.line(&format!("{mark}{}({target}, {source})", helper.name()));
+            }
+            StmtKind::If {
+                condition,
+                then,
+                r#else,
+            } => {
+                self.if_statement(&format!("{mark}if"), condition, then, r#else, scope);
+                self.line("end");
+            }
+            StmtKind::Return(value) => {
+                let value = self.expr(value, 0);
+                self.line(&format!("{mark}return {value}"));
+            }
+        }
+    }
+
+    /// Print an `if` or `elseif` clause and the clauses after it, Except for the `end`.
+    fn if_statement(
+        &mut self,
+        keyword: &str,
+        condition: &Expr,
+        then: &[Stmt],
+        r#else: &[Stmt],
+        scope: &mut Scope,
+    ) {
+        // Negate the condition instead of printing an empty then branch.
+        let negated = then.is_empty() && !r#else.is_empty();
+        let condition = if negated {
+            self.negated(condition)
+        } else {
+            self.expr(condition, 0)
+        };
+        self.line(&format!("{keyword} {condition} then"));
+        self.indent += 1;
+        self.list(then, scope);
+        if negated {
+            self.list(r#else, scope);
+            self.indent -= 1;
+            return;
+        }
+        self.indent -= 1;
+
+        let else_list = scope.lists;
+        match r#else {
+            [Stmt {
+                span,
+                kind:
+                    StmtKind::If {
+                        condition,
+                        then,
+                        r#else,
+                    },
+            }] if !scope.declarations.before.contains_key(&(else_list, 0)) => {
+                scope.lists += 1;
+                let keyword = format!("{}elseif", self.mark(*span));
+                self.if_statement(&keyword, condition, then, r#else, scope);
+            }
+            [] => self.list(r#else, scope),
+            _ => {
+                self.line("else");
+                self.indent += 1;
+                self.list(r#else, scope);
+                self.indent -= 1;
+            }
+        }
+    }
+
+    fn negated(&mut self, condition: &Expr) -> String {
+        match condition {
+            Expr::Marked(span, value) => {
+                let mark = self.mark(*span);
+                format!("{mark}{}", self.negated(value))
+            }
+            Expr::Unary(UnaryOp::Not, value) => self.expr(value, 0),
+            Expr::Binary(op @ (BinaryOp::Equal | BinaryOp::NotEqual), lhs, rhs) => {
+                let op = match op {
+                    BinaryOp::Equal => BinaryOp::NotEqual,
+                    _ => BinaryOp::Equal,
+                };
+                let (precedence, _) = binary_precedence(op);
+                let lhs = self.expr(lhs, precedence);
+                let rhs = self.expr(rhs, precedence + 1);
+                format!("{lhs} {} {rhs}", binary_operator(op))
+            }
+            condition => {
+                let condition = self.expr(condition, UNARY_PRECEDENCE);
+                format!("not {condition}")
+            }
+        }
+    }
+
+    /// Print a binary operation and return its precedence.
+    fn binary(&mut self, op: BinaryOp, lhs: &Expr, rhs: &Expr) -> (u8, String) {
+        let (own, right_associative) = binary_precedence(op);
+        let (lhs_precedence, rhs_precedence) = if right_associative {
+            (own + 1, own)
+        } else {
+            (own, own + 1)
+        };
+        let lhs = self.expr(lhs, lhs_precedence);
+        let rhs = self.expr(rhs, rhs_precedence);
+        (own, format!("{lhs} {} {rhs}", binary_operator(op)))
+    }
+
+    /// Print an expression, Wrapping it in parentheses if it binds looser than `precedence`.
+    fn expr(&mut self, expr: &Expr, precedence: u8) -> String {
+        let (own, text) = match expr {
+            Expr::Local(local) => (ATOM_PRECEDENCE, self.name(*local)),
+            Expr::Global(name) => (ATOM_PRECEDENCE, name.as_str().to_string()),
+            // Negative numbers are written with a unary minus.
+            Expr::Constant(constant @ Constant::Number(value))
+                if value.value.is_sign_negative() =>
+            {
+                (UNARY_PRECEDENCE, self.constant(constant))
+            }
+            Expr::Constant(constant) => (ATOM_PRECEDENCE, self.constant(constant)),
+            Expr::Unary(UnaryOp::Debug, value) => {
+                let value = self.expr(value, 0);
+                (
+                    ATOM_PRECEDENCE,
+                    format!("{}({value})", Helper::Debug.name()),
+                )
+            }
+            Expr::Unary(op, value) => {
+                let value = self.expr(value, UNARY_PRECEDENCE);
+                let text = match op {
+                    UnaryOp::Not => format!("not {value}"),
+                    // Avoid printing `--` which starts a comment.
+                    UnaryOp::Negate if value.starts_with('-') => format!("- {value}"),
+                    UnaryOp::Negate => format!("-{value}"),
+                    UnaryOp::Length => format!("#{value}"),
+                    UnaryOp::Debug => unreachable!(),
+                };
+                (UNARY_PRECEDENCE, text)
+            }
+            Expr::Binary(op, lhs, rhs) if self.options.target == LuaTarget::LuaJit => {
+                match luajit_operator(*op) {
+                    Some(function) => {
+                        let lhs = self.expr(lhs, 0);
+                        let rhs = self.expr(rhs, 0);
+                        (ATOM_PRECEDENCE, format!("{function}({lhs}, {rhs})"))
+                    }
+                    None if *op == BinaryOp::FloorDivide => {
+                        let (own, _) = binary_precedence(BinaryOp::Divide);
+                        let lhs = self.expr(lhs, own);
+                        let rhs = self.expr(rhs, own + 1);
+                        (ATOM_PRECEDENCE, format!("math.floor({lhs} / {rhs})"))
+                    }
+                    None => self.binary(*op, lhs, rhs),
+                }
+            }
+            Expr::Binary(op, lhs, rhs) => self.binary(*op, lhs, rhs),
+            Expr::Concat(parts) if parts.len() > CONCAT_LIMIT => {
+                let parts: Vec<String> = parts.iter().map(|part| self.expr(part, 0)).collect();
+                (
+                    ATOM_PRECEDENCE,
+                    format!("table.concat({{{}}})", parts.join(", ")),
+                )
+            }
+            Expr::Concat(parts) => {
+                let parts: Vec<String> = parts.iter().map(|part| self.expr(part, 9)).collect();
+                (8, parts.join(" .. "))
+            }
+            Expr::Call(callee, args) => {
+                let callee = match (callee.as_ref(), args.as_slice()) {
+                    (Expr::Global(name), [Expr::Constant(Constant::String(_))])
+                        if self.bundled && name.as_str() == "require" =>
+                    {
+                        bundle::REQUIRE_NAME.to_string()
+                    }
+                    _ => self.prefix(callee),
+                };
+                let args: Vec<String> = args.iter().map(|arg| self.expr(arg, 0)).collect();
+                (ATOM_PRECEDENCE, format!("{callee}({})", args.join(", ")))
+            }
+            Expr::Index(table, key) => (ATOM_PRECEDENCE, self.index(table, key)),
+            Expr::Table(entries) => {
+                let entries: Vec<String> = entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let value = self.expr(value, 0);
+                        match key {
+                            None => value,
+                            Some(Expr::Constant(Constant::String(key)))
+                                if is_identifier(key.as_str()) =>
+                            {
+                                format!("{} = {value}", key.as_str())
+                            }
+                            Some(key) => format!("[{}] = {value}", self.expr(key, 0)),
+                        }
+                    })
+                    .collect();
+                (ATOM_PRECEDENCE, format!("{{{}}}", entries.join(", ")))
+            }
+            Expr::Function(function) => {
+                let params = self.parameters(*function);
+                let mut text = format!("function{params}\n");
+                let out = std::mem::take(&mut self.out);
+                self.indent += 1;
+                self.function_body(*function);
+                self.indent -= 1;
+                text.push_str(&std::mem::replace(&mut self.out, out));
+                text.push_str(&INDENT.repeat(self.indent));
+                text.push_str("end");
+                (ATOM_PRECEDENCE, text)
+            }
+            Expr::VarArgs => (ATOM_PRECEDENCE, "{...}".to_string()),
+            Expr::Marked(span, value) => {
+                let mark = self.mark(*span);
+                return format!("{mark}{}", self.expr(value, precedence));
+            }
+        };
+        if own < precedence {
+            format!("({text})")
+        } else {
+            text
+        }
+    }
+
+    /// Print an expression that can be called or indexed, e.g. `a`, `a.b` or `(1)`.
+    fn prefix(&mut self, expr: &Expr) -> String {
+        let text = self.expr(expr, 0);
+        match expr.unmarked() {
+            Expr::Local(_) | Expr::Global(_) | Expr::Index(..) | Expr::Call(..) => text,
+            _ => format!("({text})"),
+        }
+    }
+
+    fn index(&mut self, table: &Expr, key: &Expr) -> String {
+        let table = self.prefix(table);
+        match key {
+            Expr::Constant(Constant::String(key)) if is_identifier(key.as_str()) => {
+                format!("{table}.{}", key.as_str())
+            }
+            key => format!("{table}[{}]", self.expr(key, 0)),
+        }
+    }
+
+    fn constant(&self, constant: &Constant) -> String {
+        match constant {
+            Constant::Nil => "nil".to_string(),
+            Constant::Boolean(value) => value.to_string(),
+            Constant::Number(value) if self.options.minify => short_number(value),
+            Constant::Number(value) => number(value),
+            Constant::String(value) if self.options.minify => short_string(value.as_str()),
+            Constant::String(value) => string(value.as_str()),
+        }
+    }
+}
+
+/// Precedence of a binary operator and whether it is right associative.
+fn binary_precedence(op: BinaryOp) -> (u8, bool) {
+    match op {
+        BinaryOp::Or => (1, false),
+        BinaryOp::And => (2, false),
+        BinaryOp::Equal
+        | BinaryOp::NotEqual
+        | BinaryOp::LessThan
+        | BinaryOp::LessThanEqual
+        | BinaryOp::GreaterThan
+        | BinaryOp::GreaterThanEqual => (3, false),
+        BinaryOp::BitwiseOr => (4, false),
+        BinaryOp::BitwiseXor => (5, false),
+        BinaryOp::BitwiseAnd => (6, false),
+        BinaryOp::ShiftLeft | BinaryOp::ShiftRight => (7, false),
+        BinaryOp::Add | BinaryOp::Subtract => (9, false),
+        BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::FloorDivide | BinaryOp::Modulo => {
+            (10, false)
+        }
+        BinaryOp::Power => (12, true),
+    }
+}
+
+/// Function of the LuaJIT `bit` library implementing a bitwise operator.
+fn luajit_operator(op: BinaryOp) -> Option<&'static str> {
+    match op {
+        BinaryOp::BitwiseAnd => Some("bit.band"),
+        BinaryOp::BitwiseOr => Some("bit.bor"),
+        BinaryOp::BitwiseXor => Some("bit.bxor"),
+        BinaryOp::ShiftLeft => Some("bit.lshift"),
+        BinaryOp::ShiftRight => Some("bit.rshift"),
+        _ => None,
+    }
+}
+
+fn binary_operator(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Subtract => "-",
+        BinaryOp::Multiply => "*",
+        BinaryOp::Divide => "/",
+        BinaryOp::FloorDivide => "//",
+        BinaryOp::Modulo => "%",
+        BinaryOp::Power => "^",
+        BinaryOp::Equal => "==",
+        BinaryOp::NotEqual => "~=",
+        BinaryOp::LessThan => "<",
+        BinaryOp::LessThanEqual => "<=",
+        BinaryOp::GreaterThan => ">",
+        BinaryOp::GreaterThanEqual => ">=",
+        BinaryOp::BitwiseAnd => "&",
+        BinaryOp::BitwiseOr => "|",
+        BinaryOp::BitwiseXor => "~",
+        BinaryOp::ShiftLeft => "<<",
+        BinaryOp::ShiftRight => ">>",
+        BinaryOp::And => "and",
+        BinaryOp::Or => "or",
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+        && !KEYWORDS.contains(&name)
+}
+
+/// Number as written in the source if Lua reads it the same, Floats keep their fraction so
+/// they don't turn into integers on the targets that have them, e.g. `3.0`.
+fn number(number: &Number) -> String {
+    match number.lua_literal() {
+        Some(literal) => literal,
+        None if number.value.is_nan() => "(0 / 0)".to_string(),
+        None if number.value > 0.0 => "math.huge".to_string(),
+        None => "-math.huge".to_string(),
+    }
+}
+
+/// Number without the leading zero of its fraction, e.g. `.5`.
+fn short_number(value: &Number) -> String {
+    let text = number(value);
+    if let Some(fraction) = text.strip_prefix("0.") {
+        format!(".{fraction}")
+    } else if let Some(fraction) = text.strip_prefix("-0.") {
+        format!("-.{fraction}")
+    } else {
+        text
+    }
+}
+
+/// String quoted with the quote needing fewer escapes.
+fn short_string(value: &str) -> String {
+    let double = value.matches('"').count();
+    let single = value.matches('\'').count();
+    quoted(value, if single < double { '\'' } else { '"' })
+}
+
+pub(crate) fn string(value: &str) -> String {
+    quoted(value, '"')
+}
+
+fn quoted(value: &str, quote: char) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push(quote);
+    for char in value.chars() {
+        match char {
+            char if char == quote => {
+                out.push('\\');
+                out.push(char);
+            }
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            char if char.is_ascii_control() => out.push_str(&format!("\\{:03}", char as u32)),
+            char => out.push(char),
+        }
+    }
+    out.push(quote);
+    out
+}
@@ -0,0 +1,171 @@
+//! Runtime library, Lua functions used by the generated code. Chunks either print the helpers
+//! they use at their top or take them from the `fuse_runtime` module, See `Runtime`.
+
+use std::fmt::Write;
+
+/// Name of the runtime module, As passed to `require`.
+pub const RUNTIME_MODULE: &str = "fuse_runtime";
+
+/// Version of the runtime module, It is always the version of the compiler.
+pub const RUNTIME_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Name of the local holding the runtime module in chunks requiring it.
+pub(crate) const MODULE_NAME: &str = "_fuse_runtime";
+
+/// How the generated chunks get the runtime helpers they use.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Runtime {
+    /// Print the used helpers at the top of each chunk.
+    #[default]
+    Inline,
+    /// Require the `fuse_runtime` module, It must be in the `package.path` of the program.
+    Require,
+}
+
+/// A function of the runtime library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum Helper {
+    Debug,
+    SpreadElements,
+    SpreadFields,
+}
+
+impl Helper {
+    pub(crate) const ALL: [Helper; 3] =
+        [Helper::Debug, Helper::SpreadElements, Helper::SpreadFields];
+
+    /// Name of the local holding the helper in the generated chunks.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Helper::Debug => "_fuse_debug",
+            Helper::SpreadElements => "_fuse_spread_elements",
+            Helper::SpreadFields => "_fuse_spread_fields",
+        }
+    }
+
+    /// Name of the helper in the runtime module.
+    fn field(self) -> &'static str {
+        match self {
+            Helper::Debug => "debug",
+            Helper::SpreadElements => "spread_elements",
+            Helper::SpreadFields => "spread_fields",
+        }
+    }
+
+    /// Lua code declaring the helper as a local function named `Helper::name`.
+    fn source(self) -> &'static str {
+        match self {
+            Helper::Debug => DEBUG,
+            Helper::SpreadElements => SPREAD_ELEMENTS,
+            Helper::SpreadFields => SPREAD_FIELDS,
+        }
+    }
+}
+
+/// Lua code of the `fuse_runtime` module, It has every helper and the `version` of the compiler.
+pub fn runtime_module() -> String {
+    let mut out = String::new();
+    for helper in Helper::ALL {
+        out.push_str(helper.source());
+    }
+    writeln!(out, "return {{").unwrap();
+    writeln!(out, "    version = \"{RUNTIME_VERSION}\",").unwrap();
+    for helper in Helper::ALL {
+        writeln!(out, "    {} = {},", helper.field(), helper.name()).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+/// Lua code giving a chunk access to the `helpers` it uses, Nothing if it uses none.
+pub(crate) fn prelude(runtime: Runtime, helpers: impl IntoIterator<Item = Helper>) -> String {
+    let mut out = String::new();
+    let mut helpers = helpers.into_iter().peekable();
+    if helpers.peek().is_none() {
+        return out;
+    }
+    match runtime {
+        Runtime::Inline => helpers.for_each(|helper| out.push_str(helper.source())),
+        Runtime::Require => {
+            // A runtime of another version may lack or change the helpers.
+            writeln!(out, "local {MODULE_NAME} = require(\"{RUNTIME_MODULE}\")").unwrap();
+            writeln!(
+                out,
+                "assert({MODULE_NAME}.version == \"{RUNTIME_VERSION}\", \"Expected {RUNTIME_MODULE} {RUNTIME_VERSION}, found \" .. tostring({MODULE_NAME}.version))"
+            )
+            .unwrap();
+            for helper in helpers {
+                writeln!(
+                    out,
+                    "local {} = {MODULE_NAME}.{}",
+                    helper.name(),
+                    helper.field()
+                )
+                .unwrap();
+            }
+        }
+    }
+    out
+}
+
+/// Format a value like `${value:?}`, Strings are quoted, Sequences are printed as arrays and
+/// other tables as their fields sorted by key. Cycles are printed as `<cycle>`.
+const DEBUG: &str = r#"local function _fuse_debug(value, seen)
+    local kind = type(value)
+    if kind == "string" then
+        local escapes = {["\\"] = "\\\\", ["\""] = "\\\"", ["\n"] = "\\n", ["\r"] = "\\r", ["\t"] = "\\t"}
+        return "\"" .. (value:gsub("[\\\"\n\r\t]", escapes)) .. "\""
+    elseif kind ~= "table" then
+        return tostring(value)
+    end
+    seen = seen or {}
+    if seen[value] then
+        return "<cycle>"
+    end
+    seen[value] = true
+    local keys = {}
+    for key in pairs(value) do
+        keys[#keys + 1] = key
+    end
+    local parts = {}
+    local text
+    if #keys > 0 and #keys == #value then
+        for index = 1, #value do
+            parts[index] = _fuse_debug(value[index], seen)
+        end
+        text = "[" .. table.concat(parts, ", ") .. "]"
+    elseif #keys > 0 then
+        table.sort(keys, function(a, b)
+            return tostring(a) < tostring(b)
+        end)
+        for index, key in ipairs(keys) do
+            local name = key
+            if type(key) ~= "string" or not key:match("^[%a_][%w_]*$") then
+                name = "[" .. _fuse_debug(key, seen) .. "]"
+            end
+            parts[index] = name .. ": " .. _fuse_debug(value[key], seen)
+        end
+        text = "{ " .. table.concat(parts, ", ") .. " }"
+    else
+        text = "{}"
+    end
+    seen[value] = nil
+    return text
+end
+"#;
+
+/// Append the elements of a sequence to another, e.g. `[...items, 1]`.
+const SPREAD_ELEMENTS: &str = r#"local function _fuse_spread_elements(target, source)
+    for _, value in ipairs(source) do
+        target[#target + 1] = value
+    end
+end
+"#;
+
+/// Copy the fields of a table to another, e.g. `Point { ...point, x: 1 }`.
+const SPREAD_FIELDS: &str = r#"local function _fuse_spread_fields(target, source)
+    for key, value in pairs(source) do
+        target[key] = value
+    end
+end
+"#;
@@ -0,0 +1,390 @@
+use fuse_common::Span;
+use serde::{Deserialize, Serialize};
+use thiserror::Error as ThisError;
+
+/// Characters around the index of a mark, The printer escapes control characters in
+/// strings so they never appear in the generated code.
+pub(crate) const MARK_START: char = '\u{1}';
+pub(crate) const MARK_END: char = '\u{2}';
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A zero based line and column, Columns are counted in UTF-16 code units like the
+/// other tools consuming source maps.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// Mapping, It relates a position of the generated code to a position of a source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mapping {
+    pub generated: Position,
+    /// Index of the source in `SourceMap::sources`.
+    pub source: u32,
+    pub original: Position,
+}
+
+/// Source map, It relates the generated Lua code to the Fuse code it originates from.
+///
+/// It is serialized in the [v3 format](https://sourcemaps.info/spec.html).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SourceMap {
+    /// Name of the generated file.
+    pub file: String,
+    /// Names of the source files.
+    pub sources: Vec<String>,
+    /// Mappings of the generated code, Sorted by their generated position.
+    pub mappings: Vec<Mapping>,
+}
+
+#[derive(ThisError, Debug)]
+pub enum SourceMapError {
+    #[error("Invalid source map: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unsupported source map version {0}, Expected 3")]
+    Version(u32),
+    #[error("Invalid source map mappings at segment {0}")]
+    Mappings(usize),
+}
+
+/// JSON representation of a source map.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+    version: u32,
+    #[serde(default)]
+    file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    source_root: Option<String>,
+    sources: Vec<String>,
+    #[serde(default)]
+    names: Vec<String>,
+    mappings: String,
+}
+
+impl SourceMap {
+    /// Serialize the source map in the v3 JSON format.
+    pub fn to_json(&self) -> String {
+        let raw = RawSourceMap {
+            version: 3,
+            file: self.file.clone(),
+            source_root: None,
+            sources: self.sources.clone(),
+            names: Vec::new(),
+            mappings: self.encode_mappings(),
+        };
+        serde_json::to_string(&raw).expect("Failed to serialize a source map.")
+    }
+
+    /// Parse a source map in the v3 JSON format, Mappings without a source are ignored.
+    pub fn from_json(json: &str) -> Result<Self, SourceMapError> {
+        let raw: RawSourceMap = serde_json::from_str(json)?;
+        if raw.version != 3 {
+            return Err(SourceMapError::Version(raw.version));
+        }
+        let sources = match raw.source_root.as_deref() {
+            Some(root) if !root.is_empty() => raw
+                .sources
+                .iter()
+                .map(|source| format!("{}/{source}", root.trim_end_matches('/')))
+                .collect(),
+            _ => raw.sources,
+        };
+        let mappings = decode_mappings(&raw.mappings)?;
+        Ok(Self {
+            file: raw.file,
+            sources,
+            mappings,
+        })
+    }
+
+    /// Original position of a generated line, It is the first mapping of the line or the
+    /// last mapping before it if the line has none.
+    pub fn lookup(&self, line: u32) -> Option<(&str, Position)> {
+        let after = self
+            .mappings
+            .partition_point(|mapping| mapping.generated.line < line);
+        let mapping = match self.mappings.get(after) {
+            Some(mapping) if mapping.generated.line == line => mapping,
+            _ => self.mappings.get(after.checked_sub(1)?)?,
+        };
+        let source = self.sources.get(mapping.source as usize)?;
+        Some((source, mapping.original))
+    }
+
+    /// Rewrite the positions of the generated file in a Lua traceback or error message,
+    /// e.g. `main.lua:12:` becomes `main.fuse:3:5:`. Lines and columns are one based.
+    pub fn rewrite_traceback(&self, traceback: &str) -> String {
+        let name = file_name(&self.file);
+        if name.is_empty() {
+            return traceback.to_string();
+        }
+        let mut out = String::with_capacity(traceback.len());
+        let mut rest = traceback;
+        while let Some(index) = rest.find(name) {
+            let end = index + name.len();
+            let replacement = traceback_line(&rest[end..]).and_then(|(line, length)| {
+                let start = path_start(&rest[..index])?;
+                let (source, original) = self.lookup(line.checked_sub(1)?)?;
+                let position = format!("{source}:{}:{}:", original.line + 1, original.column + 1);
+                Some((start, end + length, position))
+            });
+            match replacement {
+                Some((start, end, position)) => {
+                    out.push_str(&rest[..start]);
+                    out.push_str(&position);
+                    rest = &rest[end..];
+                }
+                None => {
+                    out.push_str(&rest[..end]);
+                    rest = &rest[end..];
+                }
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn encode_mappings(&self) -> String {
+        let mut out = String::new();
+        let mut line = 0;
+        let mut column = 0;
+        let mut source = 0;
+        let mut original = Position::default();
+        for mapping in &self.mappings {
+            if line < mapping.generated.line {
+                while line < mapping.generated.line {
+                    out.push(';');
+                    line += 1;
+                }
+                column = 0;
+            } else if !out.is_empty() {
+                out.push(',');
+            }
+            // Fields are relative to the previous segment, Except for the column which
+            // restarts at each line.
+            encode_vlq(&mut out, delta(mapping.generated.column, column));
+            encode_vlq(&mut out, delta(mapping.source, source));
+            encode_vlq(&mut out, delta(mapping.original.line, original.line));
+            encode_vlq(&mut out, delta(mapping.original.column, original.column));
+            column = mapping.generated.column;
+            source = mapping.source;
+            original = mapping.original;
+        }
+        out
+    }
+}
+
+fn delta(value: u32, previous: u32) -> i64 {
+    i64::from(value) - i64::from(previous)
+}
+
+fn decode_mappings(text: &str) -> Result<Vec<Mapping>, SourceMapError> {
+    let mut mappings = Vec::new();
+    let mut fields = [0i64; 4];
+    let mut segment = 0;
+    for (line, groups) in text.split(';').enumerate() {
+        // The generated column restarts at each line.
+        fields[0] = 0;
+        for group in groups.split(',').filter(|group| !group.is_empty()) {
+            let mut bytes = group.bytes();
+            let mut values = Vec::with_capacity(5);
+            while bytes.len() > 0 {
+                values.push(decode_vlq(&mut bytes).ok_or(SourceMapError::Mappings(segment))?);
+            }
+            if !matches!(values.len(), 1 | 4 | 5) {
+                return Err(SourceMapError::Mappings(segment));
+            }
+            for (field, value) in fields.iter_mut().zip(&values) {
+                *field += value;
+            }
+            if values.len() >= 4 {
+                let field = |index: usize| {
+                    u32::try_from(fields[index]).map_err(|_| SourceMapError::Mappings(segment))
+                };
+                mappings.push(Mapping {
+                    generated: Position {
+                        line: line as u32,
+                        column: field(0)?,
+                    },
+                    source: field(1)?,
+                    original: Position {
+                        line: field(2)?,
+                        column: field(3)?,
+                    },
+                });
+            }
+            segment += 1;
+        }
+    }
+    mappings.sort_by_key(|mapping| mapping.generated);
+    Ok(mappings)
+}
+
+/// Append a base64 VLQ, The sign is stored in the lowest bit.
+fn encode_vlq(out: &mut String, value: i64) {
+    let mut value = if value < 0 {
+        (value.unsigned_abs() << 1) | 1
+    } else {
+        (value as u64) << 1
+    };
+    loop {
+        let mut digit = (value & 0b11111) as u8;
+        value >>= 5;
+        if value > 0 {
+            digit |= 0b100000;
+        }
+        out.push(BASE64[digit as usize] as char);
+        if value == 0 {
+            break;
+        }
+    }
+}
+
+fn decode_vlq(bytes: &mut impl Iterator<Item = u8>) -> Option<i64> {
+    let mut value: u64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes.next()?;
+        let digit = BASE64.iter().position(|char| *char == byte)? as u64;
+        value |= (digit & 0b11111).checked_shl(shift)?;
+        shift += 5;
+        if digit & 0b100000 == 0 {
+            break;
+        }
+        if shift >= 64 {
+            return None;
+        }
+    }
+    let magnitude = i64::try_from(value >> 1).ok()?;
+    Some(if value & 1 == 1 {
+        -magnitude
+    } else {
+        magnitude
+    })
+}
+
+/// Last component of a path.
+fn file_name(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// Start of the path ending at the end of `text`, It is `None` if the file name is only the
+/// end of a longer name, e.g. `xmain.lua`.
+fn path_start(text: &str) -> Option<usize> {
+    let is_path = |char: char| !char.is_whitespace() && !"'\"()[]<>,;".contains(char);
+    match text.chars().next_back() {
+        Some(char) if is_path(char) && char != '/' && char != '\\' => None,
+        _ => Some(
+            text.char_indices()
+                .rev()
+                .take_while(|(_, char)| is_path(*char))
+                .last()
+                .map_or(text.len(), |(index, _)| index),
+        ),
+    }
+}
+
+/// Line number of a `:<line>:` suffix and its length.
+fn traceback_line(text: &str) -> Option<(u32, usize)> {
+    let digits = text.strip_prefix(':')?;
+    let length = digits.bytes().take_while(u8::is_ascii_digit).count();
+    if length == 0 || digits.as_bytes().get(length) != Some(&b':') {
+        return None;
+    }
+    Some((digits[..length].parse().ok()?, length + 2))
+}
+
+/// Spans of the statements and values marked in the generated code.
+#[derive(Debug, Default)]
+pub(crate) struct Marks {
+    spans: Vec<Span>,
+}
+
+impl Marks {
+    /// Add a span and return the mark referring to it.
+    pub(crate) fn push(&mut self, span: Span) -> String {
+        self.spans.push(span);
+        format!("{MARK_START}{}{MARK_END}", self.spans.len() - 1)
+    }
+
+    /// Remove the marks from the generated code, It returns the code and the mappings of the
+    /// marks to their position in `source`.
+    pub(crate) fn strip(&self, text: &str, source: &str) -> (String, Vec<Mapping>) {
+        let lines = LineIndex::new(source);
+        let mut out = String::with_capacity(text.len());
+        let mut mappings = Vec::with_capacity(self.spans.len());
+        let mut generated = Position::default();
+        let mut chars = text.chars();
+        while let Some(char) = chars.next() {
+            match char {
+                MARK_START => {
+                    let index: String = chars
+                        .by_ref()
+                        .take_while(|char| *char != MARK_END)
+                        .collect();
+                    let span = self.spans[index.parse::<usize>().expect("Invalid mark.")];
+                    let original = lines.position(span.start);
+                    // Nested values starting at the same position only need the outer mapping,
+                    // And a mapping to the same position as the last one of the line adds nothing.
+                    let redundant = mappings.last().is_some_and(|last: &Mapping| {
+                        last.generated == generated
+                            || (last.generated.line == generated.line && last.original == original)
+                    });
+                    if !redundant {
+                        mappings.push(Mapping {
+                            generated,
+                            source: 0,
+                            original,
+                        });
+                    }
+                }
+                '\n' => {
+                    out.push(char);
+                    generated.line += 1;
+                    generated.column = 0;
+                }
+                char => {
+                    out.push(char);
+                    generated.column += char.len_utf16() as u32;
+                }
+            }
+        }
+        (out, mappings)
+    }
+}
+
+/// Offsets of the lines of a source.
+struct LineIndex<'a> {
+    source: &'a str,
+    starts: Vec<u32>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(source: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(
+                source
+                    .match_indices('\n')
+                    .map(|(index, _)| index as u32 + 1),
+            )
+            .collect();
+        Self { source, starts }
+    }
+
+    fn position(&self, offset: u32) -> Position {
+        let offset = offset.min(self.source.len() as u32);
+        let line = self.starts.partition_point(|start| *start <= offset) - 1;
+        let start = self.starts[line] as usize;
+        let column = self
+            .source
+            .get(start..offset as usize)
+            .map_or(0, |text| text.encode_utf16().count());
+        Position {
+            line: line as u32,
+            column: column as u32,
+        }
+    }
+}
@@ -0,0 +1,469 @@
+use std::collections::HashMap;
+
+use fuse_ast::Atom;
+use fuse_common::Span;
+use fuse_ir::{
+    BinaryOp, BlockId, Constant, Function, FunctionId, Instruction, InstructionKind, LocalId,
+    Module, Operand, Place, Rvalue, SpreadKind, TableEntry, Terminator, UnaryOp,
+};
+
+use crate::usage::Usage;
+
+/// A statement of the structured tree, Control flow of the basic blocks is turned into `If` statements.
+#[derive(Debug)]
+pub(crate) struct Stmt {
+    /// Span of the source code the statement originates from.
+    pub span: Span,
+    pub kind: StmtKind,
+}
+
+#[derive(Debug)]
+pub(crate) enum StmtKind {
+    Assign(Target, Expr),
+    Call(Expr),
+    Spread {
+        target: Expr,
+        source: Expr,
+        kind: SpreadKind,
+    },
+    If {
+        condition: Expr,
+        then: Vec<Stmt>,
+        r#else: Vec<Stmt>,
+    },
+    Return(Expr),
+}
+
+#[derive(Debug)]
+pub(crate) enum Target {
+    Local(LocalId),
+    Global(Atom),
+    Index(Expr, Expr),
+}
+
+#[derive(Debug)]
+pub(crate) enum Expr {
+    Local(LocalId),
+    Global(Atom),
+    Constant(Constant),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Concat(Vec<Expr>),
+    Call(Box<Expr>, Vec<Expr>),
+    Index(Box<Expr>, Box<Expr>),
+    Table(Vec<(Option<Expr>, Expr)>),
+    Function(FunctionId),
+    VarArgs,
+    /// Value of an inlined temporary, The span is the one of the instruction assigning it.
+    Marked(Span, Box<Expr>),
+}
+
+impl Expr {
+    /// The expression without the marks around it.
+    pub(crate) fn unmarked(&self) -> &Expr {
+        match self {
+            Expr::Marked(_, value) => value.unmarked(),
+            expr => expr,
+        }
+    }
+}
+
+/// Turn the basic blocks of a function into a tree of statements.
+pub(crate) fn structure(module: &Module, usage: &Usage, function: &Function) -> Vec<Stmt> {
+    let graph = Graph::new(function);
+    let mut assigned = HashMap::new();
+    for block in &function.blocks {
+        for instruction in &block.instructions {
+            if let InstructionKind::Assign(Place::Local(local), _) = &instruction.kind {
+                assigned
+                    .entry(*local)
+                    .and_modify(|span| *span = Span::with_spans(vec![*span, instruction.span]))
+                    .or_insert(instruction.span);
+            }
+        }
+    }
+    let structurer = Structurer {
+        module,
+        usage,
+        function,
+        graph,
+        assigned,
+    };
+    structurer.region(Function::ENTRY, None)
+}
+
+/// Order and post-dominators of the blocks. Lowered functions have no loops and a single
+/// exit, So each branch rejoins at its immediate post-dominator.
+struct Graph {
+    /// Position of each block in reverse postorder.
+    rank: Vec<usize>,
+    /// Immediate post-dominator of each block, `None` is the exit of the function.
+    post_dominators: Vec<Option<BlockId>>,
+}
+
+impl Graph {
+    fn new(function: &Function) -> Self {
+        fn visit(
+            function: &Function,
+            block: BlockId,
+            visited: &mut [bool],
+            order: &mut Vec<BlockId>,
+        ) {
+            if visited[block.0] {
+                return;
+            }
+            visited[block.0] = true;
+            for successor in function.block(block).terminator.successors() {
+                visit(function, successor, visited, order);
+            }
+            order.push(block);
+        }
+
+        let count = function.blocks.len();
+        let mut postorder = Vec::with_capacity(count);
+        visit(
+            function,
+            Function::ENTRY,
+            &mut vec![false; count],
+            &mut postorder,
+        );
+
+        let mut graph = Self {
+            rank: vec![usize::MAX; count],
+            post_dominators: vec![None; count],
+        };
+        for (rank, block) in postorder.iter().rev().enumerate() {
+            graph.rank[block.0] = rank;
+        }
+        // Successors come before their predecessors in postorder.
+        for block in &postorder {
+            let mut successors = function.block(*block).terminator.successors().into_iter();
+            let first = successors.next();
+            let post_dominator = successors.fold(first, |dominator, successor| {
+                graph.intersect(dominator, Some(successor))
+            });
+            graph.post_dominators[block.0] = post_dominator;
+        }
+        graph
+    }
+
+    fn rank(&self, block: Option<BlockId>) -> usize {
+        block.map_or(usize::MAX, |block| self.rank[block.0])
+    }
+
+    /// Nearest common post-dominator of two blocks.
+    fn intersect(&self, mut lhs: Option<BlockId>, mut rhs: Option<BlockId>) -> Option<BlockId> {
+        while lhs != rhs {
+            while self.rank(lhs) < self.rank(rhs) {
+                lhs = self.post_dominators[lhs.unwrap().0];
+            }
+            while self.rank(rhs) < self.rank(lhs) {
+                rhs = self.post_dominators[rhs.unwrap().0];
+            }
+        }
+        lhs
+    }
+}
+
+struct Structurer<'a> {
+    module: &'a Module,
+    usage: &'a Usage,
+    function: &'a Function,
+    graph: Graph,
+    /// Span covering the assignments of each local of the function, e.g. the values of the
+    /// branches of an `if` expression.
+    assigned: HashMap<LocalId, Span>,
+}
+
+impl<'a> Structurer<'a> {
+    /// Span of a terminator using an operand, It is the span of the assignments of a local
+    /// or the span of the last instruction before the terminator.
+    fn span_of(&self, operand: &Operand, last: Span) -> Span {
+        match operand {
+            Operand::Local(local) => self.assigned.get(local).copied().unwrap_or(last),
+            _ => last,
+        }
+    }
+
+    /// Statements of the blocks from `start` until reaching `stop`.
+    fn region(&self, start: BlockId, stop: Option<BlockId>) -> Vec<Stmt> {
+        let mut list = StatementList::new(self.module, self.usage);
+        let mut current = Some(start);
+        // Terminators have no span, They take the span of the last instruction before them
+        // unless their operand is assigned by an instruction.
+        let mut span = self.function.span;
+        while current != stop {
+            let Some(block_id) = current else {
+                break;
+            };
+            let block = self.function.block(block_id);
+            for instruction in &block.instructions {
+                list.push(statement(instruction));
+                span = instruction.span;
+            }
+            current = match &block.terminator {
+                Terminator::Goto(target) => Some(*target),
+                Terminator::Branch {
+                    condition,
+                    then,
+                    r#else,
+                } => {
+                    let merge = self.graph.post_dominators[block_id.0];
+                    list.push(Stmt {
+                        span: self.span_of(condition, span),
+                        kind: StmtKind::If {
+                            condition: operand(condition),
+                            then: self.region(*then, merge),
+                            r#else: self.region(*r#else, merge),
+                        },
+                    });
+                    merge
+                }
+                Terminator::Return(value) => {
+                    if let Some(value) = value {
+                        list.push(Stmt {
+                            span: self.span_of(value, span),
+                            kind: StmtKind::Return(operand(value)),
+                        });
+                    }
+                    None
+                }
+                Terminator::Unreachable => None,
+            };
+        }
+        list.finish()
+    }
+}
+
+/// Statements of a list being built, Temporaries used by the next statement get inlined into it.
+struct StatementList<'a> {
+    module: &'a Module,
+    usage: &'a Usage,
+    statements: Vec<Stmt>,
+    /// Values of the inlinable temporaries assigned by the last statements, in order.
+    pending: Vec<(LocalId, Expr, Span)>,
+}
+
+impl<'a> StatementList<'a> {
+    fn new(module: &'a Module, usage: &'a Usage) -> Self {
+        Self {
+            module,
+            usage,
+            statements: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, mut stmt: Stmt) {
+        // Inline the pending temporaries if the statement uses the last ones in the same order,
+        // So they are still evaluated in the order they were assigned.
+        let mut used = Vec::new();
+        stmt_locals(&stmt.kind, &mut used);
+        used.retain(|local| self.pending.iter().any(|(pending, ..)| pending == local));
+        let start = self.pending.len().saturating_sub(used.len());
+        let suffix = self.pending[start..].iter().map(|(local, ..)| *local);
+        if suffix.eq(used.iter().copied()) {
+            let mut values: HashMap<LocalId, (Expr, Span)> = self
+                .pending
+                .drain(start..)
+                .map(|(local, value, span)| (local, (value, span)))
+                .collect();
+            substitute_stmt(&mut stmt.kind, &mut values);
+            // A statement made of an inlined value takes its span, e.g. `return a + b`.
+            if let StmtKind::Assign(_, value) | StmtKind::Return(value) = &mut stmt.kind {
+                if let Expr::Marked(span, inner) = value {
+                    stmt.span = *span;
+                    *value = std::mem::replace(inner.as_mut(), Expr::VarArgs);
+                }
+            }
+        } else {
+            self.flush();
+        }
+
+        // Temporaries assigned one after another stay pending, Their values are still
+        // evaluated in order once a statement uses them.
+        match stmt {
+            Stmt {
+                span,
+                kind: StmtKind::Assign(Target::Local(local), value),
+            } if self.usage.is_inlinable(self.module, local) => {
+                self.pending.push((local, value, span));
+            }
+            stmt => {
+                self.flush();
+                self.statements.push(stmt);
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        for (local, value, span) in self.pending.drain(..) {
+            self.statements.push(Stmt {
+                span,
+                kind: StmtKind::Assign(Target::Local(local), value),
+            });
+        }
+    }
+
+    fn finish(mut self) -> Vec<Stmt> {
+        self.flush();
+        self.statements
+    }
+}
+
+fn statement(instruction: &Instruction) -> Stmt {
+    let kind = match &instruction.kind {
+        InstructionKind::Assign(place, value) => {
+            let target = match place {
+                Place::Local(local) => Target::Local(*local),
+                Place::Global(name) => Target::Global(name.clone()),
+                Place::Index(table, key) => Target::Index(operand(table), operand(key)),
+            };
+            StmtKind::Assign(target, rvalue(value))
+        }
+        InstructionKind::Call(callee, args) => StmtKind::Call(Expr::Call(
+            Box::new(operand(callee)),
+            args.iter().map(operand).collect(),
+        )),
+        InstructionKind::Spread {
+            target,
+            source,
+            kind,
+        } => StmtKind::Spread {
+            target: operand(target),
+            source: operand(source),
+            kind: *kind,
+        },
+    };
+    Stmt {
+        span: instruction.span,
+        kind,
+    }
+}
+
+fn operand(operand: &Operand) -> Expr {
+    match operand {
+        Operand::Local(local) => Expr::Local(*local),
+        Operand::Global(name) => Expr::Global(name.clone()),
+        Operand::Constant(constant) => Expr::Constant(constant.clone()),
+    }
+}
+
+fn rvalue(value: &Rvalue) -> Expr {
+    match value {
+        Rvalue::Use(value) => operand(value),
+        Rvalue::Unary(op, value) => Expr::Unary(*op, Box::new(operand(value))),
+        Rvalue::Binary(op, lhs, rhs) => {
+            Expr::Binary(*op, Box::new(operand(lhs)), Box::new(operand(rhs)))
+        }
+        Rvalue::Concat(values) => Expr::Concat(values.iter().map(operand).collect()),
+        Rvalue::Call(callee, args) => Expr::Call(
+            Box::new(operand(callee)),
+            args.iter().map(operand).collect(),
+        ),
+        Rvalue::Index(table, key) => Expr::Index(Box::new(operand(table)), Box::new(operand(key))),
+        Rvalue::Table(entries) => Expr::Table(
+            entries
+                .iter()
+                .map(|entry| match entry {
+                    TableEntry::Positional(value) => (None, operand(value)),
+                    TableEntry::Keyed(key, value) => (Some(operand(key)), operand(value)),
+                })
+                .collect(),
+        ),
+        Rvalue::Closure(function) => Expr::Function(*function),
+        Rvalue::VarArgs => Expr::VarArgs,
+    }
+}
+
+/// Locals read by a statement in evaluation order, Excluding its nested statements.
+fn stmt_locals(stmt: &StmtKind, locals: &mut Vec<LocalId>) {
+    match stmt {
+        StmtKind::Assign(target, value) => {
+            if let Target::Index(table, key) = target {
+                expr_locals(table, locals);
+                expr_locals(key, locals);
+            }
+            expr_locals(value, locals);
+        }
+        StmtKind::Call(expr) | StmtKind::Return(expr) => expr_locals(expr, locals),
+        StmtKind::Spread { target, source, .. } => {
+            expr_locals(target, locals);
+            expr_locals(source, locals);
+        }
+        StmtKind::If { condition, .. } => expr_locals(condition, locals),
+    }
+}
+
+fn expr_locals(expr: &Expr, locals: &mut Vec<LocalId>) {
+    match expr {
+        Expr::Local(local) => locals.push(*local),
+        Expr::Global(_) | Expr::Constant(_) | Expr::Function(_) | Expr::VarArgs => {}
+        Expr::Unary(_, value) | Expr::Marked(_, value) => expr_locals(value, locals),
+        Expr::Binary(_, lhs, rhs) | Expr::Index(lhs, rhs) => {
+            expr_locals(lhs, locals);
+            expr_locals(rhs, locals);
+        }
+        Expr::Concat(values) => values.iter().for_each(|value| expr_locals(value, locals)),
+        Expr::Call(callee, args) => {
+            expr_locals(callee, locals);
+            args.iter().for_each(|arg| expr_locals(arg, locals));
+        }
+        Expr::Table(entries) => {
+            for (key, value) in entries {
+                if let Some(key) = key {
+                    expr_locals(key, locals);
+                }
+                expr_locals(value, locals);
+            }
+        }
+    }
+}
+
+fn substitute_stmt(stmt: &mut StmtKind, values: &mut HashMap<LocalId, (Expr, Span)>) {
+    match stmt {
+        StmtKind::Assign(target, value) => {
+            if let Target::Index(table, key) = target {
+                substitute(table, values);
+                substitute(key, values);
+            }
+            substitute(value, values);
+        }
+        StmtKind::Call(expr) | StmtKind::Return(expr) => substitute(expr, values),
+        StmtKind::Spread { target, source, .. } => {
+            substitute(target, values);
+            substitute(source, values);
+        }
+        StmtKind::If { condition, .. } => substitute(condition, values),
+    }
+}
+
+/// Replace the temporaries by their values, Marked with the span of their assignment.
+fn substitute(expr: &mut Expr, values: &mut HashMap<LocalId, (Expr, Span)>) {
+    match expr {
+        Expr::Local(local) => {
+            if let Some((value, span)) = values.remove(local) {
+                *expr = Expr::Marked(span, Box::new(value));
+            }
+        }
+        Expr::Global(_) | Expr::Constant(_) | Expr::Function(_) | Expr::VarArgs => {}
+        Expr::Unary(_, value) | Expr::Marked(_, value) => substitute(value, values),
+        Expr::Binary(_, lhs, rhs) | Expr::Index(lhs, rhs) => {
+            substitute(lhs, values);
+            substitute(rhs, values);
+        }
+        Expr::Concat(parts) => parts.iter_mut().for_each(|part| substitute(part, values)),
+        Expr::Call(callee, args) => {
+            substitute(callee, values);
+            args.iter_mut().for_each(|arg| substitute(arg, values));
+        }
+        Expr::Table(entries) => {
+            for (key, value) in entries {
+                if let Some(key) = key {
+                    substitute(key, values);
+                }
+                substitute(value, values);
+            }
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::{BTreeSet, HashSet};
+
+use fuse_ast::Atom;
+use fuse_ir::{
+    FunctionId, InstructionKind, LocalId, LocalKind, Module, Operand, Place, Rvalue, SpreadKind,
+    TableEntry, Terminator, UnaryOp,
+};
+
+use crate::runtime::Helper;
+
+/// How the locals of a module are used, It decides which temporaries get inlined.
+pub(crate) struct Usage {
+    /// Function declaring each local.
+    owners: Vec<Option<FunctionId>>,
+    uses: Vec<usize>,
+    assignments: Vec<usize>,
+    /// Locals used or assigned by a function other than their owner.
+    captured: Vec<bool>,
+    /// Locals mentioned by each function, including the ones mentioned by its nested functions.
+    mentions: Vec<HashSet<LocalId>>,
+    /// Names of the globals used by the module.
+    pub(crate) globals: HashSet<Atom>,
+    /// Runtime helpers used by the module.
+    pub(crate) helpers: BTreeSet<Helper>,
+}
+
+impl Usage {
+    pub(crate) fn new(module: &Module) -> Self {
+        let mut owners = vec![None; module.locals.len()];
+        for (index, function) in module.functions.iter().enumerate() {
+            let locals = function.params.iter().chain(&function.locals);
+            for local in locals.chain(&function.rest) {
+                owners[local.0] = Some(FunctionId(index));
+            }
+        }
+
+        let mut usage = Self {
+            owners: owners.clone(),
+            uses: vec![0; module.locals.len()],
+            assignments: vec![0; module.locals.len()],
+            captured: vec![false; module.locals.len()],
+            mentions: vec![HashSet::new(); module.functions.len()],
+            globals: HashSet::new(),
+            helpers: BTreeSet::new(),
+        };
+        // Nested functions come after their parents, So we visit them first.
+        for index in (0..module.functions.len()).rev() {
+            let mut visitor = FunctionUsage {
+                usage: &mut usage,
+                owners: &owners,
+                function: FunctionId(index),
+                mentions: HashSet::new(),
+            };
+            visitor.visit(module);
+            let mentions = visitor.mentions;
+            usage.mentions[index] = mentions;
+        }
+        usage
+    }
+
+    /// Returns true for temporaries that are assigned and used exactly once in their own function.
+    pub(crate) fn is_inlinable(&self, module: &Module, local: LocalId) -> bool {
+        module.local(local).kind == LocalKind::Temporary
+            && self.uses[local.0] == 1
+            && self.assignments[local.0] == 1
+            && !self.captured[local.0]
+    }
+
+    pub(crate) fn owner(&self, local: LocalId) -> Option<FunctionId> {
+        self.owners[local.0]
+    }
+
+    pub(crate) fn mentions(&self, function: FunctionId) -> &HashSet<LocalId> {
+        &self.mentions[function.0]
+    }
+}
+
+struct FunctionUsage<'a> {
+    usage: &'a mut Usage,
+    owners: &'a [Option<FunctionId>],
+    function: FunctionId,
+    mentions: HashSet<LocalId>,
+}
+
+impl<'a> FunctionUsage<'a> {
+    fn visit(&mut self, module: &Module) {
+        for block in &module.function(self.function).blocks {
+            for instruction in &block.instructions {
+                match &instruction.kind {
+                    InstructionKind::Assign(place, value) => {
+                        self.place(place);
+                        self.rvalue(value);
+                    }
+                    InstructionKind::Call(callee, args) => {
+                        self.operand(callee);
+                        args.iter().for_each(|arg| self.operand(arg));
+                    }
+                    InstructionKind::Spread {
+                        target,
+                        source,
+                        kind,
+                    } => {
+                        self.usage.helpers.insert(match kind {
+                            SpreadKind::Elements => Helper::SpreadElements,
+                            SpreadKind::Fields => Helper::SpreadFields,
+                        });
+                        self.operand(target);
+                        self.operand(source);
+                    }
+                }
+            }
+            match &block.terminator {
+                Terminator::Branch { condition, .. } => self.operand(condition),
+                Terminator::Return(Some(value)) => self.operand(value),
+                Terminator::Goto(_) | Terminator::Return(None) | Terminator::Unreachable => {}
+            }
+        }
+    }
+
+    fn mention(&mut self, local: LocalId) {
+        if self.owners[local.0] != Some(self.function) {
+            self.usage.captured[local.0] = true;
+        }
+        self.mentions.insert(local);
+    }
+
+    fn place(&mut self, place: &Place) {
+        match place {
+            Place::Local(local) => {
+                self.usage.assignments[local.0] += 1;
+                self.mention(*local);
+            }
+            Place::Global(name) => {
+                self.usage.globals.insert(name.clone());
+            }
+            Place::Index(table, key) => {
+                self.operand(table);
+                self.operand(key);
+            }
+        }
+    }
+
+    fn operand(&mut self, operand: &Operand) {
+        match operand {
+            Operand::Local(local) => {
+                self.usage.uses[local.0] += 1;
+                self.mention(*local);
+            }
+            Operand::Global(name) => {
+                self.usage.globals.insert(name.clone());
+            }
+            Operand::Constant(_) => {}
+        }
+    }
+
+    fn rvalue(&mut self, value: &Rvalue) {
+        match value {
+            Rvalue::Use(operand) => self.operand(operand),
+            Rvalue::Unary(op, operand) => {
+                if *op == UnaryOp::Debug {
+                    self.usage.helpers.insert(Helper::Debug);
+                }
+                self.operand(operand);
+            }
+            Rvalue::Binary(_, lhs, rhs) | Rvalue::Index(lhs, rhs) => {
+                self.operand(lhs);
+                self.operand(rhs);
+            }
+            Rvalue::Concat(operands) => operands.iter().for_each(|operand| self.operand(operand)),
+            Rvalue::Call(callee, args) => {
+                self.operand(callee);
+                args.iter().for_each(|arg| self.operand(arg));
+            }
+            Rvalue::Table(entries) => {
+                for entry in entries {
+                    match entry {
+                        TableEntry::Positional(value) => self.operand(value),
+                        TableEntry::Keyed(key, value) => {
+                            self.operand(key);
+                            self.operand(value);
+                        }
+                    }
+                }
+            }
+            // Captures are already marked while visiting the nested function.
+            Rvalue::Closure(function) => {
+                let nested = &self.usage.mentions[function.0];
+                self.mentions.extend(nested.iter().copied());
+            }
+            Rvalue::VarArgs => {}
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use std::{ffi::OsStr, fs, path::PathBuf};
+
+use fuse_codegen::{
+    generate_with_options, generate_with_source_map, runtime_module, CodegenOptions, LuaTarget,
+    Runtime, SourceMap,
+};
+use fuse_ir::{lower, Module, Pipeline};
+use fuse_parser::parse;
+use fuse_semantic::{Semantic, LUA_DECLARATIONS};
+
+struct Context<'a> {
+    root: PathBuf,
+    test_dir: PathBuf,
+    source_name: &'a str,
+    settings: insta::Settings,
+    optimize: bool,
+    options: CodegenOptions,
+}
+
+impl<'a> Context<'a> {
+    fn path(&self) -> PathBuf {
+        self.root.join(&self.test_dir)
+    }
+
+    fn settings(&self) -> insta::Settings {
+        self.settings.clone()
+    }
+}
+
+#[test]
+fn pass() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("pass"),
+        source_name: "case.fuse",
+        settings: insta::Settings::clone_current(),
+        optimize: false,
+        options: CodegenOptions::default(),
+    };
+
+    for case in load_cases(&ctx) {
+        run(&ctx, case);
+    }
+}
+
+#[test]
+fn optimize() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("optimize"),
+        source_name: "case.fuse",
+        settings: insta::Settings::clone_current(),
+        optimize: true,
+        options: CodegenOptions::default(),
+    };
+
+    for case in load_cases(&ctx) {
+        run(&ctx, case);
+    }
+}
+
+#[test]
+fn runtime() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("runtime"),
+        source_name: "case.fuse",
+        settings: insta::Settings::clone_current(),
+        optimize: false,
+        options: CodegenOptions {
+            runtime: Runtime::Require,
+            ..Default::default()
+        },
+    };
+
+    for case in load_cases(&ctx) {
+        run(&ctx, case);
+    }
+
+    let mut settings = ctx.settings();
+    settings.set_snapshot_path(ctx.path());
+    settings.set_prepend_module_to_snapshot(false);
+    settings.bind(|| insta::assert_snapshot!("fuse_runtime", runtime_module()));
+}
+
+#[test]
+fn source_map() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new()
+            .join("tests")
+            .join("cases")
+            .join("source-map"),
+        source_name: "case.fuse",
+        settings: insta::Settings::clone_current(),
+        optimize: false,
+        options: CodegenOptions::default(),
+    };
+
+    for case in load_cases(&ctx) {
+        run_source_map(&ctx, case);
+    }
+}
+
+#[test]
+fn minify() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("minify"),
+        source_name: "case.fuse",
+        settings: insta::Settings::clone_current(),
+        optimize: false,
+        options: CodegenOptions {
+            minify: true,
+            ..Default::default()
+        },
+    };
+
+    for case in load_cases(&ctx) {
+        run_source_map(&ctx, case);
+    }
+}
+
+#[test]
+fn luajit() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("luajit"),
+        source_name: "case.fuse",
+        settings: insta::Settings::clone_current(),
+        optimize: false,
+        options: CodegenOptions {
+            target: LuaTarget::LuaJit,
+            ..Default::default()
+        },
+    };
+
+    for case in load_cases(&ctx) {
+        run(&ctx, case);
+    }
+}
+
+// helpers
+fn load_cases(ctx: &Context) -> Vec<PathBuf> {
+    fs::read_dir(ctx.path())
+        .unwrap_or_else(|_| panic!("Failed to read {}", ctx.path().to_str().unwrap()))
+        .filter_map(|x| x.ok())
+        .filter(|x| x.metadata().is_ok_and(|meta| meta.is_dir()))
+        .map(|node| node.path())
+        .collect()
+}
+
+fn run_source_map(ctx: &Context, case_dir: PathBuf) {
+    let mut settings = ctx.settings();
+    let source_path = case_dir.join(ctx.source_name);
+    let path_str = source_path.to_str().unwrap_or("unknown source");
+    let source = read_source_normalized(source_path.as_os_str()).unwrap();
+    let traceback =
+        read_source_normalized(case_dir.join("traceback.txt").as_os_str()).unwrap_or_default();
+
+    settings.set_input_file(&source_path);
+    settings.set_snapshot_path(case_dir);
+    settings.set_prepend_module_to_snapshot(false);
+
+    let _guard = settings.bind_to_scope();
+    test_source_map(path_str, &source, &traceback, ctx.options);
+}
+
+fn run(ctx: &Context, case_dir: PathBuf) {
+    let mut settings = ctx.settings();
+    let source_path = case_dir.join(ctx.source_name);
+    let path_str = source_path.to_str().unwrap_or("unknown source");
+    let source = read_source_normalized(source_path.as_os_str()).unwrap();
+
+    settings.set_input_file(&source_path);
+    settings.set_snapshot_path(case_dir);
+    // if case source code is small include it in the snapshot.
+    if source.lines().count() <= 5 {
+        settings.set_description(source.clone());
+    }
+    settings.set_prepend_module_to_snapshot(false);
+
+    let _guard = settings.bind_to_scope();
+    test_codegen(path_str, &source, ctx.optimize, ctx.options);
+}
+
+fn test_codegen(path: &str, source: &str, optimize: bool, options: CodegenOptions) {
+    let module = lower_case(path, source, optimize);
+    insta::assert_snapshot!("lua", generate_with_options(&module, options));
+}
+
+fn lower_case(path: &str, source: &str, optimize: bool) -> Module {
+    let parsed = parse(source);
+    assert!(
+        parsed.errors.is_empty() && !parsed.paniced,
+        "Failed to parse the test case: {path}\n{:?}",
+        parsed.errors
+    );
+    let chunk = parsed.chunk.unwrap();
+
+    let declarations = parse(LUA_DECLARATIONS).chunk.unwrap();
+    let semantic = Semantic::new()
+        .with_declarations(&declarations)
+        .build(&chunk);
+    assert!(
+        semantic.errors.is_empty(),
+        "Semantic errors in the test case: {path}\n{:#?}",
+        semantic.errors
+    );
+
+    let mut module = lower(&chunk, &semantic, source);
+    if optimize {
+        Pipeline::optimizations().run(&mut module);
+    }
+    module
+}
+
+fn test_source_map(path: &str, source: &str, traceback: &str, options: CodegenOptions) {
+    let module = lower_case(path, source, false);
+    let (lua, map) = generate_with_source_map(&module, options, source, "case.fuse", "case.lua");
+    let json = map.to_json();
+    assert_eq!(
+        SourceMap::from_json(&json).unwrap(),
+        map,
+        "Source map doesn't survive a round trip: {path}"
+    );
+
+    // Show each mapping next to the generated and the original lines.
+    let lua_lines: Vec<&str> = lua.lines().collect();
+    let source_lines: Vec<&str> = source.lines().collect();
+    let mut mappings = String::new();
+    for mapping in &map.mappings {
+        let (generated, original) = (mapping.generated, mapping.original);
+        mappings.push_str(&format!(
+            "{}:{} -> {}:{}\n    {}\n    {}\n",
+            generated.line + 1,
+            generated.column + 1,
+            original.line + 1,
+            original.column + 1,
+            lua_lines[generated.line as usize].trim(),
+            source_lines[original.line as usize].trim(),
+        ));
+    }
+    insta::assert_snapshot!(
+        "source-map",
+        format!(
+            "{lua}\n{json}\n\n{mappings}\n{}",
+            map.rewrite_traceback(traceback)
+        )
+    );
+}
+
+fn read_source_normalized(path: &OsStr) -> Result<String, std::io::Error> {
+    fs::read_to_string(path).map(|it| it.replace("\r\n", "\n"))
+}
@@ -4,7 +4,7 @@ pub trait SpanView {
     fn view(self, span: &Span) -> Self;
 }
 
-impl<'a> SpanView for &'a str {
+impl SpanView for &str {
     #[inline]
     fn view(self, span: &Span) -> Self {
         &self[span.start as usize..span.end as usize]
@@ -0,0 +1,153 @@
+//! Documents describing the layout of the formatted code, The printer puts the lines of a
+//! group on a single line if they fit in the line width and breaks all of them otherwise.
+
+use crate::{INDENT, TAB_WIDTH};
+
+#[derive(Debug, Clone)]
+pub(crate) enum Doc {
+    Text(String),
+    /// A space, Or a newline if the enclosing group breaks.
+    Line,
+    /// Nothing, Or a newline if the enclosing group breaks.
+    SoftLine,
+    /// A newline even in the groups that fit.
+    HardLine,
+    /// Indent the lines of the documents by one level.
+    Indent(Vec<Doc>),
+    Group(Vec<Doc>),
+    Concat(Vec<Doc>),
+}
+
+impl Doc {
+    pub(crate) fn nil() -> Self {
+        Self::Concat(Vec::new())
+    }
+
+    pub(crate) fn text(text: impl Into<String>) -> Self {
+        Self::Text(text.into())
+    }
+
+    /// Join the documents with `separator` between each two of them.
+    pub(crate) fn join(docs: Vec<Doc>, separator: impl Fn() -> Doc) -> Self {
+        let mut joined = Vec::with_capacity(docs.len() * 2);
+        for (index, doc) in docs.into_iter().enumerate() {
+            if index > 0 {
+                joined.push(separator());
+            }
+            joined.push(doc);
+        }
+        Self::Concat(joined)
+    }
+
+    pub(crate) fn has_hard_line(&self) -> bool {
+        match self {
+            Self::HardLine => true,
+            Self::Indent(docs) | Self::Group(docs) | Self::Concat(docs) => {
+                docs.iter().any(Self::has_hard_line)
+            }
+            Self::Text(_) | Self::Line | Self::SoftLine => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+/// Print a document, Lines are kept under `width` columns where the groups allow it.
+pub(crate) fn print(doc: &Doc, width: usize) -> String {
+    let mut printer = Printer {
+        out: String::new(),
+        column: 0,
+        line_start: true,
+        width,
+    };
+    printer.print(doc);
+    printer.out
+}
+
+struct Printer {
+    out: String,
+    column: usize,
+    /// Whether nothing is printed on the current line yet, The indentation is printed along
+    /// with the first text so empty lines have no trailing whitespace.
+    line_start: bool,
+    width: usize,
+}
+
+impl Printer {
+    fn print(&mut self, doc: &Doc) {
+        let mut stack = vec![(0, Mode::Break, doc)];
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Text(text) => self.text(indent, text),
+                Doc::Line if mode == Mode::Flat => self.text(indent, " "),
+                Doc::SoftLine if mode == Mode::Flat => {}
+                Doc::Line | Doc::SoftLine | Doc::HardLine => self.newline(),
+                Doc::Indent(docs) => {
+                    stack.extend(docs.iter().rev().map(|doc| (indent + 1, mode, doc)))
+                }
+                Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+                Doc::Group(docs) => {
+                    let column = if self.line_start {
+                        indent * TAB_WIDTH
+                    } else {
+                        self.column
+                    };
+                    let remaining = self.width as isize - column as isize;
+                    let mode = if mode == Mode::Flat || fits(docs, &stack, remaining) {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+                    stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+                }
+            }
+        }
+    }
+
+    fn text(&mut self, indent: usize, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if self.line_start {
+            self.out.push_str(&INDENT.repeat(indent));
+            self.column = indent * TAB_WIDTH;
+            self.line_start = false;
+        }
+        self.out.push_str(text);
+        self.column += text.chars().count();
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        self.column = 0;
+        self.line_start = true;
+    }
+}
+
+/// Whether the documents fit in `remaining` columns on a single line along with the rest of
+/// the line, `rest` is the stack of the documents printed after them. Only the text before a
+/// hard line has to fit.
+fn fits(docs: &[Doc], rest: &[(usize, Mode, &Doc)], mut remaining: isize) -> bool {
+    let mut stack: Vec<(Mode, &Doc)> = docs.iter().rev().map(|doc| (Mode::Flat, doc)).collect();
+    let mut rest = rest.iter().rev().map(|(_, mode, doc)| (*mode, *doc));
+    while let Some((mode, doc)) = stack.pop().or_else(|| rest.next()) {
+        match doc {
+            Doc::Text(text) => remaining -= text.chars().count() as isize,
+            Doc::Line | Doc::SoftLine if mode == Mode::Break => return true,
+            Doc::Line => remaining -= 1,
+            Doc::SoftLine => {}
+            Doc::HardLine => return true,
+            Doc::Indent(docs) | Doc::Group(docs) | Doc::Concat(docs) => {
+                stack.extend(docs.iter().rev().map(|doc| (mode, doc)))
+            }
+        }
+        if remaining < 0 {
+            return false;
+        }
+    }
+    true
+}
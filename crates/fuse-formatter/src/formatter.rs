@@ -0,0 +1,835 @@
+use std::collections::VecDeque;
+
+use fuse_ast::{
+    ArrayExpressionElement, BinaryOperator, BinaryOperatorKind, BindingIdentifier, BindingPattern,
+    BindingPatternKind, Block, CallExpression, Chunk, ConstructionExpression, ConstructionField,
+    Else, Expression, ExternItem, Function, FunctionBody, FunctionSignature, GetSpan, If, Match,
+    MemberExpression, MemberExpressionLHS, MemberExpressionRHS, Pattern, Precedence, Statement,
+    TupleExpressionElement, TypeAnnotation, TypeAnnotationKind, TypeParameter, UnaryOperator,
+    UnaryOperatorKind, VariableDeclarationKind, VisibilityModifier,
+};
+use fuse_common::Span;
+use fuse_parser::lexer::{TokenKind, TokenReference};
+
+use crate::{
+    doc::{self, Doc},
+    LINE_WIDTH,
+};
+
+/// Formatter, It prints a chunk back to canonical Fuse code.
+///
+/// Comments aren't part of the AST, They are taken from the trivia of the tokens and printed
+/// on their own lines before the statement or declaration item that follows them. Comments on
+/// the same line after a statement stay after it.
+pub(crate) struct Formatter<'a> {
+    source: &'a str,
+    /// Comments not printed yet, In source order.
+    comments: VecDeque<Span>,
+    /// Offsets of the keywords closing a block, e.g. `end` and `else`. In source order.
+    closers: Vec<u32>,
+}
+
+impl<'a> Formatter<'a> {
+    pub(crate) fn new(source: &'a str, tokens: &[TokenReference]) -> Self {
+        let mut comments = VecDeque::new();
+        let mut closers = Vec::new();
+        for token in tokens {
+            comments.extend(
+                token
+                    .leading_trivia
+                    .iter()
+                    .filter(|trivia| trivia.kind() == TokenKind::Comment)
+                    .map(|trivia| trivia.span()),
+            );
+            if matches!(
+                token.kind(),
+                TokenKind::End | TokenKind::Else | TokenKind::ElseIf | TokenKind::When
+            ) {
+                closers.push(token.start());
+            }
+            comments.extend(
+                token
+                    .trailing_trivia
+                    .iter()
+                    .filter(|trivia| trivia.kind() == TokenKind::Comment)
+                    .map(|trivia| trivia.span()),
+            );
+        }
+        Self {
+            source,
+            comments,
+            closers,
+        }
+    }
+
+    pub(crate) fn format(mut self, chunk: &Chunk) -> String {
+        let lines = self.lines(
+            &chunk.body.statements,
+            None,
+            statement_span,
+            Self::statement,
+        );
+        let mut out = doc::print(&Doc::join(lines, || Doc::HardLine), LINE_WIDTH);
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Lines of the items of a block with the comments before them, The comments before `end`
+    /// go after the last item. All of the remaining comments do if `end` is `None`.
+    fn lines<T>(
+        &mut self,
+        items: &[T],
+        end: Option<u32>,
+        span: impl Fn(&T) -> Span,
+        mut item: impl FnMut(&mut Self, &T) -> Doc,
+    ) -> Vec<Doc> {
+        let mut lines = Vec::new();
+        let mut last = None;
+        for it in items {
+            let span = span(it);
+            self.comments_before(span.start, &mut lines, &mut last);
+            self.blank_line(&mut lines, last, span.start);
+            let doc = item(self, it);
+            last = Some(span.end);
+            let doc = match self.trailing_comment(span.end) {
+                Some(comment) => {
+                    last = Some(comment.end);
+                    Doc::Concat(vec![doc, Doc::text(" "), self.comment(comment)])
+                }
+                None => doc,
+            };
+            lines.push(doc);
+        }
+        self.comments_before(end.unwrap_or(u32::MAX), &mut lines, &mut last);
+        lines
+    }
+
+    fn comments_before(&mut self, offset: u32, lines: &mut Vec<Doc>, last: &mut Option<u32>) {
+        while let Some(comment) = self.comments.front().copied() {
+            if comment.start >= offset {
+                break;
+            }
+            self.comments.pop_front();
+            self.blank_line(lines, *last, comment.start);
+            lines.push(self.comment(comment));
+            *last = Some(comment.end);
+        }
+    }
+
+    /// Comment on the same line after the code ending at `end`.
+    fn trailing_comment(&mut self, end: u32) -> Option<Span> {
+        let comment = *self.comments.front()?;
+        let between = self.source.get(end as usize..comment.start as usize)?;
+        if between.contains('\n') {
+            return None;
+        }
+        self.comments.pop_front()
+    }
+
+    fn comment(&self, comment: Span) -> Doc {
+        Doc::text(self.view(comment).trim_end())
+    }
+
+    /// Keep a single empty line where the source has empty lines between two lines.
+    fn blank_line(&self, lines: &mut Vec<Doc>, last: Option<u32>, start: u32) {
+        let Some(last) = last.filter(|last| *last < start) else {
+            return;
+        };
+        if self.source[last as usize..start as usize]
+            .matches('\n')
+            .count()
+            > 1
+        {
+            lines.push(Doc::nil());
+        }
+    }
+
+    /// Offset of the first keyword closing a block at or after `offset`.
+    fn closer(&self, offset: u32) -> u32 {
+        let index = self.closers.partition_point(|closer| *closer < offset);
+        self.closers.get(index).copied().unwrap_or(u32::MAX)
+    }
+
+    /// Lines of a block, `start` is the end of the code before its first statement.
+    fn block_lines(&mut self, block: &Block, start: u32) -> Vec<Doc> {
+        let end = self.closer(block_end(block, start));
+        self.lines(
+            &block.statements,
+            Some(end),
+            statement_span,
+            Self::statement,
+        )
+    }
+
+    /// A header, The items under it and an `end`, e.g. a struct and its fields.
+    fn declaration<T>(
+        &mut self,
+        header: Doc,
+        header_end: u32,
+        items: &[T],
+        span: impl Fn(&T) -> Span,
+        item: impl FnMut(&mut Self, &T) -> Doc,
+    ) -> Doc {
+        let after = items.last().map(|it| span(it).end).unwrap_or(header_end);
+        let end = self.closer(after);
+        let lines = self.lines(items, Some(end), span, item);
+        Doc::Concat(vec![
+            header,
+            indented(lines, Doc::HardLine),
+            Doc::HardLine,
+            Doc::text("end"),
+        ])
+    }
+
+    fn view(&self, span: Span) -> &'a str {
+        &self.source[span.start as usize..span.end as usize]
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Doc {
+        match statement {
+            Statement::Empty(_) => Doc::text(";"),
+            Statement::Expression(expr) => match &**expr {
+                Expression::If(expr) => self.if_expression(expr, false),
+                expr => self.expression(expr),
+            },
+            Statement::VariableDeclaration(decl) => {
+                let keyword = match decl.kind {
+                    VariableDeclarationKind::Let => "let",
+                    VariableDeclarationKind::Const => "const",
+                    VariableDeclarationKind::Global => "global",
+                };
+                let mut docs = vec![Doc::text(format!(
+                    "{keyword} {}",
+                    binding_pattern(&decl.binding)
+                ))];
+                if let Some(expr) = &decl.expression {
+                    docs.push(Doc::text(" = "));
+                    docs.push(self.expression(expr));
+                }
+                Doc::Concat(docs)
+            }
+            Statement::FunctionDeclaration(function) => self.function(function),
+            Statement::EnumDeclaration(decl) => self.declaration(
+                Doc::text(format!("enum {}", decl.identifier.name.as_str())),
+                decl.identifier.span.end,
+                &decl.variants,
+                |variant| {
+                    let end = variant.value.as_ref().map(Expression::span);
+                    Span::new(
+                        variant.identifier.span.start,
+                        end.unwrap_or(variant.identifier.span).end,
+                    )
+                },
+                |this, variant| {
+                    let name = Doc::text(variant.identifier.name.as_str());
+                    match &variant.value {
+                        Some(value) => {
+                            Doc::Concat(vec![name, Doc::text(" = "), this.expression(value)])
+                        }
+                        None => name,
+                    }
+                },
+            ),
+            Statement::StructDeclaration(decl) => self.declaration(
+                Doc::text(format!(
+                    "struct {}{}",
+                    decl.identifier.name.as_str(),
+                    type_parameters(&decl.type_parameters)
+                )),
+                decl.type_parameters
+                    .last()
+                    .map_or(decl.identifier.span.end, |param| param.span.end),
+                &decl.fields,
+                |field| {
+                    Span::new(
+                        modifier_start(&field.modifier).unwrap_or(field.identifier.span.start),
+                        field.type_annotation.span.end,
+                    )
+                },
+                |_, field| {
+                    Doc::text(format!(
+                        "{}{}: {}",
+                        modifier(&field.modifier),
+                        field.identifier.name.as_str(),
+                        type_annotation(&field.type_annotation)
+                    ))
+                },
+            ),
+            Statement::ImplStatement(decl) => self.declaration(
+                Doc::text(format!(
+                    "impl {} for {}",
+                    type_annotation(&decl.r#trait),
+                    type_annotation(&decl.target)
+                )),
+                decl.target.span.end,
+                &decl.methods,
+                |method| {
+                    Span::new(
+                        modifier_start(&method.modifier).unwrap_or(method.function.span.start),
+                        method.function.span.end,
+                    )
+                },
+                |this, method| {
+                    Doc::Concat(vec![
+                        Doc::text(modifier(&method.modifier)),
+                        this.function(&method.function),
+                    ])
+                },
+            ),
+            Statement::TypeAliasDeclaration(decl) => Doc::text(format!(
+                "type {} = {}",
+                decl.identifier.name.as_str(),
+                type_annotation(&decl.annotation)
+            )),
+            Statement::UnionDeclaration(decl) => self.declaration(
+                Doc::text(format!("union {}", decl.identifier.name.as_str())),
+                decl.identifier.span.end,
+                &decl.variants,
+                |variant| variant.span,
+                |_, variant| {
+                    let name = variant.identifier.name.as_str();
+                    if variant.fields.is_empty() {
+                        return Doc::text(name);
+                    }
+                    Doc::text(format!("{name}({})", type_list(&variant.fields)))
+                },
+            ),
+            Statement::ExternDeclaration(decl) => {
+                Doc::Concat(vec![Doc::text("extern "), self.extern_item(&decl.item)])
+            }
+        }
+    }
+
+    fn extern_item(&mut self, item: &ExternItem) -> Doc {
+        match item {
+            ExternItem::Function(signature) => self.signature(signature),
+            ExternItem::Global(global) => Doc::text(format!(
+                "{}: {}",
+                global.identifier.name.as_str(),
+                type_annotation(&global.type_annotation)
+            )),
+            ExternItem::Table(table) => self.declaration(
+                Doc::text(table.identifier.name.as_str()),
+                table.identifier.span.end,
+                &table.items,
+                extern_item_span,
+                Self::extern_item,
+            ),
+        }
+    }
+
+    fn function(&mut self, function: &Function) -> Doc {
+        let signature = self.signature(&function.signature);
+        match &function.body {
+            FunctionBody::Expression(expr) => {
+                Doc::Concat(vec![signature, Doc::text(" => "), self.expression(expr)])
+            }
+            FunctionBody::Block(block) => self.declaration(
+                signature,
+                function.signature.span.end,
+                &block.statements,
+                statement_span,
+                Self::statement,
+            ),
+        }
+    }
+
+    fn signature(&mut self, signature: &FunctionSignature) -> Doc {
+        let mut header = String::from("fn");
+        if let Some(identifier) = &signature.identifier {
+            header.push(' ');
+            header.push_str(identifier.name.as_str());
+        }
+        header.push_str(&type_parameters(&signature.type_parameters));
+
+        let mut params: Vec<Doc> = signature
+            .params
+            .items
+            .iter()
+            .map(|param| Doc::text(binding_pattern(&param.pattern)))
+            .collect();
+        if let Some(rest) = &signature.params.rest {
+            let mut doc = format!("...{}", binding_identifier(&rest.binding));
+            if let Some(annotation) = &rest.type_annotation {
+                doc.push_str(&format!(": {}", type_annotation(annotation)));
+            }
+            params.push(Doc::text(doc));
+        }
+
+        let mut docs = vec![Doc::text(header), list("(", params, ")", false)];
+        if let Some(return_type) = &signature.return_type {
+            docs.push(Doc::text(format!(" -> {}", type_annotation(return_type))));
+        }
+        Doc::Concat(docs)
+    }
+
+    fn expression(&mut self, expr: &Expression) -> Doc {
+        match expr {
+            Expression::NilLiteral(_) => Doc::text("nil"),
+            Expression::BooleanLiteral(lit) => Doc::text(if lit.value { "true" } else { "false" }),
+            Expression::NumberLiteral(lit) => Doc::text(lit.raw.as_str()),
+            // Strings are kept as they are written, Including their escapes and interpolations.
+            Expression::StringLiteral(lit) => Doc::text(self.view(lit.span)),
+            Expression::Identifier(ident) => Doc::text(ident.name.as_str()),
+            Expression::Function(function) => self.function(function),
+            Expression::If(expr) => self.if_expression(expr, true),
+            Expression::Match(expr) => self.match_expression(expr),
+            Expression::UnaryOperator(op) => self.unary(op),
+            Expression::BinaryOperator(op) => self.binary(op, false),
+            Expression::ArrayExpression(array) => {
+                let elements = array
+                    .elements
+                    .iter()
+                    .map(|element| match element {
+                        ArrayExpressionElement::Expression(expr) => self.expression(expr),
+                        ArrayExpressionElement::Spread(spread) => self.spread(&spread.element),
+                    })
+                    .collect();
+                list("[", elements, "]", false)
+            }
+            Expression::TupleExpression(tuple) => {
+                let mut elements: Vec<Doc> = tuple
+                    .elements
+                    .iter()
+                    .map(|element| match element {
+                        TupleExpressionElement::Expression(expr) => self.expression(expr),
+                        TupleExpressionElement::Spread(spread) => self.spread(&spread.element),
+                    })
+                    .collect();
+                // A single expression needs a comma to be a tuple.
+                if let [TupleExpressionElement::Expression(_)] = tuple.elements.as_slice() {
+                    let element = elements.pop().unwrap();
+                    return Doc::Concat(vec![Doc::text("("), element, Doc::text(",)")]);
+                }
+                list("(", elements, ")", false)
+            }
+            Expression::ParenthesizedExpression(expr) => Doc::Concat(vec![
+                Doc::text("("),
+                self.expression(&expr.expression),
+                Doc::text(")"),
+            ]),
+            Expression::MemberExpression(member) => self.member(member),
+            Expression::CallExpression(call) => self.call(call),
+            Expression::TableConstructionExpression(construction) => {
+                self.construction(construction)
+            }
+            Expression::StructConstructionExpression(expr) => Doc::Concat(vec![
+                self.expression(&expr.target),
+                Doc::text(" "),
+                self.construction(&expr.construction),
+            ]),
+        }
+    }
+
+    fn spread(&mut self, expr: &Expression) -> Doc {
+        Doc::Concat(vec![Doc::text("..."), self.expression(expr)])
+    }
+
+    /// Print an `if`, It stays on a single line if it fits and `inline` is set. It shouldn't
+    /// have an `elseif` and each of its branches should be a single line.
+    fn if_expression(&mut self, expr: &If, inline: bool) -> Doc {
+        let mut branches = Vec::new();
+        let mut keyword = "if";
+        let mut current = expr;
+        let else_lines = loop {
+            let header = Doc::Concat(vec![
+                Doc::text(format!("{keyword} ")),
+                self.expression(&current.cond),
+                Doc::text(" then"),
+            ]);
+            let cond_end = current.cond.span().end;
+            branches.push((header, self.block_lines(&current.body, cond_end)));
+            match &current.r#else {
+                Some(Else::If(next)) => {
+                    keyword = "elseif";
+                    current = next;
+                }
+                Some(Else::Block(block)) => {
+                    let start = self.closer(block_end(&current.body, cond_end)) + 1;
+                    break Some(self.block_lines(block, start));
+                }
+                None => break None,
+            }
+        };
+
+        let is_single_line = |lines: &Vec<Doc>| lines.len() == 1 && !lines[0].has_hard_line();
+        let inline = inline
+            && branches.len() == 1
+            && is_single_line(&branches[0].1)
+            && else_lines.as_ref().is_none_or(is_single_line);
+        let line = if inline { Doc::Line } else { Doc::HardLine };
+
+        let mut docs = Vec::new();
+        for (index, (header, lines)) in branches.into_iter().enumerate() {
+            if index > 0 {
+                docs.push(line.clone());
+            }
+            docs.push(header);
+            docs.push(indented(lines, line.clone()));
+        }
+        if let Some(lines) = else_lines {
+            docs.push(line.clone());
+            docs.push(Doc::text("else"));
+            docs.push(indented(lines, line.clone()));
+        }
+        docs.push(line);
+        docs.push(Doc::text("end"));
+        if inline {
+            Doc::Group(docs)
+        } else {
+            Doc::Concat(docs)
+        }
+    }
+
+    fn match_expression(&mut self, expr: &Match) -> Doc {
+        let header = Doc::Concat(vec![Doc::text("match "), self.expression(&expr.expression)]);
+        let arms_end = expr
+            .arms
+            .last()
+            .map_or(expr.expression.span().end, |arm| arm.span.end);
+        let end = self.closer(arms_end);
+        let mut lines = self.lines(
+            &expr.arms,
+            Some(end),
+            |arm| arm.span,
+            |this, arm| {
+                let header = Doc::text(format!("when {} then", this.pattern(&arm.pattern)));
+                let lines = this.block_lines(&arm.body, arm.pattern.span().end);
+                branch(header, lines)
+            },
+        );
+        if let Some(block) = &expr.r#else {
+            let body = self.block_lines(block, end + 1);
+            lines.push(branch(Doc::text("else"), body));
+        }
+        Doc::Concat(vec![
+            header,
+            indented(lines, Doc::HardLine),
+            Doc::HardLine,
+            Doc::text("end"),
+        ])
+    }
+
+    fn pattern(&self, pattern: &Pattern) -> String {
+        match pattern {
+            Pattern::Wildcard(_) => "_".to_string(),
+            Pattern::Literal(expr) => self.view(expr.span()).to_string(),
+            Pattern::Binding(binding) => binding_identifier(binding),
+            Pattern::Variant(variant) => {
+                let mut pattern = format!(
+                    "{}.{}",
+                    variant.target.name.as_str(),
+                    variant.variant.name.as_str()
+                );
+                if !variant.fields.is_empty() {
+                    let fields: Vec<String> = variant
+                        .fields
+                        .iter()
+                        .map(|field| self.pattern(field))
+                        .collect();
+                    pattern.push_str(&format!("({})", fields.join(", ")));
+                }
+                pattern
+            }
+        }
+    }
+
+    fn unary(&mut self, op: &UnaryOperator) -> Doc {
+        let operator = match op.kind {
+            UnaryOperatorKind::Not(_) => "not ",
+            UnaryOperatorKind::Plus(_) => "+",
+            UnaryOperatorKind::Minus(_) => "-",
+        };
+        // `--` starts a comment, A nested minus needs a space before it.
+        let is_minus = |kind: &UnaryOperatorKind| matches!(kind, UnaryOperatorKind::Minus(_));
+        let separator = match &op.expression {
+            Expression::UnaryOperator(inner) if is_minus(&op.kind) && is_minus(&inner.kind) => " ",
+            _ => "",
+        };
+        Doc::Concat(vec![
+            Doc::text(format!("{operator}{separator}")),
+            self.expression(&op.expression),
+        ])
+    }
+
+    /// Print a binary operator, Operators binding tighter than `+` lose their spaces when they
+    /// are operands of `+`, `-` or a shift. e.g. `a*b + c`, It shows how the operators group.
+    fn binary(&mut self, op: &BinaryOperator, tight: bool) -> Doc {
+        let parent = precedence(&op.kind);
+        let mut operand = |expr: &Expression| match expr {
+            Expression::BinaryOperator(inner) => {
+                let tight = precedence(&inner.kind) >= Precedence::Multiply
+                    && (tight || matches!(parent, Precedence::Add | Precedence::Shift));
+                self.binary(inner, tight)
+            }
+            expr => self.expression(expr),
+        };
+        let lhs = operand(&op.lhs);
+        let rhs = operand(&op.rhs);
+        let symbol = operator(&op.kind);
+        let symbol = if tight {
+            symbol.to_string()
+        } else {
+            format!(" {symbol} ")
+        };
+        Doc::Concat(vec![lhs, Doc::text(symbol), rhs])
+    }
+
+    fn member(&mut self, member: &MemberExpression) -> Doc {
+        let lhs = match &*member.lhs {
+            MemberExpressionLHS::Identifier(ident) => Doc::text(ident.name.as_str()),
+            MemberExpressionLHS::Expression(expr) => self.expression(expr),
+            MemberExpressionLHS::Member(member) => self.member(member),
+            MemberExpressionLHS::Call(call) => self.call(call),
+        };
+        let rhs = match &*member.rhs {
+            MemberExpressionRHS::Identifier(ident) => Doc::text(ident.name.as_str()),
+            MemberExpressionRHS::Number(number) => Doc::text(number.raw.as_str()),
+            MemberExpressionRHS::Member(member) => self.member(member),
+            MemberExpressionRHS::Call(call) => self.call(call),
+        };
+        let dot = if member.optional { "?." } else { "." };
+        Doc::Concat(vec![lhs, Doc::text(dot), rhs])
+    }
+
+    fn call(&mut self, call: &CallExpression) -> Doc {
+        let arguments = call
+            .arguments
+            .iter()
+            .map(|argument| self.expression(argument))
+            .collect();
+        Doc::Concat(vec![
+            self.expression(&call.callee),
+            list("(", arguments, ")", false),
+        ])
+    }
+
+    fn construction(&mut self, construction: &ConstructionExpression) -> Doc {
+        let fields = construction
+            .fields
+            .iter()
+            .map(|field| match field {
+                ConstructionField::Expression(expr) => self.expression(expr),
+                ConstructionField::KeyValueArgument(argument) => Doc::Concat(vec![
+                    Doc::text(format!("{}: ", argument.key.name.as_str())),
+                    self.expression(&argument.value),
+                ]),
+                ConstructionField::Spread(spread) => self.spread(&spread.element),
+            })
+            .collect();
+        list("{", fields, "}", true)
+    }
+}
+
+/// A branch of a match, Its body stays on the line of `header` if it is a single line that fits.
+fn branch(header: Doc, lines: Vec<Doc>) -> Doc {
+    if lines.len() == 1 && !lines[0].has_hard_line() {
+        return Doc::Group(vec![header, indented(lines, Doc::Line)]);
+    }
+    Doc::Concat(vec![header, indented(lines, Doc::HardLine)])
+}
+
+/// Indent the lines after `line`, Nothing is printed for no lines.
+fn indented(lines: Vec<Doc>, line: Doc) -> Doc {
+    if lines.is_empty() {
+        return Doc::nil();
+    }
+    Doc::Indent(vec![line, Doc::join(lines, || Doc::HardLine)])
+}
+
+/// A comma separated list, It is broken into a line per item if it doesn't fit. Lists with
+/// multiline items such as functions are kept on the lines of the items.
+fn list(open: &str, items: Vec<Doc>, close: &str, padded: bool) -> Doc {
+    if items.is_empty() {
+        return Doc::text(format!("{open}{close}"));
+    }
+    let padding = if padded { " " } else { "" };
+    if items.iter().any(Doc::has_hard_line) {
+        return Doc::Concat(vec![
+            Doc::text(format!("{open}{padding}")),
+            Doc::join(items, || Doc::text(", ")),
+            Doc::text(format!("{padding}{close}")),
+        ]);
+    }
+    let line = || if padded { Doc::Line } else { Doc::SoftLine };
+    Doc::Group(vec![
+        Doc::text(open),
+        Doc::Indent(vec![
+            line(),
+            Doc::join(items, || Doc::Concat(vec![Doc::text(","), Doc::Line])),
+        ]),
+        line(),
+        Doc::text(close),
+    ])
+}
+
+/// End of the last statement of a block, Or `start` if it is empty.
+fn block_end(block: &Block, start: u32) -> u32 {
+    block
+        .statements
+        .last()
+        .map_or(start, |statement| statement_span(statement).end)
+}
+
+fn statement_span(statement: &Statement) -> Span {
+    match statement {
+        Statement::Empty(stmt) => stmt.span,
+        Statement::Expression(expr) => expr.span(),
+        Statement::VariableDeclaration(decl) => decl.span,
+        Statement::FunctionDeclaration(function) => function.span,
+        Statement::EnumDeclaration(decl) => decl.span,
+        Statement::StructDeclaration(decl) => decl.span,
+        Statement::ImplStatement(decl) => decl.span,
+        Statement::TypeAliasDeclaration(decl) => decl.span,
+        Statement::UnionDeclaration(decl) => decl.span,
+        Statement::ExternDeclaration(decl) => decl.span,
+    }
+}
+
+fn extern_item_span(item: &ExternItem) -> Span {
+    match item {
+        ExternItem::Function(signature) => signature.span,
+        ExternItem::Global(global) => global.span,
+        ExternItem::Table(table) => table.span,
+    }
+}
+
+fn modifier(modifier: &VisibilityModifier) -> &'static str {
+    match modifier {
+        VisibilityModifier::Private => "",
+        VisibilityModifier::Public(_) => "pub ",
+    }
+}
+
+fn modifier_start(modifier: &VisibilityModifier) -> Option<u32> {
+    match modifier {
+        VisibilityModifier::Private => None,
+        VisibilityModifier::Public(span) => Some(span.start),
+    }
+}
+
+fn binding_pattern(pattern: &BindingPattern) -> String {
+    let mut binding = match &pattern.kind {
+        BindingPatternKind::Identifier(identifier) => binding_identifier(identifier),
+        BindingPatternKind::Tuple => "()".to_string(),
+    };
+    if pattern.optional {
+        binding.push('?');
+    }
+    if let Some(annotation) = &pattern.type_annotation {
+        binding.push_str(&format!(": {}", type_annotation(annotation)));
+    }
+    binding
+}
+
+fn binding_identifier(binding: &BindingIdentifier) -> String {
+    let name = binding.identifier.name.as_str();
+    if binding.mutable {
+        format!("mut {name}")
+    } else {
+        name.to_string()
+    }
+}
+
+fn type_annotation(annotation: &TypeAnnotation) -> String {
+    match &annotation.kind {
+        TypeAnnotationKind::Identifier(identifier) => identifier.name.as_str().to_string(),
+        // A single type needs a comma to be a tuple.
+        TypeAnnotationKind::Tuple(elements) if elements.len() == 1 => {
+            format!("({},)", type_annotation(&elements[0]))
+        }
+        TypeAnnotationKind::Tuple(elements) => format!("({})", type_list(elements)),
+        TypeAnnotationKind::Array(element) => format!("[{}]", type_annotation(element)),
+        TypeAnnotationKind::Function(function) => {
+            let mut text = format!("fn({})", type_list(&function.params));
+            if let Some(return_type) = &function.return_type {
+                text.push_str(&format!(" -> {}", type_annotation(return_type)));
+            }
+            text
+        }
+        // The `?` would be a part of the return type without the parentheses.
+        TypeAnnotationKind::Optional(inner) => match inner.kind {
+            TypeAnnotationKind::Function(_) => format!("({})?", type_annotation(inner)),
+            _ => format!("{}?", type_annotation(inner)),
+        },
+        TypeAnnotationKind::Generic(generic) => format!(
+            "{}<{}>",
+            generic.identifier.name.as_str(),
+            type_list(&generic.arguments)
+        ),
+    }
+}
+
+fn type_list(annotations: &[TypeAnnotation]) -> String {
+    let annotations: Vec<String> = annotations.iter().map(type_annotation).collect();
+    annotations.join(", ")
+}
+
+fn type_parameters(params: &[TypeParameter]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+    let params: Vec<String> = params
+        .iter()
+        .map(|param| {
+            let name = param.identifier.name.as_str();
+            if param.bounds.is_empty() {
+                return name.to_string();
+            }
+            let bounds: Vec<String> = param.bounds.iter().map(type_annotation).collect();
+            format!("{name}: {}", bounds.join(" + "))
+        })
+        .collect();
+    format!("<{}>", params.join(", "))
+}
+
+fn precedence(kind: &BinaryOperatorKind) -> Precedence {
+    use BinaryOperatorKind::*;
+    match kind {
+        Assignment(_) => Precedence::Assignment,
+        Coalesce(_) => Precedence::Coalesce,
+        LogicalOr(_) => Precedence::LogicalOr,
+        LogicalAnd(_) => Precedence::LogicalAnd,
+        BitwiseOr(_) => Precedence::BitwiseOr,
+        BitwiseXor(_) => Precedence::BitwiseXor,
+        BitwiseAnd(_) => Precedence::BitwiseAnd,
+        Equality(_) | NonEquality(_) => Precedence::Equality,
+        LessThanEqual(_) | LessThan(_) | GreaterThanEqual(_) | GreaterThan(_) => {
+            Precedence::Relational
+        }
+        ShiftLeft(_) | ShiftRight(_) => Precedence::Shift,
+        Plus(_) | Minus(_) => Precedence::Add,
+        Multiply(_) | Division(_) | FloorDivision(_) | Modulo(_) => Precedence::Multiply,
+        Exponential(_) => Precedence::Exponential,
+    }
+}
+
+fn operator(kind: &BinaryOperatorKind) -> &'static str {
+    use BinaryOperatorKind::*;
+    match kind {
+        Assignment(_) => "=",
+        Coalesce(_) => "??",
+        LogicalOr(_) => "or",
+        LogicalAnd(_) => "and",
+        BitwiseOr(_) => "|",
+        BitwiseXor(_) => "^",
+        BitwiseAnd(_) => "&",
+        Equality(_) => "==",
+        NonEquality(_) => "!=",
+        LessThanEqual(_) => "<=",
+        LessThan(_) => "<",
+        GreaterThanEqual(_) => ">=",
+        GreaterThan(_) => ">",
+        Plus(_) => "+",
+        Minus(_) => "-",
+        Multiply(_) => "*",
+        Exponential(_) => "**",
+        Division(_) => "/",
+        FloorDivision(_) => "//",
+        Modulo(_) => "%",
+        ShiftLeft(_) => "<<",
+        ShiftRight(_) => ">>",
+    }
+}
@@ -0,0 +1,27 @@
+mod doc;
+mod formatter;
+
+use fuse_parser::Parser;
+
+use formatter::Formatter;
+
+/// Width the formatter keeps the lines under where it can, In columns.
+pub const LINE_WIDTH: usize = 100;
+
+/// Indentation of a block.
+const INDENT: &str = "\t";
+/// Width of the indentation, In columns.
+const TAB_WIDTH: usize = 4;
+
+/// Format a chunk of Fuse code, It returns the parse errors if the code is invalid.
+///
+/// The output is canonical, Formatting it again returns it unchanged.
+pub fn format(source: &str) -> Result<String, Vec<fuse_parser::Error>> {
+    let (parsed, tokens) = Parser::new(source).parse_with_tokens();
+    match parsed.chunk {
+        Some(chunk) if parsed.errors.is_empty() => {
+            Ok(Formatter::new(source, &tokens).format(&chunk))
+        }
+        _ => Err(parsed.errors),
+    }
+}
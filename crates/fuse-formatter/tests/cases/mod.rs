@@ -0,0 +1,74 @@
+use std::{ffi::OsStr, fs, path::PathBuf};
+
+use fuse_formatter::format;
+
+struct Context<'a> {
+    root: PathBuf,
+    test_dir: PathBuf,
+    source_name: &'a str,
+    settings: insta::Settings,
+}
+
+impl<'a> Context<'a> {
+    fn path(&self) -> PathBuf {
+        self.root.join(&self.test_dir)
+    }
+
+    fn settings(&self) -> insta::Settings {
+        self.settings.clone()
+    }
+}
+
+#[test]
+fn pass() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("pass"),
+        source_name: "case.fuse",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run(&ctx, case);
+    }
+}
+
+// helpers
+fn load_cases(ctx: &Context) -> Vec<PathBuf> {
+    fs::read_dir(ctx.path())
+        .unwrap_or_else(|_| panic!("Failed to read {}", ctx.path().to_str().unwrap()))
+        .filter_map(|x| x.ok())
+        .filter(|x| x.metadata().is_ok_and(|meta| meta.is_dir()))
+        .map(|node| node.path())
+        .collect()
+}
+
+fn run(ctx: &Context, case_dir: PathBuf) {
+    let mut settings = ctx.settings();
+    let source_path = case_dir.join(ctx.source_name);
+    let path_str = source_path.to_str().unwrap_or("unknown source");
+    let source = read_source_normalized(source_path.as_os_str()).unwrap();
+
+    settings.set_input_file(&source_path);
+    settings.set_snapshot_path(case_dir);
+    settings.set_prepend_module_to_snapshot(false);
+
+    let _guard = settings.bind_to_scope();
+    test_format(path_str, &source);
+}
+
+fn test_format(path: &str, source: &str) {
+    let formatted =
+        format(source).unwrap_or_else(|errors| panic!("Failed to parse {path}: {errors:?}"));
+    let again = format(&formatted)
+        .unwrap_or_else(|errors| panic!("Failed to parse the formatted {path}: {errors:?}"));
+    assert_eq!(formatted, again, "Formatting {path} twice changes it.");
+
+    insta::assert_snapshot!("formatted", formatted);
+}
+
+fn read_source_normalized(path: &OsStr) -> Result<String, std::io::Error> {
+    fs::read_to_string(path).map(|it| it.replace("\r\n", "\n"))
+}
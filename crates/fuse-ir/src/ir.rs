@@ -1,4 +1,7 @@
-use fuse_ast::Identifier;
+use std::rc::Rc;
+
+use fuse_ast::{Atom, Identifier};
+use fuse_common::Span;
 
 pub enum PrimitiveType {
     Number,
@@ -19,3 +22,500 @@ impl PrimitiveType {
         }
     }
 }
+
+/// Index of a function in its `Module`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FunctionId(pub usize);
+
+/// Index of a basic block in its `Function`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BlockId(pub usize);
+
+/// Index of a local in its `Module`, Locals are unique across the whole module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LocalId(pub usize);
+
+/// A lowered chunk, It owns every function and local of the chunk.
+#[derive(Debug, Default)]
+pub struct Module {
+    pub functions: Vec<Function>,
+    pub locals: Vec<Local>,
+}
+
+impl Module {
+    /// The function holding the top level statements of the chunk.
+    pub const MAIN: FunctionId = FunctionId(0);
+
+    pub fn function(&self, id: FunctionId) -> &Function {
+        &self.functions[id.0]
+    }
+
+    pub fn function_mut(&mut self, id: FunctionId) -> &mut Function {
+        &mut self.functions[id.0]
+    }
+
+    pub fn local(&self, id: LocalId) -> &Local {
+        &self.locals[id.0]
+    }
+}
+
+#[derive(Debug)]
+pub struct Local {
+    pub name: Atom,
+    pub kind: LocalKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalKind {
+    /// A variable declared in the source code.
+    Variable,
+    /// A parameter of its function.
+    Parameter,
+    /// An intermediate value introduced by the lowering.
+    Temporary,
+}
+
+#[derive(Debug)]
+pub struct Function {
+    pub span: Span,
+    pub name: Option<Atom>,
+    pub params: Vec<LocalId>,
+    /// Rest parameter of variadic functions, It holds the extra arguments in an array.
+    pub rest: Option<LocalId>,
+    /// Locals owned by this function, Excluding its parameters.
+    pub locals: Vec<LocalId>,
+    /// Basic blocks of the function, The first one is the entry block.
+    pub blocks: Vec<BasicBlock>,
+}
+
+impl Function {
+    pub const ENTRY: BlockId = BlockId(0);
+
+    pub fn block(&self, id: BlockId) -> &BasicBlock {
+        &self.blocks[id.0]
+    }
+
+    pub fn block_mut(&mut self, id: BlockId) -> &mut BasicBlock {
+        &mut self.blocks[id.0]
+    }
+
+    /// Order the blocks in reverse postorder and drop the unreachable ones.
+    pub fn sort_blocks(&mut self) {
+        fn visit(
+            function: &Function,
+            block: BlockId,
+            visited: &mut [bool],
+            order: &mut Vec<BlockId>,
+        ) {
+            if visited[block.0] {
+                return;
+            }
+            visited[block.0] = true;
+            // Visit the else branches first so the then branches come first in reverse postorder.
+            for successor in function
+                .block(block)
+                .terminator
+                .successors()
+                .into_iter()
+                .rev()
+            {
+                visit(function, successor, visited, order);
+            }
+            order.push(block);
+        }
+
+        let mut visited = vec![false; self.blocks.len()];
+        let mut order = Vec::with_capacity(self.blocks.len());
+        visit(self, Self::ENTRY, &mut visited, &mut order);
+        order.reverse();
+
+        let mut ids = vec![None; self.blocks.len()];
+        for (index, block) in order.iter().enumerate() {
+            ids[block.0] = Some(BlockId(index));
+        }
+
+        let mut blocks: Vec<Option<BasicBlock>> = std::mem::take(&mut self.blocks)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.blocks = order
+            .iter()
+            .map(|block| blocks[block.0].take().unwrap())
+            .collect();
+        for block in &mut self.blocks {
+            for successor in block.terminator.successors_mut() {
+                *successor = ids[successor.0].expect("Jump to an unreachable block.");
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BasicBlock {
+    pub instructions: Vec<Instruction>,
+    pub terminator: Terminator,
+}
+
+impl BasicBlock {
+    pub fn new() -> Self {
+        Self {
+            instructions: Vec::new(),
+            terminator: Terminator::Unreachable,
+        }
+    }
+}
+
+impl Default for BasicBlock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    pub span: Span,
+    pub kind: InstructionKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstructionKind {
+    /// Store the value of `Rvalue` in the `Place`.
+    Assign(Place, Rvalue),
+    /// Call a function and discard its results.
+    Call(Operand, Vec<Operand>),
+    /// Copy the elements or fields of `source` into the `target` table.
+    Spread {
+        target: Operand,
+        source: Operand,
+        kind: SpreadKind,
+    },
+}
+
+impl InstructionKind {
+    /// Operands read by the instruction, Including the table and key of an indexed place.
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Self::Assign(place, value) => {
+                let mut operands = place.operands();
+                operands.extend(value.operands());
+                operands
+            }
+            Self::Call(callee, args) => std::iter::once(callee).chain(args).collect(),
+            Self::Spread { target, source, .. } => vec![target, source],
+        }
+    }
+
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Self::Assign(place, value) => {
+                let mut operands = place.operands_mut();
+                operands.extend(value.operands_mut());
+                operands
+            }
+            Self::Call(callee, args) => std::iter::once(callee).chain(args).collect(),
+            Self::Spread { target, source, .. } => vec![target, source],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpreadKind {
+    /// Append the array elements of the source.
+    Elements,
+    /// Copy every key of the source, Overwriting the existing keys.
+    Fields,
+}
+
+/// A location that can be assigned to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Place {
+    Local(LocalId),
+    Global(Atom),
+    /// A key of a table, e.g. `point.x` or `array[1]`.
+    Index(Operand, Operand),
+}
+
+impl Place {
+    /// Operands read to find the place.
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Self::Local(_) | Self::Global(_) => Vec::new(),
+            Self::Index(table, key) => vec![table, key],
+        }
+    }
+
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Self::Local(_) | Self::Global(_) => Vec::new(),
+            Self::Index(table, key) => vec![table, key],
+        }
+    }
+}
+
+/// An atomic value, Evaluating it has no side effects.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    Local(LocalId),
+    Global(Atom),
+    Constant(Constant),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Nil,
+    Boolean(bool),
+    Number(Number),
+    String(Atom),
+}
+
+/// A number constant, It keeps whether it is an integer or a float since the targets with an
+/// integer subtype treat them differently, e.g. `math.type`, `//` and `tostring`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Number {
+    pub value: f64,
+    pub kind: NumberKind,
+    /// Text of the literal in the source, Only kept if Lua reads it as the same number and it
+    /// differs from the text `lua_literal` would write otherwise.
+    pub raw: Option<Atom>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberKind {
+    Integer,
+    Float,
+}
+
+impl Number {
+    /// Integers from `-MAX_SAFE_INTEGER` to `MAX_SAFE_INTEGER` are exactly representable.
+    pub const MAX_SAFE_INTEGER: f64 = 9007199254740992.0;
+
+    pub fn integer(value: i64) -> Self {
+        Self {
+            value: value as f64,
+            kind: NumberKind::Integer,
+            raw: None,
+        }
+    }
+
+    pub fn float(value: f64) -> Self {
+        Self {
+            value,
+            kind: NumberKind::Float,
+            raw: None,
+        }
+    }
+
+    /// Number written as `raw`, e.g. a literal of the source or of the textual IR.
+    pub fn literal(value: f64, kind: NumberKind, raw: &str) -> Self {
+        let mut number = Self {
+            value,
+            kind,
+            raw: None,
+        };
+        let raw = raw.replace('_', "");
+        if is_lua_number(&raw, value) && number.lua_literal().as_deref() != Some(raw.as_str()) {
+            number.raw = Some(Atom(Rc::from(raw)));
+        }
+        number
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.kind == NumberKind::Integer
+    }
+
+    /// Value of an integer, `None` for floats and integers that aren't exactly representable.
+    pub fn as_integer(&self) -> Option<i64> {
+        let is_safe = self.value.fract() == 0.0 && self.value.abs() < Self::MAX_SAFE_INTEGER;
+        (self.is_integer() && is_safe).then_some(self.value as i64)
+    }
+
+    /// Text of the number as a Lua literal, Floats always have a fraction or an exponent so
+    /// they stay floats, e.g. `3.0` and `-0.0`. It is `None` for NaN and the infinities which
+    /// have no literal.
+    pub fn lua_literal(&self) -> Option<String> {
+        if !self.value.is_finite() {
+            return None;
+        }
+        if let Some(raw) = &self.raw {
+            return Some(raw.as_str().to_string());
+        }
+        // Integers out of the 64 bits range are read as floats by Lua anyway.
+        let fits = self.value.fract() == 0.0 && self.value.abs() < 9.223372036854776e18;
+        Some(match self.kind {
+            NumberKind::Integer if fits => format!("{}", self.value as i64),
+            _ => format!("{:?}", self.value),
+        })
+    }
+}
+
+/// Returns true if Lua reads `raw` as the number `value`, Hexadecimal integers wrap around
+/// past 64 bits and binary literals don't exist in Lua.
+fn is_lua_number(raw: &str, value: f64) -> bool {
+    if let Some(digits) = raw.strip_prefix("0x").or_else(|| raw.strip_prefix("0X")) {
+        return !digits.is_empty()
+            && digits.chars().all(|char| char.is_ascii_hexdigit())
+            && value < 9.223372036854776e18;
+    }
+    raw.starts_with(|char: char| char.is_ascii_digit())
+        && raw
+            .chars()
+            .all(|char| char.is_ascii_digit() || matches!(char, '.' | 'e' | 'E' | '+' | '-'))
+        && raw.parse::<f64>() == Ok(value)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rvalue {
+    Use(Operand),
+    Unary(UnaryOp, Operand),
+    Binary(BinaryOp, Operand, Operand),
+    /// Concatenation of strings, e.g. the segments of an interpolated string.
+    Concat(Vec<Operand>),
+    Call(Operand, Vec<Operand>),
+    /// Read a key of a table.
+    Index(Operand, Operand),
+    /// A new table with the given entries.
+    Table(Vec<TableEntry>),
+    /// Instance of a function of the module, Capturing the locals it uses.
+    Closure(FunctionId),
+    /// Extra arguments passed to a variadic function, As an array.
+    VarArgs,
+}
+
+impl Rvalue {
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Self::Use(value) | Self::Unary(_, value) => vec![value],
+            Self::Binary(_, lhs, rhs) | Self::Index(lhs, rhs) => vec![lhs, rhs],
+            Self::Concat(values) => values.iter().collect(),
+            Self::Call(callee, args) => std::iter::once(callee).chain(args).collect(),
+            Self::Table(entries) => entries.iter().flat_map(TableEntry::operands).collect(),
+            Self::Closure(_) | Self::VarArgs => Vec::new(),
+        }
+    }
+
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Self::Use(value) | Self::Unary(_, value) => vec![value],
+            Self::Binary(_, lhs, rhs) | Self::Index(lhs, rhs) => vec![lhs, rhs],
+            Self::Concat(values) => values.iter_mut().collect(),
+            Self::Call(callee, args) => std::iter::once(callee).chain(args).collect(),
+            Self::Table(entries) => entries
+                .iter_mut()
+                .flat_map(TableEntry::operands_mut)
+                .collect(),
+            Self::Closure(_) | Self::VarArgs => Vec::new(),
+        }
+    }
+
+    /// Returns true if evaluating the value may have side effects other than raising an error.
+    pub fn has_side_effects(&self) -> bool {
+        matches!(self, Self::Call(..))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableEntry {
+    Positional(Operand),
+    Keyed(Operand, Operand),
+}
+
+impl TableEntry {
+    pub fn operands(&self) -> Vec<&Operand> {
+        match self {
+            Self::Positional(value) => vec![value],
+            Self::Keyed(key, value) => vec![key, value],
+        }
+    }
+
+    pub fn operands_mut(&mut self) -> Vec<&mut Operand> {
+        match self {
+            Self::Positional(value) => vec![value],
+            Self::Keyed(key, value) => vec![key, value],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Not,
+    Negate,
+    /// Length of a string or an array.
+    Length,
+    /// Debug representation of a value as a string, e.g. `{ x: 1 }` for tables.
+    Debug,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    FloorDivide,
+    Modulo,
+    Power,
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanEqual,
+    GreaterThan,
+    GreaterThanEqual,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
+    /// Logical and, Only used when the rhs has no side effects.
+    And,
+    /// Logical or, Only used when the rhs has no side effects.
+    Or,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Terminator {
+    Goto(BlockId),
+    /// Jump to `then` if the condition is truthy, Otherwise to `else`.
+    Branch {
+        condition: Operand,
+        then: BlockId,
+        r#else: BlockId,
+    },
+    Return(Option<Operand>),
+    /// Control never leaves the block.
+    Unreachable,
+}
+
+impl Terminator {
+    pub fn successors(&self) -> Vec<BlockId> {
+        match self {
+            Self::Goto(target) => vec![*target],
+            Self::Branch { then, r#else, .. } => vec![*then, *r#else],
+            Self::Return(_) | Self::Unreachable => Vec::new(),
+        }
+    }
+
+    pub fn successors_mut(&mut self) -> Vec<&mut BlockId> {
+        match self {
+            Self::Goto(target) => vec![target],
+            Self::Branch { then, r#else, .. } => vec![then, r#else],
+            Self::Return(_) | Self::Unreachable => Vec::new(),
+        }
+    }
+
+    /// Operand read by the terminator.
+    pub fn operand(&self) -> Option<&Operand> {
+        match self {
+            Self::Branch { condition, .. } => Some(condition),
+            Self::Return(value) => value.as_ref(),
+            Self::Goto(_) | Self::Unreachable => None,
+        }
+    }
+
+    pub fn operand_mut(&mut self) -> Option<&mut Operand> {
+        match self {
+            Self::Branch { condition, .. } => Some(condition),
+            Self::Return(value) => value.as_mut(),
+            Self::Goto(_) | Self::Unreachable => None,
+        }
+    }
+}
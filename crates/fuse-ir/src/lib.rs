@@ -1,3 +1,11 @@
 mod ir;
+mod lower;
+mod passes;
+mod text;
 
 pub use ir::*;
+pub use lower::lower;
+pub use passes::{
+    ConstantFolding, CopyPropagation, DeadCodeElimination, Inlining, Pass, Pipeline, PruneBranches,
+};
+pub use text::{parse_module, ParseError};
@@ -0,0 +1,623 @@
+use std::rc::Rc;
+
+use fuse_ast::{
+    ArrayExpressionElement, Atom, BinaryOperator, BinaryOperatorKind, Block, CallExpression,
+    ConstructionExpression, ConstructionField, Else, Expression, GetSpan, Identifier, If,
+    InterpolationFormat, MemberExpression, MemberExpressionLHS, MemberExpressionRHS, NumberKind,
+    NumberLiteral, Statement, StringLiteral, StringSegment, TupleExpressionElement, UnaryOperator,
+    UnaryOperatorKind,
+};
+use fuse_common::Span;
+use fuse_semantic::{SymbolKind, Type};
+
+use super::{Chain, Lowering, Mark};
+use crate::{
+    BinaryOp, Constant, InstructionKind, LocalId, Number, Operand, Place, Rvalue, SpreadKind,
+    TableEntry, Terminator, UnaryOp,
+};
+
+/// An element of array, tuple and table constructions.
+enum Element<'e> {
+    Positional(&'e Expression),
+    Keyed(Atom, &'e Expression),
+    Spread(&'e Expression),
+}
+
+impl<'a> Lowering<'a> {
+    /// Lower an expression into an operand, Storing its value in a temporary if needed.
+    pub(super) fn lower_operand(&mut self, expr: &Expression) -> Operand {
+        let value = self.lower_rvalue(expr);
+        self.operand_of(expr.span(), value)
+    }
+
+    pub(super) fn lower_rvalue(&mut self, expr: &Expression) -> Rvalue {
+        let span = expr.span();
+        match expr {
+            Expression::NilLiteral(_) => Rvalue::Use(Operand::Constant(Constant::Nil)),
+            Expression::NumberLiteral(lit) => Rvalue::Use(number_constant(lit)),
+            Expression::StringLiteral(lit) => self.lower_string(lit),
+            Expression::BooleanLiteral(lit) => {
+                Rvalue::Use(Operand::Constant(Constant::Boolean(lit.value)))
+            }
+            Expression::Identifier(ident) => Rvalue::Use(self.identifier_operand(ident)),
+            Expression::Function(func) => Rvalue::Closure(self.lower_function(func, None)),
+            Expression::If(r#if) => {
+                let result = self.temporary();
+                self.lower_if(r#if, Some(result));
+                Rvalue::Use(Operand::Local(result))
+            }
+            Expression::Match(r#match) => {
+                let result = self.temporary();
+                self.lower_match(r#match, Some(result));
+                Rvalue::Use(Operand::Local(result))
+            }
+            Expression::UnaryOperator(op) => self.lower_unary_operator(op),
+            Expression::BinaryOperator(op) => self.lower_binary_operator(op),
+            Expression::ArrayExpression(array) => {
+                let elements = array
+                    .elements
+                    .iter()
+                    .map(|element| match element {
+                        ArrayExpressionElement::Expression(expr) => Element::Positional(expr),
+                        ArrayExpressionElement::Spread(arg) => Element::Spread(&arg.element),
+                    })
+                    .collect();
+                self.lower_table(span, elements, SpreadKind::Elements)
+            }
+            Expression::TupleExpression(tuple) => {
+                let elements = tuple
+                    .elements
+                    .iter()
+                    .map(|element| match element {
+                        TupleExpressionElement::Expression(expr) => Element::Positional(expr),
+                        TupleExpressionElement::Spread(arg) => Element::Spread(&arg.element),
+                    })
+                    .collect();
+                self.lower_table(span, elements, SpreadKind::Elements)
+            }
+            Expression::ParenthesizedExpression(expr) => self.lower_rvalue(&expr.expression),
+            Expression::MemberExpression(member) => self.lower_member_expression(member),
+            Expression::CallExpression(call) => self.lower_call_expression(call),
+            Expression::TableConstructionExpression(construction) => {
+                self.lower_construction(construction, false)
+            }
+            Expression::StructConstructionExpression(expr) => {
+                // Structs are plain tables, The target only matters to the type checker.
+                if !matches!(expr.target, Expression::Identifier(_)) {
+                    self.lower_effect(&expr.target);
+                }
+                self.lower_construction(&expr.construction, true)
+            }
+        }
+    }
+
+    /// Lower an expression for its side effects, discarding its value.
+    pub(super) fn lower_effect(&mut self, expr: &Expression) {
+        match expr {
+            Expression::If(r#if) => self.lower_if(r#if, None),
+            Expression::Match(r#match) => self.lower_match(r#match, None),
+            Expression::ParenthesizedExpression(expr) => self.lower_effect(&expr.expression),
+            Expression::BinaryOperator(op)
+                if matches!(op.kind, BinaryOperatorKind::Assignment(_)) =>
+            {
+                self.lower_assignment(op);
+            }
+            expr => {
+                if let Rvalue::Call(callee, args) = self.lower_rvalue(expr) {
+                    self.emit(expr.span(), InstructionKind::Call(callee, args));
+                }
+            }
+        }
+    }
+
+    pub(super) fn lower_if(&mut self, r#if: &If, result: Option<LocalId>) {
+        let condition = self.lower_operand(&r#if.cond);
+        let then = self.new_block();
+        let r#else = self.new_block();
+        let merge = self.new_block();
+        self.terminate(Terminator::Branch {
+            condition,
+            then,
+            r#else,
+        });
+
+        self.switch_to(then);
+        self.lower_branch(&r#if.body, result, r#if.span);
+        self.terminate(Terminator::Goto(merge));
+
+        self.switch_to(r#else);
+        match &r#if.r#else {
+            Some(Else::If(r#if)) => self.lower_if(r#if, result),
+            Some(Else::Block(block)) => self.lower_branch(block, result, r#if.span),
+            None => self.assign_nil(r#if.span, result),
+        }
+        self.goto(merge);
+    }
+
+    /// Lower a block of a conditional expression, Storing its value in the `result`. The
+    /// assignment takes the span of the last expression of the block if it has one.
+    pub(super) fn lower_branch(&mut self, block: &Block, result: Option<LocalId>, span: Span) {
+        let value = self.lower_block(block, result.is_some());
+        if let Some(result) = result {
+            let span = match block.statements.last() {
+                Some(Statement::Expression(expr)) => expr.span(),
+                _ => span,
+            };
+            let value = value.unwrap_or(Operand::Constant(Constant::Nil));
+            self.assign(span, Place::Local(result), Rvalue::Use(value));
+        }
+    }
+
+    pub(super) fn assign_nil(&mut self, span: Span, result: Option<LocalId>) {
+        if let Some(result) = result {
+            self.assign(
+                span,
+                Place::Local(result),
+                Rvalue::Use(Operand::Constant(Constant::Nil)),
+            );
+        }
+    }
+
+    fn lower_string(&mut self, lit: &StringLiteral) -> Rvalue {
+        let mut parts = Vec::with_capacity(lit.segments.len());
+        let mut marks = Vec::with_capacity(lit.segments.len());
+        for segment in &lit.segments {
+            match segment {
+                StringSegment::Literal(segment) => parts.push(Operand::Constant(Constant::String(
+                    self.literal_text(segment),
+                ))),
+                StringSegment::Interpolated(segment) => {
+                    let span = segment.expression.span();
+                    let value = self.lower_operand(&segment.expression);
+                    let part = match segment.format {
+                        InterpolationFormat::Debug => {
+                            self.operand_of(span, Rvalue::Unary(UnaryOp::Debug, value))
+                        }
+                        // Strings and numbers are concatenated as is.
+                        InterpolationFormat::Display
+                            if matches!(
+                                value,
+                                Operand::Constant(Constant::String(_) | Constant::Number(_))
+                            ) || self.is_concatenable(&segment.expression) =>
+                        {
+                            value
+                        }
+                        InterpolationFormat::Display => self.operand_of(
+                            span,
+                            Rvalue::Call(Operand::Global(Atom(Rc::from("tostring"))), vec![value]),
+                        ),
+                    };
+                    parts.push(part);
+                }
+            }
+            marks.push((self.mark(), lit.span));
+        }
+        self.pin(
+            parts
+                .iter_mut()
+                .zip(marks)
+                .map(|(operand, (mark, span))| (operand, mark, span)),
+        );
+        match parts.len() {
+            0 => Rvalue::Use(Operand::Constant(Constant::String(Atom(Rc::from(""))))),
+            1 => Rvalue::Use(parts.pop().unwrap()),
+            _ => Rvalue::Concat(parts),
+        }
+    }
+
+    /// Returns true for the expressions known to be strings or numbers, `..` accepts them
+    /// without converting them first.
+    fn is_concatenable(&self, expr: &Expression) -> bool {
+        match expr {
+            Expression::NumberLiteral(_) | Expression::StringLiteral(_) => true,
+            Expression::Identifier(ident) => self
+                .semantic
+                .symbols
+                .of(ident)
+                .is_some_and(|symbol| matches!(symbol.ty, Type::Number | Type::String)),
+            Expression::ParenthesizedExpression(expr) => self.is_concatenable(&expr.expression),
+            _ => false,
+        }
+    }
+
+    fn lower_unary_operator(&mut self, op: &UnaryOperator) -> Rvalue {
+        let operand = self.lower_operand(&op.expression);
+        match op.kind {
+            UnaryOperatorKind::Not(_) => Rvalue::Unary(UnaryOp::Not, operand),
+            UnaryOperatorKind::Minus(_) => Rvalue::Unary(UnaryOp::Negate, operand),
+            UnaryOperatorKind::Plus(_) => Rvalue::Use(operand),
+        }
+    }
+
+    fn lower_binary_operator(&mut self, op: &BinaryOperator) -> Rvalue {
+        let span = op.span();
+        let kind = match op.kind {
+            BinaryOperatorKind::Assignment(_) => return Rvalue::Use(self.lower_assignment(op)),
+            BinaryOperatorKind::Coalesce(_) => return self.lower_coalesce(op),
+            BinaryOperatorKind::LogicalAnd(_) | BinaryOperatorKind::LogicalOr(_) => {
+                let kind = match op.kind {
+                    BinaryOperatorKind::LogicalAnd(_) => BinaryOp::And,
+                    _ => BinaryOp::Or,
+                };
+                let lhs = self.lower_operand(&op.lhs);
+                return self.short_circuit(span, lhs, kind, |this| this.lower_operand(&op.rhs));
+            }
+            BinaryOperatorKind::BitwiseOr(_) => BinaryOp::BitwiseOr,
+            BinaryOperatorKind::BitwiseXor(_) => BinaryOp::BitwiseXor,
+            BinaryOperatorKind::BitwiseAnd(_) => BinaryOp::BitwiseAnd,
+            BinaryOperatorKind::Equality(_) => BinaryOp::Equal,
+            BinaryOperatorKind::NonEquality(_) => BinaryOp::NotEqual,
+            BinaryOperatorKind::LessThanEqual(_) => BinaryOp::LessThanEqual,
+            BinaryOperatorKind::LessThan(_) => BinaryOp::LessThan,
+            BinaryOperatorKind::GreaterThanEqual(_) => BinaryOp::GreaterThanEqual,
+            BinaryOperatorKind::GreaterThan(_) => BinaryOp::GreaterThan,
+            BinaryOperatorKind::Plus(_) => BinaryOp::Add,
+            BinaryOperatorKind::Minus(_) => BinaryOp::Subtract,
+            BinaryOperatorKind::Multiply(_) => BinaryOp::Multiply,
+            BinaryOperatorKind::Exponential(_) => BinaryOp::Power,
+            BinaryOperatorKind::Division(_) => BinaryOp::Divide,
+            BinaryOperatorKind::FloorDivision(_) => BinaryOp::FloorDivide,
+            BinaryOperatorKind::Modulo(_) => BinaryOp::Modulo,
+            BinaryOperatorKind::ShiftLeft(_) => BinaryOp::ShiftLeft,
+            BinaryOperatorKind::ShiftRight(_) => BinaryOp::ShiftRight,
+        };
+        let mut operands = self.lower_operands([&op.lhs, &op.rhs]);
+        let rhs = operands.pop().unwrap();
+        let lhs = operands.pop().unwrap();
+        Rvalue::Binary(kind, lhs, rhs)
+    }
+
+    /// Lower an assignment, Returns the assigned value which is the value of the expression.
+    fn lower_assignment(&mut self, op: &BinaryOperator) -> Operand {
+        let place = self.lower_place(&op.lhs);
+        let value = self.lower_operand(&op.rhs);
+        self.assign(op.span(), place, Rvalue::Use(value.clone()));
+        value
+    }
+
+    fn lower_place(&mut self, expr: &Expression) -> Place {
+        match expr {
+            Expression::Identifier(ident) => self.identifier_place(ident),
+            Expression::ParenthesizedExpression(expr) => self.lower_place(&expr.expression),
+            Expression::MemberExpression(member) => {
+                let base = self.lower_member_base(member, None);
+                match member.rhs.as_ref() {
+                    MemberExpressionRHS::Identifier(ident) => {
+                        Place::Index(base, string_constant(&ident.name))
+                    }
+                    MemberExpressionRHS::Number(index) => Place::Index(base, tuple_index(index)),
+                    _ => Place::Local(self.temporary()),
+                }
+            }
+            // Other expressions aren't assignable, The type checker reports them.
+            expr => {
+                self.lower_effect(expr);
+                Place::Local(self.temporary())
+            }
+        }
+    }
+
+    fn lower_coalesce(&mut self, op: &BinaryOperator) -> Rvalue {
+        let span = op.span();
+        let lhs = self.lower_operand(&op.lhs);
+        let result = self.temporary();
+        self.assign(span, Place::Local(result), Rvalue::Use(lhs));
+        let missing = self.operand_of(
+            span,
+            Rvalue::Binary(
+                BinaryOp::Equal,
+                Operand::Local(result),
+                Operand::Constant(Constant::Nil),
+            ),
+        );
+        let rhs_block = self.new_block();
+        let merge = self.new_block();
+        self.terminate(Terminator::Branch {
+            condition: missing,
+            then: rhs_block,
+            r#else: merge,
+        });
+
+        self.switch_to(rhs_block);
+        let rhs = self.lower_operand(&op.rhs);
+        self.assign(span, Place::Local(result), Rvalue::Use(rhs));
+        self.goto(merge);
+        Rvalue::Use(Operand::Local(result))
+    }
+
+    /// Lower arrays, tuples and tables. Elements after the first spread are added one by one.
+    fn lower_table(&mut self, span: Span, elements: Vec<Element>, spread: SpreadKind) -> Rvalue {
+        let mut entries = Vec::with_capacity(elements.len());
+        let mut marks = Vec::with_capacity(elements.len());
+        let mut table: Option<Operand> = None;
+        for element in elements {
+            let Some(target) = table.clone() else {
+                match element {
+                    Element::Positional(expr) => {
+                        entries.push(TableEntry::Positional(self.lower_operand(expr)));
+                        marks.push((self.mark(), expr.span()));
+                        continue;
+                    }
+                    Element::Keyed(key, expr) => {
+                        let value = self.lower_operand(expr);
+                        entries.push(TableEntry::Keyed(string_constant(&key), value));
+                        marks.push((self.mark(), expr.span()));
+                        continue;
+                    }
+                    Element::Spread(expr) => {
+                        self.pin_entries(&mut entries, std::mem::take(&mut marks));
+                        let target =
+                            self.operand_of(span, Rvalue::Table(std::mem::take(&mut entries)));
+                        table = Some(target.clone());
+                        let source = self.lower_operand(expr);
+                        self.emit(
+                            span,
+                            InstructionKind::Spread {
+                                target,
+                                source,
+                                kind: spread,
+                            },
+                        );
+                        continue;
+                    }
+                }
+            };
+            match element {
+                Element::Positional(expr) => {
+                    let value = self.lower_operand(expr);
+                    let length =
+                        self.operand_of(span, Rvalue::Unary(UnaryOp::Length, target.clone()));
+                    let index = self.operand_of(
+                        span,
+                        Rvalue::Binary(
+                            BinaryOp::Add,
+                            length,
+                            Operand::Constant(Constant::Number(Number::integer(1))),
+                        ),
+                    );
+                    self.assign(span, Place::Index(target, index), Rvalue::Use(value));
+                }
+                Element::Keyed(key, expr) => {
+                    let value = self.lower_operand(expr);
+                    self.assign(
+                        span,
+                        Place::Index(target, string_constant(&key)),
+                        Rvalue::Use(value),
+                    );
+                }
+                Element::Spread(expr) => {
+                    let source = self.lower_operand(expr);
+                    self.emit(
+                        span,
+                        InstructionKind::Spread {
+                            target,
+                            source,
+                            kind: spread,
+                        },
+                    );
+                }
+            }
+        }
+        match table {
+            Some(table) => Rvalue::Use(table),
+            None => {
+                self.pin_entries(&mut entries, marks);
+                Rvalue::Table(entries)
+            }
+        }
+    }
+
+    /// Pin the values of table entries read before the later elements are evaluated.
+    fn pin_entries(&mut self, entries: &mut [TableEntry], marks: Vec<(Mark, Span)>) {
+        self.pin(
+            entries
+                .iter_mut()
+                .zip(marks)
+                .flat_map(|(entry, (mark, span))| {
+                    entry
+                        .operands_mut()
+                        .into_iter()
+                        .map(move |operand| (operand, mark, span))
+                })
+                .collect::<Vec<_>>()
+                .into_iter(),
+        );
+    }
+
+    /// Lower table and struct constructions, Identifiers are shorthands for keys in structs.
+    fn lower_construction(
+        &mut self,
+        construction: &ConstructionExpression,
+        shorthand: bool,
+    ) -> Rvalue {
+        let elements = construction
+            .fields
+            .iter()
+            .map(|field| match field {
+                ConstructionField::Expression(expr @ Expression::Identifier(ident))
+                    if shorthand =>
+                {
+                    Element::Keyed(ident.name.clone(), expr)
+                }
+                ConstructionField::Expression(expr) => Element::Positional(expr),
+                ConstructionField::KeyValueArgument(kv) => {
+                    Element::Keyed(kv.key.name.clone(), &kv.value)
+                }
+                ConstructionField::Spread(arg) => Element::Spread(&arg.element),
+            })
+            .collect();
+        self.lower_table(construction.span, elements, SpreadKind::Fields)
+    }
+
+    fn lower_member_expression(&mut self, member: &MemberExpression) -> Rvalue {
+        if member.optional || is_optional_chain(&member.lhs) {
+            self.lower_optional_chain(member.span, |this, chain| {
+                this.lower_member_link(member, Some(chain))
+            })
+        } else {
+            self.lower_member_link(member, None)
+        }
+    }
+
+    fn lower_member_link(&mut self, member: &MemberExpression, chain: Option<Chain>) -> Rvalue {
+        if let Some(variant) = self.union_variant(member) {
+            return variant_value(variant, Vec::new());
+        }
+        let base = self.lower_member_base(member, chain);
+        self.lower_member_rhs(base, &member.rhs)
+    }
+
+    /// Lower the value being accessed by a member expression.
+    fn lower_member_base(&mut self, member: &MemberExpression, chain: Option<Chain>) -> Operand {
+        let base = match member.lhs.as_ref() {
+            MemberExpressionLHS::Identifier(ident) => self.identifier_operand(ident),
+            MemberExpressionLHS::Expression(expr) => self.lower_operand(expr),
+            MemberExpressionLHS::Member(lhs) => {
+                let value = self.lower_member_link(lhs, chain);
+                self.operand_of(lhs.span, value)
+            }
+            MemberExpressionLHS::Call(call) => {
+                let value = self.lower_call_link(call, chain);
+                self.operand_of(call.span, value)
+            }
+        };
+        if member.optional {
+            let chain = chain.expect("Optional members are lowered as a part of optional chains.");
+            self.check_chain_link(member.lhs.span(), &base, chain);
+        }
+        base
+    }
+
+    fn lower_member_rhs(&mut self, base: Operand, rhs: &MemberExpressionRHS) -> Rvalue {
+        match rhs {
+            MemberExpressionRHS::Identifier(ident) => self.lower_field_access(base, ident),
+            // Tuples are arrays, Their indices start at one.
+            MemberExpressionRHS::Number(index) => Rvalue::Index(base, tuple_index(index)),
+            // The parser only puts identifiers and numbers on the rhs of members.
+            MemberExpressionRHS::Member(_) | MemberExpressionRHS::Call(_) => {
+                Rvalue::Use(Operand::Constant(Constant::Nil))
+            }
+        }
+    }
+
+    /// Methods are dispatched statically, Other fields are keys of the table.
+    fn lower_field_access(&mut self, base: Operand, ident: &Identifier) -> Rvalue {
+        match (self.symbol_kind(ident), ident.reference.get()) {
+            (Some(SymbolKind::Method), Some(reference)) => {
+                Rvalue::Use(Operand::Local(self.local_of(reference)))
+            }
+            _ => Rvalue::Index(base, string_constant(&ident.name)),
+        }
+    }
+
+    fn lower_call_expression(&mut self, call: &CallExpression) -> Rvalue {
+        match &call.callee {
+            Expression::MemberExpression(member)
+                if member.optional || is_optional_chain(&member.lhs) =>
+            {
+                self.lower_optional_chain(call.span, |this, chain| {
+                    this.lower_call_link(call, Some(chain))
+                })
+            }
+            _ => self.lower_call_link(call, None),
+        }
+    }
+
+    fn lower_call_link(&mut self, call: &CallExpression, chain: Option<Chain>) -> Rvalue {
+        let mut callee = match &call.callee {
+            Expression::MemberExpression(member) => {
+                if let Some(variant) = self.union_variant(member) {
+                    let args = self.lower_operands(&call.arguments);
+                    return variant_value(variant, args);
+                }
+                let value = self.lower_member_link(member, chain);
+                self.operand_of(member.span, value)
+            }
+            callee => self.lower_operand(callee),
+        };
+        let mark = self.mark();
+        let args = self.lower_operands(&call.arguments);
+        self.pin(std::iter::once((&mut callee, mark, call.callee.span())));
+        Rvalue::Call(callee, args)
+    }
+
+    /// Lower sibling operands in order, A variable read by an operand is copied into a temporary
+    /// if a later sibling may change it, e.g. `c` in `c + bump()`.
+    fn lower_operands<'e>(
+        &mut self,
+        exprs: impl IntoIterator<Item = &'e Expression>,
+    ) -> Vec<Operand> {
+        let mut operands = Vec::new();
+        let mut marks = Vec::new();
+        for expr in exprs {
+            operands.push(self.lower_operand(expr));
+            marks.push((self.mark(), expr.span()));
+        }
+        self.pin(
+            operands
+                .iter_mut()
+                .zip(marks)
+                .map(|(operand, (mark, span))| (operand, mark, span)),
+        );
+        operands
+    }
+
+    /// Returns the variant if the member expression refers to a variant of a union, e.g. `Shape.Circle`.
+    fn union_variant<'m>(&self, member: &'m MemberExpression) -> Option<&'m Identifier> {
+        let (MemberExpressionLHS::Identifier(target), MemberExpressionRHS::Identifier(variant)) =
+            (member.lhs.as_ref(), member.rhs.as_ref())
+        else {
+            return None;
+        };
+        (self.symbol_kind(target) == Some(SymbolKind::Union)).then_some(variant)
+    }
+}
+
+/// Values of union variants are tables holding the name of the variant in `tag` and their fields in order.
+fn variant_value(variant: &Identifier, fields: Vec<Operand>) -> Rvalue {
+    let mut entries = Vec::with_capacity(fields.len() + 1);
+    entries.push(TableEntry::Keyed(
+        string_constant(&Atom(Rc::from("tag"))),
+        string_constant(&variant.name),
+    ));
+    entries.extend(fields.into_iter().map(TableEntry::Positional));
+    Rvalue::Table(entries)
+}
+
+pub(super) fn string_constant(value: &Atom) -> Operand {
+    Operand::Constant(Constant::String(value.clone()))
+}
+
+/// Number literal as written in the source, Literals with a fraction or an exponent are floats.
+pub(super) fn number_constant(lit: &NumberLiteral) -> Operand {
+    let kind = match lit.kind {
+        NumberKind::Float => crate::NumberKind::Float,
+        NumberKind::Decimal | NumberKind::Hexadecimal | NumberKind::Binary => {
+            crate::NumberKind::Integer
+        }
+    };
+    Operand::Constant(Constant::Number(Number::literal(
+        lit.value,
+        kind,
+        lit.raw.as_str(),
+    )))
+}
+
+/// Index of a tuple element, Tuples are arrays and their indices start at one.
+fn tuple_index(index: &NumberLiteral) -> Operand {
+    Operand::Constant(Constant::Number(Number::integer(index.value as i64 + 1)))
+}
+
+/// Returns true if the accessed value is a part of an optional chain,
+/// e.g. `b` in `a?.b.c`, Which evaluates to nil if any link is nil.
+fn is_optional_chain(lhs: &MemberExpressionLHS) -> bool {
+    let member = match lhs {
+        MemberExpressionLHS::Member(member) => member,
+        MemberExpressionLHS::Call(call) => match &call.callee {
+            Expression::MemberExpression(member) => member,
+            _ => return false,
+        },
+        _ => return false,
+    };
+    member.optional || is_optional_chain(&member.lhs)
+}
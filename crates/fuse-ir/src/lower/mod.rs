@@ -0,0 +1,439 @@
+mod expressions;
+mod patterns;
+mod statements;
+
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+use fuse_ast::{Atom, Block, Chunk, Function as AstFunction, FunctionBody, Identifier, Statement};
+use fuse_common::{ReferenceType, Span};
+use fuse_semantic::{SemanticResult, SymbolKind, Type};
+use fuse_visitor::Visitor;
+
+use crate::{
+    BasicBlock, BinaryOp, BlockId, Constant, Function, FunctionId, Instruction, InstructionKind,
+    Local, LocalId, LocalKind, Module, Operand, Place, Rvalue, Terminator,
+};
+
+/// Lower a resolved chunk into a `Module`, `source` is the code the chunk is parsed from.
+pub fn lower(chunk: &Chunk, semantic: &SemanticResult, source: &str) -> Module {
+    Lowering::new(semantic, source).lower_chunk(chunk)
+}
+
+/// Lowering, It translates the statements and expressions of a chunk into basic blocks.
+pub(crate) struct Lowering<'a> {
+    semantic: &'a SemanticResult,
+    source: &'a str,
+    module: Module,
+    /// Locals of the resolved symbols.
+    variables: HashMap<ReferenceType, LocalId>,
+    /// Locals of the mutable symbols, Calls may assign them.
+    mutable: HashSet<LocalId>,
+    /// Functions being lowered and their current block, The innermost one is the last.
+    stack: Vec<(FunctionId, BlockId)>,
+}
+
+/// The merge point of an optional chain, Every link of the chain jumps to it with
+/// the `result` set to nil as soon as one of the optional values is nil.
+#[derive(Clone, Copy)]
+struct Chain {
+    result: LocalId,
+    merge: BlockId,
+}
+
+/// A position in the function being lowered, Instructions emitted after it are evaluated later.
+#[derive(Clone, Copy)]
+struct Mark {
+    block: BlockId,
+    index: usize,
+    /// Number of blocks of the function, Blocks created later follow the mark.
+    blocks: usize,
+}
+
+impl<'a> Lowering<'a> {
+    fn new(semantic: &'a SemanticResult, source: &'a str) -> Self {
+        Self {
+            semantic,
+            source,
+            module: Module::default(),
+            variables: HashMap::new(),
+            mutable: HashSet::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    fn lower_chunk(mut self, chunk: &Chunk) -> Module {
+        self.begin_function(chunk.span, None);
+        self.lower_block(&chunk.body, false);
+        self.terminate(Terminator::Return(None));
+        self.end_function();
+        self.module
+    }
+
+    fn lower_function(&mut self, func: &AstFunction, name: Option<Atom>) -> FunctionId {
+        let id = self.begin_function(func.span, name);
+        let params = &func.signature.params;
+        for param in &params.items {
+            let local = match &param.pattern.kind {
+                fuse_ast::BindingPatternKind::Identifier(binding) => {
+                    self.declare_identifier(&binding.identifier, LocalKind::Parameter)
+                }
+                fuse_ast::BindingPatternKind::Tuple => {
+                    self.declare_local(Atom(Rc::from("_")), LocalKind::Parameter)
+                }
+            };
+            self.module.function_mut(id).params.push(local);
+        }
+        if let Some(rest) = &params.rest {
+            let local = self.declare_identifier(&rest.binding.identifier, LocalKind::Variable);
+            self.module.function_mut(id).rest = Some(local);
+            self.assign(rest.span, Place::Local(local), Rvalue::VarArgs);
+        }
+
+        let value = match &func.body {
+            FunctionBody::Block(block) => self.lower_block(block, self.returns_value(func)),
+            FunctionBody::Expression(expr) => Some(self.lower_operand(expr)),
+        };
+        self.terminate(Terminator::Return(value));
+        self.end_function();
+        id
+    }
+
+    /// Returns false for declared functions that we know return nothing.
+    fn returns_value(&self, func: &AstFunction) -> bool {
+        let Some(symbol) = func
+            .signature
+            .identifier
+            .as_ref()
+            .and_then(|ident| self.semantic.symbols.of(ident))
+        else {
+            return true;
+        };
+        match &symbol.ty {
+            Type::Function(ty) => !matches!(ty.return_type, Type::Nil),
+            _ => true,
+        }
+    }
+
+    /// Lower the statements of a block, Returns the value of its last expression if `value` is true.
+    fn lower_block(&mut self, block: &Block, value: bool) -> Option<Operand> {
+        let (last, statements) = block.statements.split_last()?;
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+        match last {
+            Statement::Expression(expr) if value => Some(self.lower_operand(expr)),
+            statement => {
+                self.visit_statement(statement);
+                None
+            }
+        }
+    }
+
+    fn begin_function(&mut self, span: Span, name: Option<Atom>) -> FunctionId {
+        let id = FunctionId(self.module.functions.len());
+        self.module.functions.push(Function {
+            span,
+            name,
+            params: Vec::new(),
+            rest: None,
+            locals: Vec::new(),
+            blocks: vec![BasicBlock::new()],
+        });
+        self.stack.push((id, Function::ENTRY));
+        id
+    }
+
+    fn end_function(&mut self) {
+        let (id, _) = self.stack.pop().expect("Unbalanced function lowering.");
+        self.module.function_mut(id).sort_blocks();
+    }
+
+    fn function(&mut self) -> &mut Function {
+        let (id, _) = *self.stack.last().expect("Lowering outside of a function.");
+        self.module.function_mut(id)
+    }
+
+    fn current_block(&self) -> BlockId {
+        self.stack
+            .last()
+            .expect("Lowering outside of a function.")
+            .1
+    }
+
+    fn new_block(&mut self) -> BlockId {
+        let function = self.function();
+        function.blocks.push(BasicBlock::new());
+        BlockId(function.blocks.len() - 1)
+    }
+
+    fn switch_to(&mut self, block: BlockId) {
+        self.stack
+            .last_mut()
+            .expect("Lowering outside of a function.")
+            .1 = block;
+    }
+
+    fn emit(&mut self, span: Span, kind: InstructionKind) {
+        let block = self.current_block();
+        self.function()
+            .block_mut(block)
+            .instructions
+            .push(Instruction { span, kind });
+    }
+
+    fn mark(&mut self) -> Mark {
+        let block = self.current_block();
+        let function = self.function();
+        Mark {
+            block,
+            index: function.block(block).instructions.len(),
+            blocks: function.blocks.len(),
+        }
+    }
+
+    /// Returns true if the instructions emitted after the mark may change the variable read by the operand.
+    fn changed_since(&mut self, mark: Mark, operand: &Operand) -> bool {
+        // Globals are left to the host, Only their assignments are considered.
+        let called = match operand {
+            Operand::Local(local) => self.mutable.contains(local),
+            Operand::Global(_) => false,
+            Operand::Constant(_) => return false,
+        };
+        let function = self.function();
+        let instructions = function.block(mark.block).instructions[mark.index..]
+            .iter()
+            .chain(
+                function.blocks[mark.blocks..]
+                    .iter()
+                    .flat_map(|block| &block.instructions),
+            );
+        for instruction in instructions {
+            let changed = match &instruction.kind {
+                InstructionKind::Call(..) | InstructionKind::Assign(_, Rvalue::Call(..)) => called,
+                InstructionKind::Assign(Place::Local(local), _) => {
+                    *operand == Operand::Local(*local)
+                }
+                InstructionKind::Assign(Place::Global(name), _) => {
+                    *operand == Operand::Global(name.clone())
+                }
+                _ => false,
+            };
+            if changed {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Copy the operands read before their mark into temporaries if a later instruction may change them,
+    /// Their values must be the ones at the mark. The last marks are pinned first to keep the earlier ones valid.
+    fn pin<'o>(
+        &mut self,
+        operands: impl DoubleEndedIterator<Item = (&'o mut Operand, Mark, Span)>,
+    ) {
+        for (operand, mark, span) in operands.rev() {
+            if !self.changed_since(mark, operand) {
+                continue;
+            }
+            let temporary = self.temporary();
+            let value = std::mem::replace(operand, Operand::Local(temporary));
+            self.function().block_mut(mark.block).instructions.insert(
+                mark.index,
+                Instruction {
+                    span,
+                    kind: InstructionKind::Assign(Place::Local(temporary), Rvalue::Use(value)),
+                },
+            );
+        }
+    }
+
+    fn assign(&mut self, span: Span, place: Place, value: Rvalue) {
+        self.emit(span, InstructionKind::Assign(place, value));
+    }
+
+    fn terminate(&mut self, terminator: Terminator) {
+        let block = self.current_block();
+        self.function().block_mut(block).terminator = terminator;
+    }
+
+    fn goto(&mut self, target: BlockId) {
+        self.terminate(Terminator::Goto(target));
+        self.switch_to(target);
+    }
+
+    fn declare_local(&mut self, name: Atom, kind: LocalKind) -> LocalId {
+        let id = LocalId(self.module.locals.len());
+        self.module.locals.push(Local { name, kind });
+        if kind != LocalKind::Parameter {
+            self.function().locals.push(id);
+        }
+        id
+    }
+
+    fn temporary(&mut self) -> LocalId {
+        self.declare_local(Atom(Rc::from("t")), LocalKind::Temporary)
+    }
+
+    /// Store the value in a temporary if it isn't already an operand.
+    fn operand_of(&mut self, span: Span, value: Rvalue) -> Operand {
+        match value {
+            Rvalue::Use(operand) => operand,
+            value => {
+                let temporary = self.temporary();
+                self.assign(span, Place::Local(temporary), value);
+                Operand::Local(temporary)
+            }
+        }
+    }
+
+    fn declare_identifier(&mut self, ident: &Identifier, kind: LocalKind) -> LocalId {
+        let local = self.declare_local(ident.name.clone(), kind);
+        if let Some(reference) = ident.reference.get() {
+            self.declare_reference(reference, local);
+        }
+        local
+    }
+
+    /// Get the local of a symbol, Symbols used before their declaration get declared here.
+    fn local_of(&mut self, reference: ReferenceType) -> LocalId {
+        if let Some(local) = self.variables.get(&reference) {
+            return *local;
+        }
+        let name = self.semantic.symbols.get(reference).name.clone();
+        let local = self.declare_local(name, LocalKind::Variable);
+        self.declare_reference(reference, local);
+        local
+    }
+
+    fn declare_reference(&mut self, reference: ReferenceType, local: LocalId) {
+        self.variables.insert(reference, local);
+        if self.semantic.symbols.get(reference).mutable {
+            self.mutable.insert(local);
+        }
+    }
+
+    fn symbol_kind(&self, ident: &Identifier) -> Option<SymbolKind> {
+        self.semantic.symbols.of(ident).map(|symbol| symbol.kind)
+    }
+
+    fn identifier_operand(&mut self, ident: &Identifier) -> Operand {
+        match self.identifier_place(ident) {
+            Place::Local(local) => Operand::Local(local),
+            Place::Global(name) => Operand::Global(name),
+            Place::Index(..) => unreachable!(),
+        }
+    }
+
+    /// Globals, Externs and unresolved identifiers of the host live in the global table.
+    fn identifier_place(&mut self, ident: &Identifier) -> Place {
+        let Some(reference) = ident.reference.get() else {
+            return Place::Global(ident.name.clone());
+        };
+        let symbol = self.semantic.symbols.get(reference);
+        match symbol.kind {
+            SymbolKind::Variable(fuse_ast::VariableDeclarationKind::Global)
+            | SymbolKind::Extern => Place::Global(symbol.name.clone()),
+            _ => Place::Local(self.local_of(reference)),
+        }
+    }
+
+    /// Text of a literal string segment.
+    fn literal_text(&self, segment: &fuse_ast::StringLiteralSegment) -> Atom {
+        match segment {
+            fuse_ast::StringLiteralSegment::Escaped(atom) => atom.clone(),
+            fuse_ast::StringLiteralSegment::Unescaped(span) => Atom(Rc::from(
+                &self.source[span.start as usize..span.end as usize],
+            )),
+        }
+    }
+
+    /// Evaluate `rhs` only if `lhs` is truthy for `and`, Or falsy for `or`.
+    fn short_circuit(
+        &mut self,
+        span: Span,
+        lhs: Operand,
+        op: BinaryOp,
+        rhs: impl FnOnce(&mut Self) -> Operand,
+    ) -> Rvalue {
+        let origin = self.current_block();
+        let rhs_block = self.new_block();
+        self.switch_to(rhs_block);
+        let value = rhs(self);
+
+        // Use the operator itself if rhs has no side effects.
+        if self.current_block() == rhs_block
+            && self.function().block(rhs_block).instructions.is_empty()
+        {
+            let function = self.function();
+            debug_assert_eq!(function.blocks.len() - 1, rhs_block.0);
+            function.blocks.pop();
+            self.switch_to(origin);
+            return Rvalue::Binary(op, lhs, value);
+        }
+
+        let result = self.temporary();
+        self.assign(span, Place::Local(result), Rvalue::Use(value));
+        let merge = self.new_block();
+        self.terminate(Terminator::Goto(merge));
+
+        self.switch_to(origin);
+        self.assign(span, Place::Local(result), Rvalue::Use(lhs));
+        let (then, r#else) = match op {
+            BinaryOp::And => (rhs_block, merge),
+            _ => (merge, rhs_block),
+        };
+        self.terminate(Terminator::Branch {
+            condition: Operand::Local(result),
+            then,
+            r#else,
+        });
+        self.switch_to(merge);
+        Rvalue::Use(Operand::Local(result))
+    }
+
+    /// Lower an optional chain, `link` lowers the chain and returns its value if none of the links are nil.
+    fn lower_optional_chain(
+        &mut self,
+        span: Span,
+        link: impl FnOnce(&mut Self, Chain) -> Rvalue,
+    ) -> Rvalue {
+        let chain = Chain {
+            result: self.temporary(),
+            merge: self.new_block(),
+        };
+        let value = link(self, chain);
+        self.assign(span, Place::Local(chain.result), value);
+        self.goto(chain.merge);
+        Rvalue::Use(Operand::Local(chain.result))
+    }
+
+    /// Continue the optional chain only if the value isn't nil.
+    fn check_chain_link(&mut self, span: Span, value: &Operand, chain: Chain) {
+        let present = self.operand_of(
+            span,
+            Rvalue::Binary(
+                BinaryOp::NotEqual,
+                value.clone(),
+                Operand::Constant(Constant::Nil),
+            ),
+        );
+        let next = self.new_block();
+        let exit = self.new_block();
+        self.terminate(Terminator::Branch {
+            condition: present,
+            then: next,
+            r#else: exit,
+        });
+        self.switch_to(exit);
+        self.assign(
+            span,
+            Place::Local(chain.result),
+            Rvalue::Use(Operand::Constant(Constant::Nil)),
+        );
+        self.terminate(Terminator::Goto(chain.merge));
+        self.switch_to(next);
+    }
+}
@@ -0,0 +1,152 @@
+use std::rc::Rc;
+
+use fuse_ast::{Atom, GetSpan, Match, Pattern, VariantPattern};
+use fuse_common::Span;
+use fuse_semantic::SymbolKind;
+
+use super::{expressions::string_constant, Lowering};
+use crate::{BinaryOp, Constant, LocalId, LocalKind, Number, Operand, Place, Rvalue, Terminator};
+
+impl<'a> Lowering<'a> {
+    /// Lower a match into a chain of conditions, Each arm is tested after the previous one fails.
+    pub(super) fn lower_match(&mut self, r#match: &Match, result: Option<LocalId>) {
+        let value = self.lower_operand(&r#match.expression);
+        let value = match value {
+            // Globals may change while matching.
+            Operand::Global(_) => {
+                let temporary = self.temporary();
+                self.assign(r#match.span, Place::Local(temporary), Rvalue::Use(value));
+                Operand::Local(temporary)
+            }
+            value => value,
+        };
+        let merge = self.new_block();
+
+        for arm in &r#match.arms {
+            let Some(condition) = self.pattern_condition(&arm.pattern, &value) else {
+                // Arms after an irrefutable pattern are unreachable.
+                self.bind_pattern(&arm.pattern, &value);
+                self.lower_branch(&arm.body, result, arm.span);
+                self.goto(merge);
+                return;
+            };
+            let body = self.new_block();
+            let next = self.new_block();
+            self.terminate(Terminator::Branch {
+                condition,
+                then: body,
+                r#else: next,
+            });
+
+            self.switch_to(body);
+            self.bind_pattern(&arm.pattern, &value);
+            self.lower_branch(&arm.body, result, arm.span);
+            self.terminate(Terminator::Goto(merge));
+            self.switch_to(next);
+        }
+
+        match &r#match.r#else {
+            Some(block) => self.lower_branch(block, result, r#match.span),
+            None => self.assign_nil(r#match.span, result),
+        }
+        self.goto(merge);
+    }
+
+    /// Lower the condition of matching the pattern, Returns `None` for patterns matching every value.
+    fn pattern_condition(&mut self, pattern: &Pattern, value: &Operand) -> Option<Operand> {
+        let span = pattern.span();
+        match pattern {
+            Pattern::Wildcard(_) | Pattern::Binding(_) => None,
+            Pattern::Literal(expr) => {
+                let expected = self.lower_operand(expr);
+                Some(self.operand_of(
+                    span,
+                    Rvalue::Binary(BinaryOp::Equal, value.clone(), expected),
+                ))
+            }
+            Pattern::Variant(variant) if self.is_union_pattern(variant) => {
+                let tag = self.operand_of(
+                    span,
+                    Rvalue::Index(value.clone(), string_constant(&Atom(Rc::from("tag")))),
+                );
+                let mut condition = self.operand_of(
+                    span,
+                    Rvalue::Binary(BinaryOp::Equal, tag, string_constant(&variant.variant.name)),
+                );
+                for (index, field) in variant.fields.iter().enumerate() {
+                    if is_irrefutable(field) {
+                        continue;
+                    }
+                    // Fields are only accessed if the tag matches.
+                    let matched = self.short_circuit(span, condition, BinaryOp::And, |this| {
+                        let field_value = this.variant_field(span, value, index);
+                        this.pattern_condition(field, &field_value)
+                            .expect("Refutable patterns have a condition.")
+                    });
+                    condition = self.operand_of(span, matched);
+                }
+                Some(condition)
+            }
+            Pattern::Variant(variant) => {
+                let target = self.identifier_operand(&variant.target);
+                let expected = self.operand_of(
+                    span,
+                    Rvalue::Index(target, string_constant(&variant.variant.name)),
+                );
+                Some(self.operand_of(
+                    span,
+                    Rvalue::Binary(BinaryOp::Equal, value.clone(), expected),
+                ))
+            }
+        }
+    }
+
+    /// Declare the bindings of a matched pattern.
+    fn bind_pattern(&mut self, pattern: &Pattern, value: &Operand) {
+        match pattern {
+            Pattern::Binding(binding) => {
+                let local = self.declare_identifier(&binding.identifier, LocalKind::Variable);
+                self.assign(
+                    binding.span,
+                    Place::Local(local),
+                    Rvalue::Use(value.clone()),
+                );
+            }
+            Pattern::Variant(variant) if self.is_union_pattern(variant) => {
+                for (index, field) in variant.fields.iter().enumerate() {
+                    if has_bindings(field) {
+                        let field_value = self.variant_field(variant.span, value, index);
+                        self.bind_pattern(field, &field_value);
+                    }
+                }
+            }
+            Pattern::Wildcard(_) | Pattern::Literal(_) | Pattern::Variant(_) => {}
+        }
+    }
+
+    fn is_union_pattern(&self, variant: &VariantPattern) -> bool {
+        self.symbol_kind(&variant.target) == Some(SymbolKind::Union)
+    }
+
+    fn variant_field(&mut self, span: Span, value: &Operand, index: usize) -> Operand {
+        self.operand_of(
+            span,
+            Rvalue::Index(
+                value.clone(),
+                Operand::Constant(Constant::Number(Number::integer(index as i64 + 1))),
+            ),
+        )
+    }
+}
+
+fn is_irrefutable(pattern: &Pattern) -> bool {
+    matches!(pattern, Pattern::Wildcard(_) | Pattern::Binding(_))
+}
+
+fn has_bindings(pattern: &Pattern) -> bool {
+    match pattern {
+        Pattern::Binding(_) => true,
+        Pattern::Variant(variant) => variant.fields.iter().any(has_bindings),
+        Pattern::Wildcard(_) | Pattern::Literal(_) => false,
+    }
+}
@@ -0,0 +1,124 @@
+use std::rc::Rc;
+
+use fuse_ast::{
+    Atom, BindingPatternKind, EnumDeclaration, Expression, ExternDeclaration, Function,
+    ImplStatement, Statement, StructDeclaration, TypeAliasDeclaration, TypeAnnotationKind,
+    UnionDeclaration, VariableDeclaration, VariableDeclarationKind,
+};
+use fuse_visitor::{walk_statement, NodeVisitor, ScopeVisitor, Visitor};
+
+use super::{expressions::number_constant, Lowering};
+use crate::{Constant, LocalKind, Number, Operand, Place, Rvalue, TableEntry};
+
+impl<'ast> Visitor<'ast> for Lowering<'_> {
+    fn visit_statement(&mut self, statement: &'ast Statement) {
+        match statement {
+            Statement::Expression(expr) => self.lower_effect(expr),
+            statement => walk_statement(self, statement),
+        }
+    }
+
+    fn visit_variable_declaration(&mut self, decl: &'ast VariableDeclaration) {
+        let BindingPatternKind::Identifier(binding) = &decl.binding.kind else {
+            if let Some(expr) = &decl.expression {
+                self.lower_effect(expr);
+            }
+            return;
+        };
+        let value = match &decl.expression {
+            Some(expr) => self.lower_rvalue(expr),
+            // Globals are nil until they get assigned.
+            None if decl.kind == VariableDeclarationKind::Global => return,
+            None => Rvalue::Use(Operand::Constant(Constant::Nil)),
+        };
+        let place = match decl.kind {
+            VariableDeclarationKind::Global => self.identifier_place(&binding.identifier),
+            _ => Place::Local(self.declare_identifier(&binding.identifier, LocalKind::Variable)),
+        };
+        self.assign(decl.span, place, value);
+    }
+
+    fn visit_function_declaration(&mut self, decl: &'ast Function) {
+        let identifier = decl
+            .signature
+            .identifier
+            .as_ref()
+            .expect("All function declarations need an identifier.");
+        let local = self.declare_identifier(identifier, LocalKind::Variable);
+        let function = self.lower_function(decl, Some(identifier.name.clone()));
+        self.assign(decl.span, Place::Local(local), Rvalue::Closure(function));
+    }
+
+    fn visit_enum_declaration(&mut self, decl: &'ast EnumDeclaration) {
+        // Variants without a value continue counting from the previous one, starting at zero.
+        let mut next = Number::integer(0);
+        let mut entries = Vec::with_capacity(decl.variants.len());
+        for variant in &decl.variants {
+            let value = match &variant.value {
+                Some(Expression::NumberLiteral(lit)) => {
+                    let value = number_constant(lit);
+                    if let Operand::Constant(Constant::Number(number)) = &value {
+                        next = number.clone();
+                    }
+                    value
+                }
+                Some(expr) => self.lower_operand(expr),
+                None => Operand::Constant(Constant::Number(next.clone())),
+            };
+            next = Number {
+                value: next.value + 1.0,
+                kind: next.kind,
+                raw: None,
+            };
+            entries.push(TableEntry::Keyed(
+                Operand::Constant(Constant::String(variant.identifier.name.clone())),
+                value,
+            ));
+        }
+        let local = self.declare_identifier(&decl.identifier, LocalKind::Variable);
+        self.assign(decl.span, Place::Local(local), Rvalue::Table(entries));
+    }
+
+    fn visit_impl_statement(&mut self, r#impl: &'ast ImplStatement) {
+        // Methods are dispatched statically, Each of them is a local named after its target.
+        let target = match &r#impl.target.kind {
+            TypeAnnotationKind::Identifier(ident) => Some(&ident.name),
+            TypeAnnotationKind::Generic(generic) => Some(&generic.identifier.name),
+            _ => None,
+        };
+        for method in &r#impl.methods {
+            let func = &method.function;
+            let Some(identifier) = &func.signature.identifier else {
+                continue;
+            };
+            let name = match target {
+                Some(target) => Atom(Rc::from(format!(
+                    "{}_{}",
+                    target.as_str(),
+                    identifier.name.as_str()
+                ))),
+                None => identifier.name.clone(),
+            };
+            let local = self.declare_local(name, LocalKind::Variable);
+            if let Some(reference) = identifier.reference.get() {
+                self.declare_reference(reference, local);
+            }
+            let function = self.lower_function(func, Some(identifier.name.clone()));
+            self.assign(func.span, Place::Local(local), Rvalue::Closure(function));
+        }
+    }
+
+    // Type declarations only exist at compile time.
+
+    fn visit_struct_declaration(&mut self, _: &'ast StructDeclaration) {}
+
+    fn visit_type_alias_declaration(&mut self, _: &'ast TypeAliasDeclaration) {}
+
+    fn visit_union_declaration(&mut self, _: &'ast UnionDeclaration) {}
+
+    fn visit_extern_declaration(&mut self, _: &'ast ExternDeclaration) {}
+}
+
+impl ScopeVisitor for Lowering<'_> {}
+
+impl NodeVisitor for Lowering<'_> {}
@@ -0,0 +1,114 @@
+use super::{constants::is_truthy, Pass};
+use crate::{BasicBlock, BlockId, Function, Module, Operand, Terminator};
+
+/// Prune the branches with constant conditions, It turns them into jumps and drops the
+/// blocks that are no longer reachable.
+///
+/// Jumps through empty blocks go straight to their target and jumps to a block without any
+/// other predecessor are merged into a single block.
+pub struct PruneBranches;
+
+impl Pass for PruneBranches {
+    fn run(&mut self, module: &mut Module) -> bool {
+        let mut changed = false;
+        for function in &mut module.functions {
+            let mut pruned = false;
+            for block in &mut function.blocks {
+                let target = match &block.terminator {
+                    Terminator::Branch {
+                        condition: Operand::Constant(condition),
+                        then,
+                        r#else,
+                    } => {
+                        if is_truthy(condition) {
+                            *then
+                        } else {
+                            *r#else
+                        }
+                    }
+                    Terminator::Branch { then, r#else, .. } if then == r#else => *then,
+                    _ => continue,
+                };
+                block.terminator = Terminator::Goto(target);
+                pruned = true;
+            }
+            pruned |= skip_empty_blocks(function);
+            pruned |= merge_blocks(function);
+            if pruned {
+                function.sort_blocks();
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Retarget the jumps to empty blocks that only jump to another block.
+fn skip_empty_blocks(function: &mut Function) -> bool {
+    let target = |mut block: BlockId| {
+        // Bounded in case of loops made of empty blocks.
+        for _ in 0..function.blocks.len() {
+            match function.block(block) {
+                BasicBlock {
+                    instructions,
+                    terminator: Terminator::Goto(next),
+                } if instructions.is_empty() => block = *next,
+                _ => break,
+            }
+        }
+        block
+    };
+    let targets: Vec<Vec<BlockId>> = function
+        .blocks
+        .iter()
+        .map(|block| {
+            block
+                .terminator
+                .successors()
+                .into_iter()
+                .map(target)
+                .collect()
+        })
+        .collect();
+
+    let mut skipped = false;
+    for (block, targets) in function.blocks.iter_mut().zip(targets) {
+        for (successor, target) in block.terminator.successors_mut().into_iter().zip(targets) {
+            skipped |= *successor != target;
+            *successor = target;
+        }
+    }
+    skipped
+}
+
+/// Merge the blocks that are only reached by a jump from another block.
+fn merge_blocks(function: &mut Function) -> bool {
+    let mut predecessors = vec![0usize; function.blocks.len()];
+    for block in &function.blocks {
+        for successor in block.terminator.successors() {
+            predecessors[successor.0] += 1;
+        }
+    }
+
+    let mut merged = false;
+    for index in 0..function.blocks.len() {
+        while let Terminator::Goto(target) = function.blocks[index].terminator {
+            // The entry block has an implicit predecessor.
+            if target.0 == index || target.0 == 0 || predecessors[target.0] != 1 {
+                break;
+            }
+            let next = std::mem::replace(
+                &mut function.blocks[target.0],
+                BasicBlock {
+                    instructions: Vec::new(),
+                    terminator: Terminator::Unreachable,
+                },
+            );
+            let block = &mut function.blocks[index];
+            block.instructions.extend(next.instructions);
+            block.terminator = next.terminator;
+            merged = true;
+        }
+    }
+    merged
+}
@@ -0,0 +1,301 @@
+use std::rc::Rc;
+
+use fuse_ast::Atom;
+
+use super::{LocalCounts, Pass};
+use crate::{BinaryOp, Constant, InstructionKind, Module, Number, Operand, Place, Rvalue, UnaryOp};
+
+/// Integers from `-MAX_SAFE_INTEGER` to `MAX_SAFE_INTEGER` are exactly representable as numbers.
+const MAX_SAFE_INTEGER: i64 = 1 << 53;
+
+/// Fold the operations on constants and propagate the constant locals, A local is constant
+/// when it is only assigned once, e.g. `const` declarations and immutable variables.
+///
+/// Folding follows the semantics shared by every Lua target, Operations whose result
+/// depends on the target are left as is, e.g. integer overflows or bitwise operations on
+/// floats. Whether a number is an integer or a float comes from its `NumberKind`, Never from
+/// its value, So `3.0 // 2` folds to the float `1.0` like on Lua 5.4.
+pub struct ConstantFolding;
+
+impl Pass for ConstantFolding {
+    fn run(&mut self, module: &mut Module) -> bool {
+        let counts = LocalCounts::new(module);
+        let mut constants: Vec<Option<Constant>> = vec![None; module.locals.len()];
+        let mut changed = false;
+        loop {
+            let mut progress = false;
+            for function in &mut module.functions {
+                for block in &mut function.blocks {
+                    for instruction in &mut block.instructions {
+                        for operand in instruction.kind.operands_mut() {
+                            progress |= propagate(operand, &constants);
+                        }
+                        let InstructionKind::Assign(place, value) = &mut instruction.kind else {
+                            continue;
+                        };
+                        if let Some(folded) = fold(value) {
+                            *value = folded;
+                            progress = true;
+                        }
+                        if let (Place::Local(local), Rvalue::Use(Operand::Constant(constant))) =
+                            (place, value)
+                        {
+                            if counts.assignments(*local) == 1 && constants[local.0].is_none() {
+                                constants[local.0] = Some(constant.clone());
+                                progress = true;
+                            }
+                        }
+                    }
+                    if let Some(operand) = block.terminator.operand_mut() {
+                        progress |= propagate(operand, &constants);
+                    }
+                }
+            }
+            if !progress {
+                return changed;
+            }
+            changed = true;
+        }
+    }
+}
+
+/// Replace a constant local with its value.
+fn propagate(operand: &mut Operand, constants: &[Option<Constant>]) -> bool {
+    let Operand::Local(local) = operand else {
+        return false;
+    };
+    match &constants[local.0] {
+        Some(constant) => {
+            *operand = Operand::Constant(constant.clone());
+            true
+        }
+        None => false,
+    }
+}
+
+/// Simplify a value, Returns `None` if it can't be simplified.
+fn fold(value: &Rvalue) -> Option<Rvalue> {
+    let folded = match value {
+        Rvalue::Unary(op, Operand::Constant(value)) => fold_unary(*op, value)?,
+        // Logical operators evaluate to one of their operands.
+        Rvalue::Binary(op @ (BinaryOp::And | BinaryOp::Or), Operand::Constant(lhs), rhs) => {
+            let is_lhs = is_truthy(lhs) == (*op == BinaryOp::Or);
+            return Some(Rvalue::Use(if is_lhs {
+                Operand::Constant(lhs.clone())
+            } else {
+                rhs.clone()
+            }));
+        }
+        Rvalue::Binary(op, Operand::Constant(lhs), Operand::Constant(rhs)) => {
+            fold_binary(*op, lhs, rhs)?
+        }
+        Rvalue::Concat(values) => return fold_concat(values),
+        _ => return None,
+    };
+    Some(Rvalue::Use(Operand::Constant(folded)))
+}
+
+fn fold_unary(op: UnaryOp, value: &Constant) -> Option<Constant> {
+    match (op, value) {
+        (UnaryOp::Not, value) => Some(Constant::Boolean(!is_truthy(value))),
+        (UnaryOp::Negate, Constant::Number(value)) if value.is_integer() => {
+            from_integer(value.as_integer()?.checked_neg()?)
+        }
+        (UnaryOp::Negate, Constant::Number(value)) => from_float(-value.value),
+        // Length of strings is in bytes.
+        (UnaryOp::Length, Constant::String(value)) => {
+            from_integer(value.as_str().len().try_into().ok()?)
+        }
+        (UnaryOp::Debug, Constant::String(value)) => Some(string(debug_string(value.as_str()))),
+        (UnaryOp::Debug, value) => to_string(value).map(string),
+        _ => None,
+    }
+}
+
+fn fold_binary(op: BinaryOp, lhs: &Constant, rhs: &Constant) -> Option<Constant> {
+    match op {
+        BinaryOp::Equal => return Some(Constant::Boolean(equals(lhs, rhs))),
+        BinaryOp::NotEqual => return Some(Constant::Boolean(!equals(lhs, rhs))),
+        _ => {}
+    }
+    let (Constant::Number(lhs), Constant::Number(rhs)) = (lhs, rhs) else {
+        return None;
+    };
+    match op {
+        BinaryOp::LessThan => Some(Constant::Boolean(lhs.value < rhs.value)),
+        BinaryOp::LessThanEqual => Some(Constant::Boolean(lhs.value <= rhs.value)),
+        BinaryOp::GreaterThan => Some(Constant::Boolean(lhs.value > rhs.value)),
+        BinaryOp::GreaterThanEqual => Some(Constant::Boolean(lhs.value >= rhs.value)),
+        _ if lhs.is_integer() && rhs.is_integer() => {
+            fold_integers(op, lhs.as_integer()?, rhs.as_integer()?)
+        }
+        // Integers are converted to floats, Which is only exact for the safe ones.
+        _ => fold_floats(op, as_float(lhs)?, as_float(rhs)?),
+    }
+}
+
+/// Operations on two integers, Their results are integers except for `/` and `^`.
+fn fold_integers(op: BinaryOp, lhs: i64, rhs: i64) -> Option<Constant> {
+    match op {
+        BinaryOp::Add => from_integer(lhs.checked_add(rhs)?),
+        BinaryOp::Subtract => from_integer(lhs.checked_sub(rhs)?),
+        BinaryOp::Multiply => from_integer(lhs.checked_mul(rhs)?),
+        // Division by zero raises an error for integers.
+        BinaryOp::FloorDivide | BinaryOp::Modulo if rhs == 0 => None,
+        BinaryOp::FloorDivide => {
+            let quotient = lhs.checked_div(rhs)?;
+            let is_inexact = lhs % rhs != 0 && (lhs < 0) != (rhs < 0);
+            from_integer(quotient - i64::from(is_inexact))
+        }
+        BinaryOp::Modulo => {
+            let remainder = lhs.checked_rem(rhs)?;
+            let needs_adjust = remainder != 0 && (remainder < 0) != (rhs < 0);
+            from_integer(if needs_adjust {
+                remainder + rhs
+            } else {
+                remainder
+            })
+        }
+        BinaryOp::BitwiseAnd => from_integer(lhs & rhs),
+        BinaryOp::BitwiseOr => from_integer(lhs | rhs),
+        BinaryOp::BitwiseXor => from_integer(lhs ^ rhs),
+        BinaryOp::ShiftLeft => from_integer(shift_left(lhs, rhs)),
+        BinaryOp::ShiftRight => from_integer(shift_left(lhs, rhs.checked_neg()?)),
+        BinaryOp::Divide | BinaryOp::Power => fold_floats(op, lhs as f64, rhs as f64),
+        _ => None,
+    }
+}
+
+/// Operations with a float operand, Their results are floats.
+fn fold_floats(op: BinaryOp, lhs: f64, rhs: f64) -> Option<Constant> {
+    match op {
+        BinaryOp::Add => from_float(lhs + rhs),
+        BinaryOp::Subtract => from_float(lhs - rhs),
+        BinaryOp::Multiply => from_float(lhs * rhs),
+        BinaryOp::Divide => from_float(lhs / rhs),
+        BinaryOp::Power => from_float(lhs.powf(rhs)),
+        // Targets without `//` and `%` on floats emulate them with `math.floor`, Which only
+        // agrees with the others on finite operands.
+        BinaryOp::FloorDivide | BinaryOp::Modulo if rhs == 0.0 || !lhs.is_finite() => None,
+        BinaryOp::FloorDivide => from_float((lhs / rhs).floor()),
+        BinaryOp::Modulo => {
+            let remainder = lhs % rhs;
+            let needs_adjust = remainder != 0.0 && (remainder < 0.0) != (rhs < 0.0);
+            from_float(if needs_adjust {
+                remainder + rhs
+            } else {
+                remainder
+            })
+        }
+        // Bitwise operators convert floats to integers on some targets and reject them on
+        // others.
+        _ => None,
+    }
+}
+
+/// Merge the adjacent strings and integers of a concatenation.
+fn fold_concat(values: &[Operand]) -> Option<Rvalue> {
+    let mut merged: Vec<Operand> = Vec::with_capacity(values.len());
+    let mut changed = false;
+    for value in values {
+        let text = match value {
+            Operand::Constant(constant @ Constant::Number(_)) => to_string(constant),
+            Operand::Constant(Constant::String(value)) => Some(value.as_str().to_string()),
+            _ => None,
+        };
+        match (merged.last_mut(), text) {
+            (Some(Operand::Constant(Constant::String(lhs))), Some(rhs)) => {
+                *lhs = Atom(Rc::from(format!("{}{rhs}", lhs.as_str())));
+                changed = true;
+            }
+            (_, Some(text)) => {
+                changed |= !matches!(value, Operand::Constant(Constant::String(_)));
+                merged.push(Operand::Constant(string(text)));
+            }
+            (_, None) => merged.push(value.clone()),
+        }
+    }
+    match merged.as_slice() {
+        [value @ Operand::Constant(Constant::String(_))] => Some(Rvalue::Use(value.clone())),
+        _ if changed => Some(Rvalue::Concat(merged)),
+        _ => None,
+    }
+}
+
+/// Text of a constant as written by `tostring`, Floats are left out since their format
+/// depends on the target.
+fn to_string(value: &Constant) -> Option<String> {
+    match value {
+        Constant::Nil => Some("nil".to_string()),
+        Constant::Boolean(value) => Some(value.to_string()),
+        Constant::Number(value) => value.as_integer().map(|value| value.to_string()),
+        Constant::String(value) => Some(value.as_str().to_string()),
+    }
+}
+
+/// Quote a string the way the debug format does.
+fn debug_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            char => out.push(char),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn string(value: String) -> Constant {
+    Constant::String(Atom(Rc::from(value)))
+}
+
+/// Lua shifts are logical and shifting by 64 bits or more results in zero.
+fn shift_left(value: i64, by: i64) -> i64 {
+    let value = value as u64;
+    let shifted = match by {
+        by if by <= -64 || by >= 64 => 0,
+        by if by >= 0 => value << by,
+        by => value >> -by,
+    };
+    shifted as i64
+}
+
+/// Value of a number as a float, `None` for the integers that floats can't represent exactly.
+fn as_float(number: &Number) -> Option<f64> {
+    if number.is_integer() {
+        number.as_integer().map(|value| value as f64)
+    } else {
+        Some(number.value)
+    }
+}
+
+fn from_integer(value: i64) -> Option<Constant> {
+    (value.unsigned_abs() < MAX_SAFE_INTEGER as u64)
+        .then(|| Constant::Number(Number::integer(value)))
+}
+
+/// Float result of an operation, NaN and the infinities are left out since they have no literal.
+fn from_float(value: f64) -> Option<Constant> {
+    value
+        .is_finite()
+        .then(|| Constant::Number(Number::float(value)))
+}
+
+/// Numbers are equal by value whatever their kind and text, e.g. `1 == 1.0` and `0x10 == 16`.
+fn equals(lhs: &Constant, rhs: &Constant) -> bool {
+    match (lhs, rhs) {
+        (Constant::Number(lhs), Constant::Number(rhs)) => lhs.value == rhs.value,
+        _ => lhs == rhs,
+    }
+}
+
+/// Returns true for the values other than `nil` and `false`.
+pub(super) fn is_truthy(value: &Constant) -> bool {
+    !matches!(value, Constant::Nil | Constant::Boolean(false))
+}
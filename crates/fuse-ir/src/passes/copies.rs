@@ -0,0 +1,67 @@
+use super::{LocalCounts, Pass};
+use crate::{InstructionKind, LocalId, Module, Operand, Place, Rvalue};
+
+/// Replace the copies of locals with the original, A copy is a local only assigned once from
+/// another local that is never reassigned, e.g. the parameters of inlined functions.
+pub struct CopyPropagation;
+
+impl Pass for CopyPropagation {
+    fn run(&mut self, module: &mut Module) -> bool {
+        let counts = LocalCounts::new(module);
+        let mut copies: Vec<Option<LocalId>> = vec![None; module.locals.len()];
+        for function in &module.functions {
+            for block in &function.blocks {
+                for instruction in &block.instructions {
+                    if let InstructionKind::Assign(
+                        Place::Local(local),
+                        Rvalue::Use(Operand::Local(source)),
+                    ) = &instruction.kind
+                    {
+                        // Parameters are never assigned and hold their argument.
+                        if counts.assignments(*local) == 1
+                            && counts.assignments(*source) <= 1
+                            && !is_copy_of(&copies, *source, *local)
+                        {
+                            copies[local.0] = Some(*source);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut changed = false;
+        for function in &mut module.functions {
+            for block in &mut function.blocks {
+                let operands = block
+                    .instructions
+                    .iter_mut()
+                    .flat_map(|instruction| instruction.kind.operands_mut())
+                    .chain(block.terminator.operand_mut());
+                for operand in operands {
+                    let Operand::Local(local) = operand else {
+                        continue;
+                    };
+                    // Follow the chains of copies, They end at the first local that isn't a copy.
+                    while let Some(source) = copies[local.0] {
+                        *local = source;
+                        changed = true;
+                    }
+                }
+            }
+        }
+        changed
+    }
+}
+
+/// Returns true if `local` is `of` or one of its copies, Such copies would form a cycle.
+fn is_copy_of(copies: &[Option<LocalId>], mut local: LocalId, of: LocalId) -> bool {
+    loop {
+        if local == of {
+            return true;
+        }
+        match copies[local.0] {
+            Some(source) => local = source,
+            None => return false,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::HashSet;
+
+use super::{remap_block_locals, LocalCounts, Pass};
+use crate::{Function, FunctionId, InstructionKind, LocalId, Module, Place, Rvalue};
+
+/// Remove the code that doesn't affect the program, It removes the assignments of unused
+/// locals, unreachable blocks, unused functions and then renumbers the remaining locals.
+///
+/// Calls are kept for their side effects even if their result is unused.
+pub struct DeadCodeElimination;
+
+impl Pass for DeadCodeElimination {
+    fn run(&mut self, module: &mut Module) -> bool {
+        let mut changed = remove_unused_assignments(module);
+        for function in &mut module.functions {
+            let count = function.blocks.len();
+            function.sort_blocks();
+            changed |= function.blocks.len() != count;
+        }
+        changed |= remove_unused_functions(module);
+        changed |= remove_unused_locals(module);
+        changed
+    }
+}
+
+/// Remove the assignments of locals that are never read, Each removal may leave more
+/// locals unused so it repeats until there is nothing left to remove.
+fn remove_unused_assignments(module: &mut Module) -> bool {
+    let mut changed = false;
+    loop {
+        let counts = LocalCounts::new(module);
+        let mut progress = false;
+        for function in &mut module.functions {
+            for block in &mut function.blocks {
+                block.instructions.retain_mut(|instruction| {
+                    let InstructionKind::Assign(Place::Local(local), value) = &instruction.kind
+                    else {
+                        return true;
+                    };
+                    if counts.uses(*local) > 0 {
+                        return true;
+                    }
+                    progress = true;
+                    match value {
+                        Rvalue::Call(callee, args) => {
+                            instruction.kind = InstructionKind::Call(callee.clone(), args.clone());
+                            true
+                        }
+                        _ => false,
+                    }
+                });
+            }
+        }
+        if !progress {
+            return changed;
+        }
+        changed = true;
+    }
+}
+
+/// Remove the functions that are never instantiated, The main function is always kept.
+fn remove_unused_functions(module: &mut Module) -> bool {
+    let mut used = vec![false; module.functions.len()];
+    let mut stack = vec![Module::MAIN];
+    while let Some(id) = stack.pop() {
+        if std::mem::replace(&mut used[id.0], true) {
+            continue;
+        }
+        for block in &module.function(id).blocks {
+            for instruction in &block.instructions {
+                if let InstructionKind::Assign(_, Rvalue::Closure(function)) = &instruction.kind {
+                    stack.push(*function);
+                }
+            }
+        }
+    }
+    if used.iter().all(|used| *used) {
+        return false;
+    }
+
+    let mut ids = Vec::with_capacity(used.len());
+    let mut next = 0;
+    for used in &used {
+        ids.push(FunctionId(next));
+        next += usize::from(*used);
+    }
+    let functions = std::mem::take(&mut module.functions);
+    module.functions = functions
+        .into_iter()
+        .zip(&used)
+        .filter_map(|(function, used)| used.then_some(function))
+        .collect();
+    for function in &mut module.functions {
+        for block in &mut function.blocks {
+            for instruction in &mut block.instructions {
+                if let InstructionKind::Assign(_, Rvalue::Closure(function)) = &mut instruction.kind
+                {
+                    *function = ids[function.0];
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Remove the locals that are never mentioned and renumber the remaining ones.
+fn remove_unused_locals(module: &mut Module) -> bool {
+    let counts = LocalCounts::new(module);
+    let is_used = |local: &LocalId| counts.uses(*local) + counts.assignments(*local) > 0;
+    for function in &mut module.functions {
+        function.locals.retain(is_used);
+        if function.rest.is_some_and(|rest| !is_used(&rest)) {
+            function.rest = None;
+        }
+    }
+
+    let declared: HashSet<LocalId> = module
+        .functions
+        .iter()
+        .flat_map(|function| function.params.iter().chain(&function.locals))
+        .copied()
+        .collect();
+    if declared.len() == module.locals.len() {
+        return false;
+    }
+
+    let mut ids = vec![None; module.locals.len()];
+    let locals = std::mem::take(&mut module.locals);
+    for (index, local) in locals.into_iter().enumerate() {
+        if declared.contains(&LocalId(index)) {
+            ids[index] = Some(LocalId(module.locals.len()));
+            module.locals.push(local);
+        }
+    }
+    for function in &mut module.functions {
+        remap_locals(function, &ids);
+    }
+    true
+}
+
+fn remap_locals(function: &mut Function, ids: &[Option<LocalId>]) {
+    let remap = |local: &mut LocalId| *local = ids[local.0].expect("Use of a removed local.");
+    let declarations = function.params.iter_mut().chain(&mut function.locals);
+    declarations.chain(&mut function.rest).for_each(remap);
+    for block in &mut function.blocks {
+        remap_block_locals(block, remap);
+    }
+}
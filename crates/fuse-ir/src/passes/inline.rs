@@ -0,0 +1,277 @@
+use std::{collections::HashMap, rc::Rc};
+
+use fuse_ast::Atom;
+
+use super::{remap_block_locals, LocalCounts, Pass};
+use crate::{
+    BasicBlock, BlockId, Constant, Function, FunctionId, Instruction, InstructionKind, Local,
+    LocalId, LocalKind, Module, Operand, Place, Rvalue, Terminator,
+};
+
+/// Inline the calls to small functions, A function is inlined when it is bound to a local
+/// assigned only once, e.g. `fn` declarations and immutable `let` or `const` bindings.
+///
+/// Recursive and variadic functions are never inlined, Neither are the functions creating
+/// closures. Arguments are assigned to copies of the parameters before entering the inlined
+/// body, So they are read once at the call like they would be by the callee. Variables read
+/// before an earlier call of the statement are already copied by the lowering, e.g. `c` in
+/// `add(c, bump())`, So inlining `bump` doesn't change the argument.
+pub struct Inlining {
+    threshold: usize,
+}
+
+impl Inlining {
+    /// Default size threshold, In instructions.
+    pub const DEFAULT_THRESHOLD: usize = 8;
+
+    /// Inline the functions with at most `threshold` instructions, Zero disables inlining.
+    pub fn new(threshold: usize) -> Self {
+        Self { threshold }
+    }
+}
+
+impl Default for Inlining {
+    fn default() -> Self {
+        Self::new(Inlining::DEFAULT_THRESHOLD)
+    }
+}
+
+impl Pass for Inlining {
+    fn run(&mut self, module: &mut Module) -> bool {
+        let bindings = bindings(module);
+        // Take a copy of the bodies, So inlining into a function doesn't affect its callers.
+        let bodies: HashMap<LocalId, Body> = bindings
+            .iter()
+            .filter(|(_, function)| self.is_inlinable(module, &bindings, **function))
+            .map(|(local, function)| (*local, Body::new(module.function(*function))))
+            .collect();
+        if bodies.is_empty() {
+            return false;
+        }
+
+        let mut changed = false;
+        for index in 0..module.functions.len() {
+            changed |= inline_calls(module, FunctionId(index), &bodies);
+        }
+        changed
+    }
+}
+
+impl Inlining {
+    fn is_inlinable(
+        &self,
+        module: &Module,
+        bindings: &HashMap<LocalId, FunctionId>,
+        id: FunctionId,
+    ) -> bool {
+        let function = module.function(id);
+        let size: usize = function
+            .blocks
+            .iter()
+            .map(|block| block.instructions.len())
+            .sum();
+        // Closures would capture the original locals and varargs belong to the caller.
+        let is_self_contained = instructions(function).all(|instruction| {
+            !matches!(
+                instruction,
+                InstructionKind::Assign(_, Rvalue::Closure(_) | Rvalue::VarArgs)
+            )
+        });
+        size <= self.threshold
+            && function.rest.is_none()
+            && is_self_contained
+            && !is_recursive(module, bindings, id)
+    }
+}
+
+/// Parameters, locals and blocks of an inlined function.
+struct Body {
+    params: Vec<LocalId>,
+    locals: Vec<LocalId>,
+    blocks: Vec<BasicBlock>,
+}
+
+impl Body {
+    fn new(function: &Function) -> Self {
+        Self {
+            params: function.params.clone(),
+            locals: function.locals.clone(),
+            blocks: function.blocks.clone(),
+        }
+    }
+}
+
+/// Functions bound to locals that are only assigned once.
+fn bindings(module: &Module) -> HashMap<LocalId, FunctionId> {
+    let counts = LocalCounts::new(module);
+    module
+        .functions
+        .iter()
+        .flat_map(instructions)
+        .filter_map(|instruction| match instruction {
+            InstructionKind::Assign(Place::Local(local), Rvalue::Closure(function))
+                if counts.assignments(*local) == 1 =>
+            {
+                Some((*local, *function))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns true if the function may call itself through the bound functions.
+fn is_recursive(module: &Module, bindings: &HashMap<LocalId, FunctionId>, id: FunctionId) -> bool {
+    let mut visited = vec![false; module.functions.len()];
+    let mut stack = vec![id];
+    while let Some(function) = stack.pop() {
+        for instruction in instructions(module.function(function)) {
+            let Some(callee) = callee(instruction).and_then(|local| bindings.get(&local)) else {
+                continue;
+            };
+            if *callee == id {
+                return true;
+            }
+            if !std::mem::replace(&mut visited[callee.0], true) {
+                stack.push(*callee);
+            }
+        }
+    }
+    false
+}
+
+fn instructions(function: &Function) -> impl Iterator<Item = &InstructionKind> {
+    function
+        .blocks
+        .iter()
+        .flat_map(|block| &block.instructions)
+        .map(|instruction| &instruction.kind)
+}
+
+/// The local called by the instruction.
+fn callee(instruction: &InstructionKind) -> Option<LocalId> {
+    match instruction {
+        InstructionKind::Call(Operand::Local(local), _)
+        | InstructionKind::Assign(_, Rvalue::Call(Operand::Local(local), _)) => Some(*local),
+        _ => None,
+    }
+}
+
+fn inline_calls(module: &mut Module, id: FunctionId, bodies: &HashMap<LocalId, Body>) -> bool {
+    let mut changed = false;
+    // Inlined blocks are appended to the function, So the calls they contain get inlined too.
+    let mut block = 0;
+    while block < module.function(id).blocks.len() {
+        let instructions = &module.function(id).blocks[block].instructions;
+        let call = instructions
+            .iter()
+            .enumerate()
+            .find_map(|(index, instruction)| {
+                let body = callee(&instruction.kind).and_then(|local| bodies.get(&local))?;
+                Some((index, body))
+            });
+        match call {
+            Some((index, body)) => {
+                inline_call(module, id, BlockId(block), index, body);
+                changed = true;
+            }
+            None => block += 1,
+        }
+    }
+    if changed {
+        module.function_mut(id).sort_blocks();
+    }
+    changed
+}
+
+/// Replace the call at `index` in the block with a copy of the body. The block jumps to the
+/// copied entry block and the copied returns jump to a new block continuing after the call.
+fn inline_call(module: &mut Module, id: FunctionId, block: BlockId, index: usize, body: &Body) {
+    let mut ids = HashMap::new();
+    for local in body.params.iter().chain(&body.locals) {
+        let Local { name, kind } = module.local(*local);
+        // Parameters become variables of the caller.
+        let kind = match kind {
+            LocalKind::Parameter => LocalKind::Variable,
+            kind => *kind,
+        };
+        ids.insert(*local, declare(module, id, name.clone(), kind));
+    }
+
+    let caller = &mut module.function_mut(id).blocks[block.0];
+    let after = caller.instructions.split_off(index + 1);
+    let Instruction { span, kind } = caller.instructions.pop().unwrap();
+    let (place, args) = match kind {
+        InstructionKind::Assign(place, Rvalue::Call(_, args)) => (Some(place), args),
+        InstructionKind::Call(_, args) => (None, args),
+        _ => unreachable!("Inlining an instruction other than a call."),
+    };
+    // Returned values are stored directly in a local, Other places are assigned after the
+    // inlined body, Like they would be after the call.
+    let (result, store) = match place {
+        Some(Place::Local(local)) => (Some(local), None),
+        Some(place) => {
+            let result = declare(module, id, Atom(Rc::from("t")), LocalKind::Temporary);
+            (Some(result), Some((place, result)))
+        }
+        None => (None, None),
+    };
+
+    let function = module.function_mut(id);
+    let offset = function.blocks.len();
+    let next = BlockId(offset + body.blocks.len());
+    let caller = &mut function.blocks[block.0];
+    for (index, param) in body.params.iter().enumerate() {
+        let value = args
+            .get(index)
+            .cloned()
+            .unwrap_or(Operand::Constant(Constant::Nil));
+        caller.instructions.push(Instruction {
+            span,
+            kind: InstructionKind::Assign(Place::Local(ids[param]), Rvalue::Use(value)),
+        });
+    }
+
+    let terminator = std::mem::replace(&mut caller.terminator, Terminator::Goto(BlockId(offset)));
+    for block in &body.blocks {
+        let mut block = block.clone();
+        remap_block_locals(&mut block, |local| {
+            if let Some(copy) = ids.get(local) {
+                *local = *copy;
+            }
+        });
+        for successor in block.terminator.successors_mut() {
+            successor.0 += offset;
+        }
+        if let Terminator::Return(value) = &block.terminator {
+            if let Some(result) = result {
+                let value = value.clone().unwrap_or(Operand::Constant(Constant::Nil));
+                block.instructions.push(Instruction {
+                    span,
+                    kind: InstructionKind::Assign(Place::Local(result), Rvalue::Use(value)),
+                });
+            }
+            block.terminator = Terminator::Goto(next);
+        }
+        function.blocks.push(block);
+    }
+
+    let mut instructions = Vec::with_capacity(after.len() + 1);
+    if let Some((place, result)) = store {
+        instructions.push(Instruction {
+            span,
+            kind: InstructionKind::Assign(place, Rvalue::Use(Operand::Local(result))),
+        });
+    }
+    instructions.extend(after);
+    function.blocks.push(BasicBlock {
+        instructions,
+        terminator,
+    });
+}
+
+fn declare(module: &mut Module, owner: FunctionId, name: Atom, kind: LocalKind) -> LocalId {
+    let local = LocalId(module.locals.len());
+    module.locals.push(Local { name, kind });
+    module.function_mut(owner).locals.push(local);
+    local
+}
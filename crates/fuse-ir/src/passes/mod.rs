@@ -0,0 +1,135 @@
+mod branches;
+mod constants;
+mod copies;
+mod dead_code;
+mod inline;
+
+pub use branches::PruneBranches;
+pub use constants::ConstantFolding;
+pub use copies::CopyPropagation;
+pub use dead_code::DeadCodeElimination;
+pub use inline::Inlining;
+
+use crate::{BasicBlock, InstructionKind, LocalId, Module, Operand, Place};
+
+/// A transformation of a module.
+pub trait Pass {
+    /// Run the pass, Returns true if it changed the module.
+    fn run(&mut self, module: &mut Module) -> bool;
+}
+
+/// Passes running in order, The whole pipeline is repeated until none of them changes the module.
+pub struct Pipeline {
+    passes: Vec<Box<dyn Pass>>,
+}
+
+impl Pipeline {
+    /// Upper bound of the pipeline repetitions, Each pass only simplifies the module so it
+    /// settles long before that.
+    const MAX_ITERATIONS: usize = 16;
+
+    pub fn new() -> Self {
+        Self { passes: Vec::new() }
+    }
+
+    /// The default optimization passes.
+    pub fn optimizations() -> Self {
+        Self::optimizations_with_threshold(Inlining::DEFAULT_THRESHOLD)
+    }
+
+    /// The default optimization passes, Inlining the functions up to `inline_threshold`
+    /// instructions.
+    pub fn optimizations_with_threshold(inline_threshold: usize) -> Self {
+        Self::new()
+            .with(Inlining::new(inline_threshold))
+            .with(CopyPropagation)
+            .with(ConstantFolding)
+            .with(PruneBranches)
+            .with(DeadCodeElimination)
+    }
+
+    pub fn with(mut self, pass: impl Pass + 'static) -> Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    pub fn run(&mut self, module: &mut Module) {
+        for _ in 0..Self::MAX_ITERATIONS {
+            let mut changed = false;
+            for pass in &mut self.passes {
+                changed |= pass.run(module);
+            }
+            if !changed {
+                break;
+            }
+        }
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::optimizations()
+    }
+}
+
+/// How many times each local is read and assigned in the whole module.
+struct LocalCounts {
+    uses: Vec<usize>,
+    assignments: Vec<usize>,
+}
+
+impl LocalCounts {
+    fn new(module: &Module) -> Self {
+        let mut counts = Self {
+            uses: vec![0; module.locals.len()],
+            assignments: vec![0; module.locals.len()],
+        };
+        for function in &module.functions {
+            for block in &function.blocks {
+                for instruction in &block.instructions {
+                    if let InstructionKind::Assign(Place::Local(local), _) = &instruction.kind {
+                        counts.assignments[local.0] += 1;
+                    }
+                    for operand in instruction.kind.operands() {
+                        counts.read(operand);
+                    }
+                }
+                if let Some(operand) = block.terminator.operand() {
+                    counts.read(operand);
+                }
+            }
+        }
+        counts
+    }
+
+    fn read(&mut self, operand: &Operand) {
+        if let Operand::Local(local) = operand {
+            self.uses[local.0] += 1;
+        }
+    }
+
+    fn uses(&self, local: LocalId) -> usize {
+        self.uses[local.0]
+    }
+
+    fn assignments(&self, local: LocalId) -> usize {
+        self.assignments[local.0]
+    }
+}
+
+/// Replace every local assigned or read in the block.
+fn remap_block_locals(block: &mut BasicBlock, mut remap: impl FnMut(&mut LocalId)) {
+    for instruction in &mut block.instructions {
+        if let InstructionKind::Assign(Place::Local(local), _) = &mut instruction.kind {
+            remap(local);
+        }
+        for operand in instruction.kind.operands_mut() {
+            if let Operand::Local(local) = operand {
+                remap(local);
+            }
+        }
+    }
+    if let Some(Operand::Local(local)) = block.terminator.operand_mut() {
+        remap(local);
+    }
+}
@@ -0,0 +1,56 @@
+//! Textual format of the IR, It is meant for debugging and for testing passes on handwritten IR.
+//!
+//! ```text
+//! fn @1 add(%0 a, %1 b) {
+//!     temp %2 t
+//!
+//!     bb0:
+//!         %2 = add %0, %1
+//!         return %2
+//! }
+//! ```
+
+mod parser;
+mod printer;
+
+pub use parser::{parse_module, ParseError};
+
+use crate::{BinaryOp, UnaryOp};
+
+const UNARY_OPS: [(UnaryOp, &str); 4] = [
+    (UnaryOp::Not, "not"),
+    (UnaryOp::Negate, "neg"),
+    (UnaryOp::Length, "len"),
+    (UnaryOp::Debug, "debug"),
+];
+
+const BINARY_OPS: [(BinaryOp, &str); 20] = [
+    (BinaryOp::Add, "add"),
+    (BinaryOp::Subtract, "sub"),
+    (BinaryOp::Multiply, "mul"),
+    (BinaryOp::Divide, "div"),
+    (BinaryOp::FloorDivide, "idiv"),
+    (BinaryOp::Modulo, "mod"),
+    (BinaryOp::Power, "pow"),
+    (BinaryOp::Equal, "eq"),
+    (BinaryOp::NotEqual, "ne"),
+    (BinaryOp::LessThan, "lt"),
+    (BinaryOp::LessThanEqual, "le"),
+    (BinaryOp::GreaterThan, "gt"),
+    (BinaryOp::GreaterThanEqual, "ge"),
+    (BinaryOp::BitwiseAnd, "band"),
+    (BinaryOp::BitwiseOr, "bor"),
+    (BinaryOp::BitwiseXor, "bxor"),
+    (BinaryOp::ShiftLeft, "shl"),
+    (BinaryOp::ShiftRight, "shr"),
+    (BinaryOp::And, "and"),
+    (BinaryOp::Or, "or"),
+];
+
+fn unary_op_name(op: UnaryOp) -> &'static str {
+    UNARY_OPS.iter().find(|(it, _)| *it == op).unwrap().1
+}
+
+fn binary_op_name(op: BinaryOp) -> &'static str {
+    BINARY_OPS.iter().find(|(it, _)| *it == op).unwrap().1
+}
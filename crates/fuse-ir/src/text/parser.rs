@@ -0,0 +1,738 @@
+use std::{iter::Peekable, rc::Rc, str::CharIndices};
+
+use fuse_ast::Atom;
+use fuse_common::Span;
+use thiserror::Error as ThisError;
+
+use super::{BINARY_OPS, UNARY_OPS};
+use crate::{
+    BasicBlock, BlockId, Constant, Function, FunctionId, Instruction, InstructionKind, Local,
+    LocalId, LocalKind, Module, Number, NumberKind, Operand, Place, Rvalue, SpreadKind, TableEntry,
+    Terminator,
+};
+
+#[derive(ThisError, Debug, PartialEq)]
+pub enum ParseError {
+    #[error("Expected {expected} instead found \"{found}\" at {line}:{column}")]
+    UnexpectedToken {
+        expected: String,
+        found: String,
+        line: usize,
+        column: usize,
+    },
+    #[error("Invalid character \"{char}\" at {line}:{column}")]
+    InvalidCharacter {
+        char: char,
+        line: usize,
+        column: usize,
+    },
+    #[error("Local %{0} is declared more than once")]
+    DuplicateLocal(usize),
+    #[error("Local %{0} is used but never declared")]
+    UndeclaredLocal(usize),
+    #[error("Function @{0} is used but never declared")]
+    UnknownFunction(usize),
+    #[error("Block bb{block} is used but never declared in the function @{function}")]
+    UnknownBlock { function: usize, block: usize },
+}
+
+type ParseResult<T> = Result<T, ParseError>;
+
+/// Parse a module from its textual format, It is the inverse of displaying a `Module`.
+/// Parsed instructions and functions have empty spans.
+pub fn parse_module(source: &str) -> ParseResult<Module> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        locals: Vec::new(),
+        used_locals: Vec::new(),
+        used_functions: Vec::new(),
+    };
+    parser.parse_module()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Identifier(String),
+    Local(usize),
+    Global(String),
+    Function(usize),
+    Number(Number),
+    String(String),
+    Punctuation(char),
+    Ellipsis,
+    Eof,
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            Self::Identifier(name) => name.clone(),
+            Self::Local(id) => format!("%{id}"),
+            Self::Global(name) => format!("${name}"),
+            Self::Function(id) => format!("@{id}"),
+            Self::Number(number) => format!("{:?}", number.value),
+            Self::String(value) => format!("{value:?}"),
+            Self::Punctuation(char) => char.to_string(),
+            Self::Ellipsis => "...".to_string(),
+            Self::Eof => "end of input".to_string(),
+        }
+    }
+}
+
+struct Token {
+    kind: TokenKind,
+    line: usize,
+    column: usize,
+}
+
+fn tokenize(source: &str) -> ParseResult<Vec<Token>> {
+    let mut tokenizer = Tokenizer {
+        source,
+        chars: source.char_indices().peekable(),
+        line: 1,
+        column: 1,
+    };
+    let mut tokens = Vec::new();
+    loop {
+        let token = tokenizer.next_token()?;
+        let is_eof = token.kind == TokenKind::Eof;
+        tokens.push(token);
+        if is_eof {
+            return Ok(tokens);
+        }
+    }
+}
+
+struct Tokenizer<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    fn next_token(&mut self) -> ParseResult<Token> {
+        self.skip_trivia();
+        let (line, column) = (self.line, self.column);
+        let token = |kind| Token { kind, line, column };
+        let Some((start, char)) = self.advance() else {
+            return Ok(token(TokenKind::Eof));
+        };
+        let kind = match char {
+            '%' => TokenKind::Local(self.index(line, column)?),
+            '@' => TokenKind::Function(self.index(line, column)?),
+            '$' => TokenKind::Global(self.word().to_string()),
+            '"' => TokenKind::String(self.string(line, column)?),
+            '.' if self.source[start..].starts_with("...") => {
+                self.advance();
+                self.advance();
+                TokenKind::Ellipsis
+            }
+            '-' | '0'..='9' => {
+                let text = self.number(start);
+                match parse_number(text) {
+                    Some(number) => TokenKind::Number(number),
+                    None => {
+                        return Err(ParseError::UnexpectedToken {
+                            expected: "a number".to_string(),
+                            found: text.to_string(),
+                            line,
+                            column,
+                        })
+                    }
+                }
+            }
+            '(' | ')' | '{' | '}' | '[' | ']' | ',' | '=' | ':' => TokenKind::Punctuation(char),
+            char if is_word_char(char) => {
+                self.word();
+                TokenKind::Identifier(self.source[start..self.offset()].to_string())
+            }
+            char => return Err(ParseError::InvalidCharacter { char, line, column }),
+        };
+        Ok(token(kind))
+    }
+
+    fn advance(&mut self) -> Option<(usize, char)> {
+        let next = self.chars.next()?;
+        if next.1 == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(next)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().map(|(_, char)| *char)
+    }
+
+    fn offset(&mut self) -> usize {
+        self.chars
+            .peek()
+            .map_or(self.source.len(), |(offset, _)| *offset)
+    }
+
+    /// Skip whitespaces and `//` comments.
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.peek() {
+                Some(char) if char.is_whitespace() => {
+                    self.advance();
+                }
+                Some('/') if self.source[self.offset()..].starts_with("//") => {
+                    while self.peek().is_some_and(|char| char != '\n') {
+                        self.advance();
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn word(&mut self) -> &'a str {
+        let start = self.offset();
+        while self.peek().is_some_and(is_word_char) {
+            self.advance();
+        }
+        &self.source[start..self.offset()]
+    }
+
+    fn index(&mut self, line: usize, column: usize) -> ParseResult<usize> {
+        let word = self.word();
+        word.parse().map_err(|_| ParseError::UnexpectedToken {
+            expected: "an index".to_string(),
+            found: word.to_string(),
+            line,
+            column,
+        })
+    }
+
+    /// Read the rest of a number, Including `-inf` and exponents such as `1e-7`.
+    fn number(&mut self, start: usize) -> &'a str {
+        let mut previous = self.source[start..].chars().next();
+        while let Some(char) = self.peek() {
+            let is_exponent_sign = matches!(char, '+' | '-') && matches!(previous, Some('e' | 'E'));
+            if !(is_word_char(char) || char == '.' || is_exponent_sign) {
+                break;
+            }
+            previous = Some(char);
+            self.advance();
+        }
+        &self.source[start..self.offset()]
+    }
+
+    fn string(&mut self, line: usize, column: usize) -> ParseResult<String> {
+        let mut value = String::new();
+        let unterminated = || ParseError::UnexpectedToken {
+            expected: "a closing quote".to_string(),
+            found: "end of input".to_string(),
+            line,
+            column,
+        };
+        loop {
+            let (_, char) = self.advance().ok_or_else(unterminated)?;
+            match char {
+                '"' => return Ok(value),
+                '\\' => {
+                    let (_, escaped) = self.advance().ok_or_else(unterminated)?;
+                    match escaped {
+                        'n' => value.push('\n'),
+                        'r' => value.push('\r'),
+                        't' => value.push('\t'),
+                        'u' => value.push(self.unicode_escape(line, column)?),
+                        escaped => value.push(escaped),
+                    }
+                }
+                char => value.push(char),
+            }
+        }
+    }
+
+    /// Read the `{hex}` part of a `\u{hex}` escape.
+    fn unicode_escape(&mut self, line: usize, column: usize) -> ParseResult<char> {
+        let start = self.offset();
+        while self.peek().is_some_and(|char| char != '}' && char != '"') {
+            self.advance();
+        }
+        let text = &self.source[start..self.offset()];
+        self.advance();
+        text.strip_prefix('{')
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .and_then(char::from_u32)
+            .ok_or_else(|| ParseError::UnexpectedToken {
+                expected: "a unicode escape".to_string(),
+                found: text.to_string(),
+                line,
+                column,
+            })
+    }
+}
+
+fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    locals: Vec<Option<Local>>,
+    used_locals: Vec<LocalId>,
+    used_functions: Vec<FunctionId>,
+}
+
+impl Parser {
+    fn parse_module(&mut self) -> ParseResult<Module> {
+        let mut functions = Vec::new();
+        while !self.at(&TokenKind::Eof) {
+            functions.push(self.parse_function(functions.len())?);
+        }
+
+        for function in &self.used_functions {
+            if function.0 >= functions.len() {
+                return Err(ParseError::UnknownFunction(function.0));
+            }
+        }
+        for local in &self.used_locals {
+            if !self.locals.get(local.0).is_some_and(Option::is_some) {
+                return Err(ParseError::UndeclaredLocal(local.0));
+            }
+        }
+        let mut locals = Vec::with_capacity(self.locals.len());
+        for (index, local) in self.locals.drain(..).enumerate() {
+            locals.push(local.ok_or(ParseError::UndeclaredLocal(index))?);
+        }
+        Ok(Module { functions, locals })
+    }
+
+    fn parse_function(&mut self, index: usize) -> ParseResult<Function> {
+        self.expect_keyword("fn")?;
+        match self.peek().clone() {
+            TokenKind::Function(id) if id == index => self.consume(),
+            _ => return Err(self.unexpected(&format!("@{index}"))),
+        };
+        let name = match self.peek().clone() {
+            TokenKind::Identifier(name) => {
+                self.consume();
+                Some(Atom(Rc::from(name)))
+            }
+            _ => None,
+        };
+
+        let mut function = Function {
+            span: Span::default(),
+            name,
+            params: Vec::new(),
+            rest: None,
+            locals: Vec::new(),
+            blocks: Vec::new(),
+        };
+        self.expect_punctuation('(')?;
+        while !self.at(&TokenKind::Punctuation(')')) {
+            if !function.params.is_empty() || function.rest.is_some() {
+                self.expect_punctuation(',')?;
+            }
+            if self.at(&TokenKind::Ellipsis) {
+                self.consume();
+                let Operand::Local(rest) = self.parse_operand()? else {
+                    return Err(self.unexpected_previous("a local"));
+                };
+                function.rest = Some(rest);
+            } else {
+                let param = self.parse_declaration(LocalKind::Parameter)?;
+                function.params.push(param);
+            }
+        }
+        self.expect_punctuation(')')?;
+        self.expect_punctuation('{')?;
+
+        loop {
+            let kind = match self.peek() {
+                TokenKind::Identifier(keyword) if keyword == "var" => LocalKind::Variable,
+                TokenKind::Identifier(keyword) if keyword == "temp" => LocalKind::Temporary,
+                TokenKind::Identifier(keyword) if keyword == "param" => LocalKind::Parameter,
+                _ => break,
+            };
+            self.consume();
+            let local = self.parse_declaration(kind)?;
+            function.locals.push(local);
+        }
+
+        while !self.at(&TokenKind::Punctuation('}')) {
+            let block = self.parse_block(function.blocks.len())?;
+            function.blocks.push(block);
+        }
+        self.expect_punctuation('}')?;
+
+        for block in &function.blocks {
+            for successor in block.terminator.successors() {
+                if successor.0 >= function.blocks.len() {
+                    return Err(ParseError::UnknownBlock {
+                        function: index,
+                        block: successor.0,
+                    });
+                }
+            }
+        }
+        Ok(function)
+    }
+
+    /// Parse a local and its name, e.g. `%1 x`.
+    fn parse_declaration(&mut self, kind: LocalKind) -> ParseResult<LocalId> {
+        let TokenKind::Local(id) = *self.peek() else {
+            return Err(self.unexpected("a local"));
+        };
+        self.consume();
+        let name = self.expect_identifier()?;
+        if self.locals.len() <= id {
+            self.locals.resize_with(id + 1, || None);
+        }
+        if self.locals[id].is_some() {
+            return Err(ParseError::DuplicateLocal(id));
+        }
+        self.locals[id] = Some(Local {
+            name: Atom(Rc::from(name)),
+            kind,
+        });
+        Ok(LocalId(id))
+    }
+
+    fn parse_block(&mut self, index: usize) -> ParseResult<BasicBlock> {
+        let label = format!("bb{index}");
+        match self.peek() {
+            TokenKind::Identifier(name) if *name == label => self.consume(),
+            _ => return Err(self.unexpected(&label)),
+        };
+        self.expect_punctuation(':')?;
+
+        let mut block = BasicBlock::new();
+        loop {
+            let keyword = match self.peek() {
+                TokenKind::Identifier(keyword) => keyword.as_str(),
+                _ => "",
+            };
+            match keyword {
+                "goto" | "branch" | "return" | "unreachable" => {
+                    block.terminator = self.parse_terminator()?;
+                    return Ok(block);
+                }
+                _ => {
+                    let kind = self.parse_instruction()?;
+                    block.instructions.push(Instruction {
+                        span: Span::default(),
+                        kind,
+                    });
+                }
+            }
+        }
+    }
+
+    fn parse_terminator(&mut self) -> ParseResult<Terminator> {
+        let keyword = self.expect_identifier()?;
+        let terminator = match keyword.as_str() {
+            "goto" => Terminator::Goto(self.parse_block_id()?),
+            "branch" => {
+                let condition = self.parse_operand()?;
+                self.expect_punctuation(',')?;
+                let then = self.parse_block_id()?;
+                self.expect_punctuation(',')?;
+                let r#else = self.parse_block_id()?;
+                Terminator::Branch {
+                    condition,
+                    then,
+                    r#else,
+                }
+            }
+            "return" if self.at_operand() => Terminator::Return(Some(self.parse_operand()?)),
+            "return" => Terminator::Return(None),
+            _ => Terminator::Unreachable,
+        };
+        Ok(terminator)
+    }
+
+    fn parse_block_id(&mut self) -> ParseResult<BlockId> {
+        let id = match self.peek() {
+            TokenKind::Identifier(name) => name.strip_prefix("bb").and_then(|id| id.parse().ok()),
+            _ => None,
+        };
+        let Some(id) = id else {
+            return Err(self.unexpected("a block"));
+        };
+        self.consume();
+        Ok(BlockId(id))
+    }
+
+    fn parse_instruction(&mut self) -> ParseResult<InstructionKind> {
+        match self.peek() {
+            TokenKind::Identifier(keyword) if keyword == "call" => {
+                self.consume();
+                let (callee, args) = self.parse_call()?;
+                Ok(InstructionKind::Call(callee, args))
+            }
+            TokenKind::Identifier(keyword) if keyword == "spread" => {
+                self.consume();
+                let kind = match self.expect_identifier()?.as_str() {
+                    "elements" => SpreadKind::Elements,
+                    "fields" => SpreadKind::Fields,
+                    _ => return Err(self.unexpected_previous("elements or fields")),
+                };
+                let target = self.parse_operand()?;
+                self.expect_punctuation(',')?;
+                let source = self.parse_operand()?;
+                Ok(InstructionKind::Spread {
+                    target,
+                    source,
+                    kind,
+                })
+            }
+            _ => {
+                let place = self.parse_place()?;
+                self.expect_punctuation('=')?;
+                let value = self.parse_rvalue()?;
+                Ok(InstructionKind::Assign(place, value))
+            }
+        }
+    }
+
+    fn parse_place(&mut self) -> ParseResult<Place> {
+        if !self.at_operand() {
+            return Err(self.unexpected("an instruction"));
+        }
+        let operand = self.parse_operand()?;
+        if self.at(&TokenKind::Punctuation('[')) {
+            let key = self.parse_index_key()?;
+            return Ok(Place::Index(operand, key));
+        }
+        match operand {
+            Operand::Local(local) => Ok(Place::Local(local)),
+            Operand::Global(name) => Ok(Place::Global(name)),
+            Operand::Constant(_) => Err(self.unexpected_previous("a place")),
+        }
+    }
+
+    fn parse_rvalue(&mut self) -> ParseResult<Rvalue> {
+        let keyword = match self.peek() {
+            TokenKind::Identifier(keyword) => keyword.clone(),
+            TokenKind::Punctuation('{') => return self.parse_table(),
+            _ => String::new(),
+        };
+        if let Some((op, _)) = UNARY_OPS.iter().find(|(_, name)| *name == keyword) {
+            self.consume();
+            return Ok(Rvalue::Unary(*op, self.parse_operand()?));
+        }
+        if let Some((op, _)) = BINARY_OPS.iter().find(|(_, name)| *name == keyword) {
+            self.consume();
+            let lhs = self.parse_operand()?;
+            self.expect_punctuation(',')?;
+            let rhs = self.parse_operand()?;
+            return Ok(Rvalue::Binary(*op, lhs, rhs));
+        }
+        match keyword.as_str() {
+            "concat" => {
+                self.consume();
+                let mut values = vec![self.parse_operand()?];
+                while self.at(&TokenKind::Punctuation(',')) {
+                    self.consume();
+                    values.push(self.parse_operand()?);
+                }
+                Ok(Rvalue::Concat(values))
+            }
+            "call" => {
+                self.consume();
+                let (callee, args) = self.parse_call()?;
+                Ok(Rvalue::Call(callee, args))
+            }
+            "closure" => {
+                self.consume();
+                let TokenKind::Function(id) = *self.peek() else {
+                    return Err(self.unexpected("a function"));
+                };
+                self.consume();
+                self.used_functions.push(FunctionId(id));
+                Ok(Rvalue::Closure(FunctionId(id)))
+            }
+            "varargs" => {
+                self.consume();
+                Ok(Rvalue::VarArgs)
+            }
+            _ => {
+                let operand = self.parse_operand()?;
+                if self.at(&TokenKind::Punctuation('[')) {
+                    let key = self.parse_index_key()?;
+                    return Ok(Rvalue::Index(operand, key));
+                }
+                Ok(Rvalue::Use(operand))
+            }
+        }
+    }
+
+    fn parse_table(&mut self) -> ParseResult<Rvalue> {
+        self.expect_punctuation('{')?;
+        let mut entries = Vec::new();
+        while !self.at(&TokenKind::Punctuation('}')) {
+            if !entries.is_empty() {
+                self.expect_punctuation(',')?;
+            }
+            let value = self.parse_operand()?;
+            if self.at(&TokenKind::Punctuation('=')) {
+                self.consume();
+                entries.push(TableEntry::Keyed(value, self.parse_operand()?));
+            } else {
+                entries.push(TableEntry::Positional(value));
+            }
+        }
+        self.expect_punctuation('}')?;
+        Ok(Rvalue::Table(entries))
+    }
+
+    /// Parse the callee and arguments of a call, e.g. `$print(%1, "text")`.
+    fn parse_call(&mut self) -> ParseResult<(Operand, Vec<Operand>)> {
+        let callee = self.parse_operand()?;
+        self.expect_punctuation('(')?;
+        let mut args = Vec::new();
+        while !self.at(&TokenKind::Punctuation(')')) {
+            if !args.is_empty() {
+                self.expect_punctuation(',')?;
+            }
+            args.push(self.parse_operand()?);
+        }
+        self.expect_punctuation(')')?;
+        Ok((callee, args))
+    }
+
+    fn parse_index_key(&mut self) -> ParseResult<Operand> {
+        self.expect_punctuation('[')?;
+        let key = self.parse_operand()?;
+        self.expect_punctuation(']')?;
+        Ok(key)
+    }
+
+    fn parse_operand(&mut self) -> ParseResult<Operand> {
+        let operand = match self.peek() {
+            TokenKind::Local(id) => Operand::Local(LocalId(*id)),
+            TokenKind::Global(name) => Operand::Global(Atom(Rc::from(name.as_str()))),
+            TokenKind::Number(number) => Operand::Constant(Constant::Number(number.clone())),
+            TokenKind::String(value) => {
+                Operand::Constant(Constant::String(Atom(Rc::from(value.as_str()))))
+            }
+            TokenKind::Identifier(name) => match name.as_str() {
+                "nil" => Operand::Constant(Constant::Nil),
+                "true" => Operand::Constant(Constant::Boolean(true)),
+                "false" => Operand::Constant(Constant::Boolean(false)),
+                "inf" => Operand::Constant(Constant::Number(Number::float(f64::INFINITY))),
+                "NaN" => Operand::Constant(Constant::Number(Number::float(f64::NAN))),
+                _ => return Err(self.unexpected("an operand")),
+            },
+            _ => return Err(self.unexpected("an operand")),
+        };
+        if let Operand::Local(local) = operand {
+            self.used_locals.push(local);
+        }
+        self.consume();
+        Ok(operand)
+    }
+
+    fn at_operand(&self) -> bool {
+        match self.peek() {
+            TokenKind::Local(_)
+            | TokenKind::Global(_)
+            | TokenKind::Number(_)
+            | TokenKind::String(_) => true,
+            TokenKind::Identifier(name) => {
+                matches!(name.as_str(), "nil" | "true" | "false" | "inf" | "NaN")
+            }
+            _ => false,
+        }
+    }
+
+    fn peek(&self) -> &TokenKind {
+        &self.tokens[self.position].kind
+    }
+
+    fn at(&self, kind: &TokenKind) -> bool {
+        self.peek() == kind
+    }
+
+    fn consume(&mut self) {
+        if self.position < self.tokens.len() - 1 {
+            self.position += 1;
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> ParseResult<()> {
+        match self.peek() {
+            TokenKind::Identifier(name) if name == keyword => {
+                self.consume();
+                Ok(())
+            }
+            _ => Err(self.unexpected(keyword)),
+        }
+    }
+
+    fn expect_identifier(&mut self) -> ParseResult<String> {
+        let TokenKind::Identifier(name) = self.peek().clone() else {
+            return Err(self.unexpected("an identifier"));
+        };
+        self.consume();
+        Ok(name)
+    }
+
+    fn expect_punctuation(&mut self, char: char) -> ParseResult<()> {
+        if self.at(&TokenKind::Punctuation(char)) {
+            self.consume();
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("\"{char}\"")))
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ParseError {
+        self.unexpected_at(self.position, expected)
+    }
+
+    fn unexpected_previous(&self, expected: &str) -> ParseError {
+        self.unexpected_at(self.position.saturating_sub(1), expected)
+    }
+
+    fn unexpected_at(&self, position: usize, expected: &str) -> ParseError {
+        let token = &self.tokens[position];
+        ParseError::UnexpectedToken {
+            expected: expected.to_string(),
+            found: token.kind.describe(),
+            line: token.line,
+            column: token.column,
+        }
+    }
+}
+
+/// Parse a number the way Lua reads it, Numbers with a fraction or an exponent are floats.
+fn parse_number(text: &str) -> Option<Number> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+    let (value, kind) = match digits.strip_prefix("0x") {
+        Some(hex) => (
+            i64::from_str_radix(hex, 16).ok()? as f64,
+            NumberKind::Integer,
+        ),
+        None if digits.contains(['.', 'e', 'E']) || digits == "inf" => {
+            (digits.parse().ok()?, NumberKind::Float)
+        }
+        None => (digits.parse::<u64>().ok()? as f64, NumberKind::Integer),
+    };
+    let number = Number::literal(value, kind, digits);
+    Some(if negative {
+        Number {
+            value: -number.value,
+            kind,
+            raw: number
+                .raw
+                .map(|raw| Atom(Rc::from(format!("-{}", raw.as_str())))),
+        }
+    } else {
+        number
+    })
+}
@@ -0,0 +1,230 @@
+use std::fmt::{self, Display, Formatter, Write};
+
+use super::{binary_op_name, unary_op_name};
+use crate::{
+    Constant, Function, FunctionId, Instruction, InstructionKind, LocalId, LocalKind, Module,
+    Number, Operand, Place, Rvalue, SpreadKind, TableEntry, Terminator,
+};
+
+impl Display for Module {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, function) in self.functions.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write_function(f, self, FunctionId(index), function)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_function(
+    f: &mut Formatter<'_>,
+    module: &Module,
+    id: FunctionId,
+    function: &Function,
+) -> fmt::Result {
+    write!(f, "fn {id}")?;
+    if let Some(name) = &function.name {
+        write!(f, " {}", name.as_str())?;
+    }
+    f.write_char('(')?;
+    for (index, param) in function.params.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{param} {}", module.local(*param).name.as_str())?;
+    }
+    // The rest parameter is declared with the other locals of the function.
+    if let Some(rest) = function.rest {
+        if !function.params.is_empty() {
+            f.write_str(", ")?;
+        }
+        write!(f, "...{rest}")?;
+    }
+    f.write_str(") {\n")?;
+
+    for local in &function.locals {
+        let keyword = match module.local(*local).kind {
+            LocalKind::Variable => "var",
+            LocalKind::Parameter => "param",
+            LocalKind::Temporary => "temp",
+        };
+        writeln!(
+            f,
+            "    {keyword} {local} {}",
+            module.local(*local).name.as_str()
+        )?;
+    }
+    if !function.locals.is_empty() {
+        writeln!(f)?;
+    }
+
+    for (index, block) in function.blocks.iter().enumerate() {
+        writeln!(f, "    bb{index}:")?;
+        for instruction in &block.instructions {
+            writeln!(f, "        {instruction}")?;
+        }
+        writeln!(f, "        {}", block.terminator)?;
+    }
+    f.write_str("}\n")
+}
+
+impl Display for FunctionId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "@{}", self.0)
+    }
+}
+
+impl Display for LocalId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "%{}", self.0)
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+impl Display for InstructionKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Assign(place, value) => write!(f, "{place} = {value}"),
+            Self::Call(callee, args) => {
+                write!(f, "call {callee}(")?;
+                write_list(f, args)?;
+                f.write_char(')')
+            }
+            Self::Spread {
+                target,
+                source,
+                kind,
+            } => {
+                let kind = match kind {
+                    SpreadKind::Elements => "elements",
+                    SpreadKind::Fields => "fields",
+                };
+                write!(f, "spread {kind} {target}, {source}")
+            }
+        }
+    }
+}
+
+impl Display for Place {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local(local) => local.fmt(f),
+            Self::Global(name) => write!(f, "${}", name.as_str()),
+            Self::Index(table, key) => write!(f, "{table}[{key}]"),
+        }
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Local(local) => local.fmt(f),
+            Self::Global(name) => write!(f, "${}", name.as_str()),
+            Self::Constant(constant) => constant.fmt(f),
+        }
+    }
+}
+
+impl Display for Constant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nil => f.write_str("nil"),
+            Self::Boolean(value) => value.fmt(f),
+            Self::Number(number) => write_number(f, number),
+            Self::String(value) => write_string(f, value.as_str()),
+        }
+    }
+}
+
+impl Display for Rvalue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Use(value) => value.fmt(f),
+            Self::Unary(op, value) => write!(f, "{} {value}", unary_op_name(*op)),
+            Self::Binary(op, lhs, rhs) => write!(f, "{} {lhs}, {rhs}", binary_op_name(*op)),
+            Self::Concat(values) => {
+                f.write_str("concat ")?;
+                write_list(f, values)
+            }
+            Self::Call(callee, args) => {
+                write!(f, "call {callee}(")?;
+                write_list(f, args)?;
+                f.write_char(')')
+            }
+            Self::Index(table, key) => write!(f, "{table}[{key}]"),
+            Self::Table(entries) => {
+                f.write_char('{')?;
+                for (index, entry) in entries.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    match entry {
+                        TableEntry::Positional(value) => write!(f, "{value}")?,
+                        TableEntry::Keyed(key, value) => write!(f, "{key} = {value}")?,
+                    }
+                }
+                f.write_char('}')
+            }
+            Self::Closure(function) => write!(f, "closure {function}"),
+            Self::VarArgs => f.write_str("varargs"),
+        }
+    }
+}
+
+impl Display for Terminator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Goto(target) => write!(f, "goto bb{}", target.0),
+            Self::Branch {
+                condition,
+                then,
+                r#else,
+            } => write!(f, "branch {condition}, bb{}, bb{}", then.0, r#else.0),
+            Self::Return(Some(value)) => write!(f, "return {value}"),
+            Self::Return(None) => f.write_str("return"),
+            Self::Unreachable => f.write_str("unreachable"),
+        }
+    }
+}
+
+fn write_list(f: &mut Formatter<'_>, operands: &[Operand]) -> fmt::Result {
+    for (index, operand) in operands.iter().enumerate() {
+        if index > 0 {
+            f.write_str(", ")?;
+        }
+        operand.fmt(f)?;
+    }
+    Ok(())
+}
+
+/// Write a number so it reads back to the same value, Integers are written without a fraction.
+/// Numbers are written as Lua literals, NaN and the infinities as `NaN`, `inf` and `-inf`.
+fn write_number(f: &mut Formatter<'_>, number: &Number) -> fmt::Result {
+    match number.lua_literal() {
+        Some(literal) => f.write_str(&literal),
+        None => write!(f, "{:?}", number.value),
+    }
+}
+
+fn write_string(f: &mut Formatter<'_>, value: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for char in value.chars() {
+        match char {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            char if char.is_control() => write!(f, "\\u{{{:x}}}", char as u32)?,
+            char => f.write_char(char)?,
+        }
+    }
+    f.write_char('"')
+}
@@ -0,0 +1,152 @@
+use std::{ffi::OsStr, fs, path::PathBuf};
+
+use fuse_ir::{lower, parse_module, Module, Pipeline};
+use fuse_parser::parse;
+use fuse_semantic::{Semantic, LUA_DECLARATIONS};
+
+struct Context<'a> {
+    root: PathBuf,
+    test_dir: PathBuf,
+    source_name: &'a str,
+    settings: insta::Settings,
+}
+
+impl<'a> Context<'a> {
+    fn new(test_dir: &str, source_name: &'a str) -> Self {
+        Self {
+            root: PathBuf::from(env!("CARGO_MANIFEST_DIR")),
+            test_dir: PathBuf::new().join("tests").join("cases").join(test_dir),
+            source_name,
+            settings: insta::Settings::clone_current(),
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        self.root.join(&self.test_dir)
+    }
+
+    fn settings(&self) -> insta::Settings {
+        self.settings.clone()
+    }
+}
+
+/// Lower Fuse code into IR.
+#[test]
+fn lowering() {
+    let ctx = Context::new("lower", "case.fuse");
+    for case in load_cases(&ctx) {
+        run(&ctx, case, |path, source| {
+            let module = lower_source(path, source);
+            test_round_trip(path, &module);
+            insta::assert_snapshot!("ir", module.to_string());
+        });
+    }
+}
+
+/// Optimize the IR of Fuse code.
+#[test]
+fn optimize() {
+    let ctx = Context::new("optimize", "case.fuse");
+    for case in load_cases(&ctx) {
+        run(&ctx, case, |path, source| {
+            let mut module = lower_source(path, source);
+            Pipeline::optimizations().run(&mut module);
+            test_round_trip(path, &module);
+            insta::assert_snapshot!("ir", module.to_string());
+        });
+    }
+}
+
+/// Read handwritten IR.
+#[test]
+fn text() {
+    let ctx = Context::new("text", "case.ir");
+    for case in load_cases(&ctx) {
+        run(&ctx, case, |path, source| {
+            let module = parse_module(source)
+                .unwrap_or_else(|error| panic!("Failed to parse the test case: {path}\n{error}"));
+            test_round_trip(path, &module);
+            insta::assert_snapshot!("ir", module.to_string());
+        });
+    }
+}
+
+/// Reject invalid IR.
+#[test]
+fn fail() {
+    let ctx = Context::new("fail", "case.ir");
+    for case in load_cases(&ctx) {
+        run(&ctx, case, |path, source| {
+            let Err(error) = parse_module(source) else {
+                panic!("Expected an error in the test case: {path}");
+            };
+            insta::assert_snapshot!("error", error.to_string());
+        });
+    }
+}
+
+// helpers
+fn load_cases(ctx: &Context) -> Vec<PathBuf> {
+    fs::read_dir(ctx.path())
+        .unwrap_or_else(|_| panic!("Failed to read {}", ctx.path().to_str().unwrap()))
+        .filter_map(|x| x.ok())
+        .filter(|x| x.metadata().is_ok_and(|meta| meta.is_dir()))
+        .map(|node| node.path())
+        .collect()
+}
+
+fn run(ctx: &Context, case_dir: PathBuf, test: impl FnOnce(&str, &str)) {
+    let mut settings = ctx.settings();
+    let source_path = case_dir.join(ctx.source_name);
+    let path_str = source_path.to_str().unwrap_or("unknown source");
+    let source = read_source_normalized(source_path.as_os_str()).unwrap();
+
+    settings.set_input_file(&source_path);
+    settings.set_snapshot_path(case_dir);
+    // if case source code is small include it in the snapshot.
+    if source.lines().count() <= 5 {
+        settings.set_description(source.clone());
+    }
+    settings.set_prepend_module_to_snapshot(false);
+
+    let _guard = settings.bind_to_scope();
+    test(path_str, &source);
+}
+
+fn lower_source(path: &str, source: &str) -> Module {
+    let parsed = parse(source);
+    assert!(
+        parsed.errors.is_empty() && !parsed.paniced,
+        "Failed to parse the test case: {path}\n{:?}",
+        parsed.errors
+    );
+    let chunk = parsed.chunk.unwrap();
+
+    let declarations = parse(LUA_DECLARATIONS).chunk.unwrap();
+    let semantic = Semantic::new()
+        .with_declarations(&declarations)
+        .build(&chunk);
+    assert!(
+        semantic.errors.is_empty(),
+        "Semantic errors in the test case: {path}\n{:#?}",
+        semantic.errors
+    );
+
+    lower(&chunk, &semantic, source)
+}
+
+/// Printing the module and parsing it back should give the same module.
+fn test_round_trip(path: &str, module: &Module) {
+    let text = module.to_string();
+    let parsed = parse_module(&text)
+        .unwrap_or_else(|error| panic!("Failed to read back the IR of: {path}\n{error}\n{text}"));
+    assert_eq!(
+        text,
+        parsed.to_string(),
+        "IR changes after reading it back: {path}"
+    );
+}
+
+fn read_source_normalized(path: &OsStr) -> Result<String, std::io::Error> {
+    fs::read_to_string(path).map(|it| it.replace("\r\n", "\n"))
+}
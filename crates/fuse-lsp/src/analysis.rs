@@ -0,0 +1,411 @@
+use fuse_ast::{Chunk, Identifier, Statement, VariableDeclarationKind};
+use fuse_common::{ReferenceType, Span};
+use fuse_parser::{cst::SyntaxTree, Parser};
+use fuse_semantic::{Semantic, SemanticResult, Symbol, SymbolKind};
+use fuse_visitor::{NodeVisitor, ScopeVisitor, Visitor};
+use fusec::CompletionKind;
+
+use crate::{
+    line_index::LineIndex,
+    protocol::{
+        self, CompletionItem, CompletionItemKind, Diagnostic, DiagnosticRelatedInformation,
+        DocumentSymbol, Hover, Location, MarkupContent, Position, Range,
+        TextDocumentContentChangeEvent, TextEdit, DIAGNOSTIC_SEVERITY_ERROR,
+    },
+};
+
+/// Analysis of a document, It answers the queries of the client about it.
+pub(crate) struct Analysis {
+    /// Syntax tree of the document, Parsed with recovery so it can be reparsed after edits.
+    tree: SyntaxTree,
+    index: LineIndex,
+    semantic: Option<SemanticResult>,
+    /// Resolved identifiers of the chunk and their symbols, Sorted by their spans.
+    identifiers: Vec<(Span, ReferenceType)>,
+    diagnostics: Vec<fusec::Diagnostic>,
+    /// False after a change with an invalid range, The document no longer matches the one of
+    /// the client until a change replaces the whole of it.
+    synced: bool,
+}
+
+impl Analysis {
+    /// Parse and analyze a document.
+    pub(crate) fn new(source: &str, declarations: &Chunk) -> Self {
+        Self::with_tree(
+            Parser::new(source).with_recovery().parse_lossless(),
+            declarations,
+        )
+    }
+
+    /// Analyze a parsed document, The semantic errors are only reported if there are no
+    /// syntax errors but the symbols of recovered chunks are still resolved.
+    pub(crate) fn with_tree(tree: SyntaxTree, declarations: &Chunk) -> Self {
+        let mut diagnostics: Vec<_> = tree
+            .parsed()
+            .errors
+            .iter()
+            .map(fusec::Diagnostic::from)
+            .collect();
+        let semantic = tree
+            .chunk()
+            .map(|chunk| Semantic::new().with_declarations(declarations).build(chunk));
+        if let (true, Some(semantic)) = (diagnostics.is_empty(), &semantic) {
+            diagnostics.extend(semantic.errors.iter().map(fusec::Diagnostic::from));
+        }
+
+        let mut collector = IdentifierCollector::default();
+        if let Some(chunk) = tree.chunk() {
+            collector.visit_chunk(chunk);
+        }
+        let mut identifiers = collector.identifiers;
+        identifiers.sort_by_key(|(span, _)| (span.start, span.end));
+
+        Self {
+            index: LineIndex::new(tree.source()),
+            tree,
+            semantic,
+            identifiers,
+            diagnostics,
+            synced: true,
+        }
+    }
+
+    /// Apply the changes of the client to the document in order and analyze the result, Only
+    /// the top-level statements touched by a change are parsed again. A change without a
+    /// range replaces the whole document.
+    ///
+    /// A change with an invalid range is rejected along with the next ranged changes, They
+    /// would edit a different text than the one of the client. The document is reported out
+    /// of sync until a change without a range resynchronizes it.
+    pub(crate) fn edit(
+        self,
+        changes: Vec<TextDocumentContentChangeEvent>,
+        declarations: &Chunk,
+    ) -> Self {
+        let (mut tree, mut index, mut synced) = (self.tree, self.index, self.synced);
+        for change in changes {
+            match change.range {
+                Some(range) if synced => {
+                    let edit = index
+                        .span(tree.source(), range)
+                        .map(|span| fuse_parser::TextEdit::new(span, change.text))
+                        .filter(|edit| edit.is_valid(tree.source()));
+                    match edit {
+                        Some(edit) => tree = Parser::reparse(tree, &edit).tree,
+                        None => synced = false,
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    tree = Parser::new(&change.text).with_recovery().parse_lossless();
+                    synced = true;
+                }
+            }
+            index = LineIndex::new(tree.source());
+        }
+        let mut analysis = Self::with_tree(tree, declarations);
+        if !synced {
+            analysis.synced = false;
+            analysis.diagnostics.insert(
+                0,
+                fusec::Diagnostic {
+                    message:
+                        "The document is out of sync with the editor, Reopen it to analyze it again"
+                            .to_string(),
+                    span: Span::new(0, 0),
+                    notes: Vec::new(),
+                },
+            );
+        }
+        analysis
+    }
+
+    pub(crate) fn source(&self) -> &str {
+        self.tree.source()
+    }
+
+    pub(crate) fn diagnostics(&self, uri: &str) -> Vec<Diagnostic> {
+        self.diagnostics
+            .iter()
+            .map(|diagnostic| Diagnostic {
+                range: self.range(diagnostic.span),
+                severity: DIAGNOSTIC_SEVERITY_ERROR,
+                source: "fuse",
+                message: diagnostic.message.clone(),
+                related_information: diagnostic
+                    .notes
+                    .iter()
+                    .map(|note| DiagnosticRelatedInformation {
+                        location: self.location(uri, note.span),
+                        message: note.message.clone(),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Location of the declaration of the symbol at `position`.
+    pub(crate) fn definition(&self, uri: &str, position: Position) -> Option<Location> {
+        let (_, reference) = self.identifier_at(position)?;
+        let span = self.declaration(reference)?;
+        Some(self.location(uri, span))
+    }
+
+    /// Locations of the identifiers referring to the symbol at `position`.
+    pub(crate) fn references(
+        &self,
+        uri: &str,
+        position: Position,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let Some((_, reference)) = self.identifier_at(position) else {
+            return Vec::new();
+        };
+        let declaration = self.declaration(reference);
+        self.identifiers
+            .iter()
+            .filter(|(span, other)| {
+                *other == reference && (include_declaration || Some(*span) != declaration)
+            })
+            .map(|(span, _)| self.location(uri, *span))
+            .collect()
+    }
+
+    /// Declaration of the symbol at `position` along with its type.
+    pub(crate) fn hover(&self, position: Position) -> Option<Hover> {
+        let (span, reference) = self.identifier_at(position)?;
+        let semantic = self.semantic.as_ref()?;
+        let symbol = semantic.symbols.get(reference);
+        Some(Hover {
+            contents: MarkupContent {
+                kind: "markdown",
+                value: format!("```fuse\n{}\n```", signature(symbol, semantic)),
+            },
+            range: self.range(span),
+        })
+    }
+
+    /// Outline of the structs, enums and functions declared at the top of the document.
+    pub(crate) fn document_symbols(&self) -> Vec<DocumentSymbol> {
+        let Some(chunk) = self.tree.chunk() else {
+            return Vec::new();
+        };
+        chunk
+            .body
+            .statements
+            .iter()
+            .filter_map(|statement| self.document_symbol(statement))
+            .collect()
+    }
+
+    fn document_symbol(&self, statement: &Statement) -> Option<DocumentSymbol> {
+        let symbol = |ident: &Identifier, kind, span, children| DocumentSymbol {
+            name: ident.name.as_str().to_string(),
+            detail: None,
+            kind,
+            range: self.range(span),
+            selection_range: self.range(ident.span),
+            children,
+        };
+        Some(match statement {
+            Statement::FunctionDeclaration(function) => {
+                let ident = function.signature.identifier.as_ref()?;
+                let mut symbol =
+                    symbol(ident, protocol::SymbolKind::Function, function.span, vec![]);
+                symbol.detail = self.detail(ident);
+                symbol
+            }
+            Statement::StructDeclaration(decl) => {
+                let fields = decl
+                    .fields
+                    .iter()
+                    .map(|field| {
+                        let span = Span::with_spans(vec![
+                            field.identifier.span,
+                            field.type_annotation.span,
+                        ]);
+                        let mut symbol =
+                            symbol(&field.identifier, protocol::SymbolKind::Field, span, vec![]);
+                        symbol.detail = self.detail(&field.identifier);
+                        symbol
+                    })
+                    .collect();
+                symbol(
+                    &decl.identifier,
+                    protocol::SymbolKind::Struct,
+                    decl.span,
+                    fields,
+                )
+            }
+            Statement::EnumDeclaration(decl) => {
+                let variants = decl
+                    .variants
+                    .iter()
+                    .map(|variant| {
+                        let span = match &variant.value {
+                            Some(value) => {
+                                Span::with_spans(vec![variant.identifier.span, value.span()])
+                            }
+                            None => variant.identifier.span,
+                        };
+                        symbol(
+                            &variant.identifier,
+                            protocol::SymbolKind::EnumMember,
+                            span,
+                            vec![],
+                        )
+                    })
+                    .collect();
+                symbol(
+                    &decl.identifier,
+                    protocol::SymbolKind::Enum,
+                    decl.span,
+                    variants,
+                )
+            }
+            _ => return None,
+        })
+    }
+
+    /// Edits formatting the whole document, It is `None` if the document doesn't parse.
+    pub(crate) fn format(&self) -> Option<Vec<TextEdit>> {
+        let formatted = fusec::format(self.source()).ok()?;
+        if formatted == self.source() {
+            return Some(Vec::new());
+        }
+        Some(vec![TextEdit {
+            range: self.range(Span::new(0, self.source().len() as u32)),
+            new_text: formatted,
+        }])
+    }
+
+    /// Completions at `position`, The document is reparsed with the cursor so they work on
+    /// broken sources too.
+    pub(crate) fn completion(
+        &self,
+        position: Position,
+        declarations: &Chunk,
+    ) -> Vec<CompletionItem> {
+        fusec::complete_with_declarations(self.source(), self.offset(position), declarations)
+            .into_iter()
+            .map(|completion| CompletionItem {
+                label: completion.label,
+                kind: completion_kind(completion.kind),
+                detail: completion.detail,
+            })
+            .collect()
+    }
+
+    /// Resolved identifier at `position`, Positions right after an identifier point to it too.
+    fn identifier_at(&self, position: Position) -> Option<(Span, ReferenceType)> {
+        let offset = self.index.offset(self.source(), position);
+        let containing =
+            |(span, _): &&(Span, ReferenceType)| span.start <= offset && offset < span.end;
+        self.identifiers
+            .iter()
+            .find(containing)
+            .or_else(|| self.identifiers.iter().find(|(span, _)| span.end == offset))
+            .copied()
+    }
+
+    /// Span of the identifier declaring a symbol, It is `None` for the symbols declared outside
+    /// of the document, e.g. the Lua globals.
+    fn declaration(&self, reference: ReferenceType) -> Option<Span> {
+        let span = self.semantic.as_ref()?.symbols.get(reference).span;
+        self.identifiers
+            .contains(&(span, reference))
+            .then_some(span)
+    }
+
+    /// Type of the symbol an identifier declares.
+    fn detail(&self, ident: &Identifier) -> Option<String> {
+        let semantic = self.semantic.as_ref()?;
+        let symbol = semantic.symbols.of(ident)?;
+        Some(symbol.ty.display(&semantic.symbols).to_string())
+    }
+
+    /// Byte offset of a position in the document.
+    pub(crate) fn offset(&self, position: Position) -> u32 {
+        self.index.offset(self.source(), position)
+    }
+
+    pub(crate) fn range(&self, span: Span) -> Range {
+        self.index.range(self.source(), span)
+    }
+
+    fn location(&self, uri: &str, span: Span) -> Location {
+        Location {
+            uri: uri.to_string(),
+            range: self.range(span),
+        }
+    }
+}
+
+fn completion_kind(kind: CompletionKind) -> CompletionItemKind {
+    match kind {
+        CompletionKind::Variable => CompletionItemKind::Variable,
+        CompletionKind::Constant => CompletionItemKind::Constant,
+        CompletionKind::Function => CompletionItemKind::Function,
+        CompletionKind::Field => CompletionItemKind::Field,
+        CompletionKind::Method => CompletionItemKind::Method,
+        CompletionKind::Struct => CompletionItemKind::Struct,
+        CompletionKind::Enum | CompletionKind::Union => CompletionItemKind::Enum,
+        CompletionKind::Variant => CompletionItemKind::EnumMember,
+        CompletionKind::TypeAlias | CompletionKind::Primitive => CompletionItemKind::Class,
+        CompletionKind::TypeParameter => CompletionItemKind::TypeParameter,
+        CompletionKind::Keyword => CompletionItemKind::Keyword,
+    }
+}
+
+/// Declaration of a symbol as it would be written in Fuse, e.g. `let count: number`.
+fn signature(symbol: &Symbol, semantic: &SemanticResult) -> String {
+    let name = symbol.name.as_str();
+    let ty = symbol.ty.display(&semantic.symbols).to_string();
+    match symbol.kind {
+        SymbolKind::Variable(kind) => {
+            let keyword = match kind {
+                VariableDeclarationKind::Let => "let",
+                VariableDeclarationKind::Const => "const",
+                VariableDeclarationKind::Global => "global",
+            };
+            let keyword = if symbol.mutable {
+                format!("{keyword} mut")
+            } else {
+                keyword.to_string()
+            };
+            format!("{keyword} {name}: {ty}")
+        }
+        SymbolKind::Function | SymbolKind::Method => match ty.strip_prefix("fn") {
+            Some(signature) => format!("fn {name}{signature}"),
+            None => format!("fn {name}: {ty}"),
+        },
+        SymbolKind::Struct => format!("struct {name}"),
+        SymbolKind::Enum => format!("enum {name}"),
+        SymbolKind::Union => format!("union {name}"),
+        SymbolKind::TypeAlias => format!("type {name} = {ty}"),
+        SymbolKind::TypeParameter => name.to_string(),
+        SymbolKind::Extern => format!("extern {name}: {ty}"),
+        SymbolKind::Parameter
+        | SymbolKind::Field
+        | SymbolKind::EnumVariant
+        | SymbolKind::UnionVariant => format!("{name}: {ty}"),
+    }
+}
+
+/// Collects the resolved identifiers of a chunk.
+#[derive(Default)]
+struct IdentifierCollector {
+    identifiers: Vec<(Span, ReferenceType)>,
+}
+
+impl ScopeVisitor for IdentifierCollector {}
+
+impl NodeVisitor for IdentifierCollector {}
+
+impl<'ast> Visitor<'ast> for IdentifierCollector {
+    fn visit_identifier(&mut self, ident: &'ast Identifier) {
+        if let Some(reference) = ident.reference.get() {
+            self.identifiers.push((ident.span, reference));
+        }
+    }
+}
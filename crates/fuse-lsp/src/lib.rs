@@ -0,0 +1,23 @@
+//! Language server of Fuse, It speaks the Language Server Protocol over stdio.
+//!
+//! Documents are synchronized incrementally, Only the statements touched by an edit are parsed
+//! again before the document is analyzed. The server supports diagnostics, go to definition,
+//! find references, hover, document symbols, formatting, rename and completion.
+
+mod analysis;
+mod line_index;
+mod protocol;
+mod rpc;
+mod server;
+
+use std::io;
+
+pub use server::Server;
+
+/// Serve a client over the standard input and output, It returns true if the client shut the
+/// server down before exiting.
+pub fn run_stdio() -> io::Result<bool> {
+    let mut server = Server::new();
+    server.run(io::stdin().lock(), io::stdout().lock())?;
+    Ok(server.is_shut_down())
+}
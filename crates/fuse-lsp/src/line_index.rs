@@ -0,0 +1,66 @@
+use fuse_common::Span;
+
+use crate::protocol::{Position, Range};
+
+/// Converts the byte offsets of a document to the positions of the protocol and back,
+/// Characters of the positions are counted in UTF-16 code units.
+pub(crate) struct LineIndex {
+    /// Offset of the start of each line.
+    line_starts: Vec<u32>,
+}
+
+impl LineIndex {
+    pub(crate) fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(
+                source
+                    .match_indices('\n')
+                    .map(|(index, _)| index as u32 + 1),
+            )
+            .collect();
+        Self { line_starts }
+    }
+
+    pub(crate) fn position(&self, source: &str, offset: u32) -> Position {
+        let offset = offset.min(source.len() as u32);
+        let line = self.line_starts.partition_point(|start| *start <= offset) - 1;
+        let start = self.line_starts[line] as usize;
+        let text = source.get(start..offset as usize).unwrap_or_default();
+        Position {
+            line: line as u32,
+            character: text.encode_utf16().count() as u32,
+        }
+    }
+
+    pub(crate) fn range(&self, source: &str, span: Span) -> Range {
+        Range {
+            start: self.position(source, span.start),
+            end: self.position(source, span.end),
+        }
+    }
+
+    /// Span of a range of the client, Positions past the end of their line or of the document
+    /// are clamped to it. Returns `None` for ranges ending before their start.
+    pub(crate) fn span(&self, source: &str, range: Range) -> Option<Span> {
+        let start = self.offset(source, range.start);
+        let end = self.offset(source, range.end);
+        (start <= end).then(|| Span::new(start, end))
+    }
+
+    /// Offset of a position, Positions past the end of their line point to the end of it.
+    pub(crate) fn offset(&self, source: &str, position: Position) -> u32 {
+        let Some(start) = self.line_starts.get(position.line as usize) else {
+            return source.len() as u32;
+        };
+        let line = &source[*start as usize..];
+        let line = &line[..line.find('\n').unwrap_or(line.len())];
+        let mut units = 0;
+        for (index, char) in line.char_indices() {
+            if units >= position.character {
+                return start + index as u32;
+            }
+            units += char.len_utf16() as u32;
+        }
+        start + line.len() as u32
+    }
+}
@@ -0,0 +1,51 @@
+use std::io::{self, BufRead, Write};
+
+use serde_json::Value;
+
+/// Read the body of the next message, It is `None` at the end of the input.
+///
+/// Messages start with headers separated from the body by an empty line, e.g.
+/// `Content-Length: 52\r\n\r\n{"jsonrpc":"2.0","id":1,"method":"shutdown"}`.
+pub(crate) fn read_message(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(invalid_data(format!("Invalid header \"{line}\"")));
+        };
+        if name.eq_ignore_ascii_case("Content-Length") {
+            let value = value.trim();
+            length = Some(
+                value
+                    .parse::<usize>()
+                    .map_err(|_| invalid_data(format!("Invalid content length \"{value}\"")))?,
+            );
+        }
+    }
+
+    let mut body = vec![0; length.unwrap_or_default()];
+    reader.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|_| invalid_data("The message isn't valid UTF-8".to_string()))
+}
+
+pub(crate) fn write_message(writer: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    writer.flush()
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
@@ -0,0 +1,377 @@
+use std::{
+    cell::OnceCell,
+    collections::HashMap,
+    io::{self, BufRead, Write},
+    panic::{self, AssertUnwindSafe},
+};
+
+use fuse_ast::Chunk;
+use fusec::{rename, RenameModule};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    analysis::Analysis,
+    protocol::{
+        error_codes, Diagnostic, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+        DidOpenTextDocumentParams, DocumentParams, Position, PublishDiagnosticsParams, Range,
+        ReferenceParams, RenameParams, TextDocumentContentChangeEvent, TextDocumentPositionParams,
+        TextEdit, WorkspaceEdit, DIAGNOSTIC_SEVERITY_ERROR, TEXT_DOCUMENT_SYNC_INCREMENTAL,
+    },
+    rpc,
+};
+
+/// Open document and its analysis.
+struct Document {
+    version: i32,
+    analysis: Analysis,
+}
+
+/// Error of a request, Sent back as the error of its response.
+struct ResponseError {
+    code: i32,
+    message: String,
+}
+
+impl ResponseError {
+    fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+type RequestResult = Result<Value, ResponseError>;
+
+/// Language server of Fuse, It answers the messages of a client one at a time.
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    /// Declarations of the Lua standard library, Parsed once for all the documents.
+    declarations: OnceCell<Chunk>,
+    initialized: bool,
+    shutdown: bool,
+    exited: bool,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve the messages of `reader` until the client exits or closes it, Responses and
+    /// notifications are written to `writer`.
+    pub fn run(&mut self, mut reader: impl BufRead, mut writer: impl Write) -> io::Result<()> {
+        while let Some(message) = rpc::read_message(&mut reader)? {
+            for outgoing in self.handle(&message) {
+                rpc::write_message(&mut writer, &outgoing)?;
+            }
+            if self.exited {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if the client asked the server to shut down before exiting.
+    pub fn is_shut_down(&self) -> bool {
+        self.shutdown
+    }
+
+    /// Handle a message and return the messages to send back.
+    pub fn handle(&mut self, message: &str) -> Vec<Value> {
+        let message: Value = match serde_json::from_str(message) {
+            Ok(message) => message,
+            Err(error) => {
+                let error = ResponseError::new(error_codes::PARSE_ERROR, error.to_string());
+                return vec![response(Value::Null, Err(error))];
+            }
+        };
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            // Responses to requests of the server, It doesn't send any.
+            return Vec::new();
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        match message.get("id") {
+            Some(id) => {
+                // A failing request is answered with an error instead of taking the server down.
+                let result = panic::catch_unwind(AssertUnwindSafe(|| self.request(method, params)))
+                    .unwrap_or_else(|_| {
+                        Err(ResponseError::new(
+                            error_codes::INTERNAL_ERROR,
+                            format!("Failed to handle \"{method}\""),
+                        ))
+                    });
+                vec![response(id.clone(), result)]
+            }
+            None => self.notification(method, params),
+        }
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> RequestResult {
+        if method == "initialize" {
+            self.initialized = true;
+            return Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": TEXT_DOCUMENT_SYNC_INCREMENTAL,
+                    "definitionProvider": true,
+                    "referencesProvider": true,
+                    "hoverProvider": true,
+                    "documentSymbolProvider": true,
+                    "documentFormattingProvider": true,
+                    "renameProvider": true,
+                    "completionProvider": { "triggerCharacters": ["."] },
+                },
+                "serverInfo": { "name": "fuse", "version": env!("CARGO_PKG_VERSION") },
+            }));
+        }
+        if !self.initialized {
+            return Err(ResponseError::new(
+                error_codes::SERVER_NOT_INITIALIZED,
+                "The server isn't initialized",
+            ));
+        }
+        if self.shutdown {
+            return Err(ResponseError::new(
+                error_codes::INVALID_REQUEST,
+                "The server is shut down",
+            ));
+        }
+
+        match method {
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/definition" => {
+                let params: TextDocumentPositionParams = parse_params(params)?;
+                let uri = &params.text_document.uri;
+                to_value(self.analysis(uri)?.definition(uri, params.position))
+            }
+            "textDocument/references" => {
+                let params: ReferenceParams = parse_params(params)?;
+                let uri = &params.position.text_document.uri;
+                let analysis = self.analysis(uri)?;
+                to_value(analysis.references(
+                    uri,
+                    params.position.position,
+                    params.context.include_declaration,
+                ))
+            }
+            "textDocument/hover" => {
+                let params: TextDocumentPositionParams = parse_params(params)?;
+                to_value(
+                    self.analysis(&params.text_document.uri)?
+                        .hover(params.position),
+                )
+            }
+            "textDocument/documentSymbol" => {
+                let params: DocumentParams = parse_params(params)?;
+                to_value(self.analysis(&params.text_document.uri)?.document_symbols())
+            }
+            "textDocument/formatting" => {
+                let params: DocumentParams = parse_params(params)?;
+                to_value(self.analysis(&params.text_document.uri)?.format())
+            }
+            "textDocument/completion" => {
+                let params: TextDocumentPositionParams = parse_params(params)?;
+                let analysis = self.analysis(&params.text_document.uri)?;
+                to_value(analysis.completion(params.position, self.declarations()))
+            }
+            "textDocument/rename" => {
+                let params: RenameParams = parse_params(params)?;
+                to_value(self.rename(params)?)
+            }
+            _ => Err(ResponseError::new(
+                error_codes::METHOD_NOT_FOUND,
+                format!("Unknown method \"{method}\""),
+            )),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: Value) -> Vec<Value> {
+        match method {
+            "exit" => {
+                self.exited = true;
+                Vec::new()
+            }
+            "textDocument/didOpen" => {
+                let Ok(params) = parse_params::<DidOpenTextDocumentParams>(params) else {
+                    return Vec::new();
+                };
+                let document = params.text_document;
+                self.open(document.uri, document.version, document.text)
+            }
+            "textDocument/didChange" => {
+                let Ok(params) = parse_params::<DidChangeTextDocumentParams>(params) else {
+                    return Vec::new();
+                };
+                let document = params.text_document;
+                self.change(document.uri, document.version, params.content_changes)
+            }
+            "textDocument/didClose" => {
+                let Ok(params) = parse_params::<DidCloseTextDocumentParams>(params) else {
+                    return Vec::new();
+                };
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                vec![publish_diagnostics(PublishDiagnosticsParams {
+                    uri,
+                    version: None,
+                    diagnostics: Vec::new(),
+                })]
+            }
+            // Other notifications, e.g. `initialized`, don't need anything.
+            _ => Vec::new(),
+        }
+    }
+
+    /// Analyze the content of an opened document and publish its diagnostics.
+    fn open(&mut self, uri: String, version: i32, text: String) -> Vec<Value> {
+        self.analyze(uri, version, |declarations| {
+            Analysis::new(&text, declarations)
+        })
+    }
+
+    /// Apply the changes of a document and publish its new diagnostics, Changes of documents
+    /// that aren't open are ignored.
+    fn change(
+        &mut self,
+        uri: String,
+        version: i32,
+        changes: Vec<TextDocumentContentChangeEvent>,
+    ) -> Vec<Value> {
+        let Some(document) = self.documents.remove(&uri) else {
+            return Vec::new();
+        };
+        self.analyze(uri, version, |declarations| {
+            document.analysis.edit(changes, declarations)
+        })
+    }
+
+    /// Store the analysis of a document and publish its diagnostics, A document whose analysis
+    /// fails is closed with a diagnostic so the other documents are still served.
+    fn analyze(
+        &mut self,
+        uri: String,
+        version: i32,
+        analyze: impl FnOnce(&Chunk) -> Analysis,
+    ) -> Vec<Value> {
+        let declarations = self.declarations();
+        let diagnostics = match panic::catch_unwind(AssertUnwindSafe(|| analyze(declarations))) {
+            Ok(analysis) => {
+                let diagnostics = analysis.diagnostics(&uri);
+                self.documents
+                    .insert(uri.clone(), Document { version, analysis });
+                diagnostics
+            }
+            Err(_) => {
+                let start = Position {
+                    line: 0,
+                    character: 0,
+                };
+                vec![Diagnostic {
+                    range: Range { start, end: start },
+                    severity: DIAGNOSTIC_SEVERITY_ERROR,
+                    source: "fuse",
+                    message: "Failed to analyze the document, Reopen it to try again".to_string(),
+                    related_information: Vec::new(),
+                }]
+            }
+        };
+        vec![publish_diagnostics(PublishDiagnosticsParams {
+            uri,
+            version: Some(version),
+            diagnostics,
+        })]
+    }
+
+    fn declarations(&self) -> &Chunk {
+        self.declarations.get_or_init(fusec::parse_declarations)
+    }
+
+    fn analysis(&self, uri: &str) -> Result<&Analysis, ResponseError> {
+        self.documents
+            .get(uri)
+            .map(|document| &document.analysis)
+            .ok_or_else(|| {
+                ResponseError::new(
+                    error_codes::INVALID_PARAMS,
+                    format!("The document \"{uri}\" isn't open"),
+                )
+            })
+    }
+
+    /// Rename the symbol at a position of a document, The open documents are the modules of
+    /// the program so module exports are renamed in all of them.
+    fn rename(&self, params: RenameParams) -> Result<WorkspaceEdit, ResponseError> {
+        let uri = &params.position.text_document.uri;
+        let offset = self.analysis(uri)?.offset(params.position.position);
+
+        let mut uris: Vec<&String> = self.documents.keys().collect();
+        uris.sort();
+        let modules: Vec<_> = uris
+            .iter()
+            .map(|uri| RenameModule::new(*uri, self.documents[*uri].analysis.source()))
+            .collect();
+        let target = uris
+            .iter()
+            .position(|it| *it == uri)
+            .expect("The document is open.");
+
+        let renamed = rename(&modules, target, offset, &params.new_name)
+            .map_err(|error| ResponseError::new(error_codes::REQUEST_FAILED, error.to_string()))?;
+        let changes = renamed
+            .into_iter()
+            .map(|module| {
+                let analysis = &self.documents[&module.module].analysis;
+                let edits = module
+                    .edits
+                    .into_iter()
+                    .map(|edit| TextEdit {
+                        range: analysis.range(edit.span),
+                        new_text: edit.text,
+                    })
+                    .collect();
+                (module.module, edits)
+            })
+            .collect();
+        Ok(WorkspaceEdit { changes })
+    }
+
+    /// Content and version of an open document.
+    pub fn document(&self, uri: &str) -> Option<(&str, i32)> {
+        self.documents
+            .get(uri)
+            .map(|document| (document.analysis.source(), document.version))
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, ResponseError> {
+    serde_json::from_value(params)
+        .map_err(|error| ResponseError::new(error_codes::INVALID_PARAMS, error.to_string()))
+}
+
+fn to_value(result: impl Serialize) -> RequestResult {
+    Ok(serde_json::to_value(result).expect("Failed to serialize a response."))
+}
+
+fn response(id: Value, result: RequestResult) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message },
+        }),
+    }
+}
+
+fn publish_diagnostics(params: PublishDiagnosticsParams) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": params,
+    })
+}
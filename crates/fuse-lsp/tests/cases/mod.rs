@@ -0,0 +1,127 @@
+use std::{ffi::OsStr, fs, io::BufRead, path::PathBuf};
+
+use fuse_lsp::Server;
+use serde_json::{json, Value};
+
+const URI: &str = "file:///case.fuse";
+
+struct Context<'a> {
+    root: PathBuf,
+    test_dir: PathBuf,
+    source_name: &'a str,
+    messages_name: &'a str,
+    settings: insta::Settings,
+}
+
+impl<'a> Context<'a> {
+    fn path(&self) -> PathBuf {
+        self.root.join(&self.test_dir)
+    }
+
+    fn settings(&self) -> insta::Settings {
+        self.settings.clone()
+    }
+}
+
+#[test]
+fn session() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("session"),
+        source_name: "case.fuse",
+        messages_name: "messages.json",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run(&ctx, case);
+    }
+}
+
+// helpers
+fn load_cases(ctx: &Context) -> Vec<PathBuf> {
+    fs::read_dir(ctx.path())
+        .unwrap_or_else(|_| panic!("Failed to read {}", ctx.path().to_str().unwrap()))
+        .filter_map(|x| x.ok())
+        .filter(|x| x.metadata().is_ok_and(|meta| meta.is_dir()))
+        .map(|node| node.path())
+        .collect()
+}
+
+fn run(ctx: &Context, case_dir: PathBuf) {
+    let mut settings = ctx.settings();
+    let source_path = case_dir.join(ctx.source_name);
+    let messages_path = case_dir.join(ctx.messages_name);
+    let source = read_source_normalized(source_path.as_os_str()).unwrap();
+    let messages = read_source_normalized(messages_path.as_os_str()).unwrap();
+    let messages: Vec<Value> = serde_json::from_str(&messages)
+        .unwrap_or_else(|error| panic!("Invalid {}: {error}", messages_path.display()));
+
+    settings.set_input_file(&messages_path);
+    settings.set_snapshot_path(case_dir);
+    settings.set_prepend_module_to_snapshot(false);
+
+    let _guard = settings.bind_to_scope();
+    test_session(&source, messages);
+}
+
+/// Drive a session opening the case and sending its messages, Messages with an `id` are
+/// requests and the others are notifications.
+fn test_session(source: &str, messages: Vec<Value>) {
+    let mut session = vec![
+        json!({ "id": 0, "method": "initialize", "params": { "capabilities": {} } }),
+        json!({ "method": "initialized", "params": {} }),
+        json!({
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": { "uri": URI, "languageId": "fuse", "version": 1, "text": source },
+            },
+        }),
+    ];
+    session.extend(messages);
+    session.push(json!({ "id": "shutdown", "method": "shutdown" }));
+    session.push(json!({ "method": "exit" }));
+
+    let mut input = Vec::new();
+    for mut message in session {
+        message["jsonrpc"] = json!("2.0");
+        let body = message.to_string();
+        input.extend(format!("Content-Length: {}\r\n\r\n{body}", body.len()).into_bytes());
+    }
+    let mut output = Vec::new();
+    let mut server = Server::new();
+    server.run(input.as_slice(), &mut output).unwrap();
+    assert!(server.is_shut_down(), "The server didn't shut down.");
+
+    let transcript = read_messages(&output)
+        .iter()
+        .map(|message| serde_json::to_string_pretty(message).unwrap())
+        .collect::<Vec<_>>()
+        .join("\n");
+    insta::assert_snapshot!("transcript", transcript);
+}
+
+fn read_messages(mut output: &[u8]) -> Vec<Value> {
+    let mut messages = Vec::new();
+    loop {
+        let mut header = String::new();
+        if output.read_line(&mut header).unwrap() == 0 {
+            return messages;
+        }
+        let length: usize = header
+            .trim()
+            .strip_prefix("Content-Length: ")
+            .and_then(|length| length.parse().ok())
+            .unwrap_or_else(|| panic!("Invalid header {header:?}"));
+        output.read_line(&mut String::new()).unwrap();
+        let (body, rest) = output.split_at(length);
+        messages.push(serde_json::from_slice(body).unwrap());
+        output = rest;
+    }
+}
+
+fn read_source_normalized(path: &OsStr) -> Result<String, std::io::Error> {
+    fs::read_to_string(path).map(|it| it.replace("\r\n", "\n"))
+}
@@ -0,0 +1,228 @@
+use std::cmp::Reverse;
+
+use fuse_ast::{AstNode, Chunk, ExternItem, GetSpan, Statement, VisibilityModifier};
+use fuse_common::Span;
+use fuse_visitor::{NodeVisitor, ScopeVisitor, Visitor};
+
+use super::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
+use crate::lexer::TokenReference;
+
+/// Build the tree of a chunk from its tokens and AST, `tokens` has to cover the whole source.
+pub(crate) fn build(
+    source_len: u32,
+    tokens: &[TokenReference],
+    chunk: Option<&Chunk>,
+) -> SyntaxNode {
+    let mut collector = NodeCollector::default();
+    if let Some(chunk) = chunk {
+        collector.visit_chunk(chunk);
+    }
+    build_node(Span::new(0, source_len), tokens, collector)
+}
+
+/// Build a `Chunk` node spanning a part of the source out of the top-level `statements` in it,
+/// `tokens` has to cover the whole `span`.
+pub(crate) fn build_statements(
+    span: Span,
+    tokens: &[TokenReference],
+    statements: &[Statement],
+) -> SyntaxNode {
+    let mut collector = NodeCollector::default();
+    for statement in statements {
+        collector.visit_statement(statement);
+    }
+    build_node(span, tokens, collector)
+}
+
+fn build_node(span: Span, tokens: &[TokenReference], collector: NodeCollector) -> SyntaxNode {
+    let mut nodes = collector.nodes;
+    // Outer nodes first when they start at the same offset.
+    nodes.sort_by_key(|node| (node.span.start, Reverse(node.span.end), node.depth));
+
+    let tokens = tokens
+        .iter()
+        .flat_map(|token| {
+            token
+                .leading_trivia
+                .iter()
+                .chain(Some(&token.token))
+                .chain(&token.trailing_trivia)
+        })
+        .filter(|token| token.start() < token.end())
+        .map(|token| SyntaxToken {
+            kind: token.kind(),
+            span: token.span(),
+        });
+
+    let mut stack = vec![SyntaxNode::new(SyntaxKind::Chunk, span)];
+    let mut nodes = nodes.into_iter().peekable();
+    for token in tokens {
+        close_nodes(&mut stack, token.span.start);
+        while let Some(node) = nodes.next_if(|node| node.span.start <= token.span.start) {
+            // Nodes without any tokens are dropped.
+            if node.span.end <= token.span.start {
+                continue;
+            }
+            let parent = stack.last().map_or(span.end, |parent| parent.span.end);
+            let span = Span::new(node.span.start, node.span.end.min(parent));
+            stack.push(SyntaxNode::new(node.kind, span));
+        }
+        if let Some(node) = stack.last_mut() {
+            node.children.push(SyntaxElement::Token(token));
+        }
+    }
+    close_nodes(&mut stack, u32::MAX);
+    stack.pop().expect("The root node is never closed.")
+}
+
+/// Close the nodes ending before `offset`, Except the root.
+fn close_nodes(stack: &mut Vec<SyntaxNode>, offset: u32) {
+    while stack.len() > 1 && stack.last().is_some_and(|node| node.span.end <= offset) {
+        let node = stack.pop().unwrap();
+        if let Some(parent) = stack.last_mut() {
+            parent.children.push(SyntaxElement::Node(node));
+        }
+    }
+}
+
+struct CollectedNode {
+    kind: SyntaxKind,
+    span: Span,
+    depth: usize,
+}
+
+struct Frame {
+    kind: Option<SyntaxKind>,
+    span: Option<Span>,
+    /// Union of the spans of the children.
+    covered: Option<Span>,
+}
+
+/// Collects the kinds and spans of the AST nodes.
+#[derive(Default)]
+struct NodeCollector {
+    stack: Vec<Frame>,
+    nodes: Vec<CollectedNode>,
+}
+
+impl ScopeVisitor for NodeCollector {}
+
+impl NodeVisitor for NodeCollector {
+    fn enter_node(&mut self, node: AstNode) {
+        let (kind, span) = node_kind(node);
+        self.stack.push(Frame {
+            kind,
+            span,
+            covered: None,
+        });
+    }
+
+    fn leave_node(&mut self, _: AstNode) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        // Some AST spans leave out parts of the node, e.g. the type of a parameter.
+        let span = match (frame.span, frame.covered) {
+            (Some(span), Some(covered)) => Span::with_spans(vec![span, covered]),
+            (span, covered) => match span.or(covered) {
+                Some(span) => span,
+                None => return,
+            },
+        };
+        if let Some(parent) = self.stack.last_mut() {
+            parent.covered = Some(match parent.covered {
+                Some(covered) => Span::with_spans(vec![covered, span]),
+                None => span,
+            });
+        }
+        if let Some(kind) = frame.kind {
+            if span.start < span.end && kind != SyntaxKind::Chunk {
+                self.nodes.push(CollectedNode {
+                    kind,
+                    span,
+                    depth: self.stack.len(),
+                });
+            }
+        }
+    }
+}
+
+impl<'ast> Visitor<'ast> for NodeCollector {}
+
+/// Kind and span of the syntax node of an AST node, The kind is `None` for the AST nodes only
+/// wrapping another one and the span is `None` if the node doesn't have one.
+fn node_kind(node: AstNode) -> (Option<SyntaxKind>, Option<Span>) {
+    use SyntaxKind as Kind;
+    let (kind, span) = match node {
+        AstNode::Chunk(chunk) => (Kind::Chunk, Some(chunk.span)),
+        AstNode::Block(_) => (Kind::Block, None),
+        AstNode::EmptyStatement(stmt) => (Kind::EmptyStatement, Some(stmt.span)),
+        AstNode::ImplStatement(stmt) => (Kind::ImplStatement, Some(stmt.span)),
+        AstNode::ImplMethod(method) => (Kind::ImplMethod, Some(method.function.span)),
+        AstNode::EnumDeclaration(decl) => (Kind::EnumDeclaration, Some(decl.span)),
+        AstNode::StructDeclaration(decl) => (Kind::StructDeclaration, Some(decl.span)),
+        AstNode::FunctionDeclaration(decl) => (Kind::FunctionDeclaration, Some(decl.span)),
+        AstNode::VariableDeclaration(decl) => (Kind::VariableDeclaration, Some(decl.span)),
+        AstNode::TypeAliasDeclaration(decl) => (Kind::TypeAliasDeclaration, Some(decl.span)),
+        AstNode::UnionDeclaration(decl) => (Kind::UnionDeclaration, Some(decl.span)),
+        AstNode::ExternDeclaration(decl) => (Kind::ExternDeclaration, Some(decl.span)),
+        AstNode::FunctionExpression(expr) => (Kind::FunctionExpression, Some(expr.span)),
+        AstNode::CallExpression(expr) => (Kind::CallExpression, Some(expr.span)),
+        AstNode::ArrayExpression(expr) => (Kind::ArrayExpression, Some(expr.span)),
+        AstNode::TupleExpression(expr) => (Kind::TupleExpression, Some(expr.span)),
+        AstNode::ParenthesizedExpression(expr) => (Kind::ParenthesizedExpression, Some(expr.span)),
+        AstNode::ConstructionExpression(expr) => (Kind::ConstructionExpression, Some(expr.span)),
+        AstNode::TableConstructionExpression(expr) => {
+            (Kind::TableConstructionExpression, Some(expr.span))
+        }
+        AstNode::StructConstructionExpression(expr) => {
+            (Kind::StructConstructionExpression, Some(expr.span()))
+        }
+        AstNode::If(expr) => (Kind::If, Some(expr.span)),
+        AstNode::Match(expr) => (Kind::Match, Some(expr.span)),
+        AstNode::MatchArm(arm) => (Kind::MatchArm, Some(arm.span)),
+        AstNode::FunctionSignature(sig) => (Kind::FunctionSignature, Some(sig.span)),
+        AstNode::FunctionParameters(params) => (Kind::FunctionParameters, Some(params.span)),
+        AstNode::FunctionParameter(param) => (Kind::FunctionParameter, Some(param.span)),
+        AstNode::EnumVariant(_) => (Kind::EnumVariant, None),
+        AstNode::UnionVariant(var) => (Kind::UnionVariant, Some(var.span)),
+        AstNode::StructField(_) => (Kind::StructField, None),
+        AstNode::ExternItem(item) => {
+            let span = match item {
+                ExternItem::Function(sig) => sig.span,
+                ExternItem::Global(global) => global.span,
+                ExternItem::Table(table) => table.span,
+            };
+            (Kind::ExternItem, Some(span))
+        }
+        AstNode::VisibilityModifier(modifier) => match modifier {
+            VisibilityModifier::Public(span) => (Kind::VisibilityModifier, Some(*span)),
+            VisibilityModifier::Private => return (None, None),
+        },
+        AstNode::NumberLiteral(lit) => (Kind::NumberLiteral, Some(lit.span)),
+        AstNode::StringLiteral(lit) => (Kind::StringLiteral, Some(lit.span)),
+        AstNode::BooleanLiteral(lit) => (Kind::BooleanLiteral, Some(lit.span)),
+        AstNode::NilLiteral(lit) => (Kind::NilLiteral, Some(lit.span)),
+        AstNode::Identifier(ident) => (Kind::Identifier, Some(ident.span)),
+        AstNode::BindingPattern(_) => (Kind::BindingPattern, None),
+        AstNode::BindingIdentifier(ident) => (Kind::BindingIdentifier, Some(ident.span)),
+        AstNode::BindingRest(rest) => (Kind::BindingRest, Some(rest.span)),
+        AstNode::KeyValueArgument(arg) => (Kind::KeyValueArgument, Some(arg.span)),
+        AstNode::SpreadArgument(arg) => (Kind::SpreadArgument, Some(arg.span)),
+        AstNode::UnaryOperator(op) => (Kind::UnaryOperator, Some(op.span())),
+        AstNode::BinaryOperator(op) => (Kind::BinaryOperator, Some(op.span())),
+        AstNode::MemberExpression(expr) => (Kind::MemberExpression, Some(expr.span)),
+        AstNode::TypeAnnotation(annotation) => (Kind::TypeAnnotation, Some(annotation.span)),
+        AstNode::TypeParameter(param) => (Kind::TypeParameter, Some(param.span)),
+        AstNode::VariantPattern(pattern) => (Kind::VariantPattern, Some(pattern.span)),
+        AstNode::Else(_)
+        | AstNode::FunctionBody(_)
+        | AstNode::ConstructionField(_)
+        | AstNode::ArrayExpressionElement(_)
+        | AstNode::TupleExpressionElement(_)
+        | AstNode::MemberExpressionLHS(_)
+        | AstNode::MemberExpressionRHS(_)
+        | AstNode::Pattern(_) => return (None, None),
+    };
+    (Some(kind), span)
+}
@@ -0,0 +1,290 @@
+//! Lossless concrete syntax tree, It keeps every token of the source including the whitespace
+//! and comments so printing it gives back the input byte for byte.
+//!
+//! Scope: This is only the lossless half of a full-fidelity tree. The AST isn't derivable from
+//! it, The parser still builds the AST directly and the tree is built afterward from the spans
+//! of that AST and the tokens of the same parse. Deriving the AST from the tree would need the
+//! parser to emit the tree itself, With error nodes, Which it doesn't do yet.
+//!
+//! Since the tree keeps the `ParsedChunk` it was built from, It only knows what the AST spans
+//! tell it:
+//! - There are no error nodes, Tokens of broken code that no AST node covers are children of
+//!   the innermost node around them, Usually the chunk or a block.
+//! - Trivia is owned by the innermost node open at its offset, Whitespace and comments between
+//!   two statements belong to the enclosing block rather than to either statement.
+
+mod builder;
+
+pub(crate) use builder::{build, build_statements};
+
+use fuse_ast::Chunk;
+use fuse_common::Span;
+
+use crate::{lexer::TokenKind, ParsedChunk, Parser};
+
+/// Kind of a syntax node, Named after the AST node it stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    Chunk,
+    Block,
+
+    EmptyStatement,
+    ImplStatement,
+    ImplMethod,
+    EnumDeclaration,
+    StructDeclaration,
+    FunctionDeclaration,
+    VariableDeclaration,
+    TypeAliasDeclaration,
+    UnionDeclaration,
+    ExternDeclaration,
+
+    FunctionExpression,
+    CallExpression,
+    ArrayExpression,
+    TupleExpression,
+    ParenthesizedExpression,
+    ConstructionExpression,
+    TableConstructionExpression,
+    StructConstructionExpression,
+    If,
+    Match,
+    MatchArm,
+
+    FunctionSignature,
+    FunctionParameters,
+    FunctionParameter,
+
+    EnumVariant,
+    UnionVariant,
+    StructField,
+    ExternItem,
+
+    VisibilityModifier,
+
+    NumberLiteral,
+    StringLiteral,
+    BooleanLiteral,
+    NilLiteral,
+
+    Identifier,
+    BindingPattern,
+    BindingIdentifier,
+    BindingRest,
+    KeyValueArgument,
+    SpreadArgument,
+
+    UnaryOperator,
+    BinaryOperator,
+
+    MemberExpression,
+
+    TypeAnnotation,
+    TypeParameter,
+
+    VariantPattern,
+}
+
+/// Token of the source, Trivia such as whitespace and comments are tokens of their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyntaxToken {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+impl SyntaxToken {
+    pub fn is_trivia(&self) -> bool {
+        self.kind.is_trivial()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Node(node) => node.span,
+            Self::Token(token) => token.span,
+        }
+    }
+}
+
+/// Node of the tree, Its children cover its whole span in order, Trivia around the first and
+/// last tokens of a node belong to its parent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxNode {
+    pub kind: SyntaxKind,
+    pub span: Span,
+    pub children: Vec<SyntaxElement>,
+}
+
+impl SyntaxNode {
+    fn new(kind: SyntaxKind, span: Span) -> Self {
+        Self {
+            kind,
+            span,
+            children: Vec::new(),
+        }
+    }
+
+    /// Child nodes, Without the tokens.
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|child| match child {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// Tokens of the node and its descendants in source order.
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        self.collect_tokens(&mut tokens);
+        tokens
+    }
+
+    fn collect_tokens(&self, tokens: &mut Vec<SyntaxToken>) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.collect_tokens(tokens),
+                SyntaxElement::Token(token) => tokens.push(*token),
+            }
+        }
+    }
+
+    /// Non-trivia token starting the node.
+    pub fn first_token(&self) -> Option<SyntaxToken> {
+        self.tokens().into_iter().find(|token| !token.is_trivia())
+    }
+
+    fn contains(&self, offset: u32) -> bool {
+        self.span.start <= offset && offset < self.span.end
+    }
+}
+
+/// Syntax tree of a chunk along with its AST, Both come from the same parse but the AST isn't
+/// derived from the tree.
+#[derive(Debug)]
+pub struct SyntaxTree {
+    source: String,
+    root: SyntaxNode,
+    parsed: ParsedChunk,
+    /// Whether the source was parsed with recovery, Reparsing the tree does the same.
+    recovery: bool,
+}
+
+impl SyntaxTree {
+    pub fn parse(source: &str) -> Self {
+        Parser::new(source).parse_lossless()
+    }
+
+    pub(crate) fn new(source: &str, root: SyntaxNode, parsed: ParsedChunk, recovery: bool) -> Self {
+        Self {
+            source: source.to_string(),
+            root,
+            parsed,
+            recovery,
+        }
+    }
+
+    /// The `Chunk` node spanning the whole source.
+    pub fn root(&self) -> &SyntaxNode {
+        &self.root
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Result of parsing the source into an AST, Its errors are the syntax errors of the tree.
+    pub fn parsed(&self) -> &ParsedChunk {
+        &self.parsed
+    }
+
+    pub fn into_parsed(self) -> ParsedChunk {
+        self.parsed
+    }
+
+    pub(crate) fn into_parts(self) -> (SyntaxNode, ParsedChunk) {
+        (self.root, self.parsed)
+    }
+
+    pub(crate) fn recovery(&self) -> bool {
+        self.recovery
+    }
+
+    /// The AST, It is `None` if the parser couldn't recover from an error.
+    pub fn chunk(&self) -> Option<&Chunk> {
+        self.parsed.chunk.as_ref()
+    }
+
+    pub fn token_text(&self, token: &SyntaxToken) -> &str {
+        &self.source[token.span.start as usize..token.span.end as usize]
+    }
+
+    /// Source text of the tree, Made of the text of its tokens so it is the same as the source.
+    pub fn text(&self) -> String {
+        self.root
+            .tokens()
+            .iter()
+            .map(|token| self.token_text(token))
+            .collect()
+    }
+
+    /// Token containing the byte `offset`.
+    pub fn token_at(&self, offset: u32) -> Option<SyntaxToken> {
+        self.ancestors_at(offset)
+            .last()?
+            .children
+            .iter()
+            .find_map(|child| match child {
+                SyntaxElement::Token(token)
+                    if token.span.start <= offset && offset < token.span.end =>
+                {
+                    Some(*token)
+                }
+                _ => None,
+            })
+    }
+
+    /// Nodes containing the byte `offset`, From the root to the innermost one.
+    pub fn ancestors_at(&self, offset: u32) -> Vec<&SyntaxNode> {
+        let mut ancestors = vec![&self.root];
+        while let Some(node) = ancestors
+            .last()
+            .and_then(|node| node.nodes().find(|child| child.contains(offset)))
+        {
+            ancestors.push(node);
+        }
+        ancestors
+    }
+
+    /// Indented outline of the tree, One line per node and token.
+    pub fn dump(&self) -> String {
+        let mut output = String::new();
+        self.dump_node(&self.root, 0, &mut output);
+        output
+    }
+
+    fn dump_node(&self, node: &SyntaxNode, depth: usize, output: &mut String) {
+        let indent = "  ".repeat(depth);
+        let Span { start, end } = node.span;
+        output.push_str(&format!("{indent}{:?}@{start}..{end}\n", node.kind));
+        for child in &node.children {
+            match child {
+                SyntaxElement::Node(node) => self.dump_node(node, depth + 1, output),
+                SyntaxElement::Token(token) => {
+                    let Span { start, end } = token.span;
+                    output.push_str(&format!(
+                        "{indent}  {:?}@{start}..{end} {:?}\n",
+                        token.kind,
+                        self.token_text(token)
+                    ));
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,3 @@
-use core::panic;
-
 use crate::{
     lexer::{Token, TokenKind, TokenReference},
     Parser, ParserResult,
@@ -16,10 +14,18 @@ impl<'a> Parser<'a> {
 
     pub(crate) fn consume(&mut self) -> TokenReference {
         let token = self.lexer.consume();
+        self.record(&token);
         self.prev_token = token.clone();
         token
     }
 
+    /// Record a consumed token if the parser keeps them.
+    pub(crate) fn record(&mut self, token: &TokenReference) {
+        if let Some(tokens) = &mut self.tokens {
+            tokens.push(token.clone());
+        }
+    }
+
     pub fn consume_if(&mut self, kind: TokenKind) -> Option<TokenReference> {
         if self.at(kind) {
             Some(self.consume())
@@ -2,6 +2,7 @@ use crate::{
     lexer::{LexerError, TokenKind, TokenReference},
     Parser,
 };
+use fuse_common::Span;
 use fuse_common_proc::serializable;
 use thiserror::Error as ThisError;
 
@@ -10,26 +11,38 @@ use thiserror::Error as ThisError;
 pub enum Error {
     #[error("{0:?}")]
     LexerError(LexerError),
-    #[error("Expected \"{expected:?}\" instead found \"{:?}\" at {token:?}", token.kind())]
+    #[error("Expected \"{expected:?}\" instead found \"{:?}\"", token.kind())]
     UnexpectedTokenKindError {
         token: TokenReference,
         expected: TokenKind,
     },
     #[error("{0}")]
     DiagnosisError(DiagnosisError),
-    #[error("Invalid number literal error at {0:?}")]
+    #[error("Invalid number literal")]
     InvalidNumberLiteralError(TokenReference),
-    #[error("Unexpected error at {0:?}")]
+    #[error("Unexpected token \"{:?}\"", .0.kind())]
     UnexpectedError(TokenReference),
 }
 
 #[serializable]
 #[derive(ThisError, Debug)]
 pub enum DiagnosisError {
-    #[error("{0:?}\n{1}")]
+    #[error("{1}")]
     GeneralError(TokenReference, String),
 }
 
+impl Error {
+    /// Span of the source code causing the error.
+    pub fn span(&self) -> Span {
+        match self {
+            Self::LexerError(error) => match *error {},
+            Self::UnexpectedTokenKindError { token, .. } => token.span(),
+            Self::DiagnosisError(DiagnosisError::GeneralError(token, _)) => token.span(),
+            Self::InvalidNumberLiteralError(token) | Self::UnexpectedError(token) => token.span(),
+        }
+    }
+}
+
 impl<'a> Parser<'a> {
     pub(crate) fn unexpected_error(token: &TokenReference) -> Error {
         Error::UnexpectedError(token.clone())
@@ -45,8 +58,4 @@ impl<'a> Parser<'a> {
     pub(crate) fn invalid_number_literal_error(token: &TokenReference) -> Error {
         Error::UnexpectedError(token.clone())
     }
-
-    pub(crate) fn diagnosis_general_error(token: &TokenReference, msg: &str) -> Error {
-        Error::DiagnosisError(DiagnosisError::GeneralError(token.clone(), msg.to_string()))
-    }
 }
@@ -0,0 +1,291 @@
+//! Incremental reparsing, After an edit only the damaged top-level statements are relexed and
+//! parsed again while the rest of the previous tree is reused with their spans moved.
+//!
+//! Parsing starts again from a statement far enough before the edit that none of the tokens
+//! the previous statements looked at were changed, And stops at the first statement boundary
+//! after the edit matching the start of an old statement. The rest of the source is the same
+//! text from there on so its old statements and tokens are the ones a full parse would give.
+
+mod shift;
+
+use std::ops::Range;
+
+use fuse_ast::Statement;
+use fuse_common::Span;
+
+use crate::{
+    cst::{self, SyntaxKind, SyntaxNode, SyntaxTree},
+    lexer::{self, Token, TokenKind, TokenReference},
+    ParsedChunk, Parser,
+};
+
+use shift::SpanShifter;
+
+/// Replacement of the `span` of a source with `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// Bytes of the old source that are replaced.
+    pub span: Span,
+    pub text: String,
+}
+
+impl TextEdit {
+    pub fn new<S: Into<String>>(span: Span, text: S) -> Self {
+        Self {
+            span,
+            text: text.into(),
+        }
+    }
+
+    /// Returns true if the span of the edit is in `source` and at its character boundaries.
+    pub fn is_valid(&self, source: &str) -> bool {
+        let (start, end) = (self.span.start as usize, self.span.end as usize);
+        start <= end && source.is_char_boundary(start) && source.is_char_boundary(end)
+    }
+
+    /// Source after the edit, It panics if the edit isn't valid for `source`, See `TextEdit::is_valid`.
+    pub fn apply(&self, source: &str) -> String {
+        let mut result = source.to_string();
+        result.replace_range(self.span.start as usize..self.span.end as usize, &self.text);
+        result
+    }
+
+    /// Length difference of the source after the edit.
+    fn delta(&self) -> i64 {
+        self.text.len() as i64 - (self.span.end - self.span.start) as i64
+    }
+}
+
+/// Result of reparsing a tree after an edit.
+#[derive(Debug)]
+pub struct Reparse {
+    pub tree: SyntaxTree,
+    /// Indices of the top-level statements of the new tree that were parsed again, The ones
+    /// around them are reused from the previous tree.
+    pub changed: Range<usize>,
+    /// Part of the new source that got relexed.
+    pub relexed: Span,
+}
+
+impl Reparse {
+    /// Top-level syntax nodes of the changed statements.
+    pub fn changed_nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.tree
+            .root()
+            .nodes()
+            .skip(self.changed.start)
+            .take(self.changed.len())
+    }
+
+    fn full(source: &str, recovery: bool) -> Self {
+        let parser = Parser::new(source);
+        let tree = if recovery {
+            parser.with_recovery().parse_lossless()
+        } else {
+            parser.parse_lossless()
+        };
+        let statements = tree.chunk().map_or(0, |chunk| chunk.body.statements.len());
+        Self {
+            tree,
+            changed: 0..statements,
+            relexed: Span::new(0, source.len() as u32),
+        }
+    }
+}
+
+/// Statements parsed again after an edit.
+struct Region {
+    span: Span,
+    statements: Vec<Statement>,
+    tokens: Vec<TokenReference>,
+    /// Index of the old statement starting at the end of the region, `None` if the region
+    /// reaches the end of the source.
+    reused: Option<usize>,
+}
+
+impl<'a> Parser<'a> {
+    /// Parse the source of `previous` after applying `edit` to it, Reusing the top-level
+    /// statements of `previous` the edit doesn't affect. The result is always the same as a
+    /// full parse of the new source, With recovery if `previous` was parsed with it.
+    ///
+    /// It falls back to a full parse of the new source, With `Reparse::changed` covering every
+    /// statement, when:
+    /// - `previous` has syntax errors, Its statements can't be trusted to be the ones a parse
+    ///   of the unchanged parts would give, e.g. a recovered parse skipped some of them.
+    /// - The reparsed statements have a syntax error or reach a top-level `end`.
+    /// - `previous` has no statements.
+    ///
+    /// So editors reparsing a broken document pay for a full parse until it is fixed. The edit
+    /// must be valid for the source of `previous`, See `TextEdit::is_valid`.
+    pub fn reparse(previous: SyntaxTree, edit: &TextEdit) -> Reparse {
+        let source = edit.apply(previous.source());
+        match Parser::parse_damaged(&previous, edit, &source) {
+            Some((first, region)) => splice(previous, edit, &source, first, region),
+            None => Reparse::full(&source, previous.recovery()),
+        }
+    }
+
+    /// Create a parser recording its tokens that starts at the byte `offset` of `source`.
+    fn new_at(source: &'a str, offset: u32) -> Self {
+        Self {
+            lexer: lexer::Lexer::new_at(source, offset),
+            errors: Vec::new(),
+            source,
+            ast: fuse_ast::AstFactory(),
+            prev_token: TokenReference::default(),
+            tokens: Some(Vec::new()),
+            recover: false,
+        }
+    }
+
+    /// Parse the statements damaged by `edit` out of the new `source`, Returns the index of the
+    /// first one along with the parsed region or `None` if it can't be done incrementally.
+    fn parse_damaged(
+        previous: &SyntaxTree,
+        edit: &TextEdit,
+        source: &'a str,
+    ) -> Option<(usize, Region)> {
+        let chunk = previous.chunk()?;
+        if !previous.parsed().errors.is_empty() || chunk.body.statements.is_empty() {
+            return None;
+        }
+        let extents: Vec<Span> = previous.root().nodes().map(|node| node.span).collect();
+        if extents.len() != chunk.body.statements.len() {
+            return None;
+        }
+
+        // A statement is reused if the 2 tokens after it, Its end and lookahead, are untouched.
+        let tokens: Vec<Span> = previous
+            .root()
+            .tokens()
+            .into_iter()
+            .filter(|token| !token.is_trivia())
+            .map(|token| token.span)
+            .collect();
+        let before_edit = tokens.partition_point(|token| token.end < edit.span.start);
+        let first = extents
+            .partition_point(|extent| {
+                let after = tokens.partition_point(|token| token.start < extent.end);
+                before_edit.saturating_sub(after) >= 2
+            })
+            .min(extents.len() - 1);
+        let start = if first == 0 { 0 } else { extents[first].start };
+
+        let delta = edit.delta();
+        let reusable = |offset: u32| {
+            let old = offset as i64 - delta;
+            if old < edit.span.end as i64 {
+                return None;
+            }
+            extents
+                .binary_search_by_key(&old, |extent| extent.start as i64)
+                .ok()
+        };
+
+        let mut parser = Self::new_at(source, start);
+        parser
+            .parse_region(start, reusable)
+            .map(|region| (first, region))
+    }
+
+    /// Parse top-level statements until the end of the source or the first statement start
+    /// accepted by `reusable`.
+    fn parse_region<F: Fn(u32) -> Option<usize>>(
+        &mut self,
+        start: u32,
+        reusable: F,
+    ) -> Option<Region> {
+        let mut statements = Vec::new();
+        let reused = loop {
+            if self.at(TokenKind::Eof) {
+                break None;
+            }
+            if let Some(index) = reusable(self.cur_token().start()) {
+                break Some(index);
+            }
+            // A top-level `end` stops the whole chunk.
+            if self.at(TokenKind::End) {
+                return None;
+            }
+            statements.push(self.parse_statement().ok()?);
+            if !self.errors.is_empty() {
+                return None;
+            }
+        };
+
+        let end = self.cur_token().start();
+        let mut tokens = self.tokens.take().unwrap_or_default();
+        // The trivia before the next token is the end of the region.
+        tokens.push(TokenReference::with_trivia(
+            Token::new(Span::new(end, end), TokenKind::Eof),
+            self.cur_token().leading_trivia.clone(),
+            Vec::new(),
+        ));
+        Some(Region {
+            span: Span::new(start, end),
+            statements,
+            tokens,
+            reused,
+        })
+    }
+}
+
+/// Put the reparsed `region` in place of the damaged statements of `previous`.
+fn splice(
+    previous: SyntaxTree,
+    edit: &TextEdit,
+    source: &str,
+    first: usize,
+    region: Region,
+) -> Reparse {
+    let mut shifter = SpanShifter::new(edit.delta());
+    let reused_start = region
+        .reused
+        .map(|_| (region.span.end as i64 - edit.delta()) as u32);
+    let region_root = cst::build_statements(region.span, &region.tokens, &region.statements);
+
+    let recovery = previous.recovery();
+    let (root, parsed) = previous.into_parts();
+    let mut children = Vec::new();
+    let mut after = Vec::new();
+    for child in root.children {
+        if child.span().end <= region.span.start {
+            children.push(child);
+        } else if reused_start.is_some_and(|start| child.span().start >= start) {
+            after.push(child);
+        }
+    }
+    children.extend(region_root.children);
+    for mut child in after {
+        shifter.shift_element(&mut child);
+        children.push(child);
+    }
+
+    let mut chunk = parsed
+        .chunk
+        .expect("Only trees with a chunk are reparsed incrementally.");
+    let statements = &mut chunk.body.statements;
+    let mut after = match region.reused {
+        Some(index) => statements.split_off(index),
+        None => Vec::new(),
+    };
+    statements.truncate(first);
+    let changed = first..first + region.statements.len();
+    statements.extend(region.statements);
+    for statement in &mut after {
+        shifter.shift_statement(statement);
+    }
+    statements.extend(after);
+    chunk.span = Span::new(0, source.len() as u32);
+
+    let root = SyntaxNode {
+        kind: SyntaxKind::Chunk,
+        span: chunk.span,
+        children,
+    };
+    Reparse {
+        tree: SyntaxTree::new(source, root, ParsedChunk::new(chunk, Vec::new()), recovery),
+        changed,
+        relexed: region.span,
+    }
+}
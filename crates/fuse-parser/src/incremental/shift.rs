@@ -0,0 +1,285 @@
+use fuse_ast::*;
+use fuse_common::Span;
+use fuse_visitor::*;
+
+use crate::cst::{SyntaxElement, SyntaxNode};
+
+/// Moves the spans of reused statements by the length difference of an edit before them.
+pub(super) struct SpanShifter {
+    delta: i64,
+}
+
+impl SpanShifter {
+    pub(super) fn new(delta: i64) -> Self {
+        Self { delta }
+    }
+
+    pub(super) fn shift_statement(&mut self, statement: &mut Statement) {
+        self.visit_statement_mut(statement);
+    }
+
+    pub(super) fn shift_element(&self, element: &mut SyntaxElement) {
+        match element {
+            SyntaxElement::Node(node) => self.shift_node(node),
+            SyntaxElement::Token(token) => self.shift(&mut token.span),
+        }
+    }
+
+    fn shift_node(&self, node: &mut SyntaxNode) {
+        self.shift(&mut node.span);
+        for child in &mut node.children {
+            self.shift_element(child);
+        }
+    }
+
+    fn shift(&self, span: &mut Span) {
+        span.start = (span.start as i64 + self.delta) as u32;
+        span.end = (span.end as i64 + self.delta) as u32;
+    }
+}
+
+impl ScopeVisitor for SpanShifter {}
+
+impl<'ast> VisitorMut<'ast> for SpanShifter {
+    fn visit_statement_mut(&mut self, statement: &'ast mut Statement) {
+        if let Statement::Empty(stmt) = statement {
+            self.shift(&mut stmt.span);
+        }
+        walk_statement_mut(self, statement)
+    }
+
+    fn visit_variable_declaration_mut(&mut self, decl: &'ast mut VariableDeclaration) {
+        self.shift(&mut decl.span);
+        walk_variable_declaration_mut(self, decl)
+    }
+
+    fn visit_function_declaration_mut(&mut self, decl: &'ast mut Function) {
+        self.visit_function_mut(decl)
+    }
+
+    fn visit_enum_declaration_mut(&mut self, decl: &'ast mut EnumDeclaration) {
+        self.shift(&mut decl.span);
+        walk_enum_declaration_mut(self, decl)
+    }
+
+    fn visit_struct_declaration_mut(&mut self, decl: &'ast mut StructDeclaration) {
+        self.shift(&mut decl.span);
+        walk_struct_declaration_mut(self, decl)
+    }
+
+    fn visit_impl_statement_mut(&mut self, r#impl: &'ast mut ImplStatement) {
+        self.shift(&mut r#impl.span);
+        walk_impl_statement_mut(self, r#impl)
+    }
+
+    fn visit_type_alias_declaration_mut(&mut self, decl: &'ast mut TypeAliasDeclaration) {
+        self.shift(&mut decl.span);
+        walk_type_alias_declaration_mut(self, decl)
+    }
+
+    fn visit_union_declaration_mut(&mut self, decl: &'ast mut UnionDeclaration) {
+        self.shift(&mut decl.span);
+        walk_union_declaration_mut(self, decl)
+    }
+
+    fn visit_union_variant_mut(&mut self, var: &'ast mut UnionVariant) {
+        self.shift(&mut var.span);
+        walk_union_variant_mut(self, var)
+    }
+
+    fn visit_extern_declaration_mut(&mut self, decl: &'ast mut ExternDeclaration) {
+        self.shift(&mut decl.span);
+        walk_extern_declaration_mut(self, decl)
+    }
+
+    fn visit_extern_item_mut(&mut self, item: &'ast mut ExternItem) {
+        match item {
+            // Shifted as a function signature.
+            ExternItem::Function(_) => {}
+            ExternItem::Global(global) => self.shift(&mut global.span),
+            ExternItem::Table(table) => self.shift(&mut table.span),
+        }
+        walk_extern_item_mut(self, item)
+    }
+
+    fn visit_visibility_modifier_mut(&mut self, modifier: &'ast mut VisibilityModifier) {
+        if let VisibilityModifier::Public(span) = modifier {
+            self.shift(span);
+        }
+    }
+
+    fn visit_number_literal_mut(&mut self, lit: &'ast mut NumberLiteral) {
+        self.shift(&mut lit.span);
+    }
+
+    fn visit_string_literal_mut(&mut self, lit: &'ast mut StringLiteral) {
+        self.shift(&mut lit.span);
+        for segment in &mut lit.segments {
+            if let StringSegment::Literal(StringLiteralSegment::Unescaped(span)) = segment {
+                self.shift(span);
+            }
+        }
+        walk_string_literal_mut(self, lit)
+    }
+
+    fn visit_boolean_literal_mut(&mut self, lit: &'ast mut BooleanLiteral) {
+        self.shift(&mut lit.span);
+    }
+
+    fn visit_nil_literal_mut(&mut self, lit: &'ast mut NilLiteral) {
+        self.shift(&mut lit.span);
+    }
+
+    fn visit_identifier_mut(&mut self, ident: &'ast mut Identifier) {
+        self.shift(&mut ident.span);
+        // References are resolved again by the semantic analysis of the new tree.
+        ident.reference.set(None);
+    }
+
+    fn visit_function_mut(&mut self, func: &'ast mut Function) {
+        self.shift(&mut func.span);
+        walk_function_mut(self, func)
+    }
+
+    fn visit_function_signature_mut(&mut self, sign: &'ast mut FunctionSignature) {
+        self.shift(&mut sign.span);
+        walk_function_signature_mut(self, sign)
+    }
+
+    fn visit_function_parameters_mut(&mut self, params: &'ast mut FunctionParameters) {
+        self.shift(&mut params.span);
+        walk_function_parameters_mut(self, params)
+    }
+
+    fn visit_function_parameter_mut(&mut self, param: &'ast mut FunctionParameter) {
+        self.shift(&mut param.span);
+        walk_function_parameter_mut(self, param)
+    }
+
+    fn visit_if_mut(&mut self, r#if: &'ast mut If) {
+        self.shift(&mut r#if.span);
+        walk_if_mut(self, r#if)
+    }
+
+    fn visit_match_mut(&mut self, r#match: &'ast mut Match) {
+        self.shift(&mut r#match.span);
+        walk_match_mut(self, r#match)
+    }
+
+    fn visit_match_arm_mut(&mut self, arm: &'ast mut MatchArm) {
+        self.shift(&mut arm.span);
+        walk_match_arm_mut(self, arm)
+    }
+
+    fn visit_pattern_mut(&mut self, pattern: &'ast mut Pattern) {
+        if let Pattern::Wildcard(span) = pattern {
+            self.shift(span);
+        }
+        walk_pattern_mut(self, pattern)
+    }
+
+    fn visit_variant_pattern_mut(&mut self, pattern: &'ast mut VariantPattern) {
+        self.shift(&mut pattern.span);
+        walk_variant_pattern_mut(self, pattern)
+    }
+
+    fn visit_unary_operator_mut(&mut self, op: &'ast mut UnaryOperator) {
+        let (UnaryOperatorKind::Not(span)
+        | UnaryOperatorKind::Plus(span)
+        | UnaryOperatorKind::Minus(span)) = &mut op.kind;
+        self.shift(span);
+        walk_unary_operator_mut(self, op)
+    }
+
+    fn visit_binary_operator_mut(&mut self, op: &'ast mut BinaryOperator) {
+        use BinaryOperatorKind::*;
+        let (Assignment(span)
+        | Coalesce(span)
+        | LogicalOr(span)
+        | LogicalAnd(span)
+        | BitwiseOr(span)
+        | BitwiseXor(span)
+        | BitwiseAnd(span)
+        | Equality(span)
+        | NonEquality(span)
+        | LessThanEqual(span)
+        | LessThan(span)
+        | GreaterThanEqual(span)
+        | GreaterThan(span)
+        | Plus(span)
+        | Minus(span)
+        | Multiply(span)
+        | Exponential(span)
+        | Division(span)
+        | FloorDivision(span)
+        | Modulo(span)
+        | ShiftLeft(span)
+        | ShiftRight(span)) = &mut op.kind;
+        self.shift(span);
+        walk_binary_operator_mut(self, op)
+    }
+
+    fn visit_array_expression_mut(&mut self, array: &'ast mut ArrayExpression) {
+        self.shift(&mut array.span);
+        walk_array_expression_mut(self, array)
+    }
+
+    fn visit_tuple_expression_mut(&mut self, tuple: &'ast mut TupleExpression) {
+        self.shift(&mut tuple.span);
+        walk_tuple_expression_mut(self, tuple)
+    }
+
+    fn visit_parenthesized_expression_mut(&mut self, expr: &'ast mut ParenthesizedExpression) {
+        self.shift(&mut expr.span);
+        walk_parenthesized_expression_mut(self, expr)
+    }
+
+    fn visit_table_construction_expression_mut(&mut self, expr: &'ast mut ConstructionExpression) {
+        self.visit_construction_expression_mut(expr)
+    }
+
+    fn visit_member_expression_mut(&mut self, expr: &'ast mut MemberExpression) {
+        self.shift(&mut expr.span);
+        walk_member_expression_mut(self, expr)
+    }
+
+    fn visit_construction_expression_mut(&mut self, expr: &'ast mut ConstructionExpression) {
+        self.shift(&mut expr.span);
+        walk_construction_expression_mut(self, expr)
+    }
+
+    fn visit_call_expression_mut(&mut self, call: &'ast mut CallExpression) {
+        self.shift(&mut call.span);
+        walk_call_expression_mut(self, call)
+    }
+
+    fn visit_binding_identifier_mut(&mut self, pattern: &'ast mut BindingIdentifier) {
+        self.shift(&mut pattern.span);
+        walk_binding_identifier_mut(self, pattern)
+    }
+
+    fn visit_binding_rest_mut(&mut self, arg: &'ast mut BindingRest) {
+        self.shift(&mut arg.span);
+        walk_binding_rest_mut(self, arg)
+    }
+
+    fn visit_key_value_argument_mut(&mut self, arg: &'ast mut KeyValueArgument) {
+        self.shift(&mut arg.span);
+        walk_key_value_argument_mut(self, arg)
+    }
+
+    fn visit_spread_argument_mut(&mut self, arg: &'ast mut SpreadArgument) {
+        self.shift(&mut arg.span);
+        walk_spread_argument_mut(self, arg)
+    }
+
+    fn visit_type_annotation_mut(&mut self, annotation: &'ast mut TypeAnnotation) {
+        self.shift(&mut annotation.span);
+        walk_type_annotation_mut(self, annotation)
+    }
+
+    fn visit_type_parameter_mut(&mut self, param: &'ast mut TypeParameter) {
+        self.shift(&mut param.span);
+        walk_type_parameter_mut(self, param)
+    }
+}
@@ -0,0 +1,13 @@
+use super::{Lexer, Token, TokenKind};
+
+impl<'a> Lexer<'a> {
+    /// Lex a line comment, They start with `--` and end at the end of the line like Lua.
+    pub(super) fn comment(&mut self, start: u32, first: char) -> Option<Token> {
+        if first != '-' || self.source.peek_char() != Some('-') {
+            return None;
+        }
+
+        self.source.advance_while(|c| c != '\n');
+        Some(self.create(start, TokenKind::Comment))
+    }
+}
@@ -17,10 +17,7 @@ impl<'a> Lexer<'a> {
         }
 
         // Eat the rest of the identifier.
-        loop {
-            let Some(next) = self.source.peek_char() else {
-                break;
-            };
+        while let Some(next) = self.source.peek_char() {
             if !next.is_ascii_alphabetic() && !next.is_ascii_digit() && next != '_' {
                 break;
             }
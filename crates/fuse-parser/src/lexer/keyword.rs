@@ -3,6 +3,7 @@ use crate::flash_match;
 
 impl<'a> Lexer<'a> {
     pub(super) fn keyword(&mut self, start: u32, first: char) -> Option<Token> {
+        let checkpoint = self.source.position();
         let source = &mut self.source;
         let kind = flash_match! ((source, start, first) {
             'a' => {
@@ -21,6 +22,7 @@ impl<'a> Lexer<'a> {
             'e' => {
                 "lseif" => TokenKind::ElseIf,
                 "xport" => TokenKind::Export,
+                "xtern" => TokenKind::Extern,
                 "lse" => TokenKind::Else,
                 "num" => TokenKind::Enum,
                 "nd" => TokenKind::End,
@@ -89,6 +91,17 @@ impl<'a> Lexer<'a> {
                 "hen" => TokenKind::When,
             }
         })?;
+
+        // A keyword followed by identifier characters is the prefix of an identifier.
+        if self
+            .source
+            .peek_char()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            // SAFETY: `checkpoint` is created from this source.
+            unsafe { self.source.set_position(checkpoint) };
+            return None;
+        }
         Some(self.create(start, kind))
     }
 }
@@ -1,3 +1,4 @@
+mod comment;
 mod flash_match;
 mod identifier;
 mod keyword;
@@ -13,7 +14,7 @@ pub use string_data::{StringData, StringValue};
 pub use token::*;
 pub use token_kind::*;
 
-use fuse_common::{Span, SpanView, debug_println};
+use fuse_common::{debug_println, Span, SpanView};
 use fuse_common_proc::serializable;
 
 use source::{Source, SourcePosition};
@@ -41,6 +42,26 @@ impl<'a> Lexer<'a> {
         lexer
     }
 
+    /// Create a lexer starting at the byte `offset` of `src`, The spans of its tokens are
+    /// still relative to the start of `src`.
+    pub(crate) fn new_at(src: &'a str, offset: u32) -> Self {
+        assert!(
+            src.is_char_boundary(offset as usize),
+            "Lexer offset is not at a character boundary."
+        );
+        let mut lexer = Self {
+            source: Source::new(src),
+            current_token: TokenReference::default(),
+            lookahead: VecDeque::new(),
+            strings_data: HashMap::new(),
+        };
+
+        // SAFETY: `offset` is asserted to be in bounds and at a character boundary.
+        unsafe { lexer.source.set_offset(offset) };
+        lexer.consume();
+        lexer
+    }
+
     pub fn current(&self) -> &TokenReference {
         debug_assert!(
             self.source.offset() != 0 || self.source.is_eof(),
@@ -79,13 +100,27 @@ impl<'a> Lexer<'a> {
         current
     }
 
+    /// Split the first character of the current token into a token of `head` kind and
+    /// returns it, The rest of the current token becomes the new current token of `tail` kind.
+    /// It allows us to parse the `>>` at the end of nested generics as 2 closing angles.
+    pub fn split_current(&mut self, head: TokenKind, tail: TokenKind) -> TokenReference {
+        let span = self.current_token.span();
+        let head = TokenReference::with_trivia(
+            Token::new(Span::new(span.start, span.start + 1), head),
+            std::mem::take(&mut self.current_token.leading_trivia),
+            Vec::new(),
+        );
+        self.current_token.token = Token::new(Span::new(span.start + 1, span.end), tail);
+        head
+    }
+
     /// Replace the current token and returns the old one back.
     /// SAFETY: `token` should be a token created using the `self`,
     /// and have the same lifetime and alignment.
     unsafe fn set_current(&mut self, token: TokenReference) -> TokenReference {
         // SAFETY: Both the current and next token are created by this
         // `Lexer` and have the same lifetime and alignment.
-        let current = std::ptr::read(&mut self.current_token);
+        let current = std::ptr::read(&self.current_token);
         std::ptr::write(&mut self.current_token, token);
         current
     }
@@ -160,6 +195,7 @@ impl<'a> Lexer<'a> {
 
         analyze! {
             | whitespace
+            | comment
             | keyword
             | operator
             | identifier
@@ -229,8 +265,8 @@ mod string_data {
     impl<'a> Lexer<'a> {
         /// Get a reference to the string data related to the given token.
         /// It can panic if token dosn't have any stored string.
-        pub fn get_string_data(&mut self, token: &Token) -> &'a mut StringData {
-            self.strings_data.get_mut(&token).unwrap()
+        pub fn get_string_data(&mut self, token: &Token) -> &'a mut StringData<'_> {
+            self.strings_data.get_mut(token).unwrap()
         }
 
         /// Get the ownership of string data related to the given token.
@@ -14,9 +14,13 @@ impl<'a> Lexer<'a> {
                 "" => TokenKind::Dot,
             }
             '=' => {
+                "=" => TokenKind::Eq2,
                 ">" => TokenKind::Arrow,
                 "" => TokenKind::Eq,
             }
+            '!' => {
+                "=" => TokenKind::Neq,
+            }
             ':' => {
                 "" => TokenKind::Colon,
             }
@@ -70,6 +74,11 @@ impl<'a> Lexer<'a> {
                 "=" => TokenKind::LtEq,
                 "" => TokenKind::LAngle,
             }
+            '?' => {
+                "." => TokenKind::QuestionDot,
+                "?" => TokenKind::Question2,
+                "" => TokenKind::Question,
+            }
             '>' => {
                 ">" => TokenKind::RShift,
                 "=" => TokenKind::GtEq,
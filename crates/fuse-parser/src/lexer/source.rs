@@ -79,7 +79,10 @@ impl<'a> Source<'a> {
     ///  its own `Source`.
     #[inline]
     pub(super) unsafe fn set_position(&mut self, pos: SourcePosition) {
-        debug_assert_char_byte!(pos.ptr.read_u8(), pos.ptr == self.end);
+        // Don't read the end position, It is out of bound and dangling for empty sources.
+        if pos.ptr != self.end {
+            debug_assert_char_byte!(pos.ptr.read_u8());
+        }
         debug_assert!(
             pos.ptr >= self.start && pos.ptr <= self.end,
             "Position out of bound."
@@ -87,6 +90,23 @@ impl<'a> Source<'a> {
         self.ptr = pos.ptr;
     }
 
+    /// Set current offset from start of source.
+    ///
+    ///  # SAFETY
+    ///  `offset` must be in bounds and at a UTF-8 character boundary.
+    #[inline]
+    pub(super) unsafe fn set_offset(&mut self, offset: u32) {
+        debug_assert!(
+            offset as usize <= self.end as usize - self.start as usize,
+            "Offset out of bound."
+        );
+        let ptr = self.start.add(offset as usize);
+        if ptr != self.end {
+            debug_assert_char_byte!(ptr.read_u8());
+        }
+        self.ptr = ptr;
+    }
+
     /// Get offset from start of source.
     #[inline]
     pub(super) fn offset(&self) -> u32 {
@@ -107,18 +127,6 @@ impl<'a> Source<'a> {
         }
     }
 
-    /// Advance if the next character is accepted by `predicate`.
-    #[inline]
-    pub(super) fn advance_if(&mut self, c: char) -> bool {
-        match self.peek_char() {
-            Some(peek) if peek == c => {
-                self.advance();
-                true
-            }
-            _ => false,
-        }
-    }
-
     /// Advance if the next character is accepted by the immutable `predicate`.
     #[inline]
     pub(super) fn advance_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'a str {
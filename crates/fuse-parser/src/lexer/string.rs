@@ -55,7 +55,8 @@ impl<'a> Lexer<'a> {
 
                 // possible point of string termination.
                 (false, c) if c == quote => {
-                    let end = self.source.offset();
+                    // ignore the quote at the end
+                    let end = self.source.offset() - 1;
                     let terminate = self.string_terminate(raw_mod, expected_hashes);
 
                     if terminate {
@@ -70,11 +71,6 @@ impl<'a> Lexer<'a> {
             }
         }
 
-        // if not terminated
-        if !builder.terminated {
-            println!("Unterminated string literal!");
-        }
-
         let token = self.create(start, TokenKind::StringLiteral);
 
         self.set_string_data(
@@ -105,6 +101,9 @@ impl<'a> Lexer<'a> {
 
         // Clear the lookahead to avoid any conflict.
         self.lookahead.clear();
+        // Whitespaces after the `}` are part of the string, Not trailing trivia.
+        // SAFETY: The end of the `}` token is in bounds and at a character boundary.
+        unsafe { self.source.set_offset(self.current().end()) };
 
         let mut builder = StringBuilder::with_head_ref(head_data);
         let mut kind = TokenKind::Undetermined;
@@ -118,9 +117,11 @@ impl<'a> Lexer<'a> {
                     kind = TokenKind::InterpolatedStringMiddle;
                     // ignore ${` at the end
                     data_end = self.source.offset() - 2;
+                    break;
                 }
                 (false, c) if c == head_data.quote => {
-                    let end = self.source.offset();
+                    // ignore the quote at the end
+                    let end = self.source.offset() - 1;
                     let terminate = self.string_terminate(head_data.raw, head_data.expected_hashes);
 
                     if terminate {
@@ -165,14 +166,13 @@ impl<'a> Lexer<'a> {
     }
 
     fn string_modifiers(&mut self, first: char) -> Option<(bool, bool)> {
-        let res = match (first, self.source.peek_pair()) {
+        match (first, self.source.peek_pair()) {
             ('"' | '\'', _) => Some((false, false)),
             ('u', Some(('\'' | '"', _))) => Some((true, false)),
             ('r', Some(('#', '\'' | '"' | '#'))) => Some((false, true)),
             ('u', Some(('r', '#'))) => Some((true, true)),
             _ => None,
-        };
-        res
+        }
     }
 
     fn string_terminate(&mut self, raw_mod: bool, expected_hashes: &str) -> bool {
@@ -216,17 +216,6 @@ impl StringBuilder {
         }
     }
 
-    fn with_raw_mod() -> Self {
-        Self {
-            chars: Vec::new(),
-            escape: false,
-            escape_whitespace: false,
-            has_ever_escaped: false,
-            terminated: false,
-            raw: true,
-        }
-    }
-
     fn with_head_ref(head_data: &StringData) -> Self {
         Self {
             chars: Vec::new(),
@@ -290,6 +279,8 @@ fn parse_escaped_character(c: char) -> Option<char> {
         '\\' => Some('\\'),
         '0' => Some('\0'),
         '$' => Some('$'),
+        '"' => Some('"'),
+        '\'' => Some('\''),
         _ => None,
     }
 }
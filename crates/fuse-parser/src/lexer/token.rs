@@ -41,10 +41,6 @@ pub struct TokenReference {
 }
 
 impl TokenReference {
-    pub(crate) fn new(token: Token) -> Self {
-        Self::with_trivia(token, Vec::default(), Vec::default())
-    }
-
     pub fn with_trivia(
         token: Token,
         leading_trivia: Vec<Token>,
@@ -11,6 +11,8 @@ pub enum TokenKind {
     Whitespace,
     Newline,
     Hashbang,
+    /// A line comment, e.g. `-- note`.
+    Comment,
 
     // Identifiers and Literals
     Identifier,
@@ -31,6 +33,7 @@ pub enum TokenKind {
     End,
     Enum,
     Export,
+    Extern,
     False,
     For,
     From,
@@ -137,11 +140,17 @@ pub enum TokenKind {
     Arrow,
     /// ->
     ThinArrow,
+    /// ?
+    Question,
+    /// ?.
+    QuestionDot,
+    /// ??
+    Question2,
 }
 
 impl TokenKind {
     pub fn is_trivial(&self) -> bool {
-        matches!(self, TokenKind::Whitespace)
+        matches!(self, TokenKind::Whitespace | TokenKind::Comment)
     }
 
     pub fn is_symbol(&self) -> bool {
@@ -170,6 +179,7 @@ impl TokenKind {
             | End
             | Enum
             | Export
+            | Extern
             | False
             | For
             | From
@@ -211,6 +221,7 @@ impl TokenKind {
         use TokenKind::*;
         match self {
             Eq => Some(Assignment),
+            Question2 => Some(Coalesce),
             Or => Some(LogicalOr),
             And => Some(LogicalAnd),
             Pipe => Some(BitwiseOr),
@@ -1,10 +1,13 @@
 mod cursor;
 mod error;
+mod incremental;
 mod parsers;
 
+pub mod cst;
 pub mod lexer;
 
 pub use error::Error;
+pub use incremental::{Reparse, TextEdit};
 
 /// Maximum length of source that can be parsed in bytes.
 /// ~4GiB on 64-bit systems, ~2 GiB on 32-bit systems.
@@ -48,7 +51,7 @@ impl ParsedChunk {
 pub struct LazyParser<'a>(&'a str);
 
 impl<'a> LazyParser<'a> {
-    pub fn parse(mut self) -> ParsedChunk {
+    pub fn parse(self) -> ParsedChunk {
         Parser::new(self.0).parse()
     }
 }
@@ -59,6 +62,10 @@ pub struct Parser<'a> {
     source: &'a str,
     ast: fuse_ast::AstFactory,
     prev_token: lexer::TokenReference,
+    /// Consumed tokens, Only recorded by `parse_with_tokens`.
+    tokens: Option<Vec<lexer::TokenReference>>,
+    /// Keep parsing after a broken statement, See `with_recovery`.
+    recover: bool,
 }
 
 impl<'a> Parser<'a> {
@@ -69,6 +76,8 @@ impl<'a> Parser<'a> {
             source,
             ast: fuse_ast::AstFactory(),
             prev_token: lexer::TokenReference::default(),
+            tokens: None,
+            recover: false,
         }
     }
 
@@ -76,12 +85,47 @@ impl<'a> Parser<'a> {
         LazyParser(source)
     }
 
+    /// Skip the broken statements instead of giving up on the whole chunk, Their errors are
+    /// still reported but the chunk holds every statement around them that could be parsed.
+    pub fn with_recovery(mut self) -> Self {
+        self.recover = true;
+        self
+    }
+
     pub fn parse(mut self) -> ParsedChunk {
+        self.parse_to_end()
+    }
+
+    /// Parse the source and return its tokens along with their trivia, The tokens cover the
+    /// whole source, Those after an unrecoverable error are lexed without being parsed.
+    pub fn parse_with_tokens(mut self) -> (ParsedChunk, Vec<lexer::TokenReference>) {
+        self.tokens = Some(Vec::new());
+        let parsed = self.parse_to_end();
+        let mut tokens = self.tokens.take().unwrap_or_default();
+        while !self.at(lexer::TokenKind::Eof) {
+            tokens.push(self.lexer.consume());
+        }
+        // The end of the file holds the trivia after the last token.
+        tokens.push(self.lexer.current().clone());
+        (parsed, tokens)
+    }
+
+    /// Parse the source into a lossless syntax tree along with its AST, The tree is built from
+    /// the spans of the AST and the parsed tokens.
+    pub fn parse_lossless(self) -> cst::SyntaxTree {
+        let source = self.source;
+        let recovery = self.recover;
+        let (parsed, tokens) = self.parse_with_tokens();
+        let root = cst::build(source.len() as u32, &tokens, parsed.chunk.as_ref());
+        cst::SyntaxTree::new(source, root, parsed, recovery)
+    }
+
+    fn parse_to_end(&mut self) -> ParsedChunk {
         match self.parse_chunk() {
-            ParserResult::Ok(chunk) => ParsedChunk::new(chunk, self.errors),
+            ParserResult::Ok(chunk) => ParsedChunk::new(chunk, std::mem::take(&mut self.errors)),
             ParserResult::Err(error) => {
                 self.push_error(error);
-                ParsedChunk::with_panic(self.errors)
+                ParsedChunk::with_panic(std::mem::take(&mut self.errors))
             }
         }
     }
@@ -90,11 +134,6 @@ impl<'a> Parser<'a> {
         self.errors.push(error.into());
     }
 
-    fn push_errors<E: Into<error::Error>>(&mut self, errors: Vec<E>) {
-        self.errors
-            .append(&mut errors.into_iter().map(E::into).collect())
-    }
-
     fn start_span(&self) -> fuse_common::Span {
         let token = self.cur_token();
         fuse_common::Span::new(token.start(), 0)
@@ -109,6 +148,14 @@ impl<'a> Parser<'a> {
 
 pub type ParserResult<T> = Result<T, error::Error>;
 
-pub fn parse<'a>(src: &'a str) -> ParsedChunk {
+pub fn parse(src: &str) -> ParsedChunk {
     Parser::new(src).parse()
 }
+
+pub fn parse_lossless(src: &str) -> cst::SyntaxTree {
+    Parser::new(src).parse_lossless()
+}
+
+pub fn reparse(previous: cst::SyntaxTree, edit: &TextEdit) -> Reparse {
+    Parser::reparse(previous, edit)
+}
@@ -33,6 +33,7 @@ impl<'a> Parser<'a> {
         }
 
         let identifier = self.parse_binding_identifier()?;
+        let optional = self.consume_if(TokenKind::Question).is_some();
         let type_annotation = if self.consume_if(TokenKind::Colon).is_some() {
             Some(self.parse_type_annotation()?)
         } else {
@@ -41,7 +42,7 @@ impl<'a> Parser<'a> {
 
         Ok(self
             .ast
-            .binding_identifier_pattern(identifier, type_annotation, false))
+            .binding_identifier_pattern(identifier, type_annotation, optional))
     }
 
     pub(crate) fn parse_binding_identifier(&mut self) -> ParserResult<BindingIdentifier> {
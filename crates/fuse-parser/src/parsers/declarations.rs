@@ -1,6 +1,8 @@
 use crate::{lexer::TokenKind, Parser, ParserResult};
 use fuse_ast::{
-    EnumDeclaration, EnumVariant, Function, StructDeclaration, StructField, VariableDeclaration, VariableDeclarationKind
+    EnumDeclaration, EnumVariant, ExternDeclaration, ExternGlobal, ExternItem, ExternTable,
+    Function, StructDeclaration, StructField, TypeAliasDeclaration, UnionDeclaration, UnionVariant,
+    VariableDeclaration, VariableDeclarationKind,
 };
 
 impl<'a> Parser<'a> {
@@ -22,7 +24,7 @@ impl<'a> Parser<'a> {
 
         let binding = self.parse_binding()?;
 
-        let expression = if let Some(_) = self.consume_if(TokenKind::Eq) {
+        let expression = if self.consume_if(TokenKind::Eq).is_some() {
             Some(self.parse_expression()?)
         } else {
             None
@@ -70,20 +72,120 @@ impl<'a> Parser<'a> {
         self.consume();
 
         let identifier = self.parse_identifier()?;
+        let type_parameters = self.parse_type_parameters()?;
         let mut fields: Vec<StructField> = Vec::new();
         while !self.at(TokenKind::End) {
             let modifier = self.try_parse_visibility_modifier();
             let identifier = self.parse_identifier()?;
             self.consume_expect(TokenKind::Colon)?;
             let type_annotation = self.parse_type_annotation()?;
-            fields.push(StructField { modifier, identifier, type_annotation })
+            fields.push(StructField {
+                modifier,
+                identifier,
+                type_annotation,
+            })
         }
         // consume the end token
         self.consume();
         Ok(StructDeclaration {
             span: self.end_span(start),
             identifier,
+            type_parameters,
             fields,
         })
     }
+
+    pub(crate) fn parse_type_alias_declaration(&mut self) -> ParserResult<TypeAliasDeclaration> {
+        debug_assert!(self.at(TokenKind::Type));
+        let start = self.start_span();
+        // Consume the type keyword.
+        self.consume();
+
+        let identifier = self.parse_identifier()?;
+        self.consume_expect(TokenKind::Eq)?;
+        let annotation = self.parse_type_annotation()?;
+        Ok(TypeAliasDeclaration {
+            span: self.end_span(start),
+            identifier,
+            annotation,
+        })
+    }
+
+    pub(crate) fn parse_union_declaration(&mut self) -> ParserResult<UnionDeclaration> {
+        debug_assert!(self.at(TokenKind::Union));
+        let start = self.start_span();
+        // Consume the union keyword.
+        self.consume();
+
+        let identifier = self.parse_identifier()?;
+        let mut variants: Vec<UnionVariant> = Vec::new();
+        while !self.at(TokenKind::End) {
+            let start = self.start_span();
+            let identifier = self.parse_identifier()?;
+            let fields = if self.consume_if(TokenKind::LParen).is_some() {
+                let (fields, _) = self.parse_comma_seperated_types(TokenKind::RParen)?;
+                self.consume_expect(TokenKind::RParen)?;
+                fields
+            } else {
+                Vec::new()
+            };
+            variants.push(UnionVariant {
+                span: self.end_span(start),
+                identifier,
+                fields,
+            })
+        }
+        // consume the end token
+        self.consume();
+        Ok(UnionDeclaration {
+            span: self.end_span(start),
+            identifier,
+            variants,
+        })
+    }
+
+    pub(crate) fn parse_extern_declaration(&mut self) -> ParserResult<ExternDeclaration> {
+        debug_assert!(self.at(TokenKind::Extern));
+        let start = self.start_span();
+        // Consume the extern keyword.
+        self.consume();
+
+        let item = self.parse_extern_item()?;
+        Ok(ExternDeclaration {
+            span: self.end_span(start),
+            item,
+        })
+    }
+
+    /// Parse an extern function `fn name()`, value `name: T` or table `name ... end`.
+    fn parse_extern_item(&mut self) -> ParserResult<ExternItem> {
+        if matches!(self.cur_kind(), TokenKind::Function | TokenKind::Fn) {
+            return self
+                .parse_function_signature(true)
+                .map(ExternItem::Function);
+        }
+
+        let start = self.start_span();
+        let identifier = self.parse_identifier()?;
+        if self.consume_if(TokenKind::Colon).is_some() {
+            let type_annotation = self.parse_type_annotation()?;
+            return Ok(ExternItem::Global(ExternGlobal {
+                span: self.end_span(start),
+                identifier,
+                type_annotation,
+            }));
+        }
+
+        let mut items: Vec<ExternItem> = Vec::new();
+        while !self.at(TokenKind::End) {
+            items.push(self.parse_extern_item()?);
+        }
+        // consume the end token
+        self.consume();
+        Ok(ExternItem::Table(ExternTable {
+            span: self.end_span(start),
+            identifier,
+            items,
+        }))
+    }
 }
@@ -3,7 +3,7 @@ use std::cell::Cell;
 use crate::{lexer::TokenKind, Parser, ParserResult};
 use fuse_ast::{
     ArrayExpressionElement, BinaryOperator, BooleanLiteral, ConstructionExpression,
-    ConstructionField, Else, Expression, Identifier, If, KeyValueArgument, MemberExpression,
+    ConstructionField, Else, Expression, Identifier, If, KeyValueArgument, Match, MatchArm,
     MemberExpressionLHS, MemberExpressionRHS, Precedence, SpreadArgument, TupleExpressionElement,
 };
 
@@ -43,6 +43,12 @@ impl<'a> Parser<'a> {
                     value: false,
                 }))
             }
+            Nil => {
+                let token = self.consume();
+                Ok(self
+                    .ast
+                    .nil_literal_expression(fuse_ast::NilLiteral { span: token.span() }))
+            }
             NumberLiteral => self
                 .parse_number_literal()
                 .map(|expr| self.ast.number_literal_expression(expr)),
@@ -55,6 +61,7 @@ impl<'a> Parser<'a> {
 
             Function | TokenKind::Fn => self.parse_function_expression(),
             If => self.parse_if_expression(),
+            Match => self.parse_match_expression(),
 
             Not | Plus | Minus => self.parse_unary_operator_expression(),
             LBrack => self.parse_array_expression(),
@@ -68,7 +75,7 @@ impl<'a> Parser<'a> {
     }
 
     pub(crate) fn parse_identifier(&mut self) -> ParserResult<Identifier> {
-        debug_assert!(self.at(TokenKind::Identifier));
+        self.expect(TokenKind::Identifier)?;
         let token = self.consume();
         let view = self.view_token(*token);
         Ok(Identifier {
@@ -124,6 +131,70 @@ impl<'a> Parser<'a> {
         })
     }
 
+    fn parse_match_expression(&mut self) -> ParserResult<Expression> {
+        self.parse_match()
+            .map(|expr| self.ast.match_expression(expr))
+    }
+
+    fn parse_match(&mut self) -> ParserResult<Match> {
+        debug_assert!(self.at(TokenKind::Match));
+        let start = self.start_span();
+        // Consume the keyword
+        self.consume();
+        let expression = self.parse_expression()?;
+
+        let mut arms = Vec::new();
+        while self.at(TokenKind::When) {
+            arms.push(self.parse_match_arm()?);
+        }
+
+        let r#else = match self.cur_kind() {
+            TokenKind::End => {
+                self.consume();
+                None
+            }
+            TokenKind::Else => {
+                self.consume();
+                Some(self.parse_block()?)
+            }
+            _ => {
+                return Err(Self::unexpect_token_kind_error(
+                    self.cur_token(),
+                    TokenKind::End,
+                ))
+            }
+        };
+
+        Ok(Match {
+            span: self.end_span(start),
+            expression,
+            arms,
+            r#else,
+        })
+    }
+
+    fn parse_match_arm(&mut self) -> ParserResult<MatchArm> {
+        debug_assert!(self.at(TokenKind::When));
+        let start = self.start_span();
+        // Consume the keyword
+        self.consume();
+        let pattern = self.parse_pattern()?;
+        self.consume_expect(TokenKind::Then)?;
+        let body = self.parse_block_while(|kind| {
+            !matches! {
+                kind,
+                    | TokenKind::End
+                    | TokenKind::Else
+                    | TokenKind::When
+            }
+        })?;
+        Ok(MatchArm {
+            span: self.end_span(start),
+            pattern,
+            body,
+        })
+    }
+
     pub(crate) fn parse_unary_operator_expression(&mut self) -> ParserResult<Expression> {
         self.parse_unary_operator()
             .map(|op| self.ast.unary_operator_expression(op))
@@ -146,12 +217,12 @@ impl<'a> Parser<'a> {
                 TokenKind::Dot3 => Some(
                     parser
                         .parse_spread_element()
-                        .map(|expr| ArrayExpressionElement::Spread(expr)),
+                        .map(ArrayExpressionElement::Spread),
                 ),
                 _ => Some(
                     parser
                         .parse_expression()
-                        .map(|expr| ArrayExpressionElement::Expression(expr)),
+                        .map(ArrayExpressionElement::Expression),
                 ),
             })?;
 
@@ -221,7 +292,10 @@ impl<'a> Parser<'a> {
         })
     }
 
-    fn parse_comma_seperated_expressions<R, F: Fn(&mut Parser<'a>) -> Option<ParserResult<R>>>(
+    pub(crate) fn parse_comma_seperated_expressions<
+        R,
+        F: Fn(&mut Parser<'a>) -> Option<ParserResult<R>>,
+    >(
         &mut self,
         parser: F,
     ) -> ParserResult<(Vec<R>, bool)> {
@@ -251,7 +325,9 @@ impl<'a> Parser<'a> {
         match self.cur_kind() {
             TokenKind::LCurly => self.parse_struct_construction_expression(expr),
             TokenKind::LParen => self.parse_call_expression(expr),
-            TokenKind::Dot => self.parse_member_chain_expression_recursive(expr),
+            TokenKind::Dot | TokenKind::QuestionDot => {
+                self.parse_member_chain_expression_recursive(expr)
+            }
             _ => Ok(expr),
         }
     }
@@ -299,13 +375,9 @@ impl<'a> Parser<'a> {
             // key value argument
             TokenKind::Identifier if self.nth_kind(1) == TokenKind::Colon => self
                 .parse_key_value_argument()
-                .map(|kv| ConstructionField::KeyValueArgument(kv)),
-            TokenKind::Dot3 => self
-                .parse_spread_element()
-                .map(|spread| ConstructionField::Spread(spread)),
-            _ => self
-                .parse_expression()
-                .map(|expr| ConstructionField::Expression(expr)),
+                .map(ConstructionField::KeyValueArgument),
+            TokenKind::Dot3 => self.parse_spread_element().map(ConstructionField::Spread),
+            _ => self.parse_expression().map(ConstructionField::Expression),
         }
     }
 
@@ -348,9 +420,13 @@ impl<'a> Parser<'a> {
         expr: Expression,
     ) -> ParserResult<Expression> {
         let start = expr.span();
-        if self.consume_if(TokenKind::Dot).is_none() {
-            return Ok(expr);
-        }
+        let optional = match self.cur_kind() {
+            TokenKind::Dot => false,
+            TokenKind::QuestionDot => true,
+            _ => return Ok(expr),
+        };
+        // Consume the dot
+        self.consume();
 
         let lhs = {
             match expr {
@@ -361,16 +437,30 @@ impl<'a> Parser<'a> {
             }
         };
 
-        // TODO: better error messages here, no panic, we should recover.
-        let rhs = match self.try_parse_primary_expression_base().expect("Expected a primary expression")? {
+        // Members are identifiers or numbers for the fields of tuples.
+        if !matches!(
+            self.cur_kind(),
+            TokenKind::Identifier | TokenKind::NumberLiteral
+        ) {
+            return Err(Self::unexpect_token_kind_error(
+                self.cur_token(),
+                TokenKind::Identifier,
+            ));
+        }
+        let Some(rhs) = self.try_parse_primary_expression_base() else {
+            return Err(Self::unexpected_error(self.cur_token()));
+        };
+        let rhs = match rhs? {
             Expression::Identifier(ident) => MemberExpressionRHS::Identifier(*ident),
             Expression::NumberLiteral(num) => MemberExpressionRHS::Number(*num),
             Expression::CallExpression(call) => MemberExpressionRHS::Call(*call),
             Expression::MemberExpression(member) => MemberExpressionRHS::Member(*member),
-            rhs => panic!("write error, member rhs should be identifier or number(only for tuple types). {rhs:?}"),
+            _ => return Err(Self::unexpected_error(self.cur_token())),
         };
 
-        let expr = self.ast.member_expression(self.end_span(start), lhs, rhs);
+        let expr = self
+            .ast
+            .member_expression(self.end_span(start), lhs, rhs, optional);
         let expr = match self.cur_kind() {
             TokenKind::LParen => self.parse_call_expression(expr)?,
             _ => expr,
@@ -3,7 +3,7 @@ use crate::{
     Parser, ParserResult,
 };
 use fuse_ast::{
-    BindingPattern, BindingPatternKind, Function, FunctionBody, FunctionParameter,
+    BindingPattern, BindingPatternKind, BindingRest, Function, FunctionBody, FunctionParameter,
     FunctionParameters, FunctionSignature, TypeAnnotation,
 };
 use fuse_common::Span;
@@ -31,12 +31,14 @@ impl<'a> Parser<'a> {
         } else {
             None
         };
+        let type_parameters = self.parse_type_parameters()?;
         let params = self.parse_function_parameters()?;
         let return_type = self.parse_function_return_type()?;
 
         Ok(FunctionSignature {
             span: self.end_span(start),
             identifier,
+            type_parameters,
             params,
             return_type,
         })
@@ -69,11 +71,36 @@ impl<'a> Parser<'a> {
             params.push(param);
         }
 
+        // The rest parameter can only come last, e.g. `...values: number`.
+        let rest = if seen_comma && self.at(TokenKind::Dot3) {
+            Some(self.parse_binding_rest()?)
+        } else {
+            None
+        };
+
         let close = self.consume_expect(TokenKind::RParen)?;
         Ok(FunctionParameters {
             span: Span::new(open.start(), close.end()),
             items: params,
-            rest: None,
+            rest,
+        })
+    }
+
+    fn parse_binding_rest(&mut self) -> ParserResult<BindingRest> {
+        debug_assert!(self.at(TokenKind::Dot3));
+        let start = self.start_span();
+        // Consume the `...` token.
+        self.consume();
+        let binding = self.parse_binding_identifier()?;
+        let type_annotation = if self.consume_if(TokenKind::Colon).is_some() {
+            Some(self.parse_type_annotation()?)
+        } else {
+            None
+        };
+        Ok(BindingRest {
+            span: self.end_span(start),
+            binding,
+            type_annotation,
         })
     }
 
@@ -102,14 +129,14 @@ impl<'a> Parser<'a> {
     }
 
     pub(crate) fn parse_function_return_type(&mut self) -> ParserResult<Option<TypeAnnotation>> {
-        if self.consume_if(TokenKind::ThinArrow) == None {
+        if self.consume_if(TokenKind::ThinArrow).is_none() {
             return Ok(None);
         }
-        self.parse_type_annotation().map(|t| Some(t))
+        self.parse_type_annotation().map(Some)
     }
 
     pub(crate) fn parse_function_body(&mut self) -> ParserResult<FunctionBody> {
-        if let Some(_) = self.consume_if(TokenKind::Arrow) {
+        if self.consume_if(TokenKind::Arrow).is_some() {
             Ok(FunctionBody::Expression(self.parse_expression()?))
         } else {
             Ok(FunctionBody::Block(self.parse_block()?))
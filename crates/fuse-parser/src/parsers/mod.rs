@@ -4,17 +4,26 @@ mod expressions;
 mod functions;
 mod numbers;
 mod operators;
+mod patterns;
 mod statements;
 mod strings;
 mod types;
 
-use crate::{Parser, ParserResult};
+use crate::{lexer::TokenKind, Parser, ParserResult};
 use fuse_ast::Chunk;
 
 impl<'a> Parser<'a> {
     pub(crate) fn parse_chunk(&mut self) -> ParserResult<Chunk> {
         let span = fuse_common::Span::new(0, self.source.len() as u32);
-        let body = self.parse_block()?;
+        let mut body = self.parse_block()?;
+        // Blocks stop at a stray `end`, Recovering parsers report it and go on to the rest.
+        while self.recover && self.prev_token.kind() == TokenKind::End {
+            let stray = self.prev_token.clone();
+            if self.errors.last().map(|error| error.span()) != Some(stray.span()) {
+                self.push_error(Self::unexpected_error(&stray));
+            }
+            body.statements.extend(self.parse_block()?.statements);
+        }
 
         let chunk = self.ast.chunk(span, body);
         ParserResult::Ok(chunk)
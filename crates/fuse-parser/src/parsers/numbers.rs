@@ -64,18 +64,17 @@ impl<'a> Parser<'a> {
             T::from_str(s).map_err(|_| Parser::invalid_number_literal_error(&parser.prev_token))
         }
 
-        let value = if let Some((pos, _)) = exponent {
-            let decimal = parse::<NumberType>(self, &str[0..pos])?;
-            let exponent = parse::<i32>(self, &str[pos + 1..])?;
-
-            decimal * (10f64.powi(exponent)) as NumberType
-        } else {
-            parse(self, str)?
-        };
+        if let Some((pos, _)) = exponent {
+            parse::<NumberType>(self, &str[0..pos])?;
+            parse::<i32>(self, &str[pos + 1..])?;
+        }
+        // Parse the whole literal at once, Scaling by a power of ten would round twice.
+        let value = parse(self, str)?;
 
         Ok((
             value,
-            if float {
+            // Lua reads the numbers with an exponent as floats too.
+            if float || exponent.is_some() {
                 NumberKind::Float
             } else {
                 NumberKind::Decimal
@@ -1,7 +1,4 @@
-use fuse_ast::{
-    BinaryOperator, BinaryOperatorKind, Expression, Precedence, UnaryOperator, UnaryOperatorKind,
-    VisibilityModifier,
-};
+use fuse_ast::{BinaryOperatorKind, UnaryOperator, UnaryOperatorKind, VisibilityModifier};
 
 use crate::{
     lexer::{Token, TokenKind},
@@ -61,6 +58,7 @@ impl<'a> Parser<'a> {
         }
         match_op! {
             Eq => Assignment
+            Question2 => Coalesce
             Or => LogicalOr
             And => LogicalAnd
             Pipe => BitwiseOr
@@ -0,0 +1,61 @@
+use crate::{lexer::TokenKind, Parser, ParserResult};
+use fuse_ast::{Pattern, VariantPattern};
+
+impl<'a> Parser<'a> {
+    pub(crate) fn parse_pattern(&mut self) -> ParserResult<Pattern> {
+        match self.cur_kind() {
+            TokenKind::NumberLiteral => self
+                .parse_number_literal()
+                .map(|lit| Pattern::Literal(self.ast.number_literal_expression(lit))),
+            TokenKind::StringLiteral => self
+                .parse_string_literal()
+                .map(|lit| Pattern::Literal(self.ast.string_literal_expression(lit))),
+            TokenKind::True | TokenKind::False => {
+                let token = self.consume();
+                Ok(Pattern::Literal(self.ast.boolean_literal_expression(
+                    fuse_ast::BooleanLiteral {
+                        span: token.span(),
+                        value: token.kind() == TokenKind::True,
+                    },
+                )))
+            }
+            TokenKind::Identifier if self.nth_kind(1) == TokenKind::Dot => {
+                self.parse_variant_pattern().map(Pattern::Variant)
+            }
+            TokenKind::Identifier if self.view_token(**self.cur_token()) == "_" => {
+                Ok(Pattern::Wildcard(self.consume().span()))
+            }
+            TokenKind::Identifier | TokenKind::Mut => {
+                self.parse_binding_identifier().map(Pattern::Binding)
+            }
+            _ => Err(Self::unexpected_error(self.cur_token())),
+        }
+    }
+
+    fn parse_variant_pattern(&mut self) -> ParserResult<VariantPattern> {
+        let start = self.start_span();
+        let target = self.parse_identifier()?;
+        // consume the dot
+        self.consume();
+        let variant = self.parse_identifier()?;
+
+        let fields = if self.consume_if(TokenKind::LParen).is_some() {
+            let (fields, _) = if self.at(TokenKind::RParen) {
+                (Vec::new(), false)
+            } else {
+                self.parse_comma_seperated_expressions(|parser| Some(parser.parse_pattern()))?
+            };
+            self.consume_expect(TokenKind::RParen)?;
+            fields
+        } else {
+            Vec::new()
+        };
+
+        Ok(VariantPattern {
+            span: self.end_span(start),
+            target,
+            variant,
+            fields,
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use fuse_ast::{Block, Function, FunctionSignature, ImplMethod, ImplStatement, Statement};
+use fuse_ast::{Block, ImplMethod, ImplStatement, Statement};
 
 use crate::{lexer::TokenKind, Parser, ParserResult};
 
@@ -29,10 +29,15 @@ impl<'a> Parser<'a> {
         let mut statements = Vec::new();
 
         while !self.at(TokenKind::Eof) && predicate(self.cur_kind()) {
+            let start = self.cur_token().start();
             match self.parse_statement() {
                 ParserResult::Ok(stmt) => {
                     statements.push(stmt);
                 }
+                ParserResult::Err(error) if self.recover => {
+                    self.push_error(error);
+                    self.synchronize(start);
+                }
                 ParserResult::Err(error) => {
                     return ParserResult::Err(error);
                 }
@@ -42,6 +47,19 @@ impl<'a> Parser<'a> {
         ParserResult::Ok(statements)
     }
 
+    /// Skip the rest of a broken statement starting at `start`, Parsing resumes at the first
+    /// token after it that starts a line.
+    fn synchronize(&mut self, start: u32) {
+        while !self.at(TokenKind::Eof) {
+            let token = self.cur_token();
+            let gap = &self.source[self.prev_token.end() as usize..token.start() as usize];
+            if token.start() > start && gap.contains('\n') {
+                break;
+            }
+            self.consume();
+        }
+    }
+
     pub(crate) fn parse_statement(&mut self) -> ParserResult<Statement> {
         let cur_kind = self.cur_kind();
 
@@ -71,6 +89,15 @@ impl<'a> Parser<'a> {
                 .parse_struct_declaration()
                 .map(|decl| self.ast.struct_declaration_statement(decl)),
             TokenKind::Impl => self.parse_impl_statement(),
+            TokenKind::Type => self
+                .parse_type_alias_declaration()
+                .map(|decl| self.ast.type_alias_declaration_statement(decl)),
+            TokenKind::Union => self
+                .parse_union_declaration()
+                .map(|decl| self.ast.union_declaration_statement(decl)),
+            TokenKind::Extern => self
+                .parse_extern_declaration()
+                .map(|decl| self.ast.extern_declaration_statement(decl)),
 
             kind if kind.is_trivial() => {
                 unreachable!("All trivial tokens should be eaten by a `TokenReference`.")
@@ -93,7 +120,7 @@ impl<'a> Parser<'a> {
     fn parse_impl_statement(&mut self) -> ParserResult<Statement> {
         debug_assert!(self.at(TokenKind::Impl));
         let start = self.start_span();
-        // Consume the struct keyword.
+        // Consume the impl keyword.
         self.consume();
 
         let r#trait = self.parse_type_annotation()?;
@@ -1,11 +1,12 @@
 use std::rc::Rc;
 
 use crate::{
-    lexer::{StringData, StringValue, Token, TokenKind, TokenReference},
+    lexer::{StringValue, TokenKind},
     Parser, ParserResult,
 };
 use fuse_ast::{
-    Atom, InterpolatedStringSegment, StringLiteral, StringLiteralSegment, StringSegment,
+    Atom, InterpolatedStringSegment, InterpolationFormat, StringLiteral, StringLiteralSegment,
+    StringSegment,
 };
 use fuse_common::Span;
 
@@ -17,11 +18,8 @@ impl<'a> Parser<'a> {
 
         let token = self.consume();
 
-        let str_data = self.lexer.eat_string_data(&*token);
-        let literal = match str_data.value {
-            StringValue::Escaped(val) => StringLiteralSegment::Escaped(Atom(Rc::from(val))),
-            StringValue::Unescaped(span) => StringLiteralSegment::Unescaped(span),
-        };
+        let str_data = self.lexer.eat_string_data(&token);
+        let literal = string_literal_segment(&str_data.value);
 
         Ok(StringLiteral {
             span: token.span,
@@ -32,17 +30,21 @@ impl<'a> Parser<'a> {
     fn parse_string_interpolation(&mut self) -> ParserResult<StringLiteral> {
         let mut segments: Vec<StringSegment> = Vec::new();
         let head = self.consume();
-        let head_data = self.lexer.eat_string_data(&*head);
+        let head_data = self.lexer.eat_string_data(&head);
+        push_literal_segment(&mut segments, &head_data.value);
         let tail = loop {
             let expression = self.parse_expression()?;
+            let format = self.parse_interpolation_format()?;
             self.lexer.follow_string_interpolation(&head_data);
             let next_segment = self.consume();
             segments.push(StringSegment::Interpolated(InterpolatedStringSegment {
                 expression,
-                format: fuse_ast::InterpolationFormat::Display,
+                format,
             }));
+            let data = self.lexer.eat_string_data(&next_segment);
+            push_literal_segment(&mut segments, &data.value);
             if next_segment.kind() == TokenKind::InterpolatedStringTail {
-                break self.consume();
+                break next_segment;
             }
         };
 
@@ -51,4 +53,31 @@ impl<'a> Parser<'a> {
             segments,
         })
     }
+
+    /// Parse the optional format of an interpolated expression, e.g. the `:?` in `${value:?}`.
+    fn parse_interpolation_format(&mut self) -> ParserResult<InterpolationFormat> {
+        if self.consume_if(TokenKind::Colon).is_none() {
+            return Ok(InterpolationFormat::Display);
+        }
+        self.consume_expect(TokenKind::Question)?;
+        Ok(InterpolationFormat::Debug)
+    }
+}
+
+fn string_literal_segment(value: &StringValue) -> StringLiteralSegment {
+    match value {
+        StringValue::Escaped(val) => StringLiteralSegment::Escaped(Atom(Rc::from(val.as_str()))),
+        StringValue::Unescaped(span) => StringLiteralSegment::Unescaped(*span),
+    }
+}
+
+/// Push the literal parts of an interpolated string, Empty parts are skipped.
+fn push_literal_segment(segments: &mut Vec<StringSegment>, value: &StringValue) {
+    let is_empty = match value {
+        StringValue::Escaped(val) => val.is_empty(),
+        StringValue::Unescaped(span) => span.start >= span.end,
+    };
+    if !is_empty {
+        segments.push(StringSegment::Literal(string_literal_segment(value)));
+    }
 }
@@ -1,12 +1,171 @@
-use crate::{Parser, ParserResult};
+use std::cell::Cell;
 
-use fuse_ast::TypeAnnotation;
+use crate::{
+    lexer::{TokenKind, TokenReference},
+    Parser, ParserResult,
+};
+
+use fuse_ast::{
+    FunctionTypeAnnotation, GenericTypeAnnotation, Identifier, TypeAnnotation, TypeAnnotationKind,
+    TypeParameter,
+};
 
 impl<'a> Parser<'a> {
     pub(crate) fn parse_type_annotation(&mut self) -> ParserResult<TypeAnnotation> {
-        println!("Type annotation not implemented yet!");
-        // TODO: Implement me, right now we consume one token and return empty type annotation.
+        let start = self.start_span();
+        let kind = match self.cur_kind() {
+            TokenKind::LParen => self.parse_tuple_type()?,
+            TokenKind::LBrack => TypeAnnotationKind::Array(Box::from(self.parse_array_type()?)),
+            TokenKind::Fn | TokenKind::Function => {
+                TypeAnnotationKind::Function(Box::from(self.parse_function_type()?))
+            }
+            _ => {
+                let identifier = self.parse_type_identifier()?;
+                if self.at(TokenKind::LAngle) {
+                    let arguments = self.parse_type_arguments()?;
+                    TypeAnnotationKind::Generic(Box::from(GenericTypeAnnotation {
+                        identifier,
+                        arguments,
+                    }))
+                } else {
+                    TypeAnnotationKind::Identifier(identifier)
+                }
+            }
+        };
+
+        let annotation = TypeAnnotation {
+            span: self.end_span(start),
+            kind,
+        };
+
+        if self.consume_if(TokenKind::Question).is_none() {
+            return Ok(annotation);
+        }
+
+        Ok(TypeAnnotation {
+            span: self.end_span(start),
+            kind: TypeAnnotationKind::Optional(Box::from(annotation)),
+        })
+    }
+
+    /// Parse the name of a type, It accepts the type keywords
+    /// such as `unknown` and `never` as identifiers.
+    fn parse_type_identifier(&mut self) -> ParserResult<Identifier> {
+        match self.cur_kind() {
+            TokenKind::Identifier => self.parse_identifier(),
+            TokenKind::Unknown | TokenKind::Never | TokenKind::Nil => {
+                let token = self.consume();
+                let view = self.view_token(*token);
+                Ok(Identifier {
+                    span: token.span(),
+                    name: self.ast.atom(view),
+                    reference: Cell::default(),
+                })
+            }
+            _ => Err(Self::unexpected_error(self.cur_token())),
+        }
+    }
+
+    /// Parse a tuple type, A single type in parentheses without
+    /// a trailing comma is only grouped, e.g. `(fn() -> number)?`.
+    fn parse_tuple_type(&mut self) -> ParserResult<TypeAnnotationKind> {
+        debug_assert!(self.at(TokenKind::LParen));
+        // consume the opening parentheses.
+        self.consume();
+        let (mut elements, met_comma) = self.parse_comma_seperated_types(TokenKind::RParen)?;
+        self.consume_expect(TokenKind::RParen)?;
+        if elements.len() == 1 && !met_comma {
+            return Ok(elements.pop().unwrap().kind);
+        }
+        Ok(TypeAnnotationKind::Tuple(elements))
+    }
+
+    fn parse_array_type(&mut self) -> ParserResult<TypeAnnotation> {
+        debug_assert!(self.at(TokenKind::LBrack));
+        // consume the opening bracket.
+        self.consume();
+        let element = self.parse_type_annotation()?;
+        self.consume_expect(TokenKind::RBrack)?;
+        Ok(element)
+    }
+
+    fn parse_function_type(&mut self) -> ParserResult<FunctionTypeAnnotation> {
+        // Consume the keyword
+        self.consume();
+        self.consume_expect(TokenKind::LParen)?;
+        let (params, _) = self.parse_comma_seperated_types(TokenKind::RParen)?;
+        self.consume_expect(TokenKind::RParen)?;
+        let return_type = self.parse_function_return_type()?;
+        Ok(FunctionTypeAnnotation {
+            params,
+            return_type,
+        })
+    }
+
+    fn parse_type_arguments(&mut self) -> ParserResult<Vec<TypeAnnotation>> {
+        debug_assert!(self.at(TokenKind::LAngle));
+        // consume the opening angle.
+        self.consume();
+        let (arguments, _) = self.parse_comma_seperated_types(TokenKind::RAngle)?;
+        self.consume_closing_angle()?;
+        Ok(arguments)
+    }
+
+    /// Parse the type parameters of a generic declaration if there is any, e.g. `<T, U: Display>`.
+    pub(crate) fn parse_type_parameters(&mut self) -> ParserResult<Vec<TypeParameter>> {
+        if self.consume_if(TokenKind::LAngle).is_none() {
+            return Ok(Vec::new());
+        }
+        let (params, _) = self.parse_comma_seperated_expressions(|parser| {
+            parser
+                .at(TokenKind::Identifier)
+                .then(|| parser.parse_type_parameter())
+        })?;
+        self.consume_closing_angle()?;
+        Ok(params)
+    }
+
+    fn parse_type_parameter(&mut self) -> ParserResult<TypeParameter> {
+        let start = self.start_span();
         let identifier = self.parse_identifier()?;
-        Ok(TypeAnnotation { identifier })
+        let mut bounds = Vec::new();
+        if self.consume_if(TokenKind::Colon).is_some() {
+            loop {
+                bounds.push(self.parse_type_annotation()?);
+                if self.consume_if(TokenKind::Plus).is_none() {
+                    break;
+                }
+            }
+        }
+        Ok(TypeParameter {
+            span: self.end_span(start),
+            identifier,
+            bounds,
+        })
+    }
+
+    /// Consume the closing angle of a generic, Splits the `>>` of nested generics in half.
+    fn consume_closing_angle(&mut self) -> ParserResult<TokenReference> {
+        if self.at(TokenKind::RShift) {
+            let token = self
+                .lexer
+                .split_current(TokenKind::RAngle, TokenKind::RAngle);
+            self.record(&token);
+            self.prev_token = token.clone();
+            return Ok(token);
+        }
+        self.consume_expect(TokenKind::RAngle)
+    }
+
+    /// Parse a comma seperated list of types, until reaching the `close` token.
+    pub(crate) fn parse_comma_seperated_types(
+        &mut self,
+        close: TokenKind,
+    ) -> ParserResult<(Vec<TypeAnnotation>, bool)> {
+        if self.at(close) {
+            return Ok((Vec::new(), false));
+        }
+
+        self.parse_comma_seperated_expressions(|parser| Some(parser.parse_type_annotation()))
     }
 }
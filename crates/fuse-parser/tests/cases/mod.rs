@@ -1,6 +1,12 @@
 use std::{ffi::OsStr, fs, path::PathBuf};
 
-use fuse_parser::{lexer::Lexer, parse};
+use fuse_ast::Statement;
+use fuse_common::Span;
+use fuse_parser::{
+    cst::SyntaxTree,
+    lexer::{Lexer, TokenKind},
+    parse, parse_lossless, reparse, Parser, TextEdit,
+};
 
 macro_rules! expect_eq {
     (
@@ -92,7 +98,7 @@ fn panic() {
 // helpers
 fn load_cases(ctx: &Context) -> Vec<PathBuf> {
     fs::read_dir(ctx.path())
-        .expect(&format!("Failed to read {}", ctx.path().to_str().unwrap()))
+        .unwrap_or_else(|_| panic!("Failed to read {}", ctx.path().to_str().unwrap()))
         .filter_map(|x| x.ok())
         .filter(|x| x.metadata().is_ok_and(|meta| meta.is_dir()))
         .map(|node| node.path())
@@ -115,6 +121,10 @@ fn run(ctx: &Context, case_dir: PathBuf, expect_error: bool, expect_panic: bool)
 
     let _guard = settings.bind_to_scope();
     test_parser(path_str, source.clone(), expect_error, expect_panic);
+    test_cst(path_str, source.clone(), expect_error);
+    if !expect_error {
+        test_reparse(path_str, &source);
+    }
     test_lexer(path_str, source);
 }
 
@@ -170,6 +180,125 @@ fn test_parser(path: &str, source: String, expect_error: bool, expect_panic: boo
     }
 }
 
+fn test_cst(path: &str, source: String, expect_error: bool) {
+    let tree = parse_lossless(source.as_str());
+    let dump = tree.dump();
+
+    expect_eq!(
+        tree.text(),
+        source,
+        "Syntax tree doesn't reproduce the source.",
+        path: path,
+        dump: dump,
+    );
+
+    if !expect_error {
+        insta::assert_snapshot!("cst", dump);
+    }
+}
+
+/// Reparse the case after a few edits around each of its tokens and compare the result with
+/// parsing the edited source from scratch.
+fn test_reparse(path: &str, source: &str) {
+    let len = source.len() as u32;
+    let mut edits = vec![
+        TextEdit::new(Span::new(0, 0), "let inserted = 1\n"),
+        TextEdit::new(Span::new(len, len), "\nlet appended = 2\n"),
+    ];
+    for token in parse_lossless(source).root().tokens() {
+        if token.is_trivia() {
+            continue;
+        }
+        let span = token.span;
+        let text = &source[span.start as usize..span.end as usize];
+        edits.push(TextEdit::new(span, text));
+        edits.push(TextEdit::new(Span::new(span.start, span.start), "\n"));
+        match token.kind {
+            TokenKind::Identifier => edits.push(TextEdit::new(span, format!("{text}_edited"))),
+            TokenKind::NumberLiteral => edits.push(TextEdit::new(span, "42")),
+            _ => {}
+        }
+    }
+
+    for edit in edits {
+        let edited = edit.apply(source);
+        let reparsed = reparse(parse_lossless(source), &edit).tree;
+        let expected = parse_lossless(&edited);
+        let dump = (&edit, &edited);
+        expect_eq!(
+            reparsed.root(),
+            expected.root(),
+            "Reparsed syntax tree is different from a full parse.",
+            path: path,
+            dump: dump,
+        );
+        expect_eq!(
+            reparsed.chunk().map(|chunk| (chunk.span, &chunk.body)),
+            expected.chunk().map(|chunk| (chunk.span, &chunk.body)),
+            "Reparsed AST is different from a full parse.",
+            path: path,
+            dump: dump,
+        );
+        expect_eq!(
+            format!("{:?}", reparsed.parsed().errors),
+            format!("{:?}", expected.parsed().errors),
+            "Reparsed errors are different from a full parse.",
+            path: path,
+            dump: dump,
+        );
+    }
+}
+
+#[test]
+fn reparse_reuses_statements() {
+    let source = "let a = 1\nlet b = 2\nlet c = 3\nlet d = 4\n";
+    let edit = TextEdit::new(Span::new(18, 19), "20");
+    let reparsed = reparse(SyntaxTree::parse(source), &edit);
+
+    assert_eq!(
+        reparsed.tree.source(),
+        "let a = 1\nlet b = 20\nlet c = 3\nlet d = 4\n"
+    );
+    assert_eq!(reparsed.changed, 1..2);
+    assert_eq!(reparsed.relexed, Span::new(10, 21));
+    let changed: Vec<_> = reparsed.changed_nodes().map(|node| node.span).collect();
+    assert_eq!(changed, [Span::new(10, 20)]);
+    let unchanged = reparsed.tree.root().nodes().nth(3).unwrap();
+    assert_eq!(unchanged.span, Span::new(31, 40));
+}
+
+#[test]
+fn recovery_skips_broken_statements() {
+    let source = "let a = 1\nfn f()\n\tlet b = )\nend\nlet c = a.\nend\nlet d = 4\n";
+    let parsed = Parser::new(source).with_recovery().parse();
+
+    assert!(!parsed.paniced);
+    assert_eq!(parsed.errors.len(), 2, "{:#?}", parsed.errors);
+    let chunk = parsed.chunk.unwrap();
+    let statements = &chunk.body.statements;
+    assert_eq!(statements.len(), 3);
+    assert!(matches!(statements[1], Statement::FunctionDeclaration(_)));
+    let Statement::VariableDeclaration(last) = &statements[2] else {
+        panic!("Expected the last declaration to be recovered.");
+    };
+    assert_eq!(last.span, Span::new(47, 56));
+}
+
+#[test]
+fn reparse_keeps_recovery() {
+    let source = "let a = 1\nlet b = )\nlet c = 3\n";
+    let tree = Parser::new(source).with_recovery().parse_lossless();
+    let edit = TextEdit::new(Span::new(28, 29), "30");
+    let reparsed = reparse(tree, &edit);
+
+    // The broken tree is parsed again from scratch, With recovery like the previous one.
+    assert_eq!(reparsed.changed, 0..2);
+    assert_eq!(reparsed.tree.parsed().errors.len(), 1);
+    assert_eq!(reparsed.tree.text(), reparsed.tree.source());
+    let statements = &reparsed.tree.chunk().unwrap().body.statements;
+    assert_eq!(statements.len(), 2);
+}
+
 fn read_source_normalized(path: &OsStr) -> Result<String, std::io::Error> {
     fs::read_to_string(path).map(|it| it.replace("\r\n", "\n"))
 }
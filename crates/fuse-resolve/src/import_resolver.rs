@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+/// Extension of the Fuse source files.
+pub const SOURCE_EXTENSION: &str = "fuse";
+
+/// Package whose modules are imported with its name as their first component, e.g.
+/// `require("utils.strings")` imports the `strings` module of the `utils` package.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    /// Directories holding the modules of the package.
+    pub roots: Vec<PathBuf>,
+}
+
+/// Finds the files of the imported modules, Module names are paths separated by dots, e.g.
+/// `require("utils.strings")` imports `utils/strings.fuse` from one of the roots.
+///
+/// Names starting with the name of a package are looked up in the package first, The package
+/// name alone imports its `init` module like Lua does.
+#[derive(Debug, Clone, Default)]
+pub struct ImportResolver {
+    roots: Vec<PathBuf>,
+    packages: Vec<Package>,
+}
+
+impl ImportResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Look up the modules in `root` too, Roots are searched in the order they are added.
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.roots.push(root.into());
+        self
+    }
+
+    pub fn with_package(mut self, package: Package) -> Self {
+        self.packages.push(package);
+        self
+    }
+
+    pub fn packages(&self) -> &[Package] {
+        &self.packages
+    }
+
+    /// Path of the file of a module, It is `None` if the name is invalid or no file exists.
+    pub fn resolve(&self, module: &str) -> Option<PathBuf> {
+        let path = module_path(module)?;
+        let (first, rest) = module.split_once('.').unwrap_or((module, "init"));
+        let package = self
+            .packages
+            .iter()
+            .find(|package| package.name == first)
+            .and_then(|package| find(&package.roots, &module_path(rest)?));
+        package.or_else(|| find(&self.roots, &path))
+    }
+}
+
+/// Relative path of the file of a module, e.g. `a/b.fuse` for `a.b`.
+pub fn module_path(module: &str) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in module.split('.') {
+        let is_valid = !component.is_empty()
+            && component
+                .chars()
+                .all(|char| char.is_alphanumeric() || char == '_' || char == '-');
+        if !is_valid {
+            return None;
+        }
+        path.push(component);
+    }
+    path.set_extension(SOURCE_EXTENSION);
+    Some(path)
+}
+
+fn find(roots: &[PathBuf], path: &Path) -> Option<PathBuf> {
+    roots
+        .iter()
+        .map(|root| root.join(path))
+        .find(|path| path.is_file())
+}
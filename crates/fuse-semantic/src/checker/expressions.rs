@@ -0,0 +1,550 @@
+use fuse_ast::{
+    ArrayExpressionElement, BinaryOperator, BinaryOperatorKind, CallExpression,
+    ConstructionExpression, ConstructionField, Else, Expression, Function, GetSpan, Identifier, If,
+    MemberExpression, MemberExpressionLHS, MemberExpressionRHS, NumberLiteral, StringSegment,
+    StructConstructionExpression, TupleExpressionElement, UnaryOperator, UnaryOperatorKind,
+};
+use fuse_common::{ReferenceType, Span};
+
+use super::TypeChecker;
+use crate::{SemanticError, Substitution, SymbolKind, Type, TypeDefinitionKind};
+
+impl<'a, 'ast> TypeChecker<'a, 'ast> {
+    pub(super) fn check_expression(&mut self, expression: &'ast Expression) -> Type {
+        match expression {
+            Expression::NilLiteral(_) => Type::Nil,
+            Expression::NumberLiteral(_) => Type::Number,
+            Expression::StringLiteral(lit) => {
+                for segment in &lit.segments {
+                    if let StringSegment::Interpolated(interpolated) = segment {
+                        self.check_expression(&interpolated.expression);
+                    }
+                }
+                Type::String
+            }
+            Expression::BooleanLiteral(_) => Type::Boolean,
+            Expression::Identifier(ident) => self.check_identifier(ident),
+            Expression::Function(func) => self.check_function_expression(func),
+            Expression::If(r#if) => self.check_if(r#if),
+            Expression::Match(r#match) => self.check_match(r#match),
+            Expression::UnaryOperator(op) => self.check_unary_operator(op),
+            Expression::BinaryOperator(op) => self.check_binary_operator(op),
+            Expression::ArrayExpression(array) => {
+                let mut element_type = Type::Never;
+                for element in &array.elements {
+                    let ty = match element {
+                        ArrayExpressionElement::Expression(expr) => self.check_expression(expr),
+                        ArrayExpressionElement::Spread(spread) => {
+                            match self.check_expression(&spread.element) {
+                                Type::Array(element) => *element,
+                                _ => Type::Unknown,
+                            }
+                        }
+                    };
+                    element_type = element_type.join(ty);
+                }
+                if matches!(element_type, Type::Never) {
+                    element_type = Type::Unknown;
+                }
+                Type::Array(Box::new(element_type))
+            }
+            Expression::TupleExpression(tuple) => {
+                let mut elements = Vec::with_capacity(tuple.elements.len());
+                let mut spread = false;
+                for element in &tuple.elements {
+                    match element {
+                        TupleExpressionElement::Expression(expr) => {
+                            elements.push(self.check_expression(expr))
+                        }
+                        TupleExpressionElement::Spread(arg) => {
+                            self.check_expression(&arg.element);
+                            spread = true;
+                        }
+                    }
+                }
+                // We can't know the length of tuples with spread elements.
+                if spread {
+                    Type::Unknown
+                } else {
+                    Type::Tuple(elements)
+                }
+            }
+            Expression::ParenthesizedExpression(expr) => self.check_expression(&expr.expression),
+            Expression::MemberExpression(member) => self.check_member_expression(member),
+            Expression::CallExpression(call) => self.check_call_expression(call),
+            Expression::TableConstructionExpression(construction) => {
+                self.check_table_construction(construction);
+                Type::Table
+            }
+            Expression::StructConstructionExpression(expr) => {
+                self.check_struct_construction_expression(expr)
+            }
+        }
+    }
+
+    fn check_identifier(&mut self, ident: &Identifier) -> Type {
+        self.identifier_type(ident)
+    }
+
+    /// Get the type of an identifier, taking the narrowed types into account.
+    pub(super) fn identifier_type(&self, ident: &Identifier) -> Type {
+        let narrowed = ident.reference.get().and_then(|reference| {
+            self.narrowed
+                .iter()
+                .rev()
+                .find_map(|narrowing| narrowing.get(&reference))
+        });
+        if let Some(ty) = narrowed {
+            return ty.clone();
+        }
+        self.declared_type(ident)
+    }
+
+    fn declared_type(&self, ident: &Identifier) -> Type {
+        match self.symbols.of(ident) {
+            Some(symbol) if !symbol.kind.is_type() => symbol.ty.clone(),
+            _ => Type::Unknown,
+        }
+    }
+
+    fn check_function_expression(&mut self, func: &'ast Function) -> Type {
+        self.define_type_parameters(&func.signature.type_parameters);
+        let params = self.parameter_types(&func.signature);
+        let rest = self.rest_type(&func.signature);
+        let return_type = func
+            .signature
+            .return_type
+            .as_ref()
+            .map(|annotation| self.resolve_annotation(annotation));
+        self.check_function_body(func, params, rest, return_type)
+    }
+
+    fn check_if(&mut self, r#if: &'ast If) -> Type {
+        self.check_expression(&r#if.cond);
+        let (truthy, falsy) = self.narrow_condition(&r#if.cond);
+        let ty = self.with_narrowing(truthy, |this| this.check_block(&r#if.body));
+        match &r#if.r#else {
+            Some(Else::If(else_if)) => {
+                let other = self.with_narrowing(falsy, |this| this.check_if(else_if));
+                // The mismatch of an `else if` belongs to the whole chain.
+                if let Some(mismatch) = self.mismatched_branches.remove(&else_if.span) {
+                    self.mismatched_branches.insert(r#if.span, mismatch);
+                    return Type::Unknown;
+                }
+                self.join_branches(r#if.span, ty, other)
+            }
+            Some(Else::Block(block)) => {
+                let other = self.with_narrowing(falsy, |this| this.check_block(block));
+                self.join_branches(r#if.span, ty, other)
+            }
+            None => ty.join(Type::Nil),
+        }
+    }
+
+    fn check_unary_operator(&mut self, op: &'ast UnaryOperator) -> Type {
+        let ty = self.check_expression(&op.expression);
+        match op.kind {
+            UnaryOperatorKind::Not(_) => Type::Boolean,
+            UnaryOperatorKind::Plus(_) | UnaryOperatorKind::Minus(_) => {
+                self.expect_type(op.expression.span(), &Type::Number, &ty);
+                Type::Number
+            }
+        }
+    }
+
+    fn check_binary_operator(&mut self, op: &'ast BinaryOperator) -> Type {
+        use BinaryOperatorKind::*;
+        match op.kind {
+            Assignment(_) => return self.check_assignment(op),
+            LogicalOr(_) | LogicalAnd(_) => return self.check_logical_operator(op),
+            _ => {}
+        }
+        let lhs = self.check_expression(&op.lhs);
+        let rhs = self.check_expression(&op.rhs);
+        match op.kind {
+            Assignment(_) | LogicalOr(_) | LogicalAnd(_) => unreachable!(),
+            Coalesce(_) => {
+                let inner = lhs.non_optional().clone();
+                if !inner.is_unknown() {
+                    self.expect_type(op.rhs.span(), &Type::optional(inner.clone()), &rhs);
+                }
+                inner.join(rhs)
+            }
+            Equality(_) | NonEquality(_) => Type::Boolean,
+            LessThanEqual(_) | LessThan(_) | GreaterThanEqual(_) | GreaterThan(_) => {
+                // Both numbers and strings are comparable.
+                let expected = match lhs {
+                    Type::String => Type::String,
+                    _ => Type::Number,
+                };
+                self.expect_type(op.lhs.span(), &expected, &lhs);
+                self.expect_type(op.rhs.span(), &expected, &rhs);
+                Type::Boolean
+            }
+            BitwiseOr(_) | BitwiseXor(_) | BitwiseAnd(_) | Plus(_) | Minus(_) | Multiply(_)
+            | Exponential(_) | Division(_) | FloorDivision(_) | Modulo(_) | ShiftLeft(_)
+            | ShiftRight(_) => {
+                self.expect_type(op.lhs.span(), &Type::Number, &lhs);
+                self.expect_type(op.rhs.span(), &Type::Number, &rhs);
+                Type::Number
+            }
+        }
+    }
+
+    fn check_assignment(&mut self, op: &'ast BinaryOperator) -> Type {
+        // Variables can be assigned any value of their declared type even while narrowed.
+        let lhs = match &op.lhs {
+            Expression::Identifier(ident) => self.declared_type(ident),
+            lhs => self.check_expression(lhs),
+        };
+        let rhs = self.check_expression(&op.rhs);
+        self.expect_type(op.rhs.span(), &lhs, &rhs);
+        if let Expression::Identifier(ident) = &op.lhs {
+            if matches!(rhs, Type::Nil | Type::Optional(_)) {
+                self.widen(ident);
+            }
+        }
+        lhs
+    }
+
+    fn check_logical_operator(&mut self, op: &'ast BinaryOperator) -> Type {
+        let lhs = self.check_expression(&op.lhs);
+        // The rhs is only evaluated if `and` has a truthy lhs or `or` has a falsy one.
+        let (truthy, falsy) = self.narrow_condition(&op.lhs);
+        let narrowing = match op.kind {
+            BinaryOperatorKind::LogicalAnd(_) => truthy,
+            _ => falsy,
+        };
+        let rhs = self.with_narrowing(narrowing, |this| this.check_expression(&op.rhs));
+        lhs.join(rhs)
+    }
+
+    fn check_member_expression(&mut self, member: &'ast MemberExpression) -> Type {
+        // Enums and unions are namespaces of their variants.
+        if let MemberExpressionLHS::Identifier(ident) = member.lhs.as_ref() {
+            if let Some(symbol) = self.symbols.of(ident) {
+                if matches!(symbol.kind, SymbolKind::Enum | SymbolKind::Union) {
+                    let target = ident.reference.get().unwrap();
+                    return self.check_variant_access(target, &member.rhs);
+                }
+            }
+        }
+
+        let lhs = match member.lhs.as_ref() {
+            MemberExpressionLHS::Identifier(ident) => self.check_identifier(ident),
+            MemberExpressionLHS::Expression(expr) => self.check_expression(expr),
+            MemberExpressionLHS::Member(member) => self.check_member_expression(member),
+            MemberExpressionLHS::Call(call) => self.check_call_expression(call),
+        };
+        let chained = member.optional || is_optional_chain(&member.lhs);
+        let (lhs, optional) = self.unwrap_optional(member.lhs.span(), lhs, chained);
+        if let MemberExpressionRHS::Identifier(ident) = member.rhs.as_ref() {
+            if self.member.is_none() && self.at_cursor(ident.span) {
+                self.member = Some(lhs.clone());
+            }
+        }
+
+        let ty = match member.rhs.as_ref() {
+            MemberExpressionRHS::Identifier(ident) => self.check_field_access(&lhs, ident),
+            MemberExpressionRHS::Number(index) => self.check_tuple_access(&lhs, index),
+            MemberExpressionRHS::Member(member) => {
+                self.check_member_expression(member);
+                Type::Unknown
+            }
+            MemberExpressionRHS::Call(call) => {
+                for arg in &call.arguments {
+                    self.check_expression(arg);
+                }
+                Type::Unknown
+            }
+        };
+        if optional {
+            Type::optional(ty)
+        } else {
+            ty
+        }
+    }
+
+    /// Unwrap the optional value being accessed, Accessing optional values is only
+    /// allowed in optional chains. Returns the inner type and whether it was optional.
+    fn unwrap_optional(&mut self, span: Span, ty: Type, chained: bool) -> (Type, bool) {
+        let Type::Optional(inner) = ty else {
+            return (ty, false);
+        };
+        if !chained {
+            self.errors.push(SemanticError::OptionalValue {
+                span,
+                found: self.type_name(&Type::Optional(inner.clone())),
+            });
+        }
+        (*inner, chained)
+    }
+
+    fn check_variant_access(&mut self, target: ReferenceType, rhs: &MemberExpressionRHS) -> Type {
+        let MemberExpressionRHS::Identifier(ident) = rhs else {
+            return Type::Unknown;
+        };
+        let Some(definition) = self.definitions.get(&target) else {
+            return Type::Unknown;
+        };
+        match definition.variant(&ident.name) {
+            Some(variant) => {
+                ident.reference.set(Some(variant.reference));
+                self.symbols.get(variant.reference).ty.clone()
+            }
+            None => {
+                self.errors.push(SemanticError::UnknownVariant {
+                    span: ident.span,
+                    target: self.symbol_name(target),
+                    variant: ident.name.as_str().to_string(),
+                });
+                Type::Unknown
+            }
+        }
+    }
+
+    fn check_field_access(&mut self, lhs: &Type, ident: &Identifier) -> Type {
+        let (Type::Struct(target, _) | Type::Enum(target) | Type::Union(target)) = lhs else {
+            return Type::Unknown;
+        };
+        let Some(definition) = self.definitions.get(target) else {
+            return Type::Unknown;
+        };
+        let substitution = match lhs {
+            Type::Struct(_, args) => definition.substitution(args),
+            _ => Substitution::new(),
+        };
+
+        let field = definition
+            .field(&ident.name)
+            .map(|field| field.reference)
+            .or_else(|| {
+                definition
+                    .methods
+                    .iter()
+                    .copied()
+                    .find(|method| self.symbols.get(*method).name == ident.name)
+            });
+        match field {
+            Some(reference) => {
+                ident.reference.set(Some(reference));
+                self.symbols.get(reference).ty.substitute(&substitution)
+            }
+            None => {
+                self.errors.push(SemanticError::UnknownField {
+                    span: ident.span,
+                    target: self.type_name(lhs),
+                    field: ident.name.as_str().to_string(),
+                });
+                Type::Unknown
+            }
+        }
+    }
+
+    fn check_tuple_access(&mut self, lhs: &Type, index: &NumberLiteral) -> Type {
+        let Type::Tuple(elements) = lhs else {
+            return Type::Unknown;
+        };
+        match elements.get(index.value as usize) {
+            Some(ty) if index.value.fract() == 0.0 => ty.clone(),
+            _ => {
+                self.errors.push(SemanticError::UnknownField {
+                    span: index.span,
+                    target: self.type_name(lhs),
+                    field: index.raw.as_str().to_string(),
+                });
+                Type::Unknown
+            }
+        }
+    }
+
+    fn check_call_expression(&mut self, call: &'ast CallExpression) -> Type {
+        let callee = self.check_expression(&call.callee);
+        let args: Vec<(Span, Type)> = call
+            .arguments
+            .iter()
+            .map(|arg| (arg.span(), self.check_expression(arg)))
+            .collect();
+
+        let chained = match &call.callee {
+            Expression::MemberExpression(member) => {
+                member.optional || is_optional_chain(&member.lhs)
+            }
+            _ => false,
+        };
+        let (callee, optional) = self.unwrap_optional(call.callee.span(), callee, chained);
+
+        match callee {
+            Type::Function(mut func) => {
+                if !func.type_params.is_empty() {
+                    let args: Vec<Type> = args.iter().map(|(_, arg)| arg.clone()).collect();
+                    func = self.instantiate_function(call.span, &func, &args);
+                }
+                // Trailing optional parameters can be omitted.
+                let required = func.params.len()
+                    - func
+                        .params
+                        .iter()
+                        .rev()
+                        .take_while(|param| matches!(param, Type::Nil | Type::Optional(_)))
+                        .count();
+                let too_many = func.rest.is_none() && args.len() > func.params.len();
+                if args.len() < required || too_many {
+                    self.errors.push(SemanticError::ArgumentCountMismatch {
+                        span: call.span,
+                        expected: if args.len() < required {
+                            required
+                        } else {
+                            func.params.len()
+                        },
+                        found: args.len(),
+                    });
+                }
+                for (param, (span, arg)) in func.params.iter().zip(&args) {
+                    self.expect_type(*span, param, arg);
+                }
+                if let Some(rest) = &func.rest {
+                    for (span, arg) in args.iter().skip(func.params.len()) {
+                        self.expect_type(*span, rest, arg);
+                    }
+                }
+                if optional {
+                    Type::optional(func.return_type.clone())
+                } else {
+                    func.return_type.clone()
+                }
+            }
+            Type::Unknown | Type::Table => Type::Unknown,
+            found => {
+                self.errors.push(SemanticError::NotCallable {
+                    span: call.callee.span(),
+                    found: self.type_name(&found),
+                });
+                Type::Unknown
+            }
+        }
+    }
+
+    fn check_table_construction(&mut self, construction: &'ast ConstructionExpression) {
+        for field in &construction.fields {
+            match field {
+                ConstructionField::Expression(expr) => {
+                    self.check_expression(expr);
+                }
+                ConstructionField::KeyValueArgument(kv) => {
+                    self.check_expression(&kv.value);
+                }
+                ConstructionField::Spread(spread) => {
+                    self.check_expression(&spread.element);
+                }
+            }
+        }
+    }
+
+    fn check_struct_construction_expression(
+        &mut self,
+        expr: &'ast StructConstructionExpression,
+    ) -> Type {
+        let target = match &expr.target {
+            Expression::Identifier(ident) => match self.symbols.of(ident) {
+                Some(symbol) if symbol.kind == SymbolKind::Struct => ident.reference.get(),
+                _ => None,
+            },
+            target => {
+                self.check_expression(target);
+                None
+            }
+        };
+        let Some(target) = target else {
+            self.check_table_construction(&expr.construction);
+            return Type::Unknown;
+        };
+
+        let mut missing: Vec<_> = match self.definitions.get(&target).map(|def| &def.kind) {
+            Some(TypeDefinitionKind::Struct(fields)) => {
+                fields.iter().map(|field| field.name.clone()).collect()
+            }
+            _ => Vec::new(),
+        };
+        let mut spread = false;
+        let mut values = Vec::new();
+        for field in &expr.construction.fields {
+            let (key, value) = match field {
+                ConstructionField::KeyValueArgument(kv) => (&kv.key, &kv.value),
+                // Identifiers are shorthands for fields with the same name.
+                ConstructionField::Expression(value) => match value {
+                    Expression::Identifier(ident) => (ident.as_ref(), value),
+                    _ => {
+                        self.check_expression(value);
+                        continue;
+                    }
+                },
+                ConstructionField::Spread(arg) => {
+                    self.check_expression(&arg.element);
+                    spread = true;
+                    continue;
+                }
+            };
+            let found = self.check_expression(value);
+            let Some(definition) = self.definitions[&target].field(&key.name) else {
+                self.errors.push(SemanticError::UnknownField {
+                    span: key.span,
+                    target: self.symbol_name(target),
+                    field: key.name.as_str().to_string(),
+                });
+                continue;
+            };
+            let expected = definition.ty.clone();
+            // Shorthand identifiers keep referring to the variable in scope.
+            if let ConstructionField::KeyValueArgument(_) = field {
+                key.reference.set(Some(definition.reference));
+            }
+            missing.retain(|name| name != &key.name);
+            values.push((value.span(), expected, found));
+        }
+
+        // Infer the type arguments of generic structs from their field values.
+        let mut inferred = Substitution::new();
+        for (_, expected, found) in &values {
+            expected.infer(found, &mut inferred);
+        }
+        let params = self.definitions[&target].params.clone();
+        let args: Vec<Type> = params
+            .iter()
+            .map(|param| inferred.get(param).cloned().unwrap_or(Type::Unknown))
+            .collect();
+        for (param, arg) in params.iter().zip(&args) {
+            self.check_bounds(expr.span(), *param, arg);
+        }
+        let substitution = self.definitions[&target].substitution(&args);
+        for (span, expected, found) in values {
+            self.expect_type(span, &expected.substitute(&substitution), &found);
+        }
+
+        if !spread && !missing.is_empty() {
+            self.errors.push(SemanticError::MissingFields {
+                span: expr.span(),
+                target: self.symbol_name(target),
+                fields: missing
+                    .iter()
+                    .map(|name| name.as_str().to_string())
+                    .collect(),
+            });
+        }
+        Type::Struct(target, args)
+    }
+}
+
+/// Returns true if the accessed value is a part of an optional chain,
+/// e.g. `b` in `a?.b.c`, Which evaluates to nil if any link is nil.
+fn is_optional_chain(lhs: &MemberExpressionLHS) -> bool {
+    let member = match lhs {
+        MemberExpressionLHS::Member(member) => member,
+        MemberExpressionLHS::Call(call) => match &call.callee {
+            Expression::MemberExpression(member) => member,
+            _ => return false,
+        },
+        _ => return false,
+    };
+    member.optional || is_optional_chain(&member.lhs)
+}
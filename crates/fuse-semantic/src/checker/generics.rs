@@ -0,0 +1,156 @@
+use std::rc::Rc;
+
+use fuse_ast::{
+    Atom, Identifier, ImplStatement, TypeAnnotation, TypeAnnotationKind, TypeParameter,
+};
+use fuse_common::{ReferenceType, Span};
+
+use super::{types::declared, TypeChecker};
+use crate::{FunctionType, SemanticError, Substitution, Type};
+
+impl<'a, 'ast> TypeChecker<'a, 'ast> {
+    /// Record the trait bounds of type parameters and return their references.
+    pub(super) fn define_type_parameters(
+        &mut self,
+        params: &[TypeParameter],
+    ) -> Vec<ReferenceType> {
+        params
+            .iter()
+            .map(|param| {
+                let reference = declared(&param.identifier);
+                let bounds = param.bounds.iter().filter_map(trait_name).collect();
+                self.bounds.insert(reference, bounds);
+                reference
+            })
+            .collect()
+    }
+
+    /// Record the trait implemented by an impl statement for its target type.
+    pub(super) fn define_implementation(&mut self, r#impl: &ImplStatement) {
+        let target = match &r#impl.target.kind {
+            TypeAnnotationKind::Identifier(ident) => ident,
+            TypeAnnotationKind::Generic(generic) => &generic.identifier,
+            _ => return,
+        };
+        if let (Some(reference), Some(name)) = (target.reference.get(), trait_name(&r#impl.r#trait))
+        {
+            self.implementations
+                .entry(reference)
+                .or_default()
+                .push(name);
+        }
+    }
+
+    /// Resolve a generic type annotation, e.g. `Stack<number>`.
+    pub(super) fn instantiate(&mut self, span: Span, ident: &Identifier, args: Vec<Type>) -> Type {
+        let ty = self.resolve_type_name(ident);
+        let params = match &ty {
+            Type::Struct(reference, _) => self.struct_type_parameters(*reference),
+            Type::Unknown => return ty,
+            _ => Vec::new(),
+        };
+        if params.len() != args.len() {
+            self.errors.push(SemanticError::TypeArgumentCountMismatch {
+                span,
+                expected: params.len(),
+                found: args.len(),
+            });
+            return ty;
+        }
+        for (param, arg) in params.iter().zip(&args) {
+            self.check_bounds(span, *param, arg);
+        }
+        match ty {
+            Type::Struct(reference, _) => Type::Struct(reference, args),
+            ty => ty,
+        }
+    }
+
+    /// Instantiate a generic function using the types of the arguments it is called with.
+    pub(super) fn instantiate_function(
+        &mut self,
+        span: Span,
+        func: &FunctionType,
+        args: &[Type],
+    ) -> Rc<FunctionType> {
+        let mut substitution = Substitution::new();
+        for (param, arg) in func.params.iter().zip(args) {
+            param.infer(arg, &mut substitution);
+        }
+        if let Some(rest) = &func.rest {
+            for arg in args.iter().skip(func.params.len()) {
+                rest.infer(arg, &mut substitution);
+            }
+        }
+        for param in &func.type_params {
+            let ty = substitution.entry(*param).or_insert(Type::Unknown).clone();
+            self.check_bounds(span, *param, &ty);
+        }
+        Rc::from(FunctionType {
+            type_params: Vec::new(),
+            params: func
+                .params
+                .iter()
+                .map(|param| param.substitute(&substitution))
+                .collect(),
+            rest: func
+                .rest
+                .as_ref()
+                .map(|rest| rest.substitute(&substitution)),
+            return_type: func.return_type.substitute(&substitution),
+        })
+    }
+
+    /// Check that the type used for a type parameter implements all of its bounds.
+    pub(super) fn check_bounds(&mut self, span: Span, param: ReferenceType, ty: &Type) {
+        let Some(bounds) = self.bounds.get(&param) else {
+            return;
+        };
+        let unsatisfied: Vec<Atom> = bounds
+            .iter()
+            .filter(|bound| !self.implements(ty, bound))
+            .cloned()
+            .collect();
+        for bound in unsatisfied {
+            self.errors.push(SemanticError::UnsatisfiedTraitBound {
+                span,
+                found: self.type_name(ty),
+                bound: bound.as_str().to_string(),
+            });
+        }
+    }
+
+    fn implements(&self, ty: &Type, r#trait: &Atom) -> bool {
+        let traits = match ty {
+            Type::Unknown | Type::Never => return true,
+            Type::Struct(reference, _) | Type::Enum(reference) | Type::Union(reference) => {
+                self.implementations.get(reference)
+            }
+            Type::Parameter(reference) => self.bounds.get(reference),
+            _ => None,
+        };
+        traits.is_some_and(|traits| traits.contains(r#trait))
+    }
+
+    fn struct_type_parameters(&self, reference: ReferenceType) -> Vec<ReferenceType> {
+        self.declarations
+            .structs
+            .iter()
+            .find(|decl| decl.identifier.reference.get() == Some(reference))
+            .map(|decl| {
+                decl.type_parameters
+                    .iter()
+                    .map(|param| declared(&param.identifier))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Get the name of the trait that a bound or impl statement refers to.
+fn trait_name(annotation: &TypeAnnotation) -> Option<Atom> {
+    match &annotation.kind {
+        TypeAnnotationKind::Identifier(ident) => Some(ident.name.clone()),
+        _ => None,
+    }
+}
@@ -0,0 +1,203 @@
+mod expressions;
+mod generics;
+mod narrowing;
+mod patterns;
+mod statements;
+mod types;
+
+use std::collections::HashMap;
+
+use fuse_ast::{
+    Atom, Chunk, EnumDeclaration, ExternDeclaration, ImplStatement, StructDeclaration,
+    TypeAliasDeclaration, UnionDeclaration,
+};
+use fuse_common::{ReferenceType, Span};
+use fuse_visitor::{
+    walk_enum_declaration, walk_extern_declaration, walk_impl_statement, walk_struct_declaration,
+    walk_type_alias_declaration, walk_union_declaration, NodeVisitor, ScopeVisitor, Visitor,
+};
+
+use crate::{SemanticError, SymbolTable, Type, TypeDefinition};
+
+/// Type checker of a resolved chunk, It expects every identifier of
+/// the chunk to be already resolved by the `Semantic`.
+pub(crate) struct TypeChecker<'a, 'ast> {
+    symbols: &'a mut SymbolTable,
+    declarations: Declarations<'ast>,
+    definitions: HashMap<ReferenceType, TypeDefinition>,
+    aliases: HashMap<ReferenceType, AliasState>,
+    /// Trait bounds of type parameters.
+    bounds: HashMap<ReferenceType, Vec<Atom>>,
+    /// Traits implemented by each declared type.
+    implementations: HashMap<ReferenceType, Vec<Atom>>,
+    /// Narrowed types of optional variables, One frame per checked branch.
+    narrowed: Vec<Narrowing>,
+    /// Types of the branches of if and match expressions that don't have a common type, By the
+    /// span of the expression. They are reported once the value is expected to have a type.
+    mismatched_branches: HashMap<Span, (Type, Type)>,
+    /// Offset of the identifier we want to know the member target of, See `Semantic::with_cursor`.
+    cursor: Option<u32>,
+    /// Type of the value whose member is at the cursor.
+    member: Option<Type>,
+    errors: Vec<SemanticError>,
+}
+
+/// Outcome of type checking a chunk.
+pub(crate) struct CheckResult {
+    pub(crate) definitions: HashMap<ReferenceType, TypeDefinition>,
+    pub(crate) errors: Vec<SemanticError>,
+    pub(crate) member: Option<Type>,
+}
+
+/// Types of variables known to be non-nil in a branch.
+type Narrowing = HashMap<ReferenceType, Type>;
+
+enum AliasState {
+    Resolving,
+    Resolved(Type),
+}
+
+impl<'a, 'ast> TypeChecker<'a, 'ast> {
+    pub(crate) fn new(symbols: &'a mut SymbolTable) -> Self {
+        Self {
+            symbols,
+            declarations: Declarations::default(),
+            definitions: HashMap::new(),
+            aliases: HashMap::new(),
+            bounds: HashMap::new(),
+            implementations: HashMap::new(),
+            narrowed: Vec::new(),
+            mismatched_branches: HashMap::new(),
+            cursor: None,
+            member: None,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Use the definitions of types declared outside of the chunk, e.g. in declaration files.
+    pub(crate) fn with_definitions(
+        mut self,
+        definitions: HashMap<ReferenceType, TypeDefinition>,
+    ) -> Self {
+        self.definitions = definitions;
+        self
+    }
+
+    /// Record the type of the value accessed by the member at `cursor`.
+    pub(crate) fn with_cursor(mut self, cursor: Option<u32>) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    pub(crate) fn check(mut self, chunk: &'ast Chunk) -> CheckResult {
+        // Types can be used before their declaration so we resolve all of them beforehand.
+        self.declarations.visit_chunk(chunk);
+        self.define_types();
+        self.check_block(&chunk.body);
+        CheckResult {
+            definitions: self.definitions,
+            errors: self.errors,
+            member: self.member,
+        }
+    }
+
+    fn at_cursor(&self, span: Span) -> bool {
+        self.cursor
+            .is_some_and(|cursor| span.start <= cursor && cursor <= span.end)
+    }
+
+    fn expect_type(&mut self, span: Span, expected: &Type, found: &Type) -> bool {
+        if let Some((first, second)) = self.mismatched_branches.remove(&span) {
+            self.errors.push(SemanticError::MismatchedBranches {
+                span,
+                first: self.type_name(&first),
+                second: self.type_name(&second),
+            });
+            return false;
+        }
+        if expected.is_assignable_from(found) {
+            return true;
+        }
+        if found.is_optional() && expected.is_assignable_from(found.non_optional()) {
+            self.errors.push(SemanticError::OptionalValue {
+                span,
+                found: self.type_name(found),
+            });
+            return false;
+        }
+        self.errors.push(SemanticError::MismatchedTypes {
+            span,
+            expected: self.type_name(expected),
+            found: self.type_name(found),
+        });
+        false
+    }
+
+    /// Join the types of two branches of the expression at `span`, Types without a common type
+    /// are recorded so using the value of the expression reports them.
+    fn join_branches(&mut self, span: Span, ty: Type, other: Type) -> Type {
+        if ty.is_unknown() || other.is_unknown() {
+            return Type::Unknown;
+        }
+        let joined = ty.clone().join(other.clone());
+        if joined.is_unknown() {
+            self.mismatched_branches.insert(span, (ty, other));
+        }
+        joined
+    }
+
+    fn type_name(&self, ty: &Type) -> String {
+        ty.display(self.symbols).to_string()
+    }
+
+    fn symbol_name(&self, reference: ReferenceType) -> String {
+        self.symbols.get(reference).name.as_str().to_string()
+    }
+}
+
+/// Type declarations of a chunk, collected before checking it.
+#[derive(Default)]
+struct Declarations<'ast> {
+    structs: Vec<&'ast StructDeclaration>,
+    enums: Vec<&'ast EnumDeclaration>,
+    unions: Vec<&'ast UnionDeclaration>,
+    aliases: Vec<&'ast TypeAliasDeclaration>,
+    impls: Vec<&'ast ImplStatement>,
+    externs: Vec<&'ast ExternDeclaration>,
+}
+
+impl<'ast> Visitor<'ast> for Declarations<'ast> {
+    fn visit_struct_declaration(&mut self, decl: &'ast StructDeclaration) {
+        self.structs.push(decl);
+        walk_struct_declaration(self, decl)
+    }
+
+    fn visit_enum_declaration(&mut self, decl: &'ast EnumDeclaration) {
+        self.enums.push(decl);
+        walk_enum_declaration(self, decl)
+    }
+
+    fn visit_union_declaration(&mut self, decl: &'ast UnionDeclaration) {
+        self.unions.push(decl);
+        walk_union_declaration(self, decl)
+    }
+
+    fn visit_type_alias_declaration(&mut self, decl: &'ast TypeAliasDeclaration) {
+        self.aliases.push(decl);
+        walk_type_alias_declaration(self, decl)
+    }
+
+    fn visit_impl_statement(&mut self, r#impl: &'ast ImplStatement) {
+        self.impls.push(r#impl);
+        walk_impl_statement(self, r#impl)
+    }
+
+    fn visit_extern_declaration(&mut self, decl: &'ast ExternDeclaration) {
+        self.externs.push(decl);
+        walk_extern_declaration(self, decl)
+    }
+}
+
+impl<'ast> ScopeVisitor for Declarations<'ast> {}
+
+impl<'ast> NodeVisitor for Declarations<'ast> {}
@@ -0,0 +1,80 @@
+use fuse_ast::{BinaryOperatorKind, Expression, Identifier, UnaryOperatorKind};
+
+use super::{Narrowing, TypeChecker};
+use crate::Type;
+
+impl<'a, 'ast> TypeChecker<'a, 'ast> {
+    /// Get the narrowings implied by a condition when it is true and when it is false.
+    pub(super) fn narrow_condition(&self, cond: &Expression) -> (Narrowing, Narrowing) {
+        match cond {
+            Expression::Identifier(ident) => (self.narrow_identifier(ident), Narrowing::new()),
+            Expression::ParenthesizedExpression(expr) => self.narrow_condition(&expr.expression),
+            Expression::UnaryOperator(op) if matches!(op.kind, UnaryOperatorKind::Not(_)) => {
+                let (truthy, falsy) = self.narrow_condition(&op.expression);
+                (falsy, truthy)
+            }
+            Expression::BinaryOperator(op) => match op.kind {
+                BinaryOperatorKind::Equality(_) | BinaryOperatorKind::NonEquality(_) => {
+                    let narrowing = match (&op.lhs, &op.rhs) {
+                        (Expression::Identifier(ident), Expression::NilLiteral(_))
+                        | (Expression::NilLiteral(_), Expression::Identifier(ident)) => {
+                            self.narrow_identifier(ident)
+                        }
+                        _ => Narrowing::new(),
+                    };
+                    match op.kind {
+                        BinaryOperatorKind::Equality(_) => (Narrowing::new(), narrowing),
+                        _ => (narrowing, Narrowing::new()),
+                    }
+                }
+                // Both operands of `and` are true when it is true, and both operands
+                // of `or` are false when it is false.
+                BinaryOperatorKind::LogicalAnd(_) => {
+                    let (mut truthy, _) = self.narrow_condition(&op.lhs);
+                    truthy.extend(self.narrow_condition(&op.rhs).0);
+                    (truthy, Narrowing::new())
+                }
+                BinaryOperatorKind::LogicalOr(_) => {
+                    let (_, mut falsy) = self.narrow_condition(&op.lhs);
+                    falsy.extend(self.narrow_condition(&op.rhs).1);
+                    (Narrowing::new(), falsy)
+                }
+                _ => (Narrowing::new(), Narrowing::new()),
+            },
+            _ => (Narrowing::new(), Narrowing::new()),
+        }
+    }
+
+    /// Narrowing of an identifier known to be non-nil.
+    fn narrow_identifier(&self, ident: &Identifier) -> Narrowing {
+        let mut narrowing = Narrowing::new();
+        if let (Some(reference), Type::Optional(inner)) =
+            (ident.reference.get(), self.identifier_type(ident))
+        {
+            narrowing.insert(reference, *inner);
+        }
+        narrowing
+    }
+
+    /// Check a branch with the given narrowing in effect.
+    pub(super) fn with_narrowing<T>(
+        &mut self,
+        narrowing: Narrowing,
+        check: impl FnOnce(&mut Self) -> T,
+    ) -> T {
+        self.narrowed.push(narrowing);
+        let result = check(self);
+        self.narrowed.pop();
+        result
+    }
+
+    /// Drop the narrowed type of a variable, e.g. after assigning nil to it.
+    pub(super) fn widen(&mut self, ident: &Identifier) {
+        let Some(reference) = ident.reference.get() else {
+            return;
+        };
+        for narrowing in &mut self.narrowed {
+            narrowing.remove(&reference);
+        }
+    }
+}
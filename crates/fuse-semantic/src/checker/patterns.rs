@@ -0,0 +1,211 @@
+use fuse_ast::{Expression, GetSpan, Match, Pattern, VariantPattern};
+
+use super::{types::declared, TypeChecker};
+use crate::{SemanticError, SymbolKind, Type};
+
+/// What a single pattern covers of the matched value.
+enum Coverage {
+    /// Matches every value.
+    All,
+    /// Matches every value of a single case, e.g. a variant or `true`.
+    Case(String),
+    /// Matches some values without covering any case completely.
+    Partial,
+}
+
+/// Tracks which cases of the matched type are yet to be covered.
+struct Exhaustiveness {
+    /// Cases of the matched type, `None` if its values can't be enumerated.
+    remaining: Option<Vec<String>>,
+    exhausted: bool,
+}
+
+impl Exhaustiveness {
+    fn new(cases: Option<Vec<String>>) -> Self {
+        Self {
+            remaining: cases,
+            exhausted: false,
+        }
+    }
+
+    /// Cover the given pattern, Returns false if it is unreachable.
+    fn cover(&mut self, coverage: Coverage) -> bool {
+        if self.exhausted {
+            return false;
+        }
+        match coverage {
+            Coverage::All => self.exhausted = true,
+            Coverage::Case(case) => {
+                let Some(remaining) = &mut self.remaining else {
+                    return true;
+                };
+                let Some(index) = remaining.iter().position(|it| it == &case) else {
+                    return false;
+                };
+                remaining.remove(index);
+                self.exhausted = remaining.is_empty();
+            }
+            Coverage::Partial => {}
+        }
+        true
+    }
+
+    fn missing(self) -> Vec<String> {
+        self.remaining.unwrap_or_else(|| vec!["_".to_string()])
+    }
+}
+
+impl<'a, 'ast> TypeChecker<'a, 'ast> {
+    pub(super) fn check_match(&mut self, r#match: &'ast Match) -> Type {
+        let mut scrutinee = self.check_expression(&r#match.expression);
+        // Infer the type of unknown values from the variant patterns.
+        if scrutinee.is_unknown() {
+            scrutinee = r#match
+                .arms
+                .iter()
+                .find_map(|arm| match &arm.pattern {
+                    Pattern::Variant(variant) => self.variant_target(variant),
+                    _ => None,
+                })
+                .unwrap_or(Type::Unknown);
+        }
+
+        let mut exhaustiveness = Exhaustiveness::new(self.match_cases(&scrutinee));
+        let mut ty = Type::Never;
+        for arm in &r#match.arms {
+            let coverage = self.check_pattern(&arm.pattern, &scrutinee);
+            if !exhaustiveness.cover(coverage) {
+                self.errors
+                    .push(SemanticError::UnreachableMatchArm { span: arm.span });
+            }
+            let arm_type = self.check_block(&arm.body);
+            ty = self.join_branches(r#match.span, ty, arm_type);
+        }
+
+        match &r#match.r#else {
+            Some(block) => {
+                let else_type = self.check_block(block);
+                ty = self.join_branches(r#match.span, ty, else_type);
+            }
+            // Matching on unknown values can't be checked for exhaustiveness.
+            None if !exhaustiveness.exhausted && !scrutinee.is_unknown() => {
+                self.errors.push(SemanticError::NonExhaustiveMatch {
+                    span: r#match.span,
+                    missing: exhaustiveness.missing(),
+                });
+            }
+            None => {}
+        }
+        ty
+    }
+
+    /// Get the name of every possible case of a type, if it is enumerable.
+    fn match_cases(&self, ty: &Type) -> Option<Vec<String>> {
+        match ty {
+            Type::Boolean => Some(vec!["true".to_string(), "false".to_string()]),
+            Type::Enum(target) | Type::Union(target) => {
+                let definition = self.definitions.get(target)?;
+                let name = self.symbol_name(*target);
+                Some(
+                    definition
+                        .variants()
+                        .iter()
+                        .map(|variant| format!("{name}.{}", variant.name.as_str()))
+                        .collect(),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    fn check_pattern(&mut self, pattern: &'ast Pattern, expected: &Type) -> Coverage {
+        match pattern {
+            Pattern::Wildcard(_) => Coverage::All,
+            Pattern::Binding(binding) => {
+                self.symbols.get_mut(declared(&binding.identifier)).ty = expected.clone();
+                Coverage::All
+            }
+            Pattern::Literal(expr) => {
+                let found = self.check_expression(expr);
+                self.expect_type(pattern.span(), expected, &found);
+                match expr {
+                    Expression::BooleanLiteral(lit) => Coverage::Case(lit.value.to_string()),
+                    _ => Coverage::Partial,
+                }
+            }
+            Pattern::Variant(variant) => self.check_variant_pattern(variant, expected),
+        }
+    }
+
+    fn check_variant_pattern(
+        &mut self,
+        pattern: &'ast VariantPattern,
+        expected: &Type,
+    ) -> Coverage {
+        let Some(target) = self.variant_target(pattern) else {
+            self.errors.push(SemanticError::UnknownType {
+                span: pattern.target.span,
+                name: pattern.target.name.as_str().to_string(),
+            });
+            self.check_subpatterns(pattern, &[]);
+            return Coverage::Partial;
+        };
+        self.expect_type(pattern.span, expected, &target);
+
+        let (Type::Enum(reference) | Type::Union(reference)) = target else {
+            unreachable!()
+        };
+        let name = self.symbol_name(reference);
+        let Some(variant) = self
+            .definitions
+            .get(&reference)
+            .and_then(|definition| definition.variant(&pattern.variant.name))
+        else {
+            self.errors.push(SemanticError::UnknownVariant {
+                span: pattern.variant.span,
+                target: name,
+                variant: pattern.variant.name.as_str().to_string(),
+            });
+            self.check_subpatterns(pattern, &[]);
+            return Coverage::Partial;
+        };
+        pattern.variant.reference.set(Some(variant.reference));
+
+        let fields = variant.fields.clone();
+        if fields.len() != pattern.fields.len() {
+            self.errors.push(SemanticError::ArgumentCountMismatch {
+                span: pattern.span,
+                expected: fields.len(),
+                found: pattern.fields.len(),
+            });
+        }
+        let case = format!("{name}.{}", pattern.variant.name.as_str());
+        if self.check_subpatterns(pattern, &fields) {
+            Coverage::Case(case)
+        } else {
+            Coverage::Partial
+        }
+    }
+
+    /// Check the payload patterns of a variant pattern against its fields,
+    /// Returns true if all of them match every possible value.
+    fn check_subpatterns(&mut self, pattern: &'ast VariantPattern, fields: &[Type]) -> bool {
+        let mut irrefutable = true;
+        for (index, subpattern) in pattern.fields.iter().enumerate() {
+            let expected = fields.get(index).cloned().unwrap_or(Type::Unknown);
+            if !matches!(self.check_pattern(subpattern, &expected), Coverage::All) {
+                irrefutable = false;
+            }
+        }
+        irrefutable
+    }
+
+    fn variant_target(&self, pattern: &VariantPattern) -> Option<Type> {
+        let reference = pattern.target.reference.get()?;
+        match self.symbols.get(reference).kind {
+            SymbolKind::Enum => Some(Type::Enum(reference)),
+            SymbolKind::Union => Some(Type::Union(reference)),
+            _ => None,
+        }
+    }
+}
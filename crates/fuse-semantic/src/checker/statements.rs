@@ -0,0 +1,159 @@
+use fuse_ast::{
+    BindingPatternKind, Block, EnumDeclaration, Function, FunctionBody, ImplStatement, Statement,
+    VariableDeclaration,
+};
+
+use super::{types::declared, TypeChecker};
+use crate::Type;
+
+impl<'a, 'ast> TypeChecker<'a, 'ast> {
+    /// Check a block and return the type of its value, which is the
+    /// value of its last statement if it is an expression.
+    pub(super) fn check_block(&mut self, block: &'ast Block) -> Type {
+        let mut ty = Type::Nil;
+        for statement in &block.statements {
+            ty = self.check_statement(statement);
+        }
+        ty
+    }
+
+    fn check_statement(&mut self, statement: &'ast Statement) -> Type {
+        match statement {
+            Statement::Expression(expr) => return self.check_expression(expr),
+            Statement::VariableDeclaration(decl) => self.check_variable_declaration(decl),
+            Statement::FunctionDeclaration(func) => self.check_function_declaration(func),
+            Statement::EnumDeclaration(decl) => self.check_enum_declaration(decl),
+            Statement::ImplStatement(r#impl) => self.check_impl_statement(r#impl),
+            // These declarations are already checked while defining types.
+            Statement::StructDeclaration(_)
+            | Statement::TypeAliasDeclaration(_)
+            | Statement::UnionDeclaration(_)
+            | Statement::ExternDeclaration(_)
+            | Statement::Empty(_) => {}
+        }
+        Type::Nil
+    }
+
+    fn check_variable_declaration(&mut self, decl: &'ast VariableDeclaration) {
+        let found = decl
+            .expression
+            .as_ref()
+            .map(|expr| (expr.span(), self.check_expression(expr)));
+        let annotation = decl
+            .binding
+            .type_annotation
+            .is_some()
+            .then(|| self.binding_type(&decl.binding));
+
+        let ty = match (annotation, found) {
+            (Some(expected), Some((span, found))) => {
+                self.expect_type(span, &expected, &found);
+                expected
+            }
+            (Some(expected), None) => expected,
+            (None, Some((_, found))) => found,
+            (None, None) => Type::Unknown,
+        };
+
+        if let BindingPatternKind::Identifier(binding) = &decl.binding.kind {
+            self.symbols.get_mut(declared(&binding.identifier)).ty = ty;
+        }
+    }
+
+    fn check_function_declaration(&mut self, func: &'ast Function) {
+        let identifier = func
+            .signature
+            .identifier
+            .as_ref()
+            .expect("All function declarations need an identifier.");
+        let reference = declared(identifier);
+        let type_params = self.define_type_parameters(&func.signature.type_parameters);
+        let params = self.parameter_types(&func.signature);
+        let rest = self.rest_type(&func.signature);
+        let return_type = func
+            .signature
+            .return_type
+            .as_ref()
+            .map(|annotation| self.resolve_annotation(annotation));
+        // Set the signature before checking the body to allow recursive calls.
+        self.symbols.get_mut(reference).ty = Type::variadic_function(
+            type_params,
+            params.clone(),
+            rest.clone(),
+            return_type.clone().unwrap_or(Type::Unknown),
+        );
+        let ty = self.check_function_body(func, params, rest, return_type);
+        self.symbols.get_mut(reference).ty = ty;
+    }
+
+    fn check_enum_declaration(&mut self, decl: &'ast EnumDeclaration) {
+        for variant in &decl.variants {
+            if let Some(value) = &variant.value {
+                self.check_expression(value);
+            }
+        }
+    }
+
+    fn check_impl_statement(&mut self, r#impl: &'ast ImplStatement) {
+        for method in &r#impl.methods {
+            let func = &method.function;
+            let Some(identifier) = &func.signature.identifier else {
+                continue;
+            };
+            // Method signatures are resolved while defining types.
+            let Type::Function(signature) = self.symbols.get(declared(identifier)).ty.clone()
+            else {
+                continue;
+            };
+            let return_type = func
+                .signature
+                .return_type
+                .as_ref()
+                .map(|_| signature.return_type.clone());
+            self.check_function_body(
+                func,
+                signature.params.clone(),
+                signature.rest.clone(),
+                return_type,
+            );
+        }
+    }
+
+    /// Check the body of a function with the given signature and return the
+    /// function type, Return type of the function is inferred if it is `None`.
+    pub(super) fn check_function_body(
+        &mut self,
+        func: &'ast Function,
+        params: Vec<Type>,
+        rest: Option<Type>,
+        return_type: Option<Type>,
+    ) -> Type {
+        self.set_parameter_types(&func.signature, &params, rest.as_ref());
+
+        let (span, found) = match &func.body {
+            FunctionBody::Block(block) => {
+                let span = match block.statements.last() {
+                    Some(Statement::Expression(expr)) => expr.span(),
+                    _ => func.span,
+                };
+                (span, self.check_block(block))
+            }
+            FunctionBody::Expression(expr) => (expr.span(), self.check_expression(expr)),
+        };
+
+        let return_type = match return_type {
+            Some(expected) => {
+                self.expect_type(span, &expected, &found);
+                expected
+            }
+            None => found,
+        };
+        let type_params = func
+            .signature
+            .type_parameters
+            .iter()
+            .map(|param| declared(&param.identifier))
+            .collect();
+        Type::variadic_function(type_params, params, rest, return_type)
+    }
+}
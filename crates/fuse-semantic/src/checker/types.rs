@@ -0,0 +1,357 @@
+use fuse_ast::{
+    BindingPattern, BindingPatternKind, ExternItem, FunctionSignature, Identifier, TypeAnnotation,
+    TypeAnnotationKind,
+};
+use fuse_common::ReferenceType;
+
+use super::{AliasState, TypeChecker};
+use crate::{
+    FieldDefinition, SemanticError, SymbolKind, Type, TypeDefinition, TypeDefinitionKind,
+    VariantDefinition,
+};
+
+/// Get the symbol of a declaring identifier.
+pub(super) fn declared(ident: &Identifier) -> ReferenceType {
+    ident
+        .reference
+        .get()
+        .expect("All declarations should be resolved by the semantic.")
+}
+
+impl<'a, 'ast> TypeChecker<'a, 'ast> {
+    /// Resolve all type declarations and impl statements of the chunk.
+    pub(super) fn define_types(&mut self) {
+        // Bounds and implementations are needed to check the type arguments of any annotation.
+        for decl in self.declarations.structs.clone() {
+            self.define_type_parameters(&decl.type_parameters);
+        }
+        for r#impl in self.declarations.impls.clone() {
+            self.define_implementation(r#impl);
+        }
+
+        for decl in self.declarations.structs.clone() {
+            let reference = declared(&decl.identifier);
+            let params = decl
+                .type_parameters
+                .iter()
+                .map(|param| declared(&param.identifier))
+                .collect();
+            let fields = decl
+                .fields
+                .iter()
+                .map(|field| {
+                    let ty = self.resolve_annotation(&field.type_annotation);
+                    let field_reference = declared(&field.identifier);
+                    self.symbols.get_mut(field_reference).ty = ty.clone();
+                    FieldDefinition {
+                        name: field.identifier.name.clone(),
+                        reference: field_reference,
+                        ty,
+                    }
+                })
+                .collect();
+            self.define(reference, TypeDefinitionKind::Struct(fields), params);
+        }
+
+        for decl in self.declarations.enums.clone() {
+            let reference = declared(&decl.identifier);
+            let variants = decl
+                .variants
+                .iter()
+                .map(|variant| {
+                    let variant_reference = declared(&variant.identifier);
+                    self.symbols.get_mut(variant_reference).ty = Type::Enum(reference);
+                    VariantDefinition {
+                        name: variant.identifier.name.clone(),
+                        reference: variant_reference,
+                        fields: Vec::new(),
+                    }
+                })
+                .collect();
+            self.define(reference, TypeDefinitionKind::Enum(variants), Vec::new());
+        }
+
+        for decl in self.declarations.unions.clone() {
+            let reference = declared(&decl.identifier);
+            let variants = decl
+                .variants
+                .iter()
+                .map(|variant| {
+                    let fields: Vec<Type> = variant
+                        .fields
+                        .iter()
+                        .map(|field| self.resolve_annotation(field))
+                        .collect();
+                    let variant_reference = declared(&variant.identifier);
+                    self.symbols.get_mut(variant_reference).ty =
+                        variant_type(reference, fields.clone());
+                    VariantDefinition {
+                        name: variant.identifier.name.clone(),
+                        reference: variant_reference,
+                        fields,
+                    }
+                })
+                .collect();
+            self.define(reference, TypeDefinitionKind::Union(variants), Vec::new());
+        }
+
+        for decl in self.declarations.aliases.clone() {
+            self.resolve_alias(declared(&decl.identifier));
+        }
+
+        // Extern declarations are hoisted so their types are resolved ahead of the statements.
+        for decl in self.declarations.externs.clone() {
+            self.define_extern_item(&decl.item);
+        }
+
+        for r#impl in self.declarations.impls.clone() {
+            let target = self.resolve_annotation(&r#impl.target);
+            for method in &r#impl.methods {
+                let Some(identifier) = &method.function.signature.identifier else {
+                    continue;
+                };
+                let reference = declared(identifier);
+                self.symbols.get_mut(reference).ty =
+                    self.signature_type(&method.function.signature, Type::Unknown);
+                if let Type::Struct(target, _) | Type::Enum(target) | Type::Union(target) = target {
+                    if let Some(definition) = self.definitions.get_mut(&target) {
+                        definition.methods.push(reference);
+                    }
+                }
+            }
+        }
+    }
+
+    fn define(
+        &mut self,
+        reference: ReferenceType,
+        kind: TypeDefinitionKind,
+        params: Vec<ReferenceType>,
+    ) {
+        self.definitions.insert(
+            reference,
+            TypeDefinition {
+                kind,
+                params,
+                methods: Vec::new(),
+            },
+        );
+    }
+
+    /// Resolve the type of an extern item and set it on its symbol, Extern tables
+    /// are defined as structs with their members as fields.
+    fn define_extern_item(&mut self, item: &ExternItem) -> Type {
+        let reference = declared(item.identifier());
+        let ty = match item {
+            // Extern functions without a return type don't return anything.
+            ExternItem::Function(signature) => {
+                let ty = self.signature_type(signature, Type::Nil);
+                if let Type::Function(func) = &ty {
+                    self.set_parameter_types(signature, &func.params, func.rest.as_ref());
+                }
+                ty
+            }
+            ExternItem::Global(global) => self.resolve_annotation(&global.type_annotation),
+            ExternItem::Table(table) => {
+                let fields = table
+                    .items
+                    .iter()
+                    .map(|item| FieldDefinition {
+                        name: item.identifier().name.clone(),
+                        reference: declared(item.identifier()),
+                        ty: self.define_extern_item(item),
+                    })
+                    .collect();
+                self.define(reference, TypeDefinitionKind::Struct(fields), Vec::new());
+                Type::Struct(reference, Vec::new())
+            }
+        };
+        self.symbols.get_mut(reference).ty = ty.clone();
+        ty
+    }
+
+    pub(super) fn resolve_annotation(&mut self, annotation: &TypeAnnotation) -> Type {
+        match &annotation.kind {
+            TypeAnnotationKind::Identifier(ident) => self.resolve_type_name(ident),
+            TypeAnnotationKind::Tuple(elements) => Type::Tuple(
+                elements
+                    .iter()
+                    .map(|element| self.resolve_annotation(element))
+                    .collect(),
+            ),
+            TypeAnnotationKind::Array(element) => {
+                Type::Array(Box::new(self.resolve_annotation(element)))
+            }
+            TypeAnnotationKind::Optional(inner) => Type::optional(self.resolve_annotation(inner)),
+            TypeAnnotationKind::Generic(generic) => {
+                let args = generic
+                    .arguments
+                    .iter()
+                    .map(|arg| self.resolve_annotation(arg))
+                    .collect();
+                self.instantiate(annotation.span, &generic.identifier, args)
+            }
+            TypeAnnotationKind::Function(func) => {
+                let params = func
+                    .params
+                    .iter()
+                    .map(|param| self.resolve_annotation(param))
+                    .collect();
+                let return_type = match &func.return_type {
+                    Some(return_type) => self.resolve_annotation(return_type),
+                    None => Type::Nil,
+                };
+                Type::function(params, return_type)
+            }
+        }
+    }
+
+    pub(super) fn resolve_type_name(&mut self, ident: &Identifier) -> Type {
+        if let Some(symbol) = self.symbols.of(ident) {
+            if symbol.kind.is_type() {
+                let reference = ident.reference.get().unwrap();
+                return match symbol.kind {
+                    SymbolKind::Struct => Type::Struct(reference, Vec::new()),
+                    SymbolKind::TypeParameter => Type::Parameter(reference),
+                    SymbolKind::Enum => Type::Enum(reference),
+                    SymbolKind::Union => Type::Union(reference),
+                    _ => self.resolve_alias(reference),
+                };
+            }
+        }
+
+        match ident.name.as_str() {
+            "unknown" => Type::Unknown,
+            "never" => Type::Never,
+            "nil" => Type::Nil,
+            "number" => Type::Number,
+            "string" => Type::String,
+            "boolean" => Type::Boolean,
+            "table" => Type::Table,
+            name => {
+                self.errors.push(SemanticError::UnknownType {
+                    span: ident.span,
+                    name: name.to_string(),
+                });
+                Type::Unknown
+            }
+        }
+    }
+
+    /// Resolve a type alias to its underlying type, Aliases are resolved
+    /// lazily so they can refer to each other regardless of their order.
+    fn resolve_alias(&mut self, reference: ReferenceType) -> Type {
+        match self.aliases.get(&reference) {
+            Some(AliasState::Resolved(ty)) => return ty.clone(),
+            Some(AliasState::Resolving) => {
+                let symbol = self.symbols.get(reference);
+                self.errors.push(SemanticError::RecursiveTypeAlias {
+                    span: symbol.span,
+                    name: symbol.name.as_str().to_string(),
+                });
+                self.aliases
+                    .insert(reference, AliasState::Resolved(Type::Unknown));
+                return Type::Unknown;
+            }
+            None => {}
+        }
+
+        let Some(decl) = self
+            .declarations
+            .aliases
+            .iter()
+            .find(|decl| decl.identifier.reference.get() == Some(reference))
+            .copied()
+        else {
+            // Aliases declared outside of the chunk are already resolved.
+            return self.symbols.get(reference).ty.clone();
+        };
+
+        self.aliases.insert(reference, AliasState::Resolving);
+        let ty = self.resolve_annotation(&decl.annotation);
+        // A recursive alias is already resolved to unknown, keep it that way.
+        if let Some(AliasState::Resolved(ty)) = self.aliases.get(&reference) {
+            return ty.clone();
+        }
+        self.aliases
+            .insert(reference, AliasState::Resolved(ty.clone()));
+        self.symbols.get_mut(reference).ty = ty.clone();
+        ty
+    }
+
+    /// Get the type of a function from its signature, Parameters without annotation
+    /// are `unknown` and functions without a return type return `default_return`.
+    pub(super) fn signature_type(
+        &mut self,
+        signature: &FunctionSignature,
+        default_return: Type,
+    ) -> Type {
+        let type_params = self.define_type_parameters(&signature.type_parameters);
+        let params = self.parameter_types(signature);
+        let rest = self.rest_type(signature);
+        let return_type = match &signature.return_type {
+            Some(annotation) => self.resolve_annotation(annotation),
+            None => default_return,
+        };
+        Type::variadic_function(type_params, params, rest, return_type)
+    }
+
+    /// Set the types of parameter symbols, Extra arguments are collected in an array.
+    pub(super) fn set_parameter_types(
+        &mut self,
+        signature: &FunctionSignature,
+        params: &[Type],
+        rest: Option<&Type>,
+    ) {
+        for (param, ty) in signature.params.items.iter().zip(params) {
+            if let BindingPatternKind::Identifier(binding) = &param.pattern.kind {
+                self.symbols.get_mut(declared(&binding.identifier)).ty = ty.clone();
+            }
+        }
+        if let (Some(param), Some(ty)) = (&signature.params.rest, rest) {
+            self.symbols.get_mut(declared(&param.binding.identifier)).ty =
+                Type::Array(Box::new(ty.clone()));
+        }
+    }
+
+    pub(super) fn parameter_types(&mut self, signature: &FunctionSignature) -> Vec<Type> {
+        signature
+            .params
+            .items
+            .iter()
+            .map(|param| self.binding_type(&param.pattern))
+            .collect()
+    }
+
+    /// Get the type of the extra arguments of a variadic function, It is `unknown` if
+    /// the rest parameter has no annotation and `None` if the function isn't variadic.
+    pub(super) fn rest_type(&mut self, signature: &FunctionSignature) -> Option<Type> {
+        let rest = signature.params.rest.as_ref()?;
+        Some(match &rest.type_annotation {
+            Some(annotation) => self.resolve_annotation(annotation),
+            None => Type::Unknown,
+        })
+    }
+
+    /// Get the annotated type of a binding, Optional bindings such as `a?: T` are `T?`.
+    pub(super) fn binding_type(&mut self, binding: &BindingPattern) -> Type {
+        let ty = match &binding.type_annotation {
+            Some(annotation) => self.resolve_annotation(annotation),
+            None => Type::Unknown,
+        };
+        if binding.optional {
+            Type::optional(ty)
+        } else {
+            ty
+        }
+    }
+}
+
+/// Type of a union variant's value, Variants with payload are constructor functions.
+fn variant_type(union: ReferenceType, fields: Vec<Type>) -> Type {
+    if fields.is_empty() {
+        Type::Union(union)
+    } else {
+        Type::function(fields, Type::Union(union))
+    }
+}
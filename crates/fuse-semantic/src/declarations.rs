@@ -0,0 +1,4 @@
+/// Declaration file of the Lua 5.4 standard library, It should be loaded using
+/// `Semantic::with_declarations` before building chunks that target Lua 5.4.
+/// `type` and `string.match` are missing since they collide with Fuse keywords.
+pub const LUA_DECLARATIONS: &str = include_str!("../declarations/lua54.d.fuse");
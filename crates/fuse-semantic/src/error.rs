@@ -0,0 +1,119 @@
+use fuse_common::Span;
+use fuse_common_proc::serializable;
+use thiserror::Error as ThisError;
+
+#[serializable]
+#[derive(ThisError, Debug, PartialEq)]
+pub enum SemanticError {
+    #[error("Mismatched types, expected \"{expected}\" found \"{found}\"")]
+    MismatchedTypes {
+        span: Span,
+        expected: String,
+        found: String,
+    },
+    #[error("Branches have mismatched types \"{first}\" and \"{second}\"")]
+    MismatchedBranches {
+        span: Span,
+        first: String,
+        second: String,
+    },
+    #[error("Cannot find type \"{name}\"")]
+    UnknownType { span: Span, name: String },
+    #[error("Type alias \"{name}\" is recursive")]
+    RecursiveTypeAlias { span: Span, name: String },
+    #[error("No variant named \"{variant}\" found for \"{target}\"")]
+    UnknownVariant {
+        span: Span,
+        target: String,
+        variant: String,
+    },
+    #[error("No field named \"{field}\" found for \"{target}\"")]
+    UnknownField {
+        span: Span,
+        target: String,
+        field: String,
+    },
+    #[error("Missing fields {fields:?} in the construction of \"{target}\"")]
+    MissingFields {
+        span: Span,
+        target: String,
+        fields: Vec<String>,
+    },
+    #[error("Expected {expected} arguments but found {found}")]
+    ArgumentCountMismatch {
+        span: Span,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Expected {expected} type arguments but found {found}")]
+    TypeArgumentCountMismatch {
+        span: Span,
+        expected: usize,
+        found: usize,
+    },
+    #[error("Type \"{found}\" doesn't implement the trait \"{bound}\"")]
+    UnsatisfiedTraitBound {
+        span: Span,
+        found: String,
+        bound: String,
+    },
+    #[error("Use of undeclared global \"{name}\"")]
+    UndeclaredGlobal { span: Span, name: String },
+    #[error("Use of moved value \"{name}\"")]
+    UseAfterMove {
+        span: Span,
+        name: String,
+        moved_at: Span,
+    },
+    #[error("Cannot mutate \"{name}\", Its value is shared with another binding")]
+    SharedMutation {
+        span: Span,
+        name: String,
+        shared_at: Span,
+    },
+    #[error("Cannot mutate immutable binding \"{name}\"")]
+    ImmutableMutation { span: Span, name: String },
+    #[error("Value of type \"{found}\" may be nil and has to be checked before use")]
+    OptionalValue { span: Span, found: String },
+    #[error("Value of type \"{found}\" is not callable")]
+    NotCallable { span: Span, found: String },
+    #[error("Non-exhaustive match, patterns {missing:?} are not covered")]
+    NonExhaustiveMatch { span: Span, missing: Vec<String> },
+    #[error("Unreachable match arm")]
+    UnreachableMatchArm { span: Span },
+}
+
+impl SemanticError {
+    /// Span of the source code causing the error.
+    pub fn span(&self) -> Span {
+        match self {
+            Self::MismatchedTypes { span, .. }
+            | Self::MismatchedBranches { span, .. }
+            | Self::UnknownType { span, .. }
+            | Self::RecursiveTypeAlias { span, .. }
+            | Self::UnknownVariant { span, .. }
+            | Self::UnknownField { span, .. }
+            | Self::MissingFields { span, .. }
+            | Self::ArgumentCountMismatch { span, .. }
+            | Self::TypeArgumentCountMismatch { span, .. }
+            | Self::UnsatisfiedTraitBound { span, .. }
+            | Self::UndeclaredGlobal { span, .. }
+            | Self::UseAfterMove { span, .. }
+            | Self::SharedMutation { span, .. }
+            | Self::ImmutableMutation { span, .. }
+            | Self::OptionalValue { span, .. }
+            | Self::NotCallable { span, .. }
+            | Self::NonExhaustiveMatch { span, .. }
+            | Self::UnreachableMatchArm { span } => *span,
+        }
+    }
+
+    /// Another span related to the error and its description, e.g. where a used value was moved.
+    pub fn note(&self) -> Option<(Span, &'static str)> {
+        match self {
+            Self::UseAfterMove { moved_at, .. } => Some((*moved_at, "Moved here")),
+            Self::SharedMutation { shared_at, .. } => Some((*shared_at, "Shared here")),
+            _ => None,
+        }
+    }
+}
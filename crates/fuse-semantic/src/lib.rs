@@ -1,245 +1,432 @@
-use std::collections::HashMap;
+mod checker;
+mod declarations;
+mod error;
+mod ownership;
+mod scope;
+mod symbol;
+mod types;
+
+use std::collections::{HashMap, HashSet};
 
 use fuse_ast::{
-    Atom, BindingPatternKind, Chunk, Function, Identifier, MemberExpression, MemberExpressionLHS,
-    MemberExpressionRHS, VariableDeclaration,
+    BindingPattern, BindingPatternKind, BindingRest, Block, Chunk, EnumDeclaration, Expression,
+    ExternDeclaration, ExternItem, Function, FunctionBody, FunctionParameter, Identifier,
+    ImplStatement, KeyValueArgument, MatchArm, MemberExpression, MemberExpressionLHS,
+    MemberExpressionRHS, Pattern, Statement, StructDeclaration, TypeAliasDeclaration,
+    TypeParameter, UnionDeclaration, VariableDeclaration, VariableDeclarationKind,
 };
 use fuse_common::ReferenceType;
 use fuse_visitor::{
-    walk_function_mut, walk_member_expression_mut, walk_variable_declaration_mut, ScopeVisitor,
-    VisitorMut,
+    walk_block, walk_chunk, walk_expression, walk_extern_declaration, walk_function,
+    walk_function_body, walk_function_signature, walk_member_expression_lhs, walk_pattern,
+    walk_variable_declaration, NodeVisitor, ScopeVisitor, Visitor,
 };
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-struct ScopeId(ReferenceType);
+use checker::TypeChecker;
+use ownership::OwnershipChecker;
+use scope::ScopeTree;
+
+pub use declarations::LUA_DECLARATIONS;
+pub use error::SemanticError;
+pub use scope::ScopeId;
+pub use symbol::{Symbol, SymbolKind, SymbolTable};
+pub use types::*;
+
+pub struct Semantic {
+    scope: ScopeTree,
+    symbols: SymbolTable,
+    /// Globals provided by the host environment or declared by other modules.
+    host_globals: HashSet<String>,
+    /// Definitions of the types declared in the loaded declaration files.
+    definitions: HashMap<ReferenceType, TypeDefinition>,
+    /// Offset of the identifier to report on, See `with_cursor`.
+    cursor: Option<u32>,
+    /// Symbols visible from the identifier at the cursor once it is resolved.
+    visible: Option<Vec<ReferenceType>>,
+    errors: Vec<SemanticError>,
+}
 
-impl ScopeId {
-    #[inline(always)]
-    const fn as_index(self) -> ReferenceType {
-        self.0
+impl Semantic {
+    pub fn new() -> Self {
+        Self {
+            scope: ScopeTree::root_scope(),
+            symbols: SymbolTable::new(),
+            host_globals: HashSet::new(),
+            definitions: HashMap::new(),
+            cursor: None,
+            visible: None,
+            errors: Vec::new(),
+        }
     }
 
-    #[inline(always)]
-    const fn is_root(&self) -> bool {
-        self.0 == 0
+    /// Load a declaration file, e.g. `LUA_DECLARATIONS`. Everything it declares lives in
+    /// the root scope so it is accessible from the chunks built afterward.
+    pub fn with_declarations(mut self, chunk: &Chunk) -> Self {
+        // The cursor is an offset of the built chunk, Not the declarations.
+        let cursor = self.cursor.take();
+        self.hoist_global_declarations(chunk);
+        self.hoist_type_declarations(&chunk.body);
+        walk_chunk(&mut self, chunk);
+        let definitions = std::mem::take(&mut self.definitions);
+        let result = TypeChecker::new(&mut self.symbols)
+            .with_definitions(definitions)
+            .check(chunk);
+        self.definitions = result.definitions;
+        self.errors.extend(result.errors);
+        self.cursor = cursor;
+        self
     }
-}
 
-impl PartialEq<ReferenceType> for ScopeId {
-    fn eq(&self, other: &ReferenceType) -> bool {
-        self.0 == *other
+    /// Report what is in scope of the identifier at the byte `offset` of the built chunk,
+    /// e.g. for completions. See `SemanticResult::cursor`.
+    pub fn with_cursor(mut self, offset: u32) -> Self {
+        self.cursor = Some(offset);
+        self
     }
-}
 
-struct IdentDeclMap(HashMap<Atom, ReferenceType>);
+    /// Allow referencing the given globals without declaring them, e.g. globals of other modules.
+    pub fn with_host_globals<'a>(mut self, globals: impl IntoIterator<Item = &'a str>) -> Self {
+        self.host_globals
+            .extend(globals.into_iter().map(str::to_string));
+        self
+    }
 
-impl IdentDeclMap {
-    fn new() -> Self {
-        Self(HashMap::new())
+    /// Resolve every identifier of the chunk and type check it.
+    pub fn build(mut self, chunk: &Chunk) -> SemanticResult {
+        self.visit_chunk(chunk);
+        let mut symbols = self.symbols;
+        let mut errors = self.errors;
+        let result = TypeChecker::new(&mut symbols)
+            .with_definitions(self.definitions)
+            .with_cursor(self.cursor)
+            .check(chunk);
+        errors.extend(result.errors);
+        errors.extend(OwnershipChecker::new(&symbols).check(chunk));
+        let cursor = self.cursor.map(|_| Cursor {
+            visible: self.visible.unwrap_or_default(),
+            member: result.member,
+        });
+        SemanticResult {
+            symbols,
+            definitions: result.definitions,
+            cursor,
+            errors,
+        }
     }
 
-    fn insert(&mut self, atom: Atom, ref_id: ReferenceType) -> Option<ReferenceType> {
-        self.0.insert(atom, ref_id)
+    fn declare_identifier(
+        &mut self,
+        ident: &Identifier,
+        kind: SymbolKind,
+        mutable: bool,
+    ) -> ReferenceType {
+        let reference = self
+            .symbols
+            .declare(ident, kind, mutable, self.scope.current());
+        self.scope
+            .set_scope_identifier_reference(ident.name.clone(), reference);
+        reference
     }
 
-    fn get(&self, atom: &Atom) -> Option<ReferenceType> {
-        self.0.get(atom).map(|r| r.clone())
+    /// Declare members of types, e.g. fields and variants, These symbols
+    /// are only accessible through their type so we don't add them to the scope.
+    fn declare_member_identifier(&mut self, ident: &Identifier, kind: SymbolKind) {
+        self.symbols
+            .declare(ident, kind, false, self.scope.current());
     }
-}
 
-enum ReferenceKind {
-    Scope,
-    Member,
-}
+    fn reference_scope_identifier(&mut self, ident: &Identifier) {
+        let reference = self.scope.scope_identifier_reference(&ident.name);
+        ident.reference.set(reference);
+        let at_cursor = self
+            .cursor
+            .is_some_and(|cursor| ident.span.start <= cursor && cursor <= ident.span.end);
+        if at_cursor && self.visible.is_none() {
+            self.visible = Some(self.scope.visible_references());
+        }
+    }
 
-struct ScopeTree {
-    current: ScopeId,
-    ident_decl_maps: Vec<IdentDeclMap>,
-    /// Maps between `ReferenceId` and `ReferenceKind`
-    reference_kinds: Vec<ReferenceKind>,
-    parent_ids: Vec<ScopeId>,
-}
+    /// Resolve an identifier used as a value, Unresolved identifiers
+    /// refer to globals so they have to be known host globals.
+    fn reference_value_identifier(&mut self, ident: &Identifier) {
+        if ident.reference.get().is_some() {
+            return;
+        }
+        self.reference_scope_identifier(ident);
+        if ident.reference.get().is_none() && !self.host_globals.contains(ident.name.as_str()) {
+            self.errors.push(SemanticError::UndeclaredGlobal {
+                span: ident.span,
+                name: ident.name.as_str().to_string(),
+            });
+        }
+    }
 
-/// Tree operations for `ScopeTree`
-impl ScopeTree {
-    fn root_scope() -> Self {
-        Self {
-            current: ScopeId(0),
-            parent_ids: vec![ScopeId(0)],
-            ident_decl_maps: vec![IdentDeclMap::new()],
-            reference_kinds: Vec::new(),
+    /// Declare every global and extern of the chunk in the root scope ahead of its statements,
+    /// Globals are accessible from anywhere regardless of where they are declared, even in functions.
+    fn hoist_global_declarations(&mut self, chunk: &Chunk) {
+        let mut globals = GlobalDeclarations::default();
+        globals.visit_chunk(chunk);
+        for decl in globals.declarations {
+            let BindingPatternKind::Identifier(binding) = &decl.binding.kind else {
+                continue;
+            };
+            self.declare_global_identifier(
+                &binding.identifier,
+                SymbolKind::Variable(VariableDeclarationKind::Global),
+                binding.mutable,
+            );
+        }
+        for decl in globals.externs {
+            self.declare_global_identifier(decl.item.identifier(), SymbolKind::Extern, false);
         }
     }
 
-    fn push_stack(&mut self) -> ScopeId {
-        self.ident_decl_maps.push(IdentDeclMap::new());
-        self.parent_ids.push(self.current);
+    fn declare_global_identifier(&mut self, ident: &Identifier, kind: SymbolKind, mutable: bool) {
+        // Redeclaration of a global refers to the same symbol.
+        if let Some(reference) = self.scope.root_identifier_reference(&ident.name) {
+            ident.reference.set(Some(reference));
+            return;
+        }
+        let reference = self
+            .symbols
+            .declare(ident, kind, mutable, self.scope.root());
+        self.scope
+            .set_root_identifier_reference(ident.name.clone(), reference);
+    }
 
-        // length of all arrays should be same.
-        debug_assert!(self.ident_decl_maps.len() == self.parent_ids.len());
+    /// Declare the types of a block ahead of its statements, So they can be used before their declaration.
+    fn hoist_type_declarations(&mut self, block: &Block) {
+        for statement in &block.statements {
+            let (identifier, kind) = match statement {
+                Statement::StructDeclaration(decl) => (&decl.identifier, SymbolKind::Struct),
+                Statement::EnumDeclaration(decl) => (&decl.identifier, SymbolKind::Enum),
+                Statement::UnionDeclaration(decl) => (&decl.identifier, SymbolKind::Union),
+                Statement::TypeAliasDeclaration(decl) => (&decl.identifier, SymbolKind::TypeAlias),
+                _ => continue,
+            };
+            self.declare_identifier(identifier, kind, false);
+        }
+    }
 
-        self.current = ScopeId(self.ident_decl_maps.len() - 1);
-        self.current
+    fn declare_binding_pattern(&mut self, pattern: &BindingPattern, kind: SymbolKind) {
+        if let Some(annotation) = &pattern.type_annotation {
+            self.visit_type_annotation(annotation);
+        }
+        match &pattern.kind {
+            BindingPatternKind::Identifier(binding) => {
+                self.declare_identifier(&binding.identifier, kind, binding.mutable);
+            }
+            BindingPatternKind::Tuple => {}
+        }
     }
+}
 
-    fn pop_stack(&mut self) {
-        assert_ne!(
-            self.current, 0,
-            "Attempt to pop the root scope from the stack."
-        );
+impl Default for Semantic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        self.current = self.parent();
+impl<'ast> Visitor<'ast> for Semantic {
+    fn visit_chunk(&mut self, chunk: &'ast Chunk) {
+        self.hoist_global_declarations(chunk);
+        self.enter_scope();
+        self.hoist_type_declarations(&chunk.body);
+        walk_chunk(self, chunk);
+        self.leave_scope();
     }
 
-    fn parent(&self) -> ScopeId {
-        assert_ne!(
-            self.current, 0,
-            "Attempt to access the root scope's parent."
-        );
-        self.parent_ids[self.current.as_index()]
+    fn visit_block(&mut self, block: &'ast Block) {
+        self.enter_scope();
+        self.hoist_type_declarations(block);
+        walk_block(self, block);
+        self.leave_scope();
     }
 
-    fn parent_of(&self, children_id: ScopeId) -> ScopeId {
-        assert_ne!(
-            self.current, 0,
-            "Attempt to access the root scope's parent."
-        );
-        self.parent_ids[children_id.as_index()]
+    fn visit_function_body(&mut self, body: &'ast FunctionBody) {
+        if let FunctionBody::Block(block) = body {
+            self.hoist_type_declarations(block);
+        }
+        walk_function_body(self, body);
     }
-}
 
-/// Identifier operations for `ScopeTree`
-impl ScopeTree {
-    /// Get an identifier reference from current scope or its parents.
-    /// This function is implemented using loops instead of recursion.
-    fn scope_identifier_reference(&mut self, atom: &Atom) -> Option<ReferenceType> {
-        let mut scope_id = self.current;
-        let mut reference;
-        loop {
-            reference = self.ident_decl_maps[scope_id.as_index()].get(atom);
-
-            if reference.is_some() || scope_id.is_root() {
-                break;
-            } else {
-                scope_id = self.parent_of(scope_id);
-            }
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        match expression {
+            Expression::Identifier(ident) => self.reference_value_identifier(ident),
+            _ => walk_expression(self, expression),
         }
-        reference
     }
 
-    /// Set a `ReferenceType` for the given identifier's `Atom` in the current scope.
-    /// Would return the last `ReferenceType` if we are shadowing it.
-    fn set_scope_identifier_reference(
-        &mut self,
-        atom: Atom,
-        ref_id: ReferenceType,
-    ) -> Option<ReferenceType> {
-        self.ident_decl_maps[self.current.as_index()].insert(atom, ref_id)
+    fn visit_identifier(&mut self, ident: &'ast Identifier) {
+        if ident.reference.get().is_none() {
+            self.reference_scope_identifier(ident);
+        }
     }
 
-    fn member_identifier_reference(
-        &mut self,
-        atom: Atom,
-        ref_id: ReferenceType,
-    ) -> Option<ReferenceType> {
-        self.ident_decl_maps[self.current.as_index()].insert(atom, ref_id)
+    fn visit_variable_declaration(&mut self, decl: &'ast VariableDeclaration) {
+        // Initializer is visited first so it can't refer to the declared binding.
+        if let Some(expression) = &decl.expression {
+            self.visit_expression(expression);
+        }
+        match decl.kind {
+            // Globals are already declared while hoisting them.
+            VariableDeclarationKind::Global => {
+                if let Some(annotation) = &decl.binding.type_annotation {
+                    self.visit_type_annotation(annotation);
+                }
+            }
+            kind => self.declare_binding_pattern(&decl.binding, SymbolKind::Variable(kind)),
+        }
     }
-}
 
-pub struct Semantic<'ast> {
-    source: &'ast str,
-    scope: ScopeTree,
-    last_reference: ReferenceType,
-}
+    fn visit_function_declaration(&mut self, decl: &'ast Function) {
+        let identifier = decl
+            .signature
+            .identifier
+            .as_ref()
+            .expect("All function declarations need an identifier.");
+        self.declare_identifier(identifier, SymbolKind::Function, false);
+        self.enter_scope();
+        walk_function(self, decl);
+        self.leave_scope();
+    }
 
-impl<'ast> Semantic<'ast> {
-    pub fn new(source: &'ast str) -> Self {
-        Self {
-            source,
-            scope: ScopeTree::root_scope(),
-            last_reference: 0,
+    fn visit_function_parameter(&mut self, param: &'ast FunctionParameter) {
+        self.declare_binding_pattern(&param.pattern, SymbolKind::Parameter);
+    }
+
+    fn visit_binding_rest(&mut self, rest: &'ast BindingRest) {
+        if let Some(annotation) = &rest.type_annotation {
+            self.visit_type_annotation(annotation);
         }
+        self.declare_identifier(
+            &rest.binding.identifier,
+            SymbolKind::Parameter,
+            rest.binding.mutable,
+        );
     }
 
-    pub fn build(&mut self, chunk: &'ast mut Chunk) -> SemanticResult {
-        self.visit_chunk_mut(chunk);
-        SemanticResult {
-            errors: Vec::default(),
+    fn visit_enum_declaration(&mut self, decl: &'ast EnumDeclaration) {
+        for variant in &decl.variants {
+            self.declare_member_identifier(&variant.identifier, SymbolKind::EnumVariant);
+            if let Some(value) = &variant.value {
+                self.visit_expression(value);
+            }
         }
     }
 
-    fn declare_identifier(&mut self, ident: &Identifier) {
-        self.last_reference += 1;
-        self.scope
-            .set_scope_identifier_reference(ident.name.clone(), self.last_reference);
-        ident.reference.set(Some(self.last_reference))
+    fn visit_struct_declaration(&mut self, decl: &'ast StructDeclaration) {
+        self.enter_scope();
+        for param in &decl.type_parameters {
+            self.visit_type_parameter(param);
+        }
+        for field in &decl.fields {
+            self.declare_member_identifier(&field.identifier, SymbolKind::Field);
+            self.visit_type_annotation(&field.type_annotation);
+        }
+        self.leave_scope();
     }
 
-    fn reference_scope_identifier(&mut self, ident: &Identifier) {
-        let reference = self.scope.scope_identifier_reference(&ident.name);
-        ident.reference.set(reference)
+    fn visit_type_parameter(&mut self, param: &'ast TypeParameter) {
+        // Bounds are names of traits, They are checked by the type checker.
+        self.declare_identifier(&param.identifier, SymbolKind::TypeParameter, false);
     }
 
-    fn resolve_member_identifier(&mut self, ident: &Identifier, sup: Option<&Identifier>) {
-        let Some(sup) = sup else {
-            return self.declare_member_identifier(ident, None);
-        };
-        let reference = self.scope.scope_identifier_reference(&ident.name);
-        ident.reference.set(reference);
-        // todo!()
+    fn visit_union_declaration(&mut self, decl: &'ast UnionDeclaration) {
+        for variant in &decl.variants {
+            self.declare_member_identifier(&variant.identifier, SymbolKind::UnionVariant);
+            for field in &variant.fields {
+                self.visit_type_annotation(field);
+            }
+        }
     }
 
-    fn declare_member_identifier(&mut self, ident: &Identifier, sup: Option<&Identifier>) {
-        let reference = self.scope.scope_identifier_reference(&ident.name);
-        ident.reference.set(reference);
-        // todo!()
+    fn visit_type_alias_declaration(&mut self, decl: &'ast TypeAliasDeclaration) {
+        self.visit_type_annotation(&decl.annotation);
     }
-}
 
-impl<'ast> VisitorMut<'ast> for Semantic<'ast> {
-    fn visit_identifier_mut(&mut self, ident: &'ast mut Identifier) {
-        if ident.reference.get_mut().is_none() {
-            self.reference_scope_identifier(ident);
+    fn visit_impl_statement(&mut self, r#impl: &'ast ImplStatement) {
+        self.visit_type_annotation(&r#impl.r#trait);
+        self.visit_type_annotation(&r#impl.target);
+        for method in &r#impl.methods {
+            let function = &method.function;
+            if let Some(identifier) = &function.signature.identifier {
+                self.declare_member_identifier(identifier, SymbolKind::Method);
+            }
+            self.enter_scope();
+            walk_function(self, function);
+            self.leave_scope();
         }
     }
 
-    fn visit_variable_declaration_mut(&mut self, decl: &'ast mut VariableDeclaration) {
-        match &decl.binding.kind {
-            BindingPatternKind::Identifier(bind) => self.declare_identifier(&bind.identifier),
-            _ => todo!(),
+    fn visit_extern_item(&mut self, item: &'ast ExternItem) {
+        // Extern declarations are already declared while hoisting them.
+        match item {
+            ExternItem::Function(signature) => {
+                self.enter_scope();
+                walk_function_signature(self, signature);
+                self.leave_scope();
+            }
+            ExternItem::Global(global) => self.visit_type_annotation(&global.type_annotation),
+            ExternItem::Table(table) => {
+                for item in &table.items {
+                    self.declare_member_identifier(item.identifier(), SymbolKind::Field);
+                    self.visit_extern_item(item);
+                }
+            }
         }
+    }
 
-        walk_variable_declaration_mut(self, decl)
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        self.enter_scope();
+        self.visit_pattern(&arm.pattern);
+        self.visit_block(&arm.body);
+        self.leave_scope();
     }
 
-    fn visit_function_declaration_mut(&mut self, decl: &'ast mut Function) {
-        let identifier = decl
-            .signature
-            .identifier
-            .as_ref()
-            .expect("All function declarations need an identifier.");
-        self.declare_identifier(identifier);
-        walk_function_mut(self, decl)
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        match pattern {
+            Pattern::Binding(binding) => {
+                self.declare_identifier(
+                    &binding.identifier,
+                    SymbolKind::Variable(VariableDeclarationKind::Let),
+                    binding.mutable,
+                );
+            }
+            // Variants are resolved by the type checker, We only resolve the target here.
+            Pattern::Variant(variant) => {
+                self.visit_identifier(&variant.target);
+                for field in &variant.fields {
+                    self.visit_pattern(field);
+                }
+            }
+            _ => walk_pattern(self, pattern),
+        }
     }
 
-    fn visit_member_expression_mut(&mut self, member: &'ast mut MemberExpression) {
-        let lhs = member.lhs.as_ref();
-        let rhs = member.rhs.as_ref();
-        let sup = match lhs {
-            MemberExpressionLHS::Identifier(ident) => {
-                self.reference_scope_identifier(ident);
-                Some(ident)
+    fn visit_member_expression(&mut self, member: &'ast MemberExpression) {
+        match member.lhs.as_ref() {
+            MemberExpressionLHS::Identifier(ident) => self.reference_value_identifier(ident),
+            lhs => walk_member_expression_lhs(self, lhs),
+        }
+        // Members are resolved by the type checker since they depend on the type of lhs.
+        match member.rhs.as_ref() {
+            MemberExpressionRHS::Identifier(_) | MemberExpressionRHS::Number(_) => {}
+            MemberExpressionRHS::Member(member) => self.visit_member_expression(member),
+            MemberExpressionRHS::Call(call) => {
+                for arg in &call.arguments {
+                    self.visit_expression(arg);
+                }
             }
-            _ => None,
-        };
-        if let MemberExpressionRHS::Identifier(ident) = rhs {
-            self.resolve_member_identifier(ident, sup)
         }
-        walk_member_expression_mut(self, member)
+    }
+
+    fn visit_key_value_argument(&mut self, kv: &'ast KeyValueArgument) {
+        self.visit_expression(&kv.value);
     }
 }
 
-impl<'ast> ScopeVisitor for Semantic<'ast> {
+impl ScopeVisitor for Semantic {
     fn enter_scope(&mut self) {
         self.scope.push_stack();
     }
@@ -249,8 +436,61 @@ impl<'ast> ScopeVisitor for Semantic<'ast> {
     }
 }
 
+impl NodeVisitor for Semantic {}
+
+/// Global variable and extern declarations of a chunk, collected before resolving it.
+#[derive(Default)]
+struct GlobalDeclarations<'ast> {
+    declarations: Vec<&'ast VariableDeclaration>,
+    externs: Vec<&'ast ExternDeclaration>,
+}
+
+impl<'ast> Visitor<'ast> for GlobalDeclarations<'ast> {
+    fn visit_variable_declaration(&mut self, decl: &'ast VariableDeclaration) {
+        if decl.kind == VariableDeclarationKind::Global {
+            self.declarations.push(decl);
+        }
+        walk_variable_declaration(self, decl)
+    }
+
+    fn visit_extern_declaration(&mut self, decl: &'ast ExternDeclaration) {
+        self.externs.push(decl);
+        walk_extern_declaration(self, decl)
+    }
+}
+
+impl<'ast> ScopeVisitor for GlobalDeclarations<'ast> {}
+
+impl<'ast> NodeVisitor for GlobalDeclarations<'ast> {}
+
 pub struct SemanticResult {
+    pub symbols: SymbolTable,
+    /// Definitions of declared types, keyed by their symbol's reference.
+    pub definitions: HashMap<ReferenceType, TypeDefinition>,
+    /// What is known at the cursor, It is only `Some` if built `with_cursor`.
+    pub cursor: Option<Cursor>,
     pub errors: Vec<SemanticError>,
 }
 
-pub struct SemanticError {}
+/// Scope and member target of the identifier at the cursor of a `Semantic`.
+#[derive(Debug, Default)]
+pub struct Cursor {
+    /// Symbols visible from the identifier, Innermost declarations first. It is empty if
+    /// there is no identifier resolved in scope at the cursor, e.g. members and declarations.
+    pub visible: Vec<ReferenceType>,
+    /// Type of the value whose member is at the cursor, e.g. `point` in `point.x`.
+    pub member: Option<Type>,
+}
+
+impl SemanticResult {
+    /// Names of the globals declared by this chunk, They can be passed
+    /// as host globals to the semantic of other modules using them.
+    pub fn globals(&self) -> impl Iterator<Item = &str> {
+        self.symbols
+            .iter()
+            .filter(|(_, symbol)| {
+                symbol.kind == SymbolKind::Variable(VariableDeclarationKind::Global)
+            })
+            .map(|(_, symbol)| symbol.name.as_str())
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::HashMap;
+
+use fuse_ast::{
+    ArrayExpression, ArrayExpressionElement, BinaryOperator, BinaryOperatorKind,
+    BindingPatternKind, CallExpression, Chunk, ConstructionField, Else, Expression, FunctionBody,
+    Identifier, If, Match, MemberExpression, MemberExpressionLHS, TupleExpression,
+    TupleExpressionElement, VariableDeclaration,
+};
+use fuse_common::{ReferenceType, Span};
+use fuse_visitor::{walk_binary_operator, walk_function_body, NodeVisitor, ScopeVisitor, Visitor};
+
+use crate::{SemanticError, SymbolKind, SymbolTable, Type};
+
+/// Flow-sensitive analysis of the ownership of non-primitive values, Assigning them to another
+/// binding or passing them to a function moves them, Making the moved binding unusable.
+/// Extern functions borrow their arguments instead.
+/// It runs after the type checker since it depends on the types of the symbols.
+pub(crate) struct OwnershipChecker<'a> {
+    symbols: &'a SymbolTable,
+    state: OwnershipState,
+    errors: Vec<SemanticError>,
+}
+
+/// Ownership state of the variables at a point of the program.
+#[derive(Debug, Clone, Default)]
+struct OwnershipState {
+    /// Variables whose value is moved, along with the span of the move.
+    moved: HashMap<ReferenceType, Span>,
+    /// Variables holding a value that is also reachable from another
+    /// binding, along with the span that it got shared at.
+    shared: HashMap<ReferenceType, Span>,
+}
+
+impl OwnershipState {
+    /// Merge the state of another branch, A variable moved in any of the branches is moved.
+    fn merge(&mut self, other: OwnershipState) {
+        for (reference, span) in other.moved {
+            self.moved.entry(reference).or_insert(span);
+        }
+        for (reference, span) in other.shared {
+            self.shared.entry(reference).or_insert(span);
+        }
+    }
+}
+
+impl<'a> OwnershipChecker<'a> {
+    pub(crate) fn new(symbols: &'a SymbolTable) -> Self {
+        Self {
+            symbols,
+            state: OwnershipState::default(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub(crate) fn check(mut self, chunk: &Chunk) -> Vec<SemanticError> {
+        self.visit_chunk(chunk);
+        self.errors
+    }
+
+    /// Visit an expression whose value gets moved, e.g. a function argument.
+    fn consume(&mut self, expr: &Expression) {
+        self.visit_expression(expr);
+        if let Some(reference) = self.owned_variable(expr) {
+            self.state.moved.insert(reference, expr.span());
+        }
+    }
+
+    /// Get the variable that an expression refers to if it holds a non-primitive value.
+    fn owned_variable(&self, expr: &Expression) -> Option<ReferenceType> {
+        match expr {
+            Expression::Identifier(ident) => {
+                let reference = ident.reference.get()?;
+                let symbol = self.symbols.get(reference);
+                let variable =
+                    matches!(symbol.kind, SymbolKind::Variable(_) | SymbolKind::Parameter);
+                (variable && is_owned(&symbol.ty)).then_some(reference)
+            }
+            Expression::ParenthesizedExpression(expr) => self.owned_variable(&expr.expression),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the expression refers to an extern declaration or a global of the host,
+    /// e.g. `print` or `table.insert`.
+    fn is_extern(&self, expr: &Expression) -> bool {
+        let ident = match expr {
+            Expression::Identifier(ident) => ident,
+            Expression::MemberExpression(member) => match member_root(member) {
+                Some(ident) => ident,
+                None => return false,
+            },
+            Expression::ParenthesizedExpression(expr) => return self.is_extern(&expr.expression),
+            _ => return false,
+        };
+        ident
+            .reference
+            .get()
+            .is_none_or(|reference| self.symbols.get(reference).kind == SymbolKind::Extern)
+    }
+
+    /// Initialize a variable with the given value, Values read from the members
+    /// of another binding are shared with it instead of being moved.
+    fn initialize(&mut self, reference: ReferenceType, value: &Expression) {
+        self.state.moved.remove(&reference);
+        let shared = matches!(value, Expression::MemberExpression(_))
+            && is_owned(&self.symbols.get(reference).ty);
+        if shared {
+            self.state.shared.insert(reference, value.span());
+        } else {
+            self.state.shared.remove(&reference);
+        }
+    }
+
+    /// Check the mutation of a variable, either by assignment or through its members.
+    fn check_mutation(&mut self, ident: &Identifier, span: Span, through_member: bool) {
+        let Some(reference) = ident.reference.get() else {
+            return;
+        };
+        let symbol = self.symbols.get(reference);
+        if !matches!(symbol.kind, SymbolKind::Variable(_) | SymbolKind::Parameter) {
+            return;
+        }
+        let name = symbol.name.as_str().to_string();
+        if !symbol.mutable {
+            self.errors
+                .push(SemanticError::ImmutableMutation { span, name });
+        } else if let Some(shared_at) = self.state.shared.get(&reference).filter(|_| through_member)
+        {
+            self.errors.push(SemanticError::SharedMutation {
+                span,
+                name,
+                shared_at: *shared_at,
+            });
+        }
+    }
+
+    fn check_assignment(&mut self, op: &BinaryOperator) {
+        match &op.lhs {
+            Expression::Identifier(ident) => {
+                // Assignment gives the variable a new value so it isn't a use.
+                self.consume(&op.rhs);
+                self.check_mutation(ident, op.lhs.span(), false);
+                if let Some(reference) = ident.reference.get() {
+                    self.initialize(reference, &op.rhs);
+                }
+            }
+            Expression::MemberExpression(member) => {
+                self.visit_member_expression(member);
+                self.consume(&op.rhs);
+                if let Some(root) = member_root(member) {
+                    self.check_mutation(root, member.span, true);
+                }
+            }
+            lhs => {
+                self.visit_expression(lhs);
+                self.consume(&op.rhs);
+            }
+        }
+    }
+}
+
+impl<'ast, 'a> Visitor<'ast> for OwnershipChecker<'a> {
+    fn visit_identifier(&mut self, ident: &'ast Identifier) {
+        let Some(reference) = ident.reference.get() else {
+            return;
+        };
+        if let Some(moved_at) = self.state.moved.get(&reference) {
+            self.errors.push(SemanticError::UseAfterMove {
+                span: ident.span,
+                name: ident.name.as_str().to_string(),
+                moved_at: *moved_at,
+            });
+        }
+    }
+
+    fn visit_variable_declaration(&mut self, decl: &'ast VariableDeclaration) {
+        let Some(value) = &decl.expression else {
+            return;
+        };
+        self.consume(value);
+        if let BindingPatternKind::Identifier(binding) = &decl.binding.kind {
+            if let Some(reference) = binding.identifier.reference.get() {
+                self.initialize(reference, value);
+            }
+        }
+    }
+
+    fn visit_function_body(&mut self, body: &'ast FunctionBody) {
+        // Functions can get called any number of times, So moves inside of them don't
+        // affect the enclosing scope, But they still can't use the already moved values.
+        let state = self.state.clone();
+        walk_function_body(self, body);
+        self.state = state;
+    }
+
+    fn visit_if(&mut self, r#if: &'ast If) {
+        self.visit_expression(&r#if.cond);
+        let before = self.state.clone();
+        self.visit_block(&r#if.body);
+        let then = std::mem::replace(&mut self.state, before);
+        match &r#if.r#else {
+            Some(Else::If(r#if)) => self.visit_if(r#if),
+            Some(Else::Block(block)) => self.visit_block(block),
+            None => {}
+        }
+        self.state.merge(then);
+    }
+
+    fn visit_match(&mut self, r#match: &'ast Match) {
+        self.visit_expression(&r#match.expression);
+        let before = self.state.clone();
+        let mut after = before.clone();
+        for arm in &r#match.arms {
+            self.visit_match_arm(arm);
+            after.merge(std::mem::replace(&mut self.state, before.clone()));
+        }
+        if let Some(block) = &r#match.r#else {
+            self.visit_block(block);
+            after.merge(std::mem::replace(&mut self.state, before.clone()));
+        }
+        self.state = after;
+    }
+
+    fn visit_binary_operator(&mut self, op: &'ast BinaryOperator) {
+        match op.kind {
+            BinaryOperatorKind::Assignment(_) => self.check_assignment(op),
+            _ => walk_binary_operator(self, op),
+        }
+    }
+
+    fn visit_call_expression(&mut self, call: &'ast CallExpression) {
+        self.visit_expression(&call.callee);
+        // Extern functions are implemented by the host, They borrow their arguments.
+        let borrows = self.is_extern(&call.callee);
+        for arg in &call.arguments {
+            if borrows {
+                self.visit_expression(arg);
+            } else {
+                self.consume(arg);
+            }
+        }
+    }
+
+    fn visit_array_expression(&mut self, array: &'ast ArrayExpression) {
+        for element in &array.elements {
+            match element {
+                ArrayExpressionElement::Expression(expr) => self.consume(expr),
+                ArrayExpressionElement::Spread(spread) => self.visit_spread_argument(spread),
+            }
+        }
+    }
+
+    fn visit_tuple_expression(&mut self, tuple: &'ast TupleExpression) {
+        for element in &tuple.elements {
+            match element {
+                TupleExpressionElement::Expression(expr) => self.consume(expr),
+                TupleExpressionElement::Spread(spread) => self.visit_spread_argument(spread),
+            }
+        }
+    }
+
+    fn visit_construction_field(&mut self, field: &'ast ConstructionField) {
+        match field {
+            ConstructionField::Expression(expr) => self.consume(expr),
+            ConstructionField::KeyValueArgument(kv) => self.consume(&kv.value),
+            ConstructionField::Spread(spread) => self.visit_spread_argument(spread),
+        }
+    }
+}
+
+impl<'a> ScopeVisitor for OwnershipChecker<'a> {}
+
+impl<'a> NodeVisitor for OwnershipChecker<'a> {}
+
+/// Returns true for the types of values that are moved instead of being copied.
+fn is_owned(ty: &Type) -> bool {
+    match ty {
+        Type::Array(_) | Type::Tuple(_) | Type::Table | Type::Struct(..) => true,
+        Type::Optional(inner) => is_owned(inner),
+        _ => false,
+    }
+}
+
+/// Get the variable that a member expression is accessed through, e.g. `a` in `a.b.c`.
+fn member_root(member: &MemberExpression) -> Option<&Identifier> {
+    match member.lhs.as_ref() {
+        MemberExpressionLHS::Identifier(ident) => Some(ident),
+        MemberExpressionLHS::Member(member) => member_root(member),
+        _ => None,
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::{HashMap, HashSet};
+
+use fuse_ast::Atom;
+use fuse_common::ReferenceType;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ScopeId(ReferenceType);
+
+impl ScopeId {
+    #[inline(always)]
+    pub const fn as_index(self) -> ReferenceType {
+        self.0
+    }
+
+    #[inline(always)]
+    pub const fn is_root(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl PartialEq<ReferenceType> for ScopeId {
+    fn eq(&self, other: &ReferenceType) -> bool {
+        self.0 == *other
+    }
+}
+
+struct IdentDeclMap(HashMap<Atom, ReferenceType>);
+
+impl IdentDeclMap {
+    fn new() -> Self {
+        Self(HashMap::new())
+    }
+
+    fn insert(&mut self, atom: Atom, ref_id: ReferenceType) -> Option<ReferenceType> {
+        self.0.insert(atom, ref_id)
+    }
+
+    fn get(&self, atom: &Atom) -> Option<ReferenceType> {
+        self.0.get(atom).copied()
+    }
+}
+
+pub struct ScopeTree {
+    current: ScopeId,
+    ident_decl_maps: Vec<IdentDeclMap>,
+    parent_ids: Vec<ScopeId>,
+}
+
+/// Tree operations for `ScopeTree`
+impl ScopeTree {
+    pub fn root_scope() -> Self {
+        Self {
+            current: ScopeId(0),
+            parent_ids: vec![ScopeId(0)],
+            ident_decl_maps: vec![IdentDeclMap::new()],
+        }
+    }
+
+    pub fn current(&self) -> ScopeId {
+        self.current
+    }
+
+    pub fn root(&self) -> ScopeId {
+        ScopeId(0)
+    }
+
+    pub fn push_stack(&mut self) -> ScopeId {
+        self.ident_decl_maps.push(IdentDeclMap::new());
+        self.parent_ids.push(self.current);
+
+        // length of all arrays should be same.
+        debug_assert!(self.ident_decl_maps.len() == self.parent_ids.len());
+
+        self.current = ScopeId(self.ident_decl_maps.len() - 1);
+        self.current
+    }
+
+    pub fn pop_stack(&mut self) {
+        assert_ne!(
+            self.current, 0,
+            "Attempt to pop the root scope from the stack."
+        );
+
+        self.current = self.parent();
+    }
+
+    fn parent(&self) -> ScopeId {
+        assert_ne!(
+            self.current, 0,
+            "Attempt to access the root scope's parent."
+        );
+        self.parent_ids[self.current.as_index()]
+    }
+
+    fn parent_of(&self, children_id: ScopeId) -> ScopeId {
+        assert_ne!(children_id, 0, "Attempt to access the root scope's parent.");
+        self.parent_ids[children_id.as_index()]
+    }
+}
+
+/// Identifier operations for `ScopeTree`
+impl ScopeTree {
+    /// Get an identifier reference from current scope or its parents.
+    /// This function is implemented using loops instead of recursion.
+    pub fn scope_identifier_reference(&self, atom: &Atom) -> Option<ReferenceType> {
+        let mut scope_id = self.current;
+        let mut reference;
+        loop {
+            reference = self.ident_decl_maps[scope_id.as_index()].get(atom);
+
+            if reference.is_some() || scope_id.is_root() {
+                break;
+            } else {
+                scope_id = self.parent_of(scope_id);
+            }
+        }
+        reference
+    }
+
+    /// Set a `ReferenceType` for the given identifier's `Atom` in the current scope.
+    /// Would return the last `ReferenceType` if we are shadowing it.
+    pub fn set_scope_identifier_reference(
+        &mut self,
+        atom: Atom,
+        ref_id: ReferenceType,
+    ) -> Option<ReferenceType> {
+        self.ident_decl_maps[self.current.as_index()].insert(atom, ref_id)
+    }
+
+    /// References of every identifier visible from the current scope, Inner declarations
+    /// shadow the outer ones with the same name. Innermost scopes come first.
+    pub fn visible_references(&self) -> Vec<ReferenceType> {
+        let mut names = HashSet::new();
+        let mut references = Vec::new();
+        let mut scope_id = self.current;
+        loop {
+            let mut declared: Vec<_> = self.ident_decl_maps[scope_id.as_index()]
+                .0
+                .iter()
+                .filter(|(atom, _)| names.insert(*atom))
+                .map(|(_, reference)| *reference)
+                .collect();
+            declared.sort();
+            references.extend(declared);
+
+            if scope_id.is_root() {
+                break;
+            }
+            scope_id = self.parent_of(scope_id);
+        }
+        references
+    }
+
+    /// Get an identifier reference from the root scope.
+    pub fn root_identifier_reference(&self, atom: &Atom) -> Option<ReferenceType> {
+        self.ident_decl_maps[self.root().as_index()].get(atom)
+    }
+
+    /// Set a `ReferenceType` for the given identifier's `Atom` in the root scope.
+    pub fn set_root_identifier_reference(
+        &mut self,
+        atom: Atom,
+        ref_id: ReferenceType,
+    ) -> Option<ReferenceType> {
+        let root = self.root().as_index();
+        self.ident_decl_maps[root].insert(atom, ref_id)
+    }
+}
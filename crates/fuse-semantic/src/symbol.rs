@@ -0,0 +1,104 @@
+use fuse_ast::{Atom, Identifier, VariableDeclarationKind};
+use fuse_common::{ReferenceType, Span};
+
+use crate::{scope::ScopeId, Type};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Variable(VariableDeclarationKind),
+    Parameter,
+    Function,
+    Struct,
+    Field,
+    Enum,
+    EnumVariant,
+    Union,
+    UnionVariant,
+    TypeAlias,
+    TypeParameter,
+    Method,
+    /// A function, table or value provided by the host, declared using `extern`.
+    Extern,
+}
+
+impl SymbolKind {
+    /// Returns true if symbol declares a type instead of a value.
+    pub fn is_type(&self) -> bool {
+        matches!(
+            self,
+            Self::Struct | Self::Enum | Self::Union | Self::TypeAlias | Self::TypeParameter
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct Symbol {
+    pub name: Atom,
+    /// Span of the declaring identifier.
+    pub span: Span,
+    pub kind: SymbolKind,
+    pub mutable: bool,
+    /// The scope in which this symbol is declared.
+    pub scope: ScopeId,
+    /// Declared or inferred type of the symbol, It is `Type::Unknown`
+    /// until type checker visits the declaration.
+    pub ty: Type,
+}
+
+/// Table of every symbol declared in a chunk, `ReferenceType` of each
+/// resolved `Identifier` is an index into this table.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn declare(
+        &mut self,
+        ident: &Identifier,
+        kind: SymbolKind,
+        mutable: bool,
+        scope: ScopeId,
+    ) -> ReferenceType {
+        let reference = self.symbols.len();
+        self.symbols.push(Symbol {
+            name: ident.name.clone(),
+            span: ident.span,
+            kind,
+            mutable,
+            scope,
+            ty: Type::Unknown,
+        });
+        ident.reference.set(Some(reference));
+        reference
+    }
+
+    pub fn get(&self, reference: ReferenceType) -> &Symbol {
+        &self.symbols[reference]
+    }
+
+    pub(crate) fn get_mut(&mut self, reference: ReferenceType) -> &mut Symbol {
+        &mut self.symbols[reference]
+    }
+
+    /// Get the symbol that given identifier refers to, if it is resolved.
+    pub fn of(&self, ident: &Identifier) -> Option<&Symbol> {
+        ident.reference.get().map(|reference| self.get(reference))
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ReferenceType, &Symbol)> {
+        self.symbols.iter().enumerate()
+    }
+}
@@ -0,0 +1,369 @@
+use std::{collections::HashMap, fmt, rc::Rc};
+
+use fuse_ast::Atom;
+use fuse_common::ReferenceType;
+
+use crate::SymbolTable;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    /// Type of expressions we can't infer, It is compatible with every other type.
+    Unknown,
+    /// Type of expressions that never produce a value.
+    Never,
+    Nil,
+    Number,
+    String,
+    Boolean,
+    Array(Box<Type>),
+    /// A value of the inner type or nil, e.g. `number?`.
+    Optional(Box<Type>),
+    Tuple(Vec<Type>),
+    Function(Rc<FunctionType>),
+    /// Anonymous tables created using table construction expressions.
+    Table,
+    /// A struct type, points to the declaring symbol. Type arguments of generic
+    /// structs are empty when they are used without them, e.g. `Stack`.
+    Struct(ReferenceType, Vec<Type>),
+    /// An enum type, points to the declaring symbol.
+    Enum(ReferenceType),
+    /// A tagged union type, points to the declaring symbol.
+    Union(ReferenceType),
+    /// A type parameter of a generic declaration, points to the declaring symbol.
+    Parameter(ReferenceType),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionType {
+    /// Type parameters of generic functions, They get instantiated at each call.
+    pub type_params: Vec<ReferenceType>,
+    pub params: Vec<Type>,
+    /// Type of the extra arguments of variadic functions, e.g. `...values: number`.
+    pub rest: Option<Type>,
+    pub return_type: Type,
+}
+
+/// Types of the instantiated type parameters, keyed by their symbol's reference.
+pub type Substitution = HashMap<ReferenceType, Type>;
+
+impl Type {
+    pub fn function(params: Vec<Type>, return_type: Type) -> Self {
+        Self::generic_function(Vec::new(), params, return_type)
+    }
+
+    pub fn generic_function(
+        type_params: Vec<ReferenceType>,
+        params: Vec<Type>,
+        return_type: Type,
+    ) -> Self {
+        Self::variadic_function(type_params, params, None, return_type)
+    }
+
+    pub fn variadic_function(
+        type_params: Vec<ReferenceType>,
+        params: Vec<Type>,
+        rest: Option<Type>,
+        return_type: Type,
+    ) -> Self {
+        Self::Function(Rc::from(FunctionType {
+            type_params,
+            params,
+            rest,
+            return_type,
+        }))
+    }
+
+    /// Create an optional type, Types that can already hold nil are returned as is.
+    pub fn optional(inner: Type) -> Self {
+        match inner {
+            Self::Unknown | Self::Nil | Self::Optional(_) => inner,
+            Self::Never => Self::Nil,
+            inner => Self::Optional(Box::from(inner)),
+        }
+    }
+
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Self::Unknown)
+    }
+
+    pub fn is_optional(&self) -> bool {
+        matches!(self, Self::Optional(_))
+    }
+
+    /// Returns the type of the value when it isn't nil.
+    pub fn non_optional(&self) -> &Type {
+        match self {
+            Self::Optional(inner) => inner,
+            ty => ty,
+        }
+    }
+
+    /// Returns true if a value of `source` type can be used where `self` is expected.
+    pub fn is_assignable_from(&self, source: &Type) -> bool {
+        match (self, source) {
+            (Self::Unknown, _) | (_, Self::Unknown) | (_, Self::Never) => true,
+            (Self::Array(target), Self::Array(source)) => target.is_assignable_from(source),
+            (Self::Struct(target, target_args), Self::Struct(source, source_args)) => {
+                target == source
+                    && (target_args.is_empty()
+                        || source_args.is_empty()
+                        || target_args
+                            .iter()
+                            .zip(source_args)
+                            .all(|(target, source)| target.is_assignable_from(source)))
+            }
+            (Self::Optional(_), Self::Nil) => true,
+            (Self::Optional(target), Self::Optional(source)) => target.is_assignable_from(source),
+            (Self::Optional(target), source) => target.is_assignable_from(source),
+            (Self::Tuple(target), Self::Tuple(source)) => {
+                target.len() == source.len()
+                    && target
+                        .iter()
+                        .zip(source)
+                        .all(|(target, source)| target.is_assignable_from(source))
+            }
+            (Self::Function(target), Self::Function(source)) => {
+                target.params.len() == source.params.len()
+                    && target
+                        .params
+                        .iter()
+                        .zip(&source.params)
+                        .all(|(target, source)| source.is_assignable_from(target))
+                    && match (&target.rest, &source.rest) {
+                        (None, None) => true,
+                        (Some(target), Some(source)) => source.is_assignable_from(target),
+                        _ => false,
+                    }
+                    && target.return_type.is_assignable_from(&source.return_type)
+            }
+            (target, source) => target == source,
+        }
+    }
+
+    /// Replace the type parameters with their instantiated types.
+    pub fn substitute(&self, substitution: &Substitution) -> Type {
+        let list = |types: &[Type]| {
+            types
+                .iter()
+                .map(|ty| ty.substitute(substitution))
+                .collect::<Vec<_>>()
+        };
+        match self {
+            Self::Parameter(reference) => match substitution.get(reference) {
+                Some(ty) => ty.clone(),
+                None => self.clone(),
+            },
+            Self::Array(element) => Self::Array(Box::from(element.substitute(substitution))),
+            Self::Optional(inner) => Self::optional(inner.substitute(substitution)),
+            Self::Tuple(elements) => Self::Tuple(list(elements)),
+            Self::Struct(reference, args) => Self::Struct(*reference, list(args)),
+            Self::Function(func) => Self::variadic_function(
+                func.type_params.clone(),
+                list(&func.params),
+                func.rest.as_ref().map(|rest| rest.substitute(substitution)),
+                func.return_type.substitute(substitution),
+            ),
+            ty => ty.clone(),
+        }
+    }
+
+    /// Infer the type parameters used in `self` from a value of the `found` type.
+    pub fn infer(&self, found: &Type, substitution: &mut Substitution) {
+        match (self, found) {
+            (_, Self::Unknown | Self::Never) => {}
+            (Self::Parameter(reference), found) => {
+                substitution
+                    .entry(*reference)
+                    .or_insert_with(|| found.clone());
+            }
+            (Self::Array(target), Self::Array(source))
+            | (Self::Optional(target), Self::Optional(source)) => {
+                target.infer(source, substitution)
+            }
+            (Self::Optional(_), Self::Nil) => {}
+            (Self::Optional(target), source) => target.infer(source, substitution),
+            (Self::Tuple(targets), Self::Tuple(sources))
+            | (Self::Struct(_, targets), Self::Struct(_, sources)) => {
+                for (target, source) in targets.iter().zip(sources) {
+                    target.infer(source, substitution);
+                }
+            }
+            (Self::Function(target), Self::Function(source)) => {
+                for (target, source) in target.params.iter().zip(&source.params) {
+                    target.infer(source, substitution);
+                }
+                if let (Some(target), Some(source)) = (&target.rest, &source.rest) {
+                    target.infer(source, substitution);
+                }
+                target.return_type.infer(&source.return_type, substitution);
+            }
+            _ => {}
+        }
+    }
+
+    /// Join the types of 2 branches into a single type.
+    pub fn join(self, other: Type) -> Type {
+        match (self, other) {
+            (Self::Never, other) | (other, Self::Never) => other,
+            (lhs, rhs) if lhs == rhs => lhs,
+            (Self::Nil, other) | (other, Self::Nil) => Self::optional(other),
+            (Self::Optional(lhs), rhs) | (rhs, Self::Optional(lhs)) if *lhs == rhs => {
+                Self::Optional(lhs)
+            }
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Get a displayable representation of this type, It uses
+    /// the `symbols` table to look up names of declared types.
+    pub fn display<'a>(&'a self, symbols: &'a SymbolTable) -> TypeDisplay<'a> {
+        TypeDisplay { ty: self, symbols }
+    }
+}
+
+pub struct TypeDisplay<'a> {
+    ty: &'a Type,
+    symbols: &'a SymbolTable,
+}
+
+impl<'a> TypeDisplay<'a> {
+    fn with(&self, ty: &'a Type) -> Self {
+        Self {
+            ty,
+            symbols: self.symbols,
+        }
+    }
+
+    fn fmt_list(&self, f: &mut fmt::Formatter<'_>, types: &'a [Type]) -> fmt::Result {
+        for (index, ty) in types.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", self.with(ty))?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for TypeDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.ty {
+            Type::Unknown => write!(f, "unknown"),
+            Type::Never => write!(f, "never"),
+            Type::Nil => write!(f, "nil"),
+            Type::Number => write!(f, "number"),
+            Type::String => write!(f, "string"),
+            Type::Boolean => write!(f, "boolean"),
+            Type::Table => write!(f, "table"),
+            Type::Array(element) => write!(f, "[{}]", self.with(element)),
+            Type::Optional(inner) if matches!(**inner, Type::Function(_)) => {
+                write!(f, "({})?", self.with(inner))
+            }
+            Type::Optional(inner) => write!(f, "{}?", self.with(inner)),
+            Type::Tuple(elements) => {
+                write!(f, "(")?;
+                self.fmt_list(f, elements)?;
+                write!(f, ")")
+            }
+            Type::Function(func) => {
+                write!(f, "fn")?;
+                if !func.type_params.is_empty() {
+                    write!(f, "<")?;
+                    for (index, param) in func.type_params.iter().enumerate() {
+                        if index > 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{}", self.symbols.get(*param).name.as_str())?;
+                    }
+                    write!(f, ">")?;
+                }
+                write!(f, "(")?;
+                self.fmt_list(f, &func.params)?;
+                if let Some(rest) = &func.rest {
+                    if !func.params.is_empty() {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "...{}", self.with(rest))?;
+                }
+                write!(f, ") -> {}", self.with(&func.return_type))
+            }
+            Type::Struct(reference, args) if !args.is_empty() => {
+                write!(f, "{}<", self.symbols.get(*reference).name.as_str())?;
+                self.fmt_list(f, args)?;
+                write!(f, ">")
+            }
+            Type::Struct(reference, _)
+            | Type::Enum(reference)
+            | Type::Union(reference)
+            | Type::Parameter(reference) => {
+                write!(f, "{}", self.symbols.get(*reference).name.as_str())
+            }
+        }
+    }
+}
+
+/// Resolved definition of a declared struct, enum or union type.
+#[derive(Debug)]
+pub struct TypeDefinition {
+    pub kind: TypeDefinitionKind,
+    /// Type parameters of generic structs.
+    pub params: Vec<ReferenceType>,
+    /// Methods implemented for this type using impl statements.
+    pub methods: Vec<ReferenceType>,
+}
+
+#[derive(Debug)]
+pub enum TypeDefinitionKind {
+    Struct(Vec<FieldDefinition>),
+    Enum(Vec<VariantDefinition>),
+    Union(Vec<VariantDefinition>),
+}
+
+#[derive(Debug)]
+pub struct FieldDefinition {
+    pub name: Atom,
+    pub reference: ReferenceType,
+    pub ty: Type,
+}
+
+#[derive(Debug)]
+pub struct VariantDefinition {
+    pub name: Atom,
+    pub reference: ReferenceType,
+    /// Types of the variant's payload, It is always empty for enum variants.
+    pub fields: Vec<Type>,
+}
+
+impl TypeDefinition {
+    pub fn fields(&self) -> &[FieldDefinition] {
+        match &self.kind {
+            TypeDefinitionKind::Struct(fields) => fields,
+            _ => &[],
+        }
+    }
+
+    pub fn variants(&self) -> &[VariantDefinition] {
+        match &self.kind {
+            TypeDefinitionKind::Enum(variants) | TypeDefinitionKind::Union(variants) => variants,
+            _ => &[],
+        }
+    }
+
+    pub fn field(&self, name: &Atom) -> Option<&FieldDefinition> {
+        self.fields().iter().find(|field| &field.name == name)
+    }
+
+    pub fn variant(&self, name: &Atom) -> Option<&VariantDefinition> {
+        self.variants().iter().find(|variant| &variant.name == name)
+    }
+
+    /// Substitution of this type's parameters with the given type
+    /// arguments, Parameters without an argument are `unknown`.
+    pub fn substitution(&self, args: &[Type]) -> Substitution {
+        self.params
+            .iter()
+            .enumerate()
+            .map(|(index, param)| (*param, args.get(index).cloned().unwrap_or(Type::Unknown)))
+            .collect()
+    }
+}
@@ -0,0 +1,141 @@
+use std::{ffi::OsStr, fs, path::PathBuf};
+
+use fuse_parser::parse;
+use fuse_semantic::{Semantic, SemanticResult, LUA_DECLARATIONS};
+
+struct Context<'a> {
+    root: PathBuf,
+    test_dir: PathBuf,
+    source_name: &'a str,
+    settings: insta::Settings,
+}
+
+impl<'a> Context<'a> {
+    fn path(&self) -> PathBuf {
+        self.root.join(&self.test_dir)
+    }
+
+    fn settings(&self) -> insta::Settings {
+        self.settings.clone()
+    }
+}
+
+#[test]
+fn pass() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("pass"),
+        source_name: "case.fuse",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run(&ctx, case, false);
+    }
+}
+
+#[test]
+fn fail() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("fail"),
+        source_name: "case.fuse",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run(&ctx, case, true);
+    }
+}
+
+// helpers
+fn load_cases(ctx: &Context) -> Vec<PathBuf> {
+    fs::read_dir(ctx.path())
+        .unwrap_or_else(|_| panic!("Failed to read {}", ctx.path().to_str().unwrap()))
+        .filter_map(|x| x.ok())
+        .filter(|x| x.metadata().is_ok_and(|meta| meta.is_dir()))
+        .map(|node| node.path())
+        .collect()
+}
+
+fn run(ctx: &Context, case_dir: PathBuf, expect_error: bool) {
+    let mut settings = ctx.settings();
+    let source_path = case_dir.join(ctx.source_name);
+    let path_str = source_path.to_str().unwrap_or("unknown source");
+    let source = read_source_normalized(source_path.as_os_str()).unwrap();
+
+    settings.set_input_file(&source_path);
+    settings.set_snapshot_path(case_dir);
+    // if case source code is small include it in the snapshot.
+    if source.lines().count() <= 5 {
+        settings.set_description(source.clone());
+    }
+    settings.set_prepend_module_to_snapshot(false);
+
+    let _guard = settings.bind_to_scope();
+    test_semantic(path_str, &source, expect_error);
+}
+
+fn test_semantic(path: &str, source: &str, expect_error: bool) {
+    let parsed = parse(source);
+    assert!(
+        parsed.errors.is_empty() && !parsed.paniced,
+        "Failed to parse the test case: {path}\n{:?}",
+        parsed.errors
+    );
+    let chunk = parsed.chunk.unwrap();
+    let result = with_declarations(Semantic::new()).build(&chunk);
+
+    assert_eq!(
+        expect_error,
+        !result.errors.is_empty(),
+        "Error vector is different from expectations: {path}\n{:#?}",
+        result.errors
+    );
+
+    insta::assert_ron_snapshot!("symbols", symbols(&result));
+
+    if expect_error {
+        insta::assert_ron_snapshot!("errors", result.errors);
+    }
+}
+
+/// Dump every symbol with its type, e.g. `Variable(Let) a: number`.
+fn symbols(result: &SemanticResult) -> Vec<String> {
+    result
+        .symbols
+        .iter()
+        .skip(declaration_symbols())
+        .map(|(_, symbol)| {
+            format!(
+                "{:?} {}: {}",
+                symbol.kind,
+                symbol.name.as_str(),
+                symbol.ty.display(&result.symbols)
+            )
+        })
+        .collect()
+}
+
+fn with_declarations(semantic: Semantic) -> Semantic {
+    let parsed = parse(LUA_DECLARATIONS);
+    assert!(parsed.errors.is_empty(), "{:?}", parsed.errors);
+    semantic.with_declarations(&parsed.chunk.unwrap())
+}
+
+/// Number of symbols declared by the declaration files, They come before the symbols of the case.
+fn declaration_symbols() -> usize {
+    let chunk = parse("").chunk.unwrap();
+    with_declarations(Semantic::new())
+        .build(&chunk)
+        .symbols
+        .len()
+}
+
+fn read_source_normalized(path: &OsStr) -> Result<String, std::io::Error> {
+    fs::read_to_string(path).map(|it| it.replace("\r\n", "\n"))
+}
@@ -1,6 +1,6 @@
 use fuse_ast::AstNode;
 
 pub trait NodeVisitor {
-    fn enter_node(&mut self, node: AstNode) {}
-    fn leave_node(&mut self, node: AstNode) {}
+    fn enter_node(&mut self, _node: AstNode) {}
+    fn leave_node(&mut self, _node: AstNode) {}
 }
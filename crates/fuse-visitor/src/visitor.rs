@@ -75,6 +75,61 @@ pub trait Visitor<'ast>: ScopeVisitor + NodeVisitor + Sized {
         self.leave_node(node);
     }
 
+    fn visit_impl_statement(&mut self, r#impl: &'ast ImplStatement) {
+        visit_scope!(self => {
+            let node = AstNode::ImplStatement(r#impl);
+            self.enter_node(node);
+            walk_impl_statement(self, r#impl);
+            self.leave_node(node);
+        });
+    }
+
+    fn visit_impl_method(&mut self, method: &'ast ImplMethod) {
+        visit_scope!(self => {
+            let node = AstNode::ImplMethod(method);
+            self.enter_node(node);
+            walk_impl_method(self, method);
+            self.leave_node(node);
+        });
+    }
+
+    fn visit_type_alias_declaration(&mut self, decl: &'ast TypeAliasDeclaration) {
+        let node = AstNode::TypeAliasDeclaration(decl);
+        self.enter_node(node);
+        walk_type_alias_declaration(self, decl);
+        self.leave_node(node);
+    }
+
+    fn visit_union_declaration(&mut self, decl: &'ast UnionDeclaration) {
+        visit_scope!(self => {
+            let node = AstNode::UnionDeclaration(decl);
+            self.enter_node(node);
+            walk_union_declaration(self, decl);
+            self.leave_node(node);
+        });
+    }
+
+    fn visit_union_variant(&mut self, var: &'ast UnionVariant) {
+        let node = AstNode::UnionVariant(var);
+        self.enter_node(node);
+        walk_union_variant(self, var);
+        self.leave_node(node);
+    }
+
+    fn visit_extern_declaration(&mut self, decl: &'ast ExternDeclaration) {
+        let node = AstNode::ExternDeclaration(decl);
+        self.enter_node(node);
+        walk_extern_declaration(self, decl);
+        self.leave_node(node);
+    }
+
+    fn visit_extern_item(&mut self, item: &'ast ExternItem) {
+        let node = AstNode::ExternItem(item);
+        self.enter_node(node);
+        walk_extern_item(self, item);
+        self.leave_node(node);
+    }
+
     fn visit_visibility_modifier(&mut self, vis: &'ast VisibilityModifier) {
         let node = AstNode::VisibilityModifier(vis);
         self.enter_node(node);
@@ -94,6 +149,7 @@ pub trait Visitor<'ast>: ScopeVisitor + NodeVisitor + Sized {
     fn visit_string_literal(&mut self, lit: &'ast StringLiteral) {
         let node = AstNode::StringLiteral(lit);
         self.enter_node(node);
+        walk_string_literal(self, lit);
         self.leave_node(node);
     }
 
@@ -103,6 +159,12 @@ pub trait Visitor<'ast>: ScopeVisitor + NodeVisitor + Sized {
         self.leave_node(node);
     }
 
+    fn visit_nil_literal(&mut self, lit: &'ast NilLiteral) {
+        let node = AstNode::NilLiteral(lit);
+        self.enter_node(node);
+        self.leave_node(node);
+    }
+
     fn visit_identifier(&mut self, ident: &'ast Identifier) {
         let node = AstNode::Identifier(ident);
         self.enter_node(node);
@@ -164,6 +226,36 @@ pub trait Visitor<'ast>: ScopeVisitor + NodeVisitor + Sized {
         });
     }
 
+    fn visit_match(&mut self, r#match: &'ast Match) {
+        let node = AstNode::Match(r#match);
+        self.enter_node(node);
+        walk_match(self, r#match);
+        self.leave_node(node);
+    }
+
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        visit_scope!(self => {
+            let node = AstNode::MatchArm(arm);
+            self.enter_node(node);
+            walk_match_arm(self, arm);
+            self.leave_node(node);
+        });
+    }
+
+    fn visit_pattern(&mut self, pattern: &'ast Pattern) {
+        let node = AstNode::Pattern(pattern);
+        self.enter_node(node);
+        walk_pattern(self, pattern);
+        self.leave_node(node);
+    }
+
+    fn visit_variant_pattern(&mut self, pattern: &'ast VariantPattern) {
+        let node = AstNode::VariantPattern(pattern);
+        self.enter_node(node);
+        walk_variant_pattern(self, pattern);
+        self.leave_node(node);
+    }
+
     fn visit_unary_operator(&mut self, op: &'ast UnaryOperator) {
         let node = AstNode::UnaryOperator(op);
         self.enter_node(node);
@@ -185,6 +277,20 @@ pub trait Visitor<'ast>: ScopeVisitor + NodeVisitor + Sized {
         self.leave_node(node);
     }
 
+    fn visit_tuple_expression(&mut self, tuple: &'ast TupleExpression) {
+        let node = AstNode::TupleExpression(tuple);
+        self.enter_node(node);
+        walk_tuple_expression(self, tuple);
+        self.leave_node(node);
+    }
+
+    fn visit_tuple_expression_element(&mut self, element: &'ast TupleExpressionElement) {
+        let node = AstNode::TupleExpressionElement(element);
+        self.enter_node(node);
+        walk_tuple_expression_element(self, element);
+        self.leave_node(node);
+    }
+
     fn visit_parenthesized_expression(&mut self, expr: &'ast ParenthesizedExpression) {
         let node = AstNode::ParenthesizedExpression(expr);
         self.enter_node(node);
@@ -296,6 +402,13 @@ pub trait Visitor<'ast>: ScopeVisitor + NodeVisitor + Sized {
         walk_type_annotation(self, annotation);
         self.leave_node(node);
     }
+
+    fn visit_type_parameter(&mut self, param: &'ast TypeParameter) {
+        let node = AstNode::TypeParameter(param);
+        self.enter_node(node);
+        walk_type_parameter(self, param);
+        self.leave_node(node);
+    }
 }
 
 pub fn walk_chunk<'ast, V: Visitor<'ast>>(visitor: &mut V, chunk: &'ast Chunk) {
@@ -313,9 +426,13 @@ pub fn walk_statement<'ast, V: Visitor<'ast>>(visitor: &mut V, statement: &'ast
         Statement::VariableDeclaration(decl) => visit!(visitor.visit_variable_declaration(decl)),
         Statement::FunctionDeclaration(func) => visit!(visitor.visit_function_declaration(func)),
         Statement::EnumDeclaration(decl) => visit!(visitor.visit_enum_declaration(decl)),
-        // Statement::StructDeclaration(Box<StructDeclaration>),
-        // Statement::ImplStatement(Box<ImplStatement>),
-        _ => todo!(),
+        Statement::StructDeclaration(decl) => visit!(visitor.visit_struct_declaration(decl)),
+        Statement::ImplStatement(r#impl) => visit!(visitor.visit_impl_statement(r#impl)),
+        Statement::TypeAliasDeclaration(decl) => {
+            visit!(visitor.visit_type_alias_declaration(decl))
+        }
+        Statement::UnionDeclaration(decl) => visit!(visitor.visit_union_declaration(decl)),
+        Statement::ExternDeclaration(decl) => visit!(visitor.visit_extern_declaration(decl)),
     }
 }
 
@@ -334,13 +451,15 @@ pub fn walk_expression<'ast, V: Visitor<'ast>>(visitor: &mut V, expression: &'as
         Expression::NumberLiteral(expr) => visit!(visitor.visit_number_literal(expr)),
         Expression::StringLiteral(expr) => visit!(visitor.visit_string_literal(expr)),
         Expression::BooleanLiteral(expr) => visit!(visitor.visit_boolean_literal(expr)),
+        Expression::NilLiteral(expr) => visit!(visitor.visit_nil_literal(expr)),
         Expression::Identifier(expr) => visit!(visitor.visit_identifier(expr)),
         Expression::Function(expr) => visit!(visitor.visit_function_expression(expr)),
         Expression::If(expr) => visit!(visitor.visit_if(expr)),
+        Expression::Match(expr) => visit!(visitor.visit_match(expr)),
         Expression::UnaryOperator(expr) => visit!(visitor.visit_unary_operator(expr)),
         Expression::BinaryOperator(expr) => visit!(visitor.visit_binary_operator(expr)),
         Expression::ArrayExpression(expr) => visit!(visitor.visit_array_expression(expr)),
-        Expression::TupleExpression(..) => todo!(),
+        Expression::TupleExpression(expr) => visit!(visitor.visit_tuple_expression(expr)),
         Expression::ParenthesizedExpression(expr) => {
             visit!(visitor.visit_parenthesized_expression(expr))
         }
@@ -369,6 +488,7 @@ pub fn walk_function_signature<'ast, V: Visitor<'ast>>(
     if let Some(ident) = &sign.identifier {
         visit!(visitor.visit_identifier(ident));
     }
+    visit_list!(visitor.visit_type_parameter(&sign.type_parameters));
     visit!(visitor.visit_function_parameters(&sign.params));
     if let Some(annotation) = &sign.return_type {
         visit!(visitor.visit_type_annotation(annotation));
@@ -418,6 +538,7 @@ pub fn walk_struct_declaration<'ast, V: Visitor<'ast>>(
     decl: &'ast StructDeclaration,
 ) {
     visit!(visitor.visit_identifier(&decl.identifier));
+    visit_list!(visitor.visit_type_parameter(&decl.type_parameters));
     visit_list!(visitor.visit_struct_field(&decl.fields));
 }
 
@@ -458,6 +579,14 @@ pub fn walk_array_expression<'ast, V: Visitor<'ast>>(
     visit_list!(visitor.visit_array_expression_element(&array.elements))
 }
 
+pub fn walk_string_literal<'ast, V: Visitor<'ast>>(visitor: &mut V, lit: &'ast StringLiteral) {
+    for segment in &lit.segments {
+        if let StringSegment::Interpolated(interpolated) = segment {
+            visit!(visitor.visit_expression(&interpolated.expression));
+        }
+    }
+}
+
 pub fn walk_parenthesized_expression<'ast, V: Visitor<'ast>>(
     visitor: &mut V,
     expr: &'ast ParenthesizedExpression,
@@ -500,7 +629,7 @@ pub fn walk_member_expression_rhs<'ast, V: Visitor<'ast>>(
     match rhs {
         MemberExpressionRHS::Identifier(ident) => visit!(visitor.visit_identifier(ident)),
         MemberExpressionRHS::Call(call) => visit!(visitor.visit_call_expression(call)),
-        MemberExpressionRHS::Number(_) => todo!("support for numeric access to tuple fields."),
+        MemberExpressionRHS::Number(num) => visit!(visitor.visit_number_literal(num)),
         MemberExpressionRHS::Member(member) => visit!(visitor.visit_member_expression(member)),
     }
 }
@@ -546,7 +675,10 @@ pub fn walk_binding_pattern<'ast, V: Visitor<'ast>>(
         BindingPatternKind::Identifier(patt) => {
             visit!(visitor.visit_binding_identifier(patt))
         }
-        _ => todo!(),
+        BindingPatternKind::Tuple => {}
+    }
+    if let Some(annotation) = &pattern.type_annotation {
+        visit!(visitor.visit_type_annotation(annotation));
     }
 }
 
@@ -580,7 +712,131 @@ pub fn walk_type_annotation<'ast, V: Visitor<'ast>>(
     visitor: &mut V,
     annotation: &'ast TypeAnnotation,
 ) {
-    println!("not implemented.")
+    match &annotation.kind {
+        TypeAnnotationKind::Identifier(ident) => visit!(visitor.visit_identifier(ident)),
+        TypeAnnotationKind::Tuple(elements) => {
+            visit_list!(visitor.visit_type_annotation(elements))
+        }
+        TypeAnnotationKind::Array(element) | TypeAnnotationKind::Optional(element) => {
+            visit!(visitor.visit_type_annotation(element))
+        }
+        TypeAnnotationKind::Function(func) => {
+            visit_list!(visitor.visit_type_annotation(&func.params));
+            if let Some(return_type) = &func.return_type {
+                visit!(visitor.visit_type_annotation(return_type));
+            }
+        }
+        TypeAnnotationKind::Generic(generic) => {
+            visit!(visitor.visit_identifier(&generic.identifier));
+            visit_list!(visitor.visit_type_annotation(&generic.arguments));
+        }
+    }
+}
+
+pub fn walk_type_parameter<'ast, V: Visitor<'ast>>(visitor: &mut V, param: &'ast TypeParameter) {
+    visit!(visitor.visit_identifier(&param.identifier));
+    visit_list!(visitor.visit_type_annotation(&param.bounds));
 }
 
-pub fn walk_template<'ast, V: Visitor<'ast>>(visitor: &mut V, expression: &'ast Expression) {}
+pub fn walk_impl_statement<'ast, V: Visitor<'ast>>(visitor: &mut V, r#impl: &'ast ImplStatement) {
+    visit!(visitor.visit_type_annotation(&r#impl.r#trait));
+    visit!(visitor.visit_type_annotation(&r#impl.target));
+    visit_list!(visitor.visit_impl_method(&r#impl.methods));
+}
+
+pub fn walk_impl_method<'ast, V: Visitor<'ast>>(visitor: &mut V, method: &'ast ImplMethod) {
+    visit!(visitor.visit_visibility_modifier(&method.modifier));
+    walk_function(visitor, &method.function);
+}
+
+pub fn walk_type_alias_declaration<'ast, V: Visitor<'ast>>(
+    visitor: &mut V,
+    decl: &'ast TypeAliasDeclaration,
+) {
+    visit!(visitor.visit_identifier(&decl.identifier));
+    visit!(visitor.visit_type_annotation(&decl.annotation));
+}
+
+pub fn walk_union_declaration<'ast, V: Visitor<'ast>>(
+    visitor: &mut V,
+    decl: &'ast UnionDeclaration,
+) {
+    visit!(visitor.visit_identifier(&decl.identifier));
+    visit_list!(visitor.visit_union_variant(&decl.variants));
+}
+
+pub fn walk_extern_declaration<'ast, V: Visitor<'ast>>(
+    visitor: &mut V,
+    decl: &'ast ExternDeclaration,
+) {
+    visit!(visitor.visit_extern_item(&decl.item));
+}
+
+pub fn walk_extern_item<'ast, V: Visitor<'ast>>(visitor: &mut V, item: &'ast ExternItem) {
+    match item {
+        ExternItem::Function(sign) => {
+            visit_scope!(visitor => visit!(visitor.visit_function_signature(sign)));
+        }
+        ExternItem::Global(global) => {
+            visit!(visitor.visit_identifier(&global.identifier));
+            visit!(visitor.visit_type_annotation(&global.type_annotation));
+        }
+        ExternItem::Table(table) => {
+            visit!(visitor.visit_identifier(&table.identifier));
+            visit_list!(visitor.visit_extern_item(&table.items));
+        }
+    }
+}
+
+pub fn walk_union_variant<'ast, V: Visitor<'ast>>(visitor: &mut V, var: &'ast UnionVariant) {
+    visit!(visitor.visit_identifier(&var.identifier));
+    visit_list!(visitor.visit_type_annotation(&var.fields));
+}
+
+pub fn walk_match<'ast, V: Visitor<'ast>>(visitor: &mut V, r#match: &'ast Match) {
+    visit!(visitor.visit_expression(&r#match.expression));
+    visit_list!(visitor.visit_match_arm(&r#match.arms));
+    if let Some(r#else) = &r#match.r#else {
+        visit!(visitor.visit_block(r#else));
+    }
+}
+
+pub fn walk_match_arm<'ast, V: Visitor<'ast>>(visitor: &mut V, arm: &'ast MatchArm) {
+    visit!(visitor.visit_pattern(&arm.pattern));
+    visit!(visitor.visit_block(&arm.body));
+}
+
+pub fn walk_pattern<'ast, V: Visitor<'ast>>(visitor: &mut V, pattern: &'ast Pattern) {
+    match pattern {
+        Pattern::Wildcard(_) => {}
+        Pattern::Literal(expr) => visit!(visitor.visit_expression(expr)),
+        Pattern::Binding(binding) => visit!(visitor.visit_binding_identifier(binding)),
+        Pattern::Variant(variant) => visit!(visitor.visit_variant_pattern(variant)),
+    }
+}
+
+pub fn walk_variant_pattern<'ast, V: Visitor<'ast>>(
+    visitor: &mut V,
+    pattern: &'ast VariantPattern,
+) {
+    visit!(visitor.visit_identifier(&pattern.target));
+    visit!(visitor.visit_identifier(&pattern.variant));
+    visit_list!(visitor.visit_pattern(&pattern.fields));
+}
+
+pub fn walk_tuple_expression<'ast, V: Visitor<'ast>>(
+    visitor: &mut V,
+    tuple: &'ast TupleExpression,
+) {
+    visit_list!(visitor.visit_tuple_expression_element(&tuple.elements))
+}
+
+pub fn walk_tuple_expression_element<'ast, V: Visitor<'ast>>(
+    visitor: &mut V,
+    element: &'ast TupleExpressionElement,
+) {
+    match element {
+        TupleExpressionElement::Expression(expr) => visit!(visitor.visit_expression(expr)),
+        TupleExpressionElement::Spread(spread) => visit!(visitor.visit_spread_argument(spread)),
+    }
+}
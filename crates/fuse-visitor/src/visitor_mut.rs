@@ -38,6 +38,34 @@ pub trait VisitorMut<'ast>: ScopeVisitor + Sized {
         walk_struct_field_mut(self, field)
     }
 
+    fn visit_impl_statement_mut(&mut self, r#impl: &'ast mut ImplStatement) {
+        walk_impl_statement_mut(self, r#impl)
+    }
+
+    fn visit_impl_method_mut(&mut self, method: &'ast mut ImplMethod) {
+        walk_impl_method_mut(self, method)
+    }
+
+    fn visit_type_alias_declaration_mut(&mut self, decl: &'ast mut TypeAliasDeclaration) {
+        walk_type_alias_declaration_mut(self, decl)
+    }
+
+    fn visit_union_declaration_mut(&mut self, decl: &'ast mut UnionDeclaration) {
+        walk_union_declaration_mut(self, decl)
+    }
+
+    fn visit_union_variant_mut(&mut self, var: &'ast mut UnionVariant) {
+        walk_union_variant_mut(self, var)
+    }
+
+    fn visit_extern_declaration_mut(&mut self, decl: &'ast mut ExternDeclaration) {
+        walk_extern_declaration_mut(self, decl)
+    }
+
+    fn visit_extern_item_mut(&mut self, item: &'ast mut ExternItem) {
+        walk_extern_item_mut(self, item)
+    }
+
     fn visit_visibility_modifier_mut(&mut self, _: &'ast mut VisibilityModifier) {}
 
     fn visit_expression_mut(&mut self, expression: &'ast mut Expression) {
@@ -46,10 +74,14 @@ pub trait VisitorMut<'ast>: ScopeVisitor + Sized {
 
     fn visit_number_literal_mut(&mut self, _: &'ast mut NumberLiteral) {}
 
-    fn visit_string_literal_mut(&mut self, _: &'ast mut StringLiteral) {}
+    fn visit_string_literal_mut(&mut self, lit: &'ast mut StringLiteral) {
+        walk_string_literal_mut(self, lit)
+    }
 
     fn visit_boolean_literal_mut(&mut self, _: &'ast mut BooleanLiteral) {}
 
+    fn visit_nil_literal_mut(&mut self, _: &'ast mut NilLiteral) {}
+
     fn visit_identifier_mut(&mut self, _: &'ast mut Identifier) {}
 
     fn visit_function_mut(&mut self, func: &'ast mut Function) {
@@ -80,6 +112,22 @@ pub trait VisitorMut<'ast>: ScopeVisitor + Sized {
         walk_else_mut(self, r#else)
     }
 
+    fn visit_match_mut(&mut self, r#match: &'ast mut Match) {
+        walk_match_mut(self, r#match)
+    }
+
+    fn visit_match_arm_mut(&mut self, arm: &'ast mut MatchArm) {
+        walk_match_arm_mut(self, arm)
+    }
+
+    fn visit_pattern_mut(&mut self, pattern: &'ast mut Pattern) {
+        walk_pattern_mut(self, pattern)
+    }
+
+    fn visit_variant_pattern_mut(&mut self, pattern: &'ast mut VariantPattern) {
+        walk_variant_pattern_mut(self, pattern)
+    }
+
     fn visit_unary_operator_mut(&mut self, op: &'ast mut UnaryOperator) {
         walk_unary_operator_mut(self, op)
     }
@@ -92,6 +140,14 @@ pub trait VisitorMut<'ast>: ScopeVisitor + Sized {
         walk_array_expression_mut(self, array)
     }
 
+    fn visit_tuple_expression_mut(&mut self, tuple: &'ast mut TupleExpression) {
+        walk_tuple_expression_mut(self, tuple)
+    }
+
+    fn visit_tuple_expression_element_mut(&mut self, element: &'ast mut TupleExpressionElement) {
+        walk_tuple_expression_element_mut(self, element)
+    }
+
     fn visit_parenthesized_expression_mut(&mut self, expr: &'ast mut ParenthesizedExpression) {
         walk_parenthesized_expression_mut(self, expr)
     }
@@ -158,6 +214,10 @@ pub trait VisitorMut<'ast>: ScopeVisitor + Sized {
     fn visit_type_annotation_mut(&mut self, annotation: &'ast mut TypeAnnotation) {
         walk_type_annotation_mut(self, annotation)
     }
+
+    fn visit_type_parameter_mut(&mut self, param: &'ast mut TypeParameter) {
+        walk_type_parameter_mut(self, param)
+    }
 }
 
 pub fn walk_block_mut<'ast, V: VisitorMut<'ast>>(visitor: &mut V, block: &'ast mut Block) {
@@ -180,9 +240,13 @@ pub fn walk_statement_mut<'ast, V: VisitorMut<'ast>>(
             visit!(visitor.visit_function_declaration_mut(func))
         }
         Statement::EnumDeclaration(decl) => visit!(visitor.visit_enum_declaration_mut(decl)),
-        // Statement::StructDeclaration(Box<StructDeclaration>),
-        // Statement::ImplStatement(Box<ImplStatement>),
-        _ => todo!(),
+        Statement::StructDeclaration(decl) => visit!(visitor.visit_struct_declaration_mut(decl)),
+        Statement::ImplStatement(r#impl) => visit!(visitor.visit_impl_statement_mut(r#impl)),
+        Statement::TypeAliasDeclaration(decl) => {
+            visit!(visitor.visit_type_alias_declaration_mut(decl))
+        }
+        Statement::UnionDeclaration(decl) => visit!(visitor.visit_union_declaration_mut(decl)),
+        Statement::ExternDeclaration(decl) => visit!(visitor.visit_extern_declaration_mut(decl)),
     }
 }
 
@@ -204,13 +268,15 @@ pub fn walk_expression_mut<'ast, V: VisitorMut<'ast>>(
         Expression::NumberLiteral(expr) => visit!(visitor.visit_number_literal_mut(expr)),
         Expression::StringLiteral(expr) => visit!(visitor.visit_string_literal_mut(expr)),
         Expression::BooleanLiteral(expr) => visit!(visitor.visit_boolean_literal_mut(expr)),
+        Expression::NilLiteral(expr) => visit!(visitor.visit_nil_literal_mut(expr)),
         Expression::Identifier(expr) => visit!(visitor.visit_identifier_mut(expr)),
         Expression::Function(expr) => visit!(visitor.visit_function_mut(expr)),
         Expression::If(expr) => visit!(visitor.visit_if_mut(expr)),
+        Expression::Match(expr) => visit!(visitor.visit_match_mut(expr)),
         Expression::UnaryOperator(expr) => visit!(visitor.visit_unary_operator_mut(expr)),
         Expression::BinaryOperator(expr) => visit!(visitor.visit_binary_operator_mut(expr)),
         Expression::ArrayExpression(expr) => visit!(visitor.visit_array_expression_mut(expr)),
-        Expression::TupleExpression(..) => todo!(),
+        Expression::TupleExpression(expr) => visit!(visitor.visit_tuple_expression_mut(expr)),
         Expression::ParenthesizedExpression(expr) => {
             visit!(visitor.visit_parenthesized_expression_mut(expr))
         }
@@ -241,6 +307,7 @@ pub fn walk_function_signature_mut<'ast, V: VisitorMut<'ast>>(
     if let Some(ident) = &mut sign.identifier {
         visit!(visitor.visit_identifier_mut(ident));
     }
+    visit_list!(visitor.visit_type_parameter_mut(&mut sign.type_parameters));
     visit!(visitor.visit_function_parameters_mut(&mut sign.params));
     if let Some(annotation) = &mut sign.return_type {
         visit!(visitor.visit_type_annotation_mut(annotation));
@@ -302,6 +369,7 @@ pub fn walk_struct_declaration_mut<'ast, V: VisitorMut<'ast>>(
 ) {
     visit_scope!(visitor => {
         visit!(visitor.visit_identifier_mut(&mut decl.identifier));
+        visit_list!(visitor.visit_type_parameter_mut(&mut decl.type_parameters));
         visit_list!(visitor.visit_struct_field_mut(&mut decl.fields));
     });
 }
@@ -356,6 +424,17 @@ pub fn walk_array_expression_mut<'ast, V: VisitorMut<'ast>>(
     visit_list!(visitor.visit_array_expression_element_mut(&mut array.elements))
 }
 
+pub fn walk_string_literal_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    lit: &'ast mut StringLiteral,
+) {
+    for segment in &mut lit.segments {
+        if let StringSegment::Interpolated(interpolated) = segment {
+            visit!(visitor.visit_expression_mut(&mut interpolated.expression));
+        }
+    }
+}
+
 pub fn walk_parenthesized_expression_mut<'ast, V: VisitorMut<'ast>>(
     visitor: &mut V,
     expr: &'ast mut ParenthesizedExpression,
@@ -398,7 +477,7 @@ pub fn walk_member_expression_rhs_mut<'ast, V: VisitorMut<'ast>>(
     match rhs {
         MemberExpressionRHS::Identifier(ident) => visit!(visitor.visit_identifier_mut(ident)),
         MemberExpressionRHS::Call(call) => visit!(visitor.visit_call_expression_mut(call)),
-        MemberExpressionRHS::Number(_) => todo!("support for numeric access to tuple fields."),
+        MemberExpressionRHS::Number(num) => visit!(visitor.visit_number_literal_mut(num)),
         MemberExpressionRHS::Member(member) => visit!(visitor.visit_member_expression_mut(member)),
     }
 }
@@ -447,7 +526,10 @@ pub fn walk_binding_pattern_mut<'ast, V: VisitorMut<'ast>>(
         BindingPatternKind::Identifier(patt) => {
             visit!(visitor.visit_binding_identifier_mut(patt))
         }
-        _ => todo!(),
+        BindingPatternKind::Tuple => {}
+    }
+    if let Some(annotation) = &mut pattern.type_annotation {
+        visit!(visitor.visit_type_annotation_mut(annotation));
     }
 }
 
@@ -487,5 +569,154 @@ pub fn walk_type_annotation_mut<'ast, V: VisitorMut<'ast>>(
     visitor: &mut V,
     annotation: &'ast mut TypeAnnotation,
 ) {
-    println!("not implemented.")
+    match &mut annotation.kind {
+        TypeAnnotationKind::Identifier(ident) => visit!(visitor.visit_identifier_mut(ident)),
+        TypeAnnotationKind::Tuple(elements) => {
+            visit_list!(visitor.visit_type_annotation_mut(elements))
+        }
+        TypeAnnotationKind::Array(element) | TypeAnnotationKind::Optional(element) => {
+            visit!(visitor.visit_type_annotation_mut(element))
+        }
+        TypeAnnotationKind::Function(func) => {
+            visit_list!(visitor.visit_type_annotation_mut(&mut func.params));
+            if let Some(return_type) = &mut func.return_type {
+                visit!(visitor.visit_type_annotation_mut(return_type));
+            }
+        }
+        TypeAnnotationKind::Generic(generic) => {
+            visit!(visitor.visit_identifier_mut(&mut generic.identifier));
+            visit_list!(visitor.visit_type_annotation_mut(&mut generic.arguments));
+        }
+    }
+}
+
+pub fn walk_type_parameter_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    param: &'ast mut TypeParameter,
+) {
+    visit!(visitor.visit_identifier_mut(&mut param.identifier));
+    visit_list!(visitor.visit_type_annotation_mut(&mut param.bounds));
+}
+
+pub fn walk_impl_statement_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    r#impl: &'ast mut ImplStatement,
+) {
+    visit_scope!(visitor => {
+        visit!(visitor.visit_type_annotation_mut(&mut r#impl.r#trait));
+        visit!(visitor.visit_type_annotation_mut(&mut r#impl.target));
+        visit_list!(visitor.visit_impl_method_mut(&mut r#impl.methods));
+    });
+}
+
+pub fn walk_impl_method_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    method: &'ast mut ImplMethod,
+) {
+    visit!(visitor.visit_visibility_modifier_mut(&mut method.modifier));
+    visit!(visitor.visit_function_mut(&mut method.function));
+}
+
+pub fn walk_type_alias_declaration_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    decl: &'ast mut TypeAliasDeclaration,
+) {
+    visit!(visitor.visit_identifier_mut(&mut decl.identifier));
+    visit!(visitor.visit_type_annotation_mut(&mut decl.annotation));
+}
+
+pub fn walk_union_declaration_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    decl: &'ast mut UnionDeclaration,
+) {
+    visit_scope!(visitor => {
+        visit!(visitor.visit_identifier_mut(&mut decl.identifier));
+        visit_list!(visitor.visit_union_variant_mut(&mut decl.variants));
+    });
+}
+
+pub fn walk_extern_declaration_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    decl: &'ast mut ExternDeclaration,
+) {
+    visit!(visitor.visit_extern_item_mut(&mut decl.item));
+}
+
+pub fn walk_extern_item_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    item: &'ast mut ExternItem,
+) {
+    match item {
+        ExternItem::Function(sign) => {
+            visit_scope!(visitor => visit!(visitor.visit_function_signature_mut(sign)));
+        }
+        ExternItem::Global(global) => {
+            visit!(visitor.visit_identifier_mut(&mut global.identifier));
+            visit!(visitor.visit_type_annotation_mut(&mut global.type_annotation));
+        }
+        ExternItem::Table(table) => {
+            visit!(visitor.visit_identifier_mut(&mut table.identifier));
+            visit_list!(visitor.visit_extern_item_mut(&mut table.items));
+        }
+    }
+}
+
+pub fn walk_union_variant_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    var: &'ast mut UnionVariant,
+) {
+    visit!(visitor.visit_identifier_mut(&mut var.identifier));
+    visit_list!(visitor.visit_type_annotation_mut(&mut var.fields));
+}
+
+pub fn walk_match_mut<'ast, V: VisitorMut<'ast>>(visitor: &mut V, r#match: &'ast mut Match) {
+    visit!(visitor.visit_expression_mut(&mut r#match.expression));
+    visit_list!(visitor.visit_match_arm_mut(&mut r#match.arms));
+    if let Some(r#else) = &mut r#match.r#else {
+        visit!(visitor.visit_block_mut(r#else));
+    }
+}
+
+pub fn walk_match_arm_mut<'ast, V: VisitorMut<'ast>>(visitor: &mut V, arm: &'ast mut MatchArm) {
+    visit_scope!(visitor => {
+        visit!(visitor.visit_pattern_mut(&mut arm.pattern));
+        visit!(visitor.visit_block_mut(&mut arm.body));
+    });
+}
+
+pub fn walk_pattern_mut<'ast, V: VisitorMut<'ast>>(visitor: &mut V, pattern: &'ast mut Pattern) {
+    match pattern {
+        Pattern::Wildcard(_) => {}
+        Pattern::Literal(expr) => visit!(visitor.visit_expression_mut(expr)),
+        Pattern::Binding(binding) => visit!(visitor.visit_binding_identifier_mut(binding)),
+        Pattern::Variant(variant) => visit!(visitor.visit_variant_pattern_mut(variant)),
+    }
+}
+
+pub fn walk_variant_pattern_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    pattern: &'ast mut VariantPattern,
+) {
+    visit!(visitor.visit_identifier_mut(&mut pattern.target));
+    visit!(visitor.visit_identifier_mut(&mut pattern.variant));
+    visit_list!(visitor.visit_pattern_mut(&mut pattern.fields));
+}
+
+pub fn walk_tuple_expression_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    tuple: &'ast mut TupleExpression,
+) {
+    visit_list!(visitor.visit_tuple_expression_element_mut(&mut tuple.elements))
+}
+
+pub fn walk_tuple_expression_element_mut<'ast, V: VisitorMut<'ast>>(
+    visitor: &mut V,
+    element: &'ast mut TupleExpressionElement,
+) {
+    match element {
+        TupleExpressionElement::Expression(expr) => visit!(visitor.visit_expression_mut(expr)),
+        TupleExpressionElement::Spread(spread) => {
+            visit!(visitor.visit_spread_argument_mut(spread))
+        }
+    }
 }
@@ -0,0 +1,287 @@
+use std::path::PathBuf;
+
+use fusec::{CompileOptions, Emit, LuaTarget, Runtime};
+
+pub const USAGE: &str = "\
+Usage: fuse [OPTIONS] <FILE>
+       fuse build [BUILD OPTIONS] [INPUT]...
+       fuse check [CHECK OPTIONS] [INPUT]...
+       fuse fmt [--check] [INPUT]...
+       fuse lsp
+
+Commands:
+    build                    Compile Fuse files or directories into Lua files, Without
+                             inputs it builds the project of the nearest `fuse.toml`
+    check                    Report the errors of Fuse files or directories without
+                             compiling them, Without inputs it checks the project of the
+                             nearest `fuse.toml` or the current directory
+    fmt                      Format Fuse files or directories in place, Without inputs it
+                             formats the project of the nearest `fuse.toml` or the current
+                             directory
+    lsp                      Run the language server over stdio
+
+Options:
+    --emit=<lua|ir>          What to output, Defaults to `lua`
+    --no-optimize            Skip the optimization passes
+    --inline-threshold=<N>   Inline functions of up to N instructions, Defaults to 8
+    --source-map=<FILE>      Write the source map of the Lua output to FILE
+    --runtime=<MODE>         Get the runtime helpers `inline` or by `require`,
+                             Defaults to `inline`
+    --print-runtime          Print the `fuse_runtime` Lua module
+    --minify                 Shorten the names and remove the whitespace of the Lua output
+    --target=<TARGET>        Lua dialect of the output `lua53`, `lua54` or `luajit`,
+                             Defaults to `lua54`
+    --bundle                 Bundle FILE and the modules it imports into one Lua file
+    -h, --help               Print this message
+
+Build options:
+    --out-dir=<DIR>          Where to write the Lua files, Defaults to `out` or the
+                             `out-dir` of the project
+    --target=<TARGET>        Overrides the `target` of the project
+    --no-optimize, --inline-threshold=<N>, --runtime=<MODE> and --minify
+                             Same as above
+
+Check options:
+    --format <human|json>    How to print the diagnostics, Defaults to `human`
+
+Fmt options:
+    --check                  List the files that aren't formatted instead of rewriting them,
+                             Fails if there are any";
+
+pub enum Command {
+    Help,
+    /// Print the Lua code of the runtime module.
+    Runtime,
+    /// Compile a file and print the output.
+    Compile(CompileArgs),
+    /// Compile files and directories into Lua files.
+    Build(BuildArgs),
+    /// Report the errors of files and directories.
+    Check(CheckArgs),
+    /// Format files and directories.
+    Format(FormatArgs),
+    /// Run the language server.
+    Lsp,
+}
+
+pub struct CompileArgs {
+    pub file: PathBuf,
+    pub options: CompileOptions,
+    /// Where to write the source map of the output.
+    pub source_map: Option<PathBuf>,
+    /// Bundle the file with the modules it imports.
+    pub bundle: bool,
+}
+
+pub struct BuildArgs {
+    /// Fuse files and directories to compile, The project is built if there are none.
+    pub inputs: Vec<PathBuf>,
+    pub out_dir: Option<PathBuf>,
+    /// Target overriding the one of the project.
+    pub target: Option<LuaTarget>,
+    pub options: CompileOptions,
+}
+
+pub struct CheckArgs {
+    /// Fuse files and directories to check, The project is checked if there are none.
+    pub inputs: Vec<PathBuf>,
+    pub format: Format,
+}
+
+pub struct FormatArgs {
+    /// Fuse files and directories to format, The project is formatted if there are none.
+    pub inputs: Vec<PathBuf>,
+    /// Only report the files that aren't formatted.
+    pub check: bool,
+}
+
+/// Format of the diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Human,
+    Json,
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    match args.first().map(String::as_str) {
+        Some("build") => return parse_build_args(&args[1..]),
+        Some("check") => return parse_check_args(&args[1..]),
+        Some("fmt") => return parse_format_args(&args[1..]),
+        Some("lsp") => return parse_lsp_args(&args[1..]),
+        _ => {}
+    }
+
+    let mut file = None;
+    let mut options = CompileOptions::default();
+    let mut source_map = None;
+    let mut bundle = false;
+    for arg in args {
+        if parse_option(arg, &mut options)? {
+            continue;
+        }
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--print-runtime" => return Ok(Command::Runtime),
+            arg if arg.starts_with("--target=") => {
+                options.target = parse_target(&arg["--target=".len()..])?
+            }
+            arg if arg.starts_with("--emit=") => {
+                options.emit = parse_emit(&arg["--emit=".len()..])?
+            }
+            "--bundle" => bundle = true,
+            arg if arg.starts_with("--source-map=") => {
+                source_map = Some(PathBuf::from(&arg["--source-map=".len()..]))
+            }
+            arg if arg.starts_with('-') => return Err(format!("Unknown option \"{arg}\"")),
+            arg if file.is_none() => file = Some(PathBuf::from(arg)),
+            arg => return Err(format!("Unexpected argument \"{arg}\"")),
+        }
+    }
+    let file = file.ok_or_else(|| "Missing the input file".to_string())?;
+    if source_map.is_some() && options.emit != Emit::Lua {
+        return Err("`--source-map` requires `--emit=lua`".to_string());
+    }
+    if bundle && (options.emit != Emit::Lua || source_map.is_some()) {
+        return Err("`--bundle` requires `--emit=lua` and no `--source-map`".to_string());
+    }
+    Ok(Command::Compile(CompileArgs {
+        file,
+        options,
+        source_map,
+        bundle,
+    }))
+}
+
+fn parse_build_args(args: &[String]) -> Result<Command, String> {
+    let mut inputs = Vec::new();
+    let mut out_dir = None;
+    let mut target = None;
+    let mut options = CompileOptions::default();
+    for arg in args {
+        if parse_option(arg, &mut options)? {
+            continue;
+        }
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            arg if arg.starts_with("--out-dir=") => {
+                out_dir = Some(PathBuf::from(&arg["--out-dir=".len()..]))
+            }
+            arg if arg.starts_with("--target=") => {
+                let value = parse_target(&arg["--target=".len()..])?;
+                options.target = value;
+                target = Some(value);
+            }
+            arg if arg.starts_with('-') => return Err(format!("Unknown build option \"{arg}\"")),
+            arg => inputs.push(PathBuf::from(arg)),
+        }
+    }
+    Ok(Command::Build(BuildArgs {
+        inputs,
+        out_dir,
+        target,
+        options,
+    }))
+}
+
+fn parse_check_args(args: &[String]) -> Result<Command, String> {
+    let mut inputs = Vec::new();
+    let mut format = Format::Human;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--format" => {
+                let value = args.next().ok_or("Missing the value of `--format`")?;
+                format = parse_format(value)?
+            }
+            arg if arg.starts_with("--format=") => {
+                format = parse_format(&arg["--format=".len()..])?
+            }
+            arg if arg.starts_with('-') => return Err(format!("Unknown check option \"{arg}\"")),
+            arg => inputs.push(PathBuf::from(arg)),
+        }
+    }
+    Ok(Command::Check(CheckArgs { inputs, format }))
+}
+
+fn parse_format_args(args: &[String]) -> Result<Command, String> {
+    let mut inputs = Vec::new();
+    let mut check = false;
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--check" => check = true,
+            arg if arg.starts_with('-') => return Err(format!("Unknown fmt option \"{arg}\"")),
+            arg => inputs.push(PathBuf::from(arg)),
+        }
+    }
+    Ok(Command::Format(FormatArgs { inputs, check }))
+}
+
+fn parse_lsp_args(args: &[String]) -> Result<Command, String> {
+    match args.first().map(String::as_str) {
+        None => Ok(Command::Lsp),
+        Some("-h" | "--help") => Ok(Command::Help),
+        // Editors pass `--stdio` to servers, It is the only transport.
+        Some("--stdio") if args.len() == 1 => Ok(Command::Lsp),
+        Some(arg) => Err(format!("Unexpected lsp argument \"{arg}\"")),
+    }
+}
+
+/// Parse the options shared by the commands, It returns false if `arg` isn't one of them.
+fn parse_option(arg: &str, options: &mut CompileOptions) -> Result<bool, String> {
+    match arg {
+        "--no-optimize" => options.optimize = false,
+        "--minify" => options.minify = true,
+        arg if arg.starts_with("--inline-threshold=") => {
+            options.inline_threshold = parse_threshold(&arg["--inline-threshold=".len()..])?
+        }
+        arg if arg.starts_with("--runtime=") => {
+            options.runtime = parse_runtime(&arg["--runtime=".len()..])?
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+fn parse_emit(value: &str) -> Result<Emit, String> {
+    match value {
+        "lua" => Ok(Emit::Lua),
+        "ir" => Ok(Emit::Ir),
+        _ => Err(format!(
+            "Invalid value \"{value}\" for `--emit`, Expected `lua` or `ir`"
+        )),
+    }
+}
+
+fn parse_format(value: &str) -> Result<Format, String> {
+    match value {
+        "human" => Ok(Format::Human),
+        "json" => Ok(Format::Json),
+        _ => Err(format!(
+            "Invalid value \"{value}\" for `--format`, Expected `human` or `json`"
+        )),
+    }
+}
+
+fn parse_target(value: &str) -> Result<LuaTarget, String> {
+    LuaTarget::from_name(value).ok_or_else(|| {
+        format!("Invalid value \"{value}\" for `--target`, Expected `lua53`, `lua54` or `luajit`")
+    })
+}
+
+fn parse_runtime(value: &str) -> Result<Runtime, String> {
+    match value {
+        "inline" => Ok(Runtime::Inline),
+        "require" => Ok(Runtime::Require),
+        _ => Err(format!(
+            "Invalid value \"{value}\" for `--runtime`, Expected `inline` or `require`"
+        )),
+    }
+}
+
+fn parse_threshold(value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| {
+        format!("Invalid value \"{value}\" for `--inline-threshold`, Expected a number")
+    })
+}
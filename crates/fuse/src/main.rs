@@ -1,3 +1,260 @@
-fn main() {
-    todo!("Fuse command line isn't ready yet.")
+mod cli;
+
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use cli::{BuildArgs, CheckArgs, Command, CompileArgs, Format, FormatArgs, USAGE};
+use fusec::{
+    build, build_project, bundle, check, compile, compile_with_source_map, format_files,
+    runtime_module, Project, ProjectError,
+};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match cli::parse_args(&args) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match command {
+        Command::Help => {
+            println!("{USAGE}");
+            ExitCode::SUCCESS
+        }
+        Command::Runtime => {
+            print!("{}", runtime_module());
+            ExitCode::SUCCESS
+        }
+        Command::Compile(args) => run_compile(args),
+        Command::Build(args) => run_build(args),
+        Command::Check(args) => run_check(args),
+        Command::Format(args) => run_format(args),
+        Command::Lsp => run_lsp(),
+    }
+}
+
+fn run_compile(args: CompileArgs) -> ExitCode {
+    if args.bundle {
+        return print_output(bundle(&args.file, args.options));
+    }
+
+    let source = match fs::read_to_string(&args.file) {
+        Ok(source) => source,
+        Err(error) => {
+            eprintln!("error: Failed to read {}: {error}", args.file.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let source_name = args.file.to_string_lossy();
+    let Some(source_map) = args.source_map else {
+        let output = compile(&source, args.options);
+        return print_output(output.map_err(|error| error.render(&source, &source_name)));
+    };
+
+    let file = args.file.with_extension("lua");
+    let file = file.file_name().unwrap_or_default().to_string_lossy();
+    let (output, map) = match compile_with_source_map(&source, &source_name, &file, args.options) {
+        Ok(compiled) => compiled,
+        Err(error) => {
+            eprintln!("{}", error.render(&source, &source_name));
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(error) = fs::write(&source_map, map.to_json()) {
+        eprintln!("error: Failed to write {}: {error}", source_map.display());
+        return ExitCode::FAILURE;
+    }
+    print!("{output}");
+    ExitCode::SUCCESS
+}
+
+/// Load the project of the current directory and update its lockfile, Used by the commands
+/// building the project.
+fn find_project() -> Result<Project, ProjectError> {
+    let project = Project::find(Path::new("."))?;
+    project.write_lockfile()?;
+    Ok(project)
+}
+
+fn run_build(args: BuildArgs) -> ExitCode {
+    let (output, out_dir) = if args.inputs.is_empty() {
+        let mut project = match find_project() {
+            Ok(project) => project,
+            Err(error) => {
+                eprintln!("error: {error}");
+                return ExitCode::FAILURE;
+            }
+        };
+        if let Some(out_dir) = args.out_dir {
+            // Given relative to the current directory instead of the manifest.
+            project.manifest.out_dir = std::path::absolute(&out_dir).unwrap_or(out_dir);
+        }
+        if let Some(target) = args.target {
+            project.manifest.target = target;
+        }
+        (build_project(&project, args.options), project.out_dir())
+    } else {
+        let out_dir = args.out_dir.unwrap_or_else(|| PathBuf::from("out"));
+        (build(&args.inputs, &out_dir, args.options), out_dir)
+    };
+    let output = match output {
+        Ok(output) => output,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for failure in &output.failures {
+        eprintln!("{}\n", failure.render());
+    }
+    if !output.is_success() {
+        eprintln!(
+            "error: Failed to compile {} file(s), Wrote {} file(s) to {}",
+            output.failures.len(),
+            output.written.len(),
+            out_dir.display()
+        );
+        return ExitCode::FAILURE;
+    }
+    println!(
+        "Wrote {} file(s) to {}",
+        output.written.len(),
+        out_dir.display()
+    );
+    ExitCode::SUCCESS
+}
+
+fn run_check(args: CheckArgs) -> ExitCode {
+    let inputs = if args.inputs.is_empty() {
+        // Checking doesn't write anything, The lockfile is only updated by builds.
+        match Project::find(Path::new(".")) {
+            Ok(project) => project.all_source_roots(),
+            Err(ProjectError::NotFound(_)) => vec![PathBuf::from(".")],
+            Err(error) => {
+                eprintln!("error: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        args.inputs
+    };
+    let output = match check(&inputs) {
+        Ok(output) => output,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match args.format {
+        Format::Json => println!("{}", output.to_json()),
+        Format::Human => {
+            for failure in &output.failures {
+                eprintln!("{}\n", failure.render());
+            }
+            if output.is_success() {
+                println!("Checked {} file(s), No errors found", output.checked);
+            } else {
+                eprintln!(
+                    "error: Checked {} file(s), Found {} error(s)",
+                    output.checked,
+                    output.error_count()
+                );
+            }
+        }
+    }
+    if output.is_success() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn run_format(args: FormatArgs) -> ExitCode {
+    let inputs = if args.inputs.is_empty() {
+        // The dependencies aren't formatted, So the lockfile is left alone.
+        match Project::find(Path::new(".")) {
+            Ok(project) => project.source_roots(),
+            Err(ProjectError::NotFound(_)) => vec![PathBuf::from(".")],
+            Err(error) => {
+                eprintln!("error: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        args.inputs
+    };
+    let output = match format_files(&inputs, args.check) {
+        Ok(output) => output,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    for failure in &output.failures {
+        eprintln!("{}\n", failure.render());
+    }
+    if args.check {
+        for path in &output.changed {
+            println!("{}", path.display());
+        }
+    }
+    if !output.is_success() {
+        eprintln!(
+            "error: Failed to parse {} file(s), Formatted {} file(s)",
+            output.failures.len(),
+            output.checked - output.failures.len()
+        );
+        return ExitCode::FAILURE;
+    }
+    if args.check && !output.changed.is_empty() {
+        eprintln!(
+            "error: {} of {} file(s) aren't formatted",
+            output.changed.len(),
+            output.checked
+        );
+        return ExitCode::FAILURE;
+    }
+    if args.check {
+        println!("Checked {} file(s), All formatted", output.checked);
+    } else {
+        println!(
+            "Formatted {} file(s), Changed {}",
+            output.checked,
+            output.changed.len()
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+fn run_lsp() -> ExitCode {
+    match fuse_lsp::run_stdio() {
+        Ok(true) => ExitCode::SUCCESS,
+        // Exiting without a shutdown request is an error.
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Print the output of the compilation or its error.
+fn print_output(output: Result<String, impl Display>) -> ExitCode {
+    match output {
+        Ok(output) => {
+            print!("{output}");
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("{error}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,248 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use fuse_codegen::{generate_with_options, runtime_module, Runtime, RUNTIME_MODULE};
+use fuse_resolve::module_path;
+use thiserror::Error as ThisError;
+
+use crate::{
+    codegen_options, compile_module, CompileError, CompileOptions, ModuleError, ModuleGraph,
+    Project,
+};
+
+#[derive(ThisError, Debug)]
+pub enum BuildError {
+    #[error("Failed to read {}: {source}", .path.display())]
+    Read { path: PathBuf, source: io::Error },
+    #[error("Failed to write {}: {source}", .path.display())]
+    Write { path: PathBuf, source: io::Error },
+    #[error("Both {} and {} would be written to {}", .first.display(), .second.display(), .output.display())]
+    Conflict {
+        first: PathBuf,
+        second: PathBuf,
+        output: PathBuf,
+    },
+    #[error(transparent)]
+    Module(#[from] ModuleError),
+}
+
+/// Outcome of a build, Files failing to compile are reported and the others are still written.
+#[derive(Debug, Default)]
+pub struct BuildOutput {
+    /// Lua files written by the build.
+    pub written: Vec<PathBuf>,
+    pub failures: Vec<FailedFile>,
+}
+
+impl BuildOutput {
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Fuse file that failed to compile.
+#[derive(Debug)]
+pub struct FailedFile {
+    pub path: PathBuf,
+    /// Source code of the file.
+    pub source: String,
+    pub error: CompileError,
+}
+
+impl FailedFile {
+    /// Render the diagnostics of the failure.
+    pub fn render(&self) -> String {
+        self.error
+            .render(&self.source, &self.path.to_string_lossy())
+    }
+}
+
+/// Compile Fuse files into Lua files written to `out_dir`, `options.emit` is ignored.
+///
+/// Inputs are Fuse files or project directories, A file is written as `<out_dir>/<name>.lua`
+/// and the Fuse files of a directory keep their path relative to it, e.g. `src/a/b.fuse` is
+/// written to `<out_dir>/a/b.lua` when building `src`. The `fuse_runtime` module is written
+/// along with the files requiring it.
+pub fn build(
+    inputs: &[PathBuf],
+    out_dir: &Path,
+    options: CompileOptions,
+) -> Result<BuildOutput, BuildError> {
+    let mut output = BuildOutput::default();
+    for (path, target) in build_files(inputs, out_dir)? {
+        let source = fs::read_to_string(&path).map_err(|source| BuildError::Read {
+            path: path.clone(),
+            source,
+        })?;
+        let module = match compile_module(&source, options) {
+            Ok(module) => module,
+            Err(error) => {
+                output.failures.push(FailedFile {
+                    path,
+                    source,
+                    error,
+                });
+                continue;
+            }
+        };
+        write(
+            &target,
+            &generate_with_options(&module, codegen_options(options)),
+        )?;
+        output.written.push(target);
+    }
+
+    write_runtime(out_dir, options, &mut output)?;
+    Ok(output)
+}
+
+/// Compile the entries of a project and the modules they import into the output directory of
+/// the project, The settings of the manifest override `options`.
+///
+/// Each module is written to the path Lua's `require` looks it up at, e.g. the module
+/// `utils.strings` is written to `<out_dir>/utils/strings.lua`.
+pub fn build_project(
+    project: &Project,
+    options: CompileOptions,
+) -> Result<BuildOutput, BuildError> {
+    let options = project.options(options);
+    let resolver = project.resolver();
+    let mut graph = ModuleGraph::new(&resolver, options);
+    for entry in &project.manifest.entries {
+        graph.load_entry(entry.clone(), None)?;
+    }
+
+    let out_dir = project.out_dir();
+    let mut output = BuildOutput {
+        written: Vec::new(),
+        failures: graph
+            .failures
+            .into_iter()
+            .map(|(_, failure)| failure)
+            .collect(),
+    };
+    for module in &graph.modules {
+        // Names of loaded modules are always valid.
+        let path = module_path(&module.name).unwrap_or_default();
+        let target = out_dir.join(path).with_extension("lua");
+        write(
+            &target,
+            &generate_with_options(&module.module, codegen_options(options)),
+        )?;
+        output.written.push(target);
+    }
+    write_runtime(&out_dir, options, &mut output)?;
+    Ok(output)
+}
+
+/// Write the `fuse_runtime` module if the written files require it.
+fn write_runtime(
+    out_dir: &Path,
+    options: CompileOptions,
+    output: &mut BuildOutput,
+) -> Result<(), BuildError> {
+    if options.runtime == Runtime::Require && !output.written.is_empty() {
+        let target = out_dir.join(RUNTIME_MODULE).with_extension("lua");
+        write(&target, &runtime_module())?;
+        output.written.push(target);
+    }
+    Ok(())
+}
+
+/// Fuse files of the inputs and the Lua files they are written to.
+fn build_files(inputs: &[PathBuf], out_dir: &Path) -> Result<Vec<(PathBuf, PathBuf)>, BuildError> {
+    let files: Vec<_> = source_files(inputs, Some(out_dir))?
+        .into_iter()
+        .map(|(path, name)| {
+            let target = out_dir.join(name).with_extension("lua");
+            (path, target)
+        })
+        .collect();
+
+    let mut targets: HashMap<&Path, &Path> = HashMap::new();
+    for (path, target) in &files {
+        if let Some(first) = targets.insert(target, path) {
+            return Err(BuildError::Conflict {
+                first: first.to_path_buf(),
+                second: path.clone(),
+                output: target.clone(),
+            });
+        }
+    }
+    Ok(files)
+}
+
+/// Fuse files of the inputs and their name relative to the input, Inputs are files or
+/// directories searched recursively. The files of a directory are sorted and `skip` isn't
+/// searched, e.g. the output directory.
+pub(crate) fn source_files(
+    inputs: &[PathBuf],
+    skip: Option<&Path>,
+) -> Result<Vec<(PathBuf, PathBuf)>, BuildError> {
+    let mut files = Vec::new();
+    for input in inputs {
+        if input.is_dir() {
+            let mut found = Vec::new();
+            find_sources(input, skip, &mut found)?;
+            found.sort();
+            files.extend(found.into_iter().map(|path| {
+                let name = path.strip_prefix(input).unwrap_or(&path).to_path_buf();
+                (path, name)
+            }));
+        } else {
+            let name = PathBuf::from(input.file_name().unwrap_or_default());
+            files.push((input.clone(), name));
+        }
+    }
+    Ok(files)
+}
+
+/// Find the Fuse files of a directory recursively, Hidden directories are skipped.
+fn find_sources(
+    dir: &Path,
+    skip: Option<&Path>,
+    found: &mut Vec<PathBuf>,
+) -> Result<(), BuildError> {
+    let read_error = |source| BuildError::Read {
+        path: dir.to_path_buf(),
+        source,
+    };
+    for entry in fs::read_dir(dir).map_err(read_error)? {
+        let path = entry.map_err(read_error)?.path();
+        let is_hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+        if path.is_dir() {
+            if !is_hidden && !skip.is_some_and(|skip| is_same_path(&path, skip)) {
+                find_sources(&path, skip, found)?;
+            }
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "fuse")
+        {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), BuildError> {
+    let write_error = |source| BuildError::Write {
+        path: path.to_path_buf(),
+        source,
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(write_error)?;
+    }
+    fs::write(path, contents).map_err(write_error)
+}
@@ -0,0 +1,169 @@
+use std::{fs, path::PathBuf};
+
+use serde::Serialize;
+
+use crate::{
+    analyze,
+    build::source_files,
+    diagnostic::{position, Diagnostic},
+    BuildError, FailedFile,
+};
+
+/// Outcome of a check.
+#[derive(Debug, Default)]
+pub struct CheckOutput {
+    /// Number of checked files.
+    pub checked: usize,
+    pub failures: Vec<FailedFile>,
+}
+
+impl CheckOutput {
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// Number of diagnostics of the failed files.
+    pub fn error_count(&self) -> usize {
+        self.failures
+            .iter()
+            .map(|failure| failure.error.diagnostics().len())
+            .sum()
+    }
+
+    /// Serialize the diagnostics for tools, Lines and columns are one based and columns are
+    /// counted in characters.
+    ///
+    /// ```json
+    /// {
+    ///   "checked": 2,
+    ///   "errors": 1,
+    ///   "diagnostics": [{
+    ///     "file": "src/main.fuse",
+    ///     "severity": "error",
+    ///     "message": "Cannot find type \"numbr\"",
+    ///     "span": { "start": 7, "end": 12 },
+    ///     "start": { "line": 1, "column": 8 },
+    ///     "end": { "line": 1, "column": 13 },
+    ///     "notes": []
+    ///   }]
+    /// }
+    /// ```
+    pub fn to_json(&self) -> String {
+        let diagnostics = self
+            .failures
+            .iter()
+            .flat_map(|failure| {
+                let file = failure.path.to_string_lossy().into_owned();
+                failure
+                    .error
+                    .diagnostics()
+                    .into_iter()
+                    .map(move |diagnostic| JsonDiagnostic::new(&diagnostic, &failure.source, &file))
+            })
+            .collect();
+        let output = JsonOutput {
+            checked: self.checked,
+            errors: self.error_count(),
+            diagnostics,
+        };
+        serde_json::to_string_pretty(&output).expect("Failed to serialize the diagnostics.")
+    }
+}
+
+/// Parse and analyze Fuse files without generating code, Inputs are files or project
+/// directories like the inputs of `build`.
+pub fn check(inputs: &[PathBuf]) -> Result<CheckOutput, BuildError> {
+    let mut output = CheckOutput::default();
+    for (path, _) in source_files(inputs, None)? {
+        let source = fs::read_to_string(&path).map_err(|source| BuildError::Read {
+            path: path.clone(),
+            source,
+        })?;
+        output.checked += 1;
+        if let Err(error) = analyze(&source) {
+            output.failures.push(FailedFile {
+                path,
+                source,
+                error,
+            });
+        }
+    }
+    Ok(output)
+}
+
+#[derive(Serialize)]
+struct JsonOutput {
+    checked: usize,
+    errors: usize,
+    diagnostics: Vec<JsonDiagnostic>,
+}
+
+#[derive(Serialize)]
+struct JsonDiagnostic {
+    file: String,
+    severity: &'static str,
+    message: String,
+    #[serde(flatten)]
+    range: JsonRange,
+    notes: Vec<JsonNote>,
+}
+
+impl JsonDiagnostic {
+    fn new(diagnostic: &Diagnostic, source: &str, file: &str) -> Self {
+        Self {
+            file: file.to_string(),
+            severity: "error",
+            message: diagnostic.message.clone(),
+            range: JsonRange::new(source, diagnostic.span.start, diagnostic.span.end),
+            notes: diagnostic
+                .notes
+                .iter()
+                .map(|note| JsonNote {
+                    message: note.message.clone(),
+                    range: JsonRange::new(source, note.span.start, note.span.end),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonNote {
+    message: String,
+    #[serde(flatten)]
+    range: JsonRange,
+}
+
+#[derive(Serialize)]
+struct JsonRange {
+    /// Byte offsets in the source.
+    span: JsonSpan,
+    start: JsonPosition,
+    end: JsonPosition,
+}
+
+impl JsonRange {
+    fn new(source: &str, start: u32, end: u32) -> Self {
+        let position = |offset| {
+            let (line, column) = position(source, offset);
+            JsonPosition { line, column }
+        };
+        Self {
+            span: JsonSpan { start, end },
+            start: position(start),
+            end: position(end),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonSpan {
+    start: u32,
+    end: u32,
+}
+
+#[derive(Serialize)]
+struct JsonPosition {
+    line: usize,
+    column: usize,
+}
@@ -0,0 +1,436 @@
+use fuse_ast::{
+    Chunk, Else, Expression, FunctionBody, Identifier, If, Match, MatchArm, MemberExpression,
+    MemberExpressionLHS, MemberExpressionRHS, Statement, TypeAnnotation, VariableDeclarationKind,
+};
+use fuse_common::ReferenceType;
+use fuse_parser::{lexer::TokenKind, Parser};
+use fuse_semantic::{Semantic, SemanticResult, Substitution, Symbol, SymbolKind, Type};
+use fuse_visitor::{
+    walk_block, walk_expression, walk_function_body, walk_member_expression, walk_statement,
+    walk_type_annotation, NodeVisitor, ScopeVisitor, Visitor,
+};
+
+use crate::parse_declarations;
+
+/// Identifier put at the cursor before parsing, So there is always something to complete.
+const PLACEHOLDER: &str = "__completion";
+
+/// Types without a declaration, They are resolved by name.
+const PRIMITIVE_TYPES: [&str; 4] = ["boolean", "number", "string", "table"];
+
+/// Keywords starting an expression.
+const EXPRESSION_KEYWORDS: [&str; 7] = ["false", "fn", "if", "match", "nil", "not", "true"];
+
+/// Keywords starting a statement that isn't an expression.
+const STATEMENT_KEYWORDS: [&str; 10] = [
+    "const", "enum", "extern", "global", "impl", "let", "local", "struct", "type", "union",
+];
+
+/// A suggestion to insert at the cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub label: String,
+    pub kind: CompletionKind,
+    /// Type of the suggested value if it is known.
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Variable,
+    Constant,
+    Function,
+    Field,
+    Method,
+    Struct,
+    Enum,
+    Union,
+    Variant,
+    TypeAlias,
+    TypeParameter,
+    /// A type without a declaration, e.g. `number`.
+    Primitive,
+    Keyword,
+}
+
+/// Suggest what can be written at the byte `offset` of `source`, Only the suggestions starting
+/// with the identifier before the cursor are returned.
+///
+/// Bindings in scope are suggested for values and types, Fields and methods after a `.`
+/// based on the type of what is accessed and variants after the name of an enum or union.
+/// Broken statements of the source are skipped so they don't get in the way.
+pub fn complete(source: &str, offset: u32) -> Vec<Completion> {
+    complete_with_declarations(source, offset, &parse_declarations())
+}
+
+/// Suggest what can be written at the byte `offset` of `source` like `complete`, With the
+/// declarations already parsed by `parse_declarations`.
+pub fn complete_with_declarations(
+    source: &str,
+    offset: u32,
+    declarations: &Chunk,
+) -> Vec<Completion> {
+    if !source.is_char_boundary(offset as usize) || in_literal(source, offset) {
+        return Vec::new();
+    }
+    let prefix_start = source[..offset as usize]
+        .trim_end_matches(|char: char| char.is_ascii_alphanumeric() || char == '_')
+        .len();
+    let prefix = &source[prefix_start..offset as usize];
+    if prefix.starts_with(|char: char| char.is_ascii_digit()) {
+        return Vec::new();
+    }
+
+    let mut edited = source.to_string();
+    edited.insert_str(offset as usize, PLACEHOLDER);
+    let Some(chunk) = Parser::new(&edited).with_recovery().parse().chunk else {
+        return Vec::new();
+    };
+    let semantic = Semantic::new()
+        .with_declarations(declarations)
+        .with_cursor(offset)
+        .build(&chunk);
+
+    let mut finder = SiteFinder::new(offset);
+    finder.visit_chunk(&chunk);
+    let Some(site) = finder.site else {
+        return Vec::new();
+    };
+    let mut completions = Completions {
+        semantic: &semantic,
+        items: Vec::new(),
+    };
+    match site {
+        Site::Member(member) => completions.members(member),
+        Site::Type => completions.types(),
+        Site::Expression => {
+            completions.values();
+            completions.keywords(&EXPRESSION_KEYWORDS);
+        }
+        Site::Statement(block) => {
+            completions.values();
+            completions.keywords(&EXPRESSION_KEYWORDS);
+            completions.keywords(&STATEMENT_KEYWORDS);
+            completions.keywords(block.terminators());
+        }
+    }
+
+    let mut items = completions.items;
+    items.retain(|item| item.label.starts_with(prefix));
+    items.sort_by(|a, b| a.label.cmp(&b.label));
+    items.dedup_by(|a, b| a.label == b.label);
+    items
+}
+
+/// Returns true if `offset` is in a comment or the text of a string.
+fn in_literal(source: &str, offset: u32) -> bool {
+    // Strings are lexed along with the parser since they can hold expressions.
+    let (_, tokens) = Parser::new(source).with_recovery().parse_with_tokens();
+    tokens
+        .into_iter()
+        .flat_map(|token| {
+            let mut tokens = token.leading_trivia.clone();
+            tokens.push(token.token);
+            tokens.extend(token.trailing_trivia.iter().copied());
+            tokens
+        })
+        .any(|token| {
+            let span = token.span();
+            match token.kind() {
+                // Comments go on up to the end of their line.
+                TokenKind::Comment => span.start < offset && offset <= span.end,
+                TokenKind::StringLiteral
+                | TokenKind::InterpolatedStringHead
+                | TokenKind::InterpolatedStringMiddle
+                | TokenKind::InterpolatedStringTail => span.start < offset && offset < span.end,
+                _ => false,
+            }
+        })
+}
+
+/// Kind of block a statement is in, It decides the keywords that can end it.
+#[derive(Debug, Clone, Copy)]
+enum BlockKind {
+    Chunk,
+    If,
+    MatchArm,
+    Other,
+}
+
+impl BlockKind {
+    fn terminators(self) -> &'static [&'static str] {
+        match self {
+            Self::Chunk => &[],
+            Self::If => &["else", "elseif", "end"],
+            Self::MatchArm => &["else", "end", "when"],
+            Self::Other => &["end"],
+        }
+    }
+}
+
+/// Where the identifier at the cursor is.
+enum Site<'ast> {
+    /// Member of a value or namespace, e.g. `point.x` or `Color.Red`.
+    Member(&'ast MemberExpression),
+    Type,
+    Expression,
+    /// An identifier on its own as a statement of the given block.
+    Statement(BlockKind),
+}
+
+/// Finds the site of the identifier at the cursor, Identifiers declaring something have no site.
+struct SiteFinder<'ast> {
+    cursor: u32,
+    block: BlockKind,
+    in_type: bool,
+    site: Option<Site<'ast>>,
+}
+
+impl<'ast> SiteFinder<'ast> {
+    fn new(cursor: u32) -> Self {
+        Self {
+            cursor,
+            block: BlockKind::Chunk,
+            in_type: false,
+            site: None,
+        }
+    }
+
+    fn at_cursor(&self, ident: &Identifier) -> bool {
+        self.site.is_none() && ident.span.start <= self.cursor && self.cursor <= ident.span.end
+    }
+
+    fn in_block<F: FnOnce(&mut Self)>(&mut self, block: BlockKind, f: F) {
+        let outer = std::mem::replace(&mut self.block, block);
+        f(self);
+        self.block = outer;
+    }
+}
+
+impl<'ast> Visitor<'ast> for SiteFinder<'ast> {
+    fn visit_chunk(&mut self, chunk: &'ast Chunk) {
+        self.in_block(BlockKind::Chunk, |this| walk_block(this, &chunk.body));
+    }
+
+    fn visit_statement(&mut self, statement: &'ast Statement) {
+        if let Statement::Expression(expression) = statement {
+            if let Expression::Identifier(ident) = expression.as_ref() {
+                if self.at_cursor(ident) {
+                    self.site = Some(Site::Statement(self.block));
+                    return;
+                }
+            }
+        }
+        walk_statement(self, statement)
+    }
+
+    fn visit_expression(&mut self, expression: &'ast Expression) {
+        match expression {
+            Expression::Identifier(ident) if self.at_cursor(ident) => {
+                self.site = Some(Site::Expression);
+            }
+            _ => walk_expression(self, expression),
+        }
+    }
+
+    fn visit_member_expression(&mut self, member: &'ast MemberExpression) {
+        match member.lhs.as_ref() {
+            MemberExpressionLHS::Identifier(ident) if self.at_cursor(ident) => {
+                self.site = Some(Site::Expression);
+            }
+            _ => {}
+        }
+        match member.rhs.as_ref() {
+            MemberExpressionRHS::Identifier(ident) if self.at_cursor(ident) => {
+                self.site = Some(Site::Member(member));
+            }
+            _ => walk_member_expression(self, member),
+        }
+    }
+
+    fn visit_type_annotation(&mut self, annotation: &'ast TypeAnnotation) {
+        let outer = std::mem::replace(&mut self.in_type, true);
+        walk_type_annotation(self, annotation);
+        self.in_type = outer;
+    }
+
+    fn visit_identifier(&mut self, ident: &'ast Identifier) {
+        if self.in_type && self.at_cursor(ident) {
+            self.site = Some(Site::Type);
+        }
+    }
+
+    fn visit_function_body(&mut self, body: &'ast FunctionBody) {
+        self.in_block(BlockKind::Other, |this| walk_function_body(this, body));
+    }
+
+    fn visit_if(&mut self, r#if: &'ast If) {
+        self.visit_expression(&r#if.cond);
+        self.in_block(BlockKind::If, |this| walk_block(this, &r#if.body));
+        match &r#if.r#else {
+            Some(Else::If(r#if)) => self.visit_if(r#if),
+            Some(Else::Block(block)) => {
+                self.in_block(BlockKind::Other, |this| walk_block(this, block))
+            }
+            None => {}
+        }
+    }
+
+    fn visit_match(&mut self, r#match: &'ast Match) {
+        self.visit_expression(&r#match.expression);
+        for arm in &r#match.arms {
+            self.visit_match_arm(arm);
+        }
+        if let Some(block) = &r#match.r#else {
+            self.in_block(BlockKind::Other, |this| walk_block(this, block));
+        }
+    }
+
+    fn visit_match_arm(&mut self, arm: &'ast MatchArm) {
+        self.visit_pattern(&arm.pattern);
+        self.in_block(BlockKind::MatchArm, |this| walk_block(this, &arm.body));
+    }
+}
+
+impl<'ast> ScopeVisitor for SiteFinder<'ast> {}
+
+impl<'ast> NodeVisitor for SiteFinder<'ast> {}
+
+struct Completions<'a> {
+    semantic: &'a SemanticResult,
+    items: Vec<Completion>,
+}
+
+impl<'a> Completions<'a> {
+    fn visible(&self) -> impl Iterator<Item = &'a Symbol> {
+        let semantic = self.semantic;
+        semantic
+            .cursor
+            .iter()
+            .flat_map(|cursor| &cursor.visible)
+            .map(|reference| semantic.symbols.get(*reference))
+            .filter(|symbol| !symbol.name.as_str().contains(PLACEHOLDER))
+    }
+
+    /// Bindings usable as values, Types are included since they are namespaces and constructors.
+    fn values(&mut self) {
+        for symbol in self.visible() {
+            if matches!(
+                symbol.kind,
+                SymbolKind::TypeAlias | SymbolKind::TypeParameter
+            ) {
+                continue;
+            }
+            self.push_symbol(symbol, &Substitution::new());
+        }
+    }
+
+    fn types(&mut self) {
+        for symbol in self.visible() {
+            if symbol.kind.is_type() {
+                self.push_symbol(symbol, &Substitution::new());
+            }
+        }
+        for name in PRIMITIVE_TYPES {
+            self.push(name, CompletionKind::Primitive, None);
+        }
+    }
+
+    fn keywords(&mut self, keywords: &[&str]) {
+        for keyword in keywords {
+            self.push(keyword, CompletionKind::Keyword, None);
+        }
+    }
+
+    fn members(&mut self, member: &MemberExpression) {
+        let semantic = self.semantic;
+        // Enums and unions are namespaces of their variants.
+        if let MemberExpressionLHS::Identifier(ident) = member.lhs.as_ref() {
+            if let Some(symbol) = semantic.symbols.of(ident) {
+                if matches!(symbol.kind, SymbolKind::Enum | SymbolKind::Union) {
+                    let reference = ident.reference.get().unwrap();
+                    self.variants(reference);
+                    return;
+                }
+            }
+        }
+
+        let Some(ty) = semantic
+            .cursor
+            .as_ref()
+            .and_then(|cursor| cursor.member.as_ref())
+        else {
+            return;
+        };
+        let (Type::Struct(target, _) | Type::Enum(target) | Type::Union(target)) = ty else {
+            return;
+        };
+        let Some(definition) = semantic.definitions.get(target) else {
+            return;
+        };
+        let substitution = match ty {
+            Type::Struct(_, args) => definition.substitution(args),
+            _ => Substitution::new(),
+        };
+        for field in definition.fields() {
+            self.push_symbol(semantic.symbols.get(field.reference), &substitution);
+        }
+        for method in &definition.methods {
+            self.push_symbol(semantic.symbols.get(*method), &substitution);
+        }
+    }
+
+    fn variants(&mut self, target: ReferenceType) {
+        let semantic = self.semantic;
+        let Some(definition) = semantic.definitions.get(&target) else {
+            return;
+        };
+        for variant in definition.variants() {
+            let detail = (!variant.fields.is_empty()).then(|| {
+                let fields: Vec<_> = variant
+                    .fields
+                    .iter()
+                    .map(|field| field.display(&semantic.symbols).to_string())
+                    .collect();
+                format!("({})", fields.join(", "))
+            });
+            self.push(variant.name.as_str(), CompletionKind::Variant, detail);
+        }
+    }
+
+    fn push_symbol(&mut self, symbol: &Symbol, substitution: &Substitution) {
+        let kind = match symbol.kind {
+            SymbolKind::Variable(VariableDeclarationKind::Const) => CompletionKind::Constant,
+            SymbolKind::Variable(_) | SymbolKind::Parameter => CompletionKind::Variable,
+            SymbolKind::Extern if matches!(symbol.ty, Type::Function(_)) => {
+                CompletionKind::Function
+            }
+            SymbolKind::Extern => CompletionKind::Variable,
+            SymbolKind::Function => CompletionKind::Function,
+            SymbolKind::Field => CompletionKind::Field,
+            SymbolKind::Method => CompletionKind::Method,
+            SymbolKind::Struct => CompletionKind::Struct,
+            SymbolKind::Enum => CompletionKind::Enum,
+            SymbolKind::Union => CompletionKind::Union,
+            SymbolKind::EnumVariant | SymbolKind::UnionVariant => CompletionKind::Variant,
+            SymbolKind::TypeAlias => CompletionKind::TypeAlias,
+            SymbolKind::TypeParameter => CompletionKind::TypeParameter,
+        };
+        let detail = (!symbol.kind.is_type() && !symbol.ty.is_unknown()).then(|| {
+            symbol
+                .ty
+                .substitute(substitution)
+                .display(&self.semantic.symbols)
+                .to_string()
+        });
+        self.push(symbol.name.as_str(), kind, detail);
+    }
+
+    fn push(&mut self, label: &str, kind: CompletionKind, detail: Option<String>) {
+        self.items.push(Completion {
+            label: label.to_string(),
+            kind,
+            detail,
+        });
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt::Write;
+
+use fuse_common::Span;
+use fuse_semantic::SemanticError;
+
+use crate::CompileError;
+
+/// Diagnostic of a compilation error, It points at the source code causing the error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+    /// Other parts of the source code related to the error.
+    pub notes: Vec<Note>,
+}
+
+/// Part of the source code related to a diagnostic, e.g. where a used value was moved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    /// Render the diagnostic with the lines of `source` it points at, `path` is the name of
+    /// the source file.
+    ///
+    /// ```text
+    /// error: Cannot find type "numbr"
+    ///  --> main.fuse:1:8
+    ///   |
+    /// 1 | let a: numbr = 1
+    ///   |        ^^^^^
+    /// ```
+    pub fn render(&self, source: &str, path: &str) -> String {
+        let primary = Location::new(source, self.span);
+        let notes: Vec<_> = self
+            .notes
+            .iter()
+            .map(|note| (Location::new(source, note.span), note))
+            .collect();
+        let width = notes
+            .iter()
+            .map(|(location, _)| location.line)
+            .chain([primary.line])
+            .max()
+            .unwrap_or_default()
+            .to_string()
+            .len();
+
+        let mut out = format!("error: {}\n", self.message);
+        let _ = writeln!(
+            out,
+            "{:width$}--> {path}:{}:{}",
+            "", primary.line, primary.column
+        );
+        primary.snippet(&mut out, width, '^', "");
+        for (location, note) in notes {
+            location.snippet(&mut out, width, '-', &note.message);
+        }
+        out.pop();
+        out
+    }
+}
+
+/// One based line and column of a span and the text of its first line.
+struct Location<'a> {
+    line: usize,
+    column: usize,
+    text: &'a str,
+    /// Length of the span on its first line, In characters.
+    length: usize,
+}
+
+impl<'a> Location<'a> {
+    fn new(source: &'a str, span: Span) -> Self {
+        let start = floor_char_boundary(source, span.start as usize);
+        let end = floor_char_boundary(source, span.end as usize).max(start);
+        let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |index| start + index);
+        let text = source[line_start..line_end].trim_end_matches('\r');
+        let (line, column) = position(source, span.start);
+        Self {
+            line,
+            column,
+            text,
+            length: source[start..end.min(line_end)].chars().count().max(1),
+        }
+    }
+
+    fn snippet(&self, out: &mut String, width: usize, underline: char, message: &str) {
+        let padding = " ".repeat(self.column - 1);
+        let underline = underline.to_string().repeat(self.length);
+        let _ = writeln!(out, "{:width$} |", "");
+        let _ = writeln!(out, "{:>width$} | {}", self.line, self.text);
+        let line = format!("{:width$} | {padding}{underline} {message}", "");
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+}
+
+/// One based line and column of an offset, Columns are counted in characters.
+pub(crate) fn position(source: &str, offset: u32) -> (usize, usize) {
+    let offset = floor_char_boundary(source, offset as usize);
+    let line_start = source[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let line = source[..line_start].matches('\n').count() + 1;
+    (line, source[line_start..offset].chars().count() + 1)
+}
+
+fn floor_char_boundary(source: &str, index: usize) -> usize {
+    let mut index = index.min(source.len());
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+impl From<&SemanticError> for Diagnostic {
+    fn from(error: &SemanticError) -> Self {
+        Self {
+            message: error.to_string(),
+            span: error.span(),
+            notes: error
+                .note()
+                .map(|(span, message)| Note {
+                    message: message.to_string(),
+                    span,
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+impl From<&fuse_parser::Error> for Diagnostic {
+    fn from(error: &fuse_parser::Error) -> Self {
+        Self {
+            message: error.to_string(),
+            span: error.span(),
+            notes: Vec::new(),
+        }
+    }
+}
+
+impl CompileError {
+    /// Diagnostics of the errors.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            Self::Parse(errors) => errors.iter().map(Diagnostic::from).collect(),
+            Self::Semantic(errors) => errors.iter().map(Diagnostic::from).collect(),
+        }
+    }
+
+    /// Render the diagnostics of the errors, Separated by empty lines.
+    pub fn render(&self, source: &str, path: &str) -> String {
+        self.diagnostics()
+            .iter()
+            .map(|diagnostic| diagnostic.render(source, path))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
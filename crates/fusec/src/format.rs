@@ -0,0 +1,60 @@
+use std::{fs, path::PathBuf};
+
+use crate::{build::source_files, BuildError, CompileError, FailedFile};
+
+/// Outcome of formatting files.
+#[derive(Debug, Default)]
+pub struct FormatOutput {
+    /// Number of formatted files.
+    pub checked: usize,
+    /// Files whose formatting changed, They are rewritten unless only checking.
+    pub changed: Vec<PathBuf>,
+    /// Files failing to parse, They are left as they are.
+    pub failures: Vec<FailedFile>,
+}
+
+impl FormatOutput {
+    pub fn is_success(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Format a chunk of Fuse code into its canonical form.
+pub fn format(source: &str) -> Result<String, CompileError> {
+    fuse_formatter::format(source).map_err(CompileError::Parse)
+}
+
+/// Format Fuse files in place, Inputs are files or project directories like the inputs of
+/// `build`. The files are only compared with their formatting if `check` is set.
+pub fn format_files(inputs: &[PathBuf], check: bool) -> Result<FormatOutput, BuildError> {
+    let mut output = FormatOutput::default();
+    for (path, _) in source_files(inputs, None)? {
+        let source = fs::read_to_string(&path).map_err(|source| BuildError::Read {
+            path: path.clone(),
+            source,
+        })?;
+        output.checked += 1;
+        let formatted = match format(&source) {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                output.failures.push(FailedFile {
+                    path,
+                    source,
+                    error,
+                });
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        if !check {
+            fs::write(&path, formatted).map_err(|source| BuildError::Write {
+                path: path.clone(),
+                source,
+            })?;
+        }
+        output.changed.push(path);
+    }
+    Ok(output)
+}
@@ -0,0 +1,135 @@
+use std::{collections::HashSet, fs, io, path::PathBuf};
+
+use fuse_codegen::imports;
+use fuse_ir::Module;
+use fuse_resolve::ImportResolver;
+use thiserror::Error as ThisError;
+
+use crate::{compile_module, lower_module, CompileOptions, FailedFile};
+
+#[derive(ThisError, Debug)]
+pub enum ModuleError {
+    #[error("Failed to read {}: {source}", .path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Cannot find the module \"{module}\"{}", .importer.as_ref().map(|importer| format!(" imported by \"{importer}\"")).unwrap_or_default())]
+    NotFound {
+        module: String,
+        importer: Option<String>,
+    },
+    #[error("Import cycle between the modules {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+}
+
+/// Module of a graph and its name.
+pub(crate) struct GraphModule {
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
+    /// Source code of the module.
+    pub(crate) source: String,
+    pub(crate) module: Module,
+}
+
+/// Modules reachable from the entries through their imports, In the order they were found.
+/// Modules failing to compile are recorded and their imports aren't followed.
+pub(crate) struct ModuleGraph<'a> {
+    resolver: &'a ImportResolver,
+    options: CompileOptions,
+    /// Lower the modules with semantic errors instead of recording them as failures.
+    lenient: bool,
+    pub(crate) modules: Vec<GraphModule>,
+    pub(crate) failures: Vec<(String, FailedFile)>,
+    loaded: HashSet<String>,
+    /// Modules being loaded, Each one imports the next.
+    loading: Vec<String>,
+}
+
+impl<'a> ModuleGraph<'a> {
+    pub(crate) fn new(resolver: &'a ImportResolver, options: CompileOptions) -> Self {
+        Self {
+            resolver,
+            options,
+            lenient: false,
+            modules: Vec::new(),
+            failures: Vec::new(),
+            loaded: HashSet::new(),
+            loading: Vec::new(),
+        }
+    }
+
+    /// Keep the modules with semantic errors and follow their imports, Only the modules with
+    /// syntax errors fail. The modules aren't optimized, e.g. for tools editing their sources.
+    pub(crate) fn lenient(mut self) -> Self {
+        self.lenient = true;
+        self
+    }
+
+    /// Load an entry module, `path` is looked up with the resolver if it is `None`.
+    pub(crate) fn load_entry(
+        &mut self,
+        name: String,
+        path: Option<PathBuf>,
+    ) -> Result<(), ModuleError> {
+        let path = match path {
+            Some(path) => path,
+            None => self.resolve(&name, None)?,
+        };
+        self.load(name, path)
+    }
+
+    fn load(&mut self, name: String, path: PathBuf) -> Result<(), ModuleError> {
+        if let Some(start) = self.loading.iter().position(|loading| *loading == name) {
+            let mut cycle = self.loading[start..].to_vec();
+            cycle.push(name);
+            return Err(ModuleError::Cycle(cycle));
+        }
+        if !self.loaded.insert(name.clone()) {
+            return Ok(());
+        }
+
+        let source = fs::read_to_string(&path).map_err(|source| ModuleError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        let module = if self.lenient {
+            lower_module(&source)
+        } else {
+            compile_module(&source, self.options)
+        };
+        let module = match module {
+            Ok(module) => module,
+            Err(error) => {
+                let failure = FailedFile {
+                    path,
+                    source,
+                    error,
+                };
+                self.failures.push((name, failure));
+                return Ok(());
+            }
+        };
+        let imports = imports(&module);
+        self.modules.push(GraphModule {
+            name: name.clone(),
+            path,
+            source,
+            module,
+        });
+
+        self.loading.push(name);
+        for import in imports {
+            let path = self.resolve(&import.name, self.loading.last().cloned())?;
+            self.load(import.name, path)?;
+        }
+        self.loading.pop();
+        Ok(())
+    }
+
+    fn resolve(&self, module: &str, importer: Option<String>) -> Result<PathBuf, ModuleError> {
+        self.resolver
+            .resolve(module)
+            .ok_or_else(|| ModuleError::NotFound {
+                module: module.to_string(),
+                importer,
+            })
+    }
+}
@@ -1,13 +1,250 @@
+mod build;
+mod check;
+mod completion;
+mod diagnostic;
+mod format;
+mod graph;
+mod lockfile;
+mod project;
+mod rename;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    path::{Path, PathBuf},
+};
+
+use fuse_ast::Chunk;
+use fuse_codegen::{
+    generate_bundle, generate_with_options, generate_with_source_map, BundleModule, CodegenOptions,
+};
+use fuse_ir::{lower, ConstantFolding, Inlining, Module, Pipeline};
 use fuse_parser::Parser;
-use fuse_semantic::Semantic;
+use fuse_resolve::ImportResolver;
+use fuse_semantic::{Semantic, SemanticError, SemanticResult, LUA_DECLARATIONS};
+use thiserror::Error as ThisError;
+
+pub use build::{build, build_project, BuildError, BuildOutput, FailedFile};
+pub use check::{check, CheckOutput};
+pub use completion::{complete, complete_with_declarations, Completion, CompletionKind};
+pub use diagnostic::{Diagnostic, Note};
+pub use format::{format, format_files, FormatOutput};
+pub use fuse_codegen::{
+    runtime_module, LuaTarget, Runtime, SourceMap, SourceMapError, RUNTIME_MODULE,
+};
+pub use graph::ModuleError;
+pub use lockfile::{LockedPackage, Lockfile, PackageSource, LOCKFILE_NAME};
+pub use project::{Dependency, Manifest, Project, ProjectError, MANIFEST_NAME};
+pub use rename::{rename, ModuleEdits, RenameError, RenameModule};
+
+use graph::ModuleGraph;
+
+/// Output of the compilation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Emit {
+    /// Lua source code.
+    Lua,
+    /// Textual format of the intermediate representation.
+    Ir,
+}
+
+/// Options of the compilation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompileOptions {
+    pub emit: Emit,
+    /// Run the optimization passes over the IR before emitting it.
+    pub optimize: bool,
+    /// Maximum size of the inlined functions, In IR instructions.
+    pub inline_threshold: usize,
+    /// How the Lua output gets the runtime helpers it uses.
+    pub runtime: Runtime,
+    /// Minify the Lua output, Constants are folded even if `optimize` is off.
+    pub minify: bool,
+    /// Lua dialect of the output.
+    pub target: LuaTarget,
+}
+
+impl Default for CompileOptions {
+    fn default() -> Self {
+        Self {
+            emit: Emit::Lua,
+            optimize: true,
+            inline_threshold: Inlining::DEFAULT_THRESHOLD,
+            runtime: Runtime::Inline,
+            minify: false,
+            target: LuaTarget::default(),
+        }
+    }
+}
+
+#[derive(ThisError, Debug)]
+pub enum CompileError {
+    #[error("{}", Errors(.0))]
+    Parse(Vec<fuse_parser::Error>),
+    #[error("{}", Errors(.0))]
+    Semantic(Vec<SemanticError>),
+}
+
+#[derive(ThisError, Debug)]
+pub enum BundleError {
+    #[error(transparent)]
+    Module(#[from] ModuleError),
+    #[error("In module \"{module}\":\n{}", error.render(code, &path.to_string_lossy()))]
+    Compile {
+        module: String,
+        path: PathBuf,
+        /// Source code of the module.
+        code: String,
+        error: CompileError,
+    },
+}
+
+/// Compile a chunk of Fuse code into the `options.emit` output.
+pub fn compile(source: &str, options: CompileOptions) -> Result<String, CompileError> {
+    let module = compile_module(source, options)?;
+    Ok(match options.emit {
+        Emit::Lua => generate_with_options(&module, codegen_options(options)),
+        Emit::Ir => module.to_string(),
+    })
+}
+
+/// Compile a chunk of Fuse code into Lua along with its source map, `source_name` and `file`
+/// are the names of the Fuse and Lua files. The `options.emit` output is ignored.
+pub fn compile_with_source_map(
+    source: &str,
+    source_name: &str,
+    file: &str,
+    options: CompileOptions,
+) -> Result<(String, SourceMap), CompileError> {
+    let module = compile_module(source, options)?;
+    Ok(generate_with_source_map(
+        &module,
+        codegen_options(options),
+        source,
+        source_name,
+        file,
+    ))
+}
+
+fn codegen_options(options: CompileOptions) -> CodegenOptions {
+    CodegenOptions {
+        runtime: options.runtime,
+        minify: options.minify,
+        target: options.target,
+    }
+}
+
+/// Compile the Fuse file at `entry` and the modules it imports into a single Lua chunk, The
+/// options are applied to every module and `options.emit` is ignored.
+///
+/// Imports are calls to `require` with a constant name, Modules are looked up relative to the
+/// directory of the entry, e.g. `require("utils.strings")` loads `utils/strings.fuse`.
+pub fn bundle(entry: &Path, options: CompileOptions) -> Result<String, BundleError> {
+    let root = entry.parent().unwrap_or(Path::new(""));
+    let resolver = ImportResolver::new().with_root(root);
+    let name = entry
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    bundle_modules(&resolver, name, Some(entry.to_path_buf()), options)
+}
+
+/// Bundle the module `name` of a graph, `path` is looked up with the resolver if it's `None`.
+fn bundle_modules(
+    resolver: &ImportResolver,
+    name: String,
+    path: Option<PathBuf>,
+    options: CompileOptions,
+) -> Result<String, BundleError> {
+    let mut graph = ModuleGraph::new(resolver, options);
+    graph.load_entry(name, path)?;
+    if let Some((module, failure)) = graph.failures.into_iter().next() {
+        return Err(BundleError::Compile {
+            module,
+            path: failure.path,
+            code: failure.source,
+            error: failure.error,
+        });
+    }
+
+    let modules: Vec<BundleModule> = graph
+        .modules
+        .iter()
+        .map(|module| BundleModule {
+            name: &module.name,
+            module: &module.module,
+        })
+        .collect();
+    Ok(generate_bundle(&modules, codegen_options(options)))
+}
+
+fn compile_module(source: &str, options: CompileOptions) -> Result<Module, CompileError> {
+    let (chunk, semantic) = analyze(source)?;
+    let mut module = lower(&chunk, &semantic, source);
+    if options.optimize {
+        Pipeline::optimizations_with_threshold(options.inline_threshold).run(&mut module);
+    } else if options.minify {
+        Pipeline::new().with(ConstantFolding).run(&mut module);
+    }
+    Ok(module)
+}
+
+/// Lower a chunk of Fuse code without optimizing it, Semantic errors are ignored so the
+/// imports of modules with errors can still be found.
+fn lower_module(source: &str) -> Result<Module, CompileError> {
+    let (chunk, semantic) = resolve(source)?;
+    Ok(lower(&chunk, &semantic, source))
+}
 
-fn compile_chunk(source: &str) {
+/// Parse a chunk of Fuse code and run the semantic analysis over it.
+fn analyze(source: &str) -> Result<(Chunk, SemanticResult), CompileError> {
+    let (chunk, semantic) = resolve(source)?;
+    if !semantic.errors.is_empty() {
+        return Err(CompileError::Semantic(semantic.errors));
+    }
+    Ok((chunk, semantic))
+}
+
+/// Parse a chunk of Fuse code and run the semantic analysis over it, Whatever errors it finds.
+fn resolve(source: &str) -> Result<(Chunk, SemanticResult), CompileError> {
     let parsed = Parser::new(source).parse();
-    assert!(!parsed.paniced);
-    assert!(parsed.errors.len() == 0);
-    let mut chunk = parsed.chunk.unwrap();
-    let semantic = Semantic::new(source).build(&mut chunk);
-    // panic!("{:#?}", chunk)
+    let chunk = match parsed.chunk {
+        Some(chunk) if parsed.errors.is_empty() => chunk,
+        _ => return Err(CompileError::Parse(parsed.errors)),
+    };
+    let declarations = parse_declarations();
+    let semantic = Semantic::new()
+        .with_declarations(&declarations)
+        .build(&chunk);
+    Ok((chunk, semantic))
+}
+
+/// Compile a chunk of Fuse code into Lua.
+pub fn compile_chunk(source: &str) -> String {
+    compile(source, CompileOptions::default()).unwrap_or_else(|error| panic!("{error}"))
+}
+
+/// Parse the declarations of the Lua standard library, e.g. to analyze chunks with
+/// `Semantic::with_declarations`.
+pub fn parse_declarations() -> Chunk {
+    let parsed = Parser::new(LUA_DECLARATIONS).parse();
+    assert!(parsed.errors.is_empty(), "{:#?}", parsed.errors);
+    parsed.chunk.unwrap()
+}
+
+/// Display a list of errors, One per line.
+struct Errors<'a, T>(&'a [T]);
+
+impl<'a, T: Display> Display for Errors<'a, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, error) in self.0.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{error}")?;
+        }
+        Ok(())
+    }
 }
 
 #[test]
@@ -18,5 +255,5 @@ fn manual_test() {
         let c = 1
         let d = a.b.c()
         "#,
-    )
+    );
 }
@@ -0,0 +1,125 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    path::PathBuf,
+};
+
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// File name of the lockfiles, They sit next to the manifest.
+pub const LOCKFILE_NAME: &str = "fuse.lock";
+
+const HEADER: &str = "# Generated by fuse, Do not edit it by hand.\n";
+const FORMAT_VERSION: u32 = 1;
+
+/// Versions and sources of the packages a project depends on, It keeps later builds using the
+/// same versions of the registry packages.
+///
+/// ```toml
+/// version = 1
+///
+/// [[package]]
+/// name = "json"
+/// version = "1.2.0"
+/// source = "registry"
+///
+/// [[package]]
+/// name = "utils"
+/// version = "0.1.0"
+/// source = "path+../utils"
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Lockfile {
+    /// Sorted by name.
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: Version,
+    pub source: PackageSource,
+}
+
+/// Where a package comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackageSource {
+    /// Local directory, Relative to the project.
+    Path(PathBuf),
+    /// Vendored registry of the project.
+    Registry,
+}
+
+impl Display for PackageSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            // Forward slashes keep the lockfile the same on every platform.
+            Self::Path(path) => write!(f, "path+{}", path.to_string_lossy().replace('\\', "/")),
+            Self::Registry => f.write_str("registry"),
+        }
+    }
+}
+
+impl Lockfile {
+    /// Parse the content of a lockfile, Entries with an unknown source or an invalid version
+    /// are ignored and get resolved again.
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        let raw: RawLockfile = toml::from_str(text)?;
+        let mut packages: Vec<LockedPackage> = raw
+            .package
+            .into_iter()
+            .filter_map(|package| {
+                let source = match package.source.as_str() {
+                    "registry" => PackageSource::Registry,
+                    source => PackageSource::Path(PathBuf::from(source.strip_prefix("path+")?)),
+                };
+                Some(LockedPackage {
+                    name: package.name,
+                    version: package.version.parse().ok()?,
+                    source,
+                })
+            })
+            .collect();
+        packages.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Self { packages })
+    }
+
+    pub fn to_toml(&self) -> String {
+        let raw = RawLockfile {
+            version: FORMAT_VERSION,
+            package: self
+                .packages
+                .iter()
+                .map(|package| RawLockedPackage {
+                    name: package.name.clone(),
+                    version: package.version.to_string(),
+                    source: package.source.to_string(),
+                })
+                .collect(),
+        };
+        let text = toml::to_string(&raw).expect("Failed to serialize a lockfile.");
+        format!("{HEADER}{text}")
+    }
+
+    /// Locked version of a registry package.
+    pub fn registry_version(&self, name: &str) -> Option<&Version> {
+        self.packages
+            .iter()
+            .find(|package| package.name == name && package.source == PackageSource::Registry)
+            .map(|package| &package.version)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawLockfile {
+    version: u32,
+    #[serde(default)]
+    package: Vec<RawLockedPackage>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RawLockedPackage {
+    name: String,
+    version: String,
+    source: String,
+}
@@ -0,0 +1,674 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use fuse_resolve::{ImportResolver, Package};
+use semver::{Version, VersionReq};
+use serde::{
+    de::{Error as _, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+use thiserror::Error as ThisError;
+
+use crate::{
+    lockfile::{LockedPackage, Lockfile, PackageSource, LOCKFILE_NAME},
+    CompileOptions, LuaTarget,
+};
+
+/// File name of the project manifests.
+pub const MANIFEST_NAME: &str = "fuse.toml";
+
+#[derive(ThisError, Debug)]
+pub enum ProjectError {
+    #[error("Failed to read {}: {source}", .path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("Failed to write {}: {source}", .path.display())]
+    Write { path: PathBuf, source: io::Error },
+    #[error("Invalid manifest {}: {source}", .path.display())]
+    Manifest {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Invalid lockfile {}: {source}", .path.display())]
+    Lockfile {
+        path: PathBuf,
+        source: Box<toml::de::Error>,
+    },
+    #[error("Cannot find a {MANIFEST_NAME} in {} or its parents", .0.display())]
+    NotFound(PathBuf),
+    #[error("Dependency cycle between the packages {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+    #[error("Both {} and {} are dependencies named \"{name}\"", .first.display(), .second.display())]
+    Conflict {
+        name: String,
+        first: PathBuf,
+        second: PathBuf,
+    },
+    #[error("The dependency \"{0}\" has no path and the project has no registry")]
+    NoRegistry(String),
+    #[error("No version of \"{name}\" in the registry matches \"{requirement}\"")]
+    NoMatchingVersion {
+        name: String,
+        requirement: VersionReq,
+    },
+    #[error(
+        "The dependency \"{name}\" is at version {version} which doesn't match \"{requirement}\""
+    )]
+    VersionMismatch {
+        name: String,
+        version: Version,
+        requirement: VersionReq,
+    },
+}
+
+/// Settings of a project, They are read from its `fuse.toml`.
+///
+/// ```toml
+/// [package]
+/// name = "app"
+/// version = "0.1.0"
+/// source-roots = ["src"]
+/// entries = ["main"]
+/// target = "lua54"
+/// out-dir = "out"
+///
+/// [registry]
+/// path = "vendor"
+///
+/// [dependencies]
+/// utils = { path = "../utils" }
+/// json = "1.2"
+/// ```
+///
+/// Everything but the name is optional and defaults to the values above, The version defaults
+/// to `0.0.0` and there is no registry by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Manifest {
+    pub name: String,
+    pub version: Version,
+    /// Directories holding the modules of the package, Relative to the manifest.
+    pub source_roots: Vec<PathBuf>,
+    /// Modules compiled by a build along with the modules they import.
+    pub entries: Vec<String>,
+    pub target: LuaTarget,
+    /// Where builds write the Lua files, Relative to the manifest.
+    pub out_dir: PathBuf,
+    /// Directory of the vendored registry, Relative to the manifest. Registry packages are in
+    /// `<registry>/<name>/<version>`.
+    pub registry: Option<PathBuf>,
+    /// Packages the project imports, Sorted by name.
+    pub dependencies: Vec<Dependency>,
+}
+
+/// Package a project depends on, Its modules are imported with `name` as their first
+/// component, e.g. `require("utils.strings")`.
+///
+/// Dependencies without a path come from the registry, They have a version requirement.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    pub name: String,
+    /// Directory of the package manifest, Relative to the manifest depending on it.
+    pub path: Option<PathBuf>,
+    /// Versions of the package the project works with.
+    pub version: Option<VersionReq>,
+}
+
+impl Manifest {
+    /// Parse the content of a manifest.
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        let raw: RawManifest = toml::from_str(text)?;
+        Ok(Self {
+            name: raw.package.name,
+            version: raw.package.version,
+            source_roots: raw.package.source_roots,
+            entries: raw.package.entries,
+            target: raw.package.target,
+            out_dir: raw.package.out_dir,
+            registry: raw.registry.map(|registry| registry.path),
+            dependencies: raw
+                .dependencies
+                .into_iter()
+                .map(|(name, dependency)| Dependency {
+                    name,
+                    path: dependency.path,
+                    version: dependency.version,
+                })
+                .collect(),
+        })
+    }
+
+    /// Read the manifest of the project at `root`.
+    pub fn load(root: &Path) -> Result<Self, ProjectError> {
+        let path = root.join(MANIFEST_NAME);
+        let text = fs::read_to_string(&path).map_err(|source| ProjectError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        Self::parse(&text).map_err(|source| ProjectError::Manifest { path, source })
+    }
+}
+
+/// Project loaded from its manifest along with its dependencies.
+#[derive(Debug, Clone)]
+pub struct Project {
+    /// Directory of the manifest.
+    pub root: PathBuf,
+    pub manifest: Manifest,
+    /// Dependencies of the project and of its dependencies, Each package appears once.
+    pub packages: Vec<Package>,
+    /// Versions and sources the dependencies were resolved to.
+    pub lockfile: Lockfile,
+}
+
+impl Project {
+    /// Load the project whose manifest is in `root`.
+    pub fn load(root: &Path) -> Result<Self, ProjectError> {
+        let manifest = Manifest::load(root)?;
+        let locked = read_lockfile(root)?;
+        let canonical_root = canonicalize(root)?;
+        let registry = manifest
+            .registry
+            .as_ref()
+            .map(|registry| canonical_root.join(registry));
+        let mut resolver = VersionResolver {
+            registry: registry.as_deref(),
+            locked: &locked,
+            manifests: HashMap::new(),
+        };
+        let mut versions = BTreeMap::new();
+        resolver.resolve(&canonical_root, &mut versions)?;
+        let mut loader = PackageLoader {
+            root: &canonical_root,
+            registry: registry.clone(),
+            versions: &versions,
+            packages: Vec::new(),
+            loading: vec![(manifest.name.clone(), canonical_root.clone())],
+        };
+        loader.load_dependencies(&canonical_root, &manifest)?;
+
+        let mut locked_packages: Vec<LockedPackage> = loader
+            .packages
+            .iter()
+            .map(|loaded| LockedPackage {
+                name: loaded.package.name.clone(),
+                version: loaded.version.clone(),
+                source: loaded.source.clone(),
+            })
+            .collect();
+        locked_packages.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Self {
+            root: root.to_path_buf(),
+            manifest,
+            packages: loader
+                .packages
+                .into_iter()
+                .map(|loaded| loaded.package)
+                .collect(),
+            lockfile: Lockfile {
+                packages: locked_packages,
+            },
+        })
+    }
+
+    /// Load the project of the nearest manifest in `start` or its parents.
+    pub fn find(start: &Path) -> Result<Self, ProjectError> {
+        let start = canonicalize(start)?;
+        let root = start
+            .ancestors()
+            .find(|dir| dir.join(MANIFEST_NAME).is_file())
+            .ok_or_else(|| ProjectError::NotFound(start.clone()))?;
+        Self::load(root)
+    }
+
+    pub fn source_roots(&self) -> Vec<PathBuf> {
+        self.manifest
+            .source_roots
+            .iter()
+            .map(|root| self.root.join(root))
+            .collect()
+    }
+
+    /// Source roots of the project and of its dependencies.
+    pub fn all_source_roots(&self) -> Vec<PathBuf> {
+        let packages = self.packages.iter().flat_map(|package| &package.roots);
+        self.source_roots()
+            .into_iter()
+            .chain(packages.cloned())
+            .collect()
+    }
+
+    pub fn out_dir(&self) -> PathBuf {
+        self.root.join(&self.manifest.out_dir)
+    }
+
+    /// Resolver of the modules imported by the project and its dependencies.
+    pub fn resolver(&self) -> ImportResolver {
+        let resolver = self
+            .source_roots()
+            .into_iter()
+            .fold(ImportResolver::new(), ImportResolver::with_root);
+        self.packages
+            .iter()
+            .cloned()
+            .fold(resolver, ImportResolver::with_package)
+    }
+
+    /// Write the lockfile of the project if it changed, It isn't created for projects without
+    /// dependencies. Returns whether the file was written.
+    pub fn write_lockfile(&self) -> Result<bool, ProjectError> {
+        let path = self.root.join(LOCKFILE_NAME);
+        let text = self.lockfile.to_toml();
+        let is_unchanged = match fs::read_to_string(&path) {
+            Ok(current) => current == text,
+            Err(_) => self.lockfile.packages.is_empty(),
+        };
+        if is_unchanged {
+            return Ok(false);
+        }
+        fs::write(&path, text).map_err(|source| ProjectError::Write { path, source })?;
+        Ok(true)
+    }
+
+    /// The compile options with the settings of the manifest applied.
+    pub fn options(&self, options: CompileOptions) -> CompileOptions {
+        CompileOptions {
+            target: self.manifest.target,
+            ..options
+        }
+    }
+}
+
+/// Loads the dependencies of a project recursively.
+struct PackageLoader<'a> {
+    /// Canonical path of the project.
+    root: &'a Path,
+    /// Vendored registry of the project, Registry packages are in `<registry>/<name>/<version>`.
+    registry: Option<PathBuf>,
+    /// Versions of the registry packages, See `VersionResolver`.
+    versions: &'a BTreeMap<String, Version>,
+    packages: Vec<LoadedPackage>,
+    /// Packages being loaded and their canonical path, Each one depends on the next.
+    loading: Vec<(String, PathBuf)>,
+}
+
+struct LoadedPackage {
+    package: Package,
+    /// Canonical path of the package.
+    dir: PathBuf,
+    version: Version,
+    source: PackageSource,
+}
+
+impl<'a> PackageLoader<'a> {
+    fn load_dependencies(&mut self, dir: &Path, manifest: &Manifest) -> Result<(), ProjectError> {
+        for dependency in &manifest.dependencies {
+            let (package_dir, source) = match &dependency.path {
+                Some(path) => {
+                    let package_dir = canonicalize(&dir.join(path))?;
+                    let source = PackageSource::Path(relative_path(self.root, &package_dir));
+                    (package_dir, source)
+                }
+                None => (self.registry_dir(dependency)?, PackageSource::Registry),
+            };
+            if let Some(start) = self
+                .loading
+                .iter()
+                .position(|(_, path)| *path == package_dir)
+            {
+                let mut cycle: Vec<String> = self.loading[start..]
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .collect();
+                cycle.push(dependency.name.clone());
+                return Err(ProjectError::Cycle(cycle));
+            }
+            let loaded = self
+                .packages
+                .iter()
+                .find(|loaded| loaded.package.name == dependency.name);
+            match loaded {
+                Some(loaded) if loaded.dir == package_dir => continue,
+                Some(loaded) => {
+                    return Err(ProjectError::Conflict {
+                        name: dependency.name.clone(),
+                        first: loaded.dir.clone(),
+                        second: package_dir,
+                    })
+                }
+                None => {}
+            }
+
+            let manifest = Manifest::load(&package_dir)?;
+            if let Some(requirement) = &dependency.version {
+                if !requirement.matches(&manifest.version) {
+                    return Err(ProjectError::VersionMismatch {
+                        name: dependency.name.clone(),
+                        version: manifest.version.clone(),
+                        requirement: requirement.clone(),
+                    });
+                }
+            }
+            let roots = manifest
+                .source_roots
+                .iter()
+                .map(|root| package_dir.join(root))
+                .collect();
+            self.packages.push(LoadedPackage {
+                package: Package {
+                    name: dependency.name.clone(),
+                    roots,
+                },
+                dir: package_dir.clone(),
+                version: manifest.version.clone(),
+                source,
+            });
+
+            self.loading
+                .push((dependency.name.clone(), package_dir.clone()));
+            self.load_dependencies(&package_dir, &manifest)?;
+            self.loading.pop();
+        }
+        Ok(())
+    }
+
+    /// Directory of the resolved version of a registry package.
+    fn registry_dir(&self, dependency: &Dependency) -> Result<PathBuf, ProjectError> {
+        let name = &dependency.name;
+        let registry = self
+            .registry
+            .as_ref()
+            .ok_or_else(|| ProjectError::NoRegistry(name.clone()))?;
+        let version = &self.versions[name];
+        canonicalize(&registry.join(name).join(version.to_string()))
+    }
+}
+
+/// Chooses a version of each registry package matching every requirement on it, The
+/// requirements come from the project and from the chosen versions of the packages.
+///
+/// Packages get a version one at a time, in order of name. The locked version is tried first and
+/// then the matching versions from the greatest one. A choice leading to requirements that no
+/// version matches is undone and the next version is tried.
+struct VersionResolver<'a> {
+    /// Canonical path of the vendored registry of the project.
+    registry: Option<&'a Path>,
+    /// Lockfile of the previous resolution.
+    locked: &'a Lockfile,
+    /// Manifests read so far by their canonical directory.
+    manifests: HashMap<PathBuf, Manifest>,
+}
+
+impl<'a> VersionResolver<'a> {
+    /// Choose the versions of the packages the project at `root` depends on, `versions` holds
+    /// the choices made so far.
+    fn resolve(
+        &mut self,
+        root: &Path,
+        versions: &mut BTreeMap<String, Version>,
+    ) -> Result<(), ProjectError> {
+        let requirements = self.requirements(root, versions)?;
+        for (name, requirement) in &requirements {
+            if let Some(version) = versions.get(name) {
+                if !requirement.matches(version) {
+                    return Err(ProjectError::NoMatchingVersion {
+                        name: name.clone(),
+                        requirement: requirement.clone(),
+                    });
+                }
+            }
+        }
+        let Some((name, requirement)) = requirements
+            .into_iter()
+            .find(|(name, _)| !versions.contains_key(name))
+        else {
+            return Ok(());
+        };
+
+        let mut error = None;
+        for version in self.candidates(&name, &requirement)? {
+            versions.insert(name.clone(), version);
+            match self.resolve(root, versions) {
+                Ok(()) => return Ok(()),
+                Err(failure @ ProjectError::NoMatchingVersion { .. }) => error = Some(failure),
+                Err(failure) => return Err(failure),
+            }
+            versions.remove(&name);
+        }
+        Err(error.unwrap_or(ProjectError::NoMatchingVersion { name, requirement }))
+    }
+
+    /// Requirements on the registry packages of the project and of the packages it depends on,
+    /// Only the packages with a chosen version are visited. The requirements on a package are
+    /// merged into one.
+    fn requirements(
+        &mut self,
+        root: &Path,
+        versions: &BTreeMap<String, Version>,
+    ) -> Result<BTreeMap<String, VersionReq>, ProjectError> {
+        let mut requirements: BTreeMap<String, VersionReq> = BTreeMap::new();
+        let mut visited = HashSet::new();
+        let mut stack = vec![root.to_path_buf()];
+        while let Some(dir) = stack.pop() {
+            if !visited.insert(dir.clone()) {
+                continue;
+            }
+            let dependencies = self.manifest(&dir)?.dependencies.clone();
+            for dependency in dependencies {
+                if let Some(path) = &dependency.path {
+                    stack.push(canonicalize(&dir.join(path))?);
+                    continue;
+                }
+                let requirement = requirements.entry(dependency.name.clone()).or_default();
+                if let Some(version) = &dependency.version {
+                    requirement
+                        .comparators
+                        .extend(version.comparators.iter().cloned());
+                }
+                if let Some(version) = versions.get(&dependency.name) {
+                    let registry = self
+                        .registry
+                        .ok_or_else(|| ProjectError::NoRegistry(dependency.name.clone()))?;
+                    let dir = registry.join(&dependency.name).join(version.to_string());
+                    stack.push(canonicalize(&dir)?);
+                }
+            }
+        }
+        Ok(requirements)
+    }
+
+    /// Versions of a registry package matching a requirement, In the order they are tried.
+    fn candidates(
+        &self,
+        name: &str,
+        requirement: &VersionReq,
+    ) -> Result<Vec<Version>, ProjectError> {
+        let registry = self
+            .registry
+            .ok_or_else(|| ProjectError::NoRegistry(name.to_string()))?;
+        // A package missing from the registry has no matching version.
+        let mut versions: Vec<Version> = fs::read_dir(registry.join(name))
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .filter(|version| requirement.matches(version))
+            .collect();
+        versions.sort_by(|a, b| b.cmp(a));
+        if let Some(locked) = self.locked.registry_version(name) {
+            if let Some(index) = versions.iter().position(|version| version == locked) {
+                let locked = versions.remove(index);
+                versions.insert(0, locked);
+            }
+        }
+        Ok(versions)
+    }
+
+    fn manifest(&mut self, dir: &Path) -> Result<&Manifest, ProjectError> {
+        if !self.manifests.contains_key(dir) {
+            let manifest = Manifest::load(dir)?;
+            self.manifests.insert(dir.to_path_buf(), manifest);
+        }
+        Ok(&self.manifests[dir])
+    }
+}
+
+/// Path of `path` relative to `base`, Both are canonical.
+fn relative_path(base: &Path, path: &Path) -> PathBuf {
+    let base: Vec<_> = base.components().collect();
+    let path: Vec<_> = path.components().collect();
+    let common = base.iter().zip(&path).take_while(|(a, b)| a == b).count();
+    let mut relative: PathBuf = base[common..]
+        .iter()
+        .map(|_| Component::ParentDir)
+        .collect();
+    relative.extend(&path[common..]);
+    if relative.as_os_str().is_empty() {
+        relative.push(Component::CurDir);
+    }
+    relative
+}
+
+/// Read the lockfile of the project at `root`, It is empty if there is none.
+fn read_lockfile(root: &Path) -> Result<Lockfile, ProjectError> {
+    let path = root.join(LOCKFILE_NAME);
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Lockfile::default()),
+        Err(source) => return Err(ProjectError::Io { path, source }),
+    };
+    Lockfile::parse(&text).map_err(|source| ProjectError::Lockfile {
+        path,
+        source: Box::new(source),
+    })
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, ProjectError> {
+    path.canonicalize().map_err(|source| ProjectError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawManifest {
+    package: RawPackage,
+    registry: Option<RawRegistry>,
+    #[serde(default)]
+    dependencies: BTreeMap<String, RawDependency>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct RawPackage {
+    name: String,
+    #[serde(default = "default_version", deserialize_with = "deserialize_version")]
+    version: Version,
+    #[serde(default = "default_source_roots")]
+    source_roots: Vec<PathBuf>,
+    #[serde(default = "default_entries")]
+    entries: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_target")]
+    target: LuaTarget,
+    #[serde(default = "default_out_dir")]
+    out_dir: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRegistry {
+    path: PathBuf,
+}
+
+/// Dependency written as a version requirement or as a table with a path and or a version.
+struct RawDependency {
+    path: Option<PathBuf>,
+    version: Option<VersionReq>,
+}
+
+impl<'de> Deserialize<'de> for RawDependency {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(RawDependencyVisitor)
+    }
+}
+
+struct RawDependencyVisitor;
+
+impl<'de> Visitor<'de> for RawDependencyVisitor {
+    type Value = RawDependency;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a version requirement or a table with a path or a version")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, version: &str) -> Result<Self::Value, E> {
+        Ok(RawDependency {
+            path: None,
+            version: Some(parse_requirement(version)?),
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut dependency = RawDependency {
+            path: None,
+            version: None,
+        };
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "path" => dependency.path = Some(map.next_value()?),
+                "version" => {
+                    let version: String = map.next_value()?;
+                    dependency.version = Some(parse_requirement(&version)?);
+                }
+                key => return Err(A::Error::unknown_field(key, &["path", "version"])),
+            }
+        }
+        if dependency.path.is_none() && dependency.version.is_none() {
+            return Err(A::Error::custom("expected a path or a version"));
+        }
+        Ok(dependency)
+    }
+}
+
+fn parse_requirement<E: serde::de::Error>(requirement: &str) -> Result<VersionReq, E> {
+    requirement.parse().map_err(|error| {
+        E::custom(format!(
+            "invalid version requirement \"{requirement}\": {error}"
+        ))
+    })
+}
+
+fn default_version() -> Version {
+    Version::new(0, 0, 0)
+}
+
+fn default_source_roots() -> Vec<PathBuf> {
+    vec![PathBuf::from("src")]
+}
+
+fn default_entries() -> Vec<String> {
+    vec!["main".to_string()]
+}
+
+fn default_out_dir() -> PathBuf {
+    PathBuf::from("out")
+}
+
+fn deserialize_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Version, D::Error> {
+    let version = String::deserialize(deserializer)?;
+    version
+        .parse()
+        .map_err(|error| D::Error::custom(format!("invalid version \"{version}\": {error}")))
+}
+
+fn deserialize_target<'de, D: Deserializer<'de>>(deserializer: D) -> Result<LuaTarget, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    LuaTarget::from_name(&name).ok_or_else(|| {
+        let names: Vec<_> = LuaTarget::ALL.iter().map(|target| target.name()).collect();
+        D::Error::custom(format!(
+            "unknown target \"{name}\", expected one of {}",
+            names.join(", ")
+        ))
+    })
+}
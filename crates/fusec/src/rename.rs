@@ -0,0 +1,463 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use fuse_ast::{Atom, Chunk, Identifier, VariableDeclarationKind};
+use fuse_common::{ReferenceType, Span};
+use fuse_parser::{
+    lexer::{Lexer, TokenKind},
+    Parser, TextEdit,
+};
+use fuse_resolve::ImportResolver;
+use fuse_semantic::{Semantic, SemanticError, SemanticResult, SymbolKind};
+use fuse_visitor::{NodeVisitor, ScopeVisitor, Visitor};
+use thiserror::Error as ThisError;
+
+use crate::{
+    graph::{ModuleError, ModuleGraph},
+    parse_declarations, CompileOptions, Project,
+};
+
+/// Module taking part in a rename, `name` identifies it in the results.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameModule {
+    pub name: String,
+    pub path: Option<PathBuf>,
+    pub source: String,
+}
+
+impl RenameModule {
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            path: None,
+            source: source.into(),
+        }
+    }
+
+    /// Load the Fuse file at `entry` and the modules it imports, Modules are looked up relative
+    /// to the directory of the entry like `bundle` does. The entry comes first.
+    pub fn load_graph(entry: &Path) -> Result<Vec<Self>, ModuleError> {
+        let root = entry.parent().unwrap_or(Path::new(""));
+        let resolver = ImportResolver::new().with_root(root);
+        let name = entry
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        Self::load_modules(&resolver, [(name, Some(entry.to_path_buf()))])
+    }
+
+    /// Load the entries of a project and the modules they import, Including the ones of its
+    /// packages. Modules are looked up like `build_project` does.
+    pub fn load_project(project: &Project) -> Result<Vec<Self>, ModuleError> {
+        let entries = project
+            .manifest
+            .entries
+            .iter()
+            .map(|entry| (entry.clone(), None));
+        Self::load_modules(&project.resolver(), entries)
+    }
+
+    /// Load the modules of a graph in the order they are found, Modules with syntax errors
+    /// come last since their imports can't be followed.
+    fn load_modules(
+        resolver: &ImportResolver,
+        entries: impl IntoIterator<Item = (String, Option<PathBuf>)>,
+    ) -> Result<Vec<Self>, ModuleError> {
+        let mut graph = ModuleGraph::new(resolver, CompileOptions::default()).lenient();
+        for (name, path) in entries {
+            graph.load_entry(name, path)?;
+        }
+        let modules = graph.modules.into_iter().map(|module| Self {
+            name: module.name,
+            path: Some(module.path),
+            source: module.source,
+        });
+        let failures = graph.failures.into_iter().map(|(name, failure)| Self {
+            name,
+            path: Some(failure.path),
+            source: failure.source,
+        });
+        Ok(modules.chain(failures).collect())
+    }
+}
+
+/// Edits renaming a symbol in a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleEdits {
+    pub module: String,
+    /// Edits of the renamed identifiers, In source order.
+    pub edits: Vec<TextEdit>,
+}
+
+#[derive(ThisError, Debug)]
+pub enum RenameError {
+    #[error(transparent)]
+    Module(#[from] ModuleError),
+    #[error("Cannot rename in \"{module}\" because it has syntax errors")]
+    Syntax { module: String },
+    #[error("There is no symbol to rename at this position")]
+    NoSymbol,
+    #[error("\"{0}\" is a reserved keyword")]
+    ReservedKeyword(String),
+    #[error("\"{0}\" is not a valid identifier")]
+    InvalidName(String),
+    #[error("\"{0}\" is declared outside of the program")]
+    External(String),
+    #[error("Renaming to \"{new_name}\" would change what \"{name}\" refers to in \"{module}\"")]
+    Shadowing {
+        module: String,
+        /// Span of the identifier, In the source before the rename.
+        span: Span,
+        name: String,
+        new_name: String,
+    },
+    #[error("\"{new_name}\" is already a global of the program")]
+    GlobalConflict { new_name: String },
+    #[error("Renaming to \"{new_name}\" would introduce errors in \"{module}\"")]
+    Errors { module: String, new_name: String },
+}
+
+/// Rename the symbol at the byte `offset` of the `target` module to `new_name`, Along with
+/// every reference to it in `modules`.
+///
+/// Locals, functions, types and their members are renamed in the module declaring them. Globals
+/// are the exports of a module, They are shared by every module of the graph so renaming one
+/// renames it everywhere. The rename is rejected if any identifier would refer to another
+/// symbol afterward, e.g. when the new name is shadowed by or shadows another declaration.
+pub fn rename(
+    modules: &[RenameModule],
+    target: usize,
+    offset: u32,
+    new_name: &str,
+) -> Result<Vec<ModuleEdits>, RenameError> {
+    check_name(new_name)?;
+    let declarations = Declarations::new();
+    let analyses = modules
+        .iter()
+        .map(|module| {
+            ModuleAnalysis::new(&module.source, &declarations).ok_or_else(|| RenameError::Syntax {
+                module: module.name.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let analysis = &analyses[target];
+    let occurrence = analysis
+        .occurrence_at(offset)
+        .ok_or(RenameError::NoSymbol)?;
+    let renamed = match analysis.binding(occurrence) {
+        Binding::Declared(_) => {
+            let reference = occurrence.reference.unwrap();
+            let symbol = analysis.semantic.symbols.get(reference);
+            match symbol.kind {
+                SymbolKind::Extern => {
+                    return Err(RenameError::External(symbol.name.as_str().to_string()))
+                }
+                SymbolKind::Variable(VariableDeclarationKind::Global) => {
+                    Renamed::Global(symbol.name.as_str().to_string())
+                }
+                _ => Renamed::Symbol(reference),
+            }
+        }
+        Binding::Global(name) => Renamed::Global(name),
+        Binding::Outside(_) => {
+            return Err(RenameError::External(occurrence.name.as_str().to_string()))
+        }
+        Binding::Unbound => return Err(RenameError::NoSymbol),
+    };
+    if let Renamed::Global(name) = &renamed {
+        if !analyses
+            .iter()
+            .any(|analysis| analysis.declares_global(name))
+        {
+            return Err(RenameError::External(name.as_str().to_string()));
+        }
+        if analyses
+            .iter()
+            .any(|analysis| analysis.uses_global(new_name))
+        {
+            return Err(RenameError::GlobalConflict {
+                new_name: new_name.to_string(),
+            });
+        }
+    }
+
+    let mut results = Vec::new();
+    for (index, (module, analysis)) in modules.iter().zip(&analyses).enumerate() {
+        let mut occurrences: Vec<usize> = match &renamed {
+            Renamed::Symbol(_) if index != target => Vec::new(),
+            _ => (0..analysis.occurrences.len())
+                .filter(|&occurrence| analysis.is_renamed(occurrence, &renamed))
+                .collect(),
+        };
+        if occurrences.is_empty() {
+            continue;
+        }
+        occurrences.sort_by_key(|&occurrence| analysis.occurrences[occurrence].span.start);
+        let mut edits: Vec<TextEdit> = occurrences
+            .iter()
+            .map(|&occurrence| TextEdit::new(analysis.occurrences[occurrence].span, new_name))
+            .collect();
+        edits.dedup();
+        verify(
+            module,
+            analysis,
+            &occurrences,
+            &edits,
+            new_name,
+            &declarations,
+        )?;
+        results.push(ModuleEdits {
+            module: module.name.clone(),
+            edits,
+        });
+    }
+    Ok(results)
+}
+
+/// Check that `name` can be written as an identifier.
+fn check_name(name: &str) -> Result<(), RenameError> {
+    let tokens: Vec<_> = Lexer::new(name).collect();
+    match tokens.as_slice() {
+        [token] if token.span() == Span::new(0, name.len() as u32) => {
+            if token.kind.is_reserved_keyword() {
+                Err(RenameError::ReservedKeyword(name.to_string()))
+            } else if token.kind == TokenKind::Identifier {
+                Ok(())
+            } else {
+                Err(RenameError::InvalidName(name.to_string()))
+            }
+        }
+        _ => Err(RenameError::InvalidName(name.to_string())),
+    }
+}
+
+/// Analyze a module again after the rename and make sure its identifiers refer to the same
+/// symbols as before.
+fn verify(
+    module: &RenameModule,
+    before: &ModuleAnalysis,
+    renamed: &[usize],
+    edits: &[TextEdit],
+    new_name: &str,
+    declarations: &Declarations,
+) -> Result<(), RenameError> {
+    let errors = || RenameError::Errors {
+        module: module.name.clone(),
+        new_name: new_name.to_string(),
+    };
+    let mut source = module.source.clone();
+    for edit in edits.iter().rev() {
+        source = edit.apply(&source);
+    }
+    let after = ModuleAnalysis::new(&source, declarations).ok_or_else(errors)?;
+    if after.occurrences.len() != before.occurrences.len()
+        || after.semantic.errors.len() > before.semantic.errors.len()
+    {
+        return Err(errors());
+    }
+
+    // Offset in the renamed source of an `offset` outside of the edits.
+    let shift = |offset: u32| {
+        edits
+            .iter()
+            .filter(|edit| edit.span.end <= offset)
+            .fold(offset as i64, |offset, edit| {
+                offset + edit.text.len() as i64 - (edit.span.end - edit.span.start) as i64
+            }) as u32
+    };
+    for (index, occurrence) in before.occurrences.iter().enumerate() {
+        let expected = match before.binding(occurrence) {
+            Binding::Declared(span) => {
+                Binding::Declared(Span::new(shift(span.start), shift(span.end)))
+            }
+            Binding::Global(_) if renamed.contains(&index) => Binding::Global(new_name.to_string()),
+            binding => binding,
+        };
+        if after.binding(&after.occurrences[index]) != expected {
+            return Err(RenameError::Shadowing {
+                module: module.name.clone(),
+                span: occurrence.span,
+                name: occurrence.name.as_str().to_string(),
+                new_name: new_name.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The declaration files every module is analyzed with.
+struct Declarations {
+    chunk: Chunk,
+    /// Number of symbols they declare, The symbols of a module come after them.
+    symbols: usize,
+}
+
+impl Declarations {
+    fn new() -> Self {
+        let chunk = parse_declarations();
+        let empty = Parser::new("")
+            .parse()
+            .chunk
+            .expect("Empty chunks always parse.");
+        let symbols = Semantic::new()
+            .with_declarations(&chunk)
+            .build(&empty)
+            .symbols
+            .len();
+        Self { chunk, symbols }
+    }
+}
+
+/// What a rename applies to.
+enum Renamed {
+    /// A symbol declared in the target module.
+    Symbol(ReferenceType),
+    /// A global, Declared in any of the modules.
+    Global(String),
+}
+
+/// Symbol an identifier refers to, Comparable between analyses of the same module.
+#[derive(Debug, PartialEq)]
+enum Binding {
+    /// A symbol declared in the module, By the span of its declaring identifier.
+    Declared(Span),
+    /// A symbol of the declaration files, Their references are the same in every analysis.
+    Outside(ReferenceType),
+    /// A global declared by another module.
+    Global(String),
+    /// An identifier that isn't resolved, e.g. a member of an unknown type.
+    Unbound,
+}
+
+struct Occurrence {
+    span: Span,
+    name: Atom,
+    reference: Option<ReferenceType>,
+}
+
+struct ModuleAnalysis {
+    semantic: SemanticResult,
+    /// Every identifier of the module in the order they are visited.
+    occurrences: Vec<Occurrence>,
+    /// Identifiers of the module referring to globals it doesn't declare.
+    globals: HashSet<Span>,
+    /// Number of symbols declared outside of the module.
+    outside: usize,
+}
+
+impl ModuleAnalysis {
+    /// Parse and analyze a module, It is `None` if the module has syntax errors.
+    fn new(source: &str, declarations: &Declarations) -> Option<Self> {
+        let parsed = Parser::new(source).parse();
+        let chunk = parsed.chunk.filter(|_| parsed.errors.is_empty())?;
+        let semantic = Semantic::new()
+            .with_declarations(&declarations.chunk)
+            .build(&chunk);
+        let mut collector = OccurrenceCollector::default();
+        collector.visit_chunk(&chunk);
+        let globals = semantic
+            .errors
+            .iter()
+            .filter_map(|error| match error {
+                SemanticError::UndeclaredGlobal { span, .. } => Some(*span),
+                _ => None,
+            })
+            .collect();
+        Some(Self {
+            semantic,
+            occurrences: collector.occurrences,
+            globals,
+            outside: declarations.symbols,
+        })
+    }
+
+    /// Identifier at `offset`, Offsets right after an identifier point to it too.
+    fn occurrence_at(&self, offset: u32) -> Option<&Occurrence> {
+        self.occurrences
+            .iter()
+            .find(|occurrence| occurrence.span.start <= offset && offset < occurrence.span.end)
+            .or_else(|| {
+                self.occurrences
+                    .iter()
+                    .find(|occurrence| occurrence.span.end == offset)
+            })
+    }
+
+    fn binding(&self, occurrence: &Occurrence) -> Binding {
+        match occurrence.reference {
+            Some(reference) if reference < self.outside => Binding::Outside(reference),
+            Some(reference) => Binding::Declared(self.semantic.symbols.get(reference).span),
+            None if self.globals.contains(&occurrence.span) => {
+                Binding::Global(occurrence.name.as_str().to_string())
+            }
+            None => Binding::Unbound,
+        }
+    }
+
+    fn is_renamed(&self, index: usize, renamed: &Renamed) -> bool {
+        let occurrence = &self.occurrences[index];
+        match renamed {
+            Renamed::Symbol(reference) => occurrence.reference == Some(*reference),
+            Renamed::Global(name) => {
+                occurrence.name.as_str() == name
+                    && match self.binding(occurrence) {
+                        Binding::Declared(_) => self.is_global(occurrence.reference.unwrap()),
+                        Binding::Global(_) => true,
+                        _ => false,
+                    }
+            }
+        }
+    }
+
+    fn is_global(&self, reference: ReferenceType) -> bool {
+        self.semantic.symbols.get(reference).kind
+            == SymbolKind::Variable(VariableDeclarationKind::Global)
+    }
+
+    fn declares_global(&self, name: &str) -> bool {
+        self.occurrences.iter().any(|occurrence| {
+            occurrence.name.as_str() == name
+                && matches!(self.binding(occurrence), Binding::Declared(_))
+                && self.is_global(occurrence.reference.unwrap())
+        })
+    }
+
+    /// Returns true if the module declares, uses or has an extern global named `name`.
+    fn uses_global(&self, name: &str) -> bool {
+        let is_global = |kind| {
+            kind == SymbolKind::Extern
+                || kind == SymbolKind::Variable(VariableDeclarationKind::Global)
+        };
+        self.semantic
+            .symbols
+            .iter()
+            .any(|(_, symbol)| is_global(symbol.kind) && symbol.name.as_str() == name)
+            || self.occurrences.iter().any(|occurrence| {
+                occurrence.name.as_str() == name && self.globals.contains(&occurrence.span)
+            })
+    }
+}
+
+/// Collects every identifier of a chunk.
+#[derive(Default)]
+struct OccurrenceCollector {
+    occurrences: Vec<Occurrence>,
+}
+
+impl ScopeVisitor for OccurrenceCollector {}
+
+impl NodeVisitor for OccurrenceCollector {}
+
+impl<'ast> Visitor<'ast> for OccurrenceCollector {
+    fn visit_identifier(&mut self, ident: &'ast Identifier) {
+        self.occurrences.push(Occurrence {
+            span: ident.span,
+            name: ident.name.clone(),
+            reference: ident.reference.get(),
+        });
+    }
+}
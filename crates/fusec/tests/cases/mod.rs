@@ -0,0 +1,413 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use fusec::{
+    build, build_project, bundle, check, complete, rename, BuildOutput, CompileOptions, Project,
+    RenameModule, LOCKFILE_NAME, MANIFEST_NAME,
+};
+
+struct Context<'a> {
+    root: PathBuf,
+    test_dir: PathBuf,
+    entry_name: &'a str,
+    settings: insta::Settings,
+}
+
+impl<'a> Context<'a> {
+    fn path(&self) -> PathBuf {
+        self.root.join(&self.test_dir)
+    }
+
+    fn settings(&self) -> insta::Settings {
+        self.settings.clone()
+    }
+}
+
+#[test]
+fn bundle_pass() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new()
+            .join("tests")
+            .join("cases")
+            .join("bundle")
+            .join("pass"),
+        entry_name: "main.fuse",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run_bundle(&ctx, case, false);
+    }
+}
+
+#[test]
+fn bundle_fail() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new()
+            .join("tests")
+            .join("cases")
+            .join("bundle")
+            .join("fail"),
+        entry_name: "main.fuse",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run_bundle(&ctx, case, true);
+    }
+}
+
+#[test]
+fn build_directory() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("build"),
+        entry_name: "src",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run_build(&ctx, case);
+    }
+}
+
+#[test]
+fn check_project() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("check"),
+        entry_name: "src",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run_check(&ctx, case);
+    }
+}
+
+#[test]
+fn project() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new().join("tests").join("cases").join("project"),
+        entry_name: "app",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run_project(&ctx, case);
+    }
+}
+
+#[test]
+fn rename_pass() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new()
+            .join("tests")
+            .join("cases")
+            .join("rename")
+            .join("pass"),
+        entry_name: "main.fuse",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run_rename(&ctx, case, false);
+    }
+}
+
+#[test]
+fn rename_fail() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new()
+            .join("tests")
+            .join("cases")
+            .join("rename")
+            .join("fail"),
+        entry_name: "main.fuse",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run_rename(&ctx, case, true);
+    }
+}
+
+#[test]
+fn completion() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let ctx = Context {
+        root,
+        test_dir: PathBuf::new()
+            .join("tests")
+            .join("cases")
+            .join("completion"),
+        entry_name: "main.fuse",
+        settings: insta::Settings::clone_current(),
+    };
+
+    for case in load_cases(&ctx) {
+        run_completion(&ctx, case);
+    }
+}
+
+// helpers
+fn load_cases(ctx: &Context) -> Vec<PathBuf> {
+    fs::read_dir(ctx.path())
+        .unwrap_or_else(|_| panic!("Failed to read {}", ctx.path().to_str().unwrap()))
+        .filter_map(|x| x.ok())
+        .filter(|x| x.metadata().is_ok_and(|meta| meta.is_dir()))
+        .map(|node| node.path())
+        .collect()
+}
+
+fn run_bundle(ctx: &Context, case_dir: PathBuf, expect_error: bool) {
+    let mut settings = ctx.settings();
+    // Relative to the crate, So the paths in the errors don't depend on the checkout.
+    let entry = case_dir
+        .strip_prefix(&ctx.root)
+        .unwrap()
+        .join(ctx.entry_name);
+    let path_str = entry.to_str().unwrap_or("unknown source");
+
+    settings.set_input_file(&entry);
+    settings.set_snapshot_path(&case_dir);
+    settings.set_prepend_module_to_snapshot(false);
+
+    let _guard = settings.bind_to_scope();
+    match bundle(&entry, CompileOptions::default()) {
+        Ok(lua) => {
+            assert!(!expect_error, "Expected the bundle to fail: {path_str}");
+            insta::assert_snapshot!("lua", lua);
+        }
+        Err(error) => {
+            assert!(expect_error, "Failed to bundle: {path_str}\n{error}");
+            insta::assert_snapshot!("error", error.to_string());
+        }
+    }
+}
+
+/// Rename the symbol given by the `rename.txt` of the case, e.g. `main.fuse 2:6 size` for the
+/// one at line 2 and column 6 of `main.fuse`. The snapshot holds the renamed modules.
+///
+/// Cases with an `app` project rename in the modules of the project instead of the graph of
+/// `main.fuse`.
+fn run_rename(ctx: &Context, case_dir: PathBuf, expect_error: bool) {
+    let mut settings = ctx.settings();
+    let project = case_dir.join("app");
+    let entry = match project.exists() {
+        true => project.join(MANIFEST_NAME),
+        false => case_dir.join(ctx.entry_name),
+    };
+    let path_str = entry.to_str().unwrap_or("unknown source");
+    let request = fs::read_to_string(case_dir.join("rename.txt")).unwrap();
+    let [file, position, new_name] = request.split_whitespace().collect::<Vec<_>>()[..] else {
+        panic!("Invalid rename request: {path_str}");
+    };
+    let (line, column) = parse_position(position);
+
+    settings.set_input_file(&entry);
+    settings.set_snapshot_path(&case_dir);
+    settings.set_prepend_module_to_snapshot(false);
+    settings.set_description(request.trim());
+
+    let _guard = settings.bind_to_scope();
+    let modules = match project.exists() {
+        true => RenameModule::load_project(&Project::load(&project).unwrap()),
+        false => RenameModule::load_graph(&entry),
+    }
+    .unwrap();
+    let target = modules
+        .iter()
+        .position(|module| module.path.as_ref().unwrap().ends_with(file))
+        .unwrap();
+    let offset = offset_of(&modules[target].source, line, column);
+
+    match rename(&modules, target, offset as u32, new_name) {
+        Ok(renamed) => {
+            assert!(!expect_error, "Expected the rename to fail: {path_str}");
+            let snapshot: String = renamed
+                .iter()
+                .map(|renamed| {
+                    let module = modules
+                        .iter()
+                        .find(|module| module.name == renamed.module)
+                        .unwrap();
+                    let source = renamed
+                        .edits
+                        .iter()
+                        .rev()
+                        .fold(module.source.clone(), |source, edit| edit.apply(&source));
+                    format!("-- {}\n{source}", module.name)
+                })
+                .collect();
+            insta::assert_snapshot!("rename", snapshot);
+        }
+        Err(error) => {
+            assert!(expect_error, "Failed to rename: {path_str}\n{error}");
+            insta::assert_snapshot!("error", error.to_string());
+        }
+    }
+}
+
+/// Complete the source of the case at the `line:column` of its `cursor.txt`, The snapshot holds
+/// a completion per line.
+fn run_completion(ctx: &Context, case_dir: PathBuf) {
+    let mut settings = ctx.settings();
+    let path = case_dir.join(ctx.entry_name);
+    let source = fs::read_to_string(&path).unwrap();
+    let cursor = fs::read_to_string(case_dir.join("cursor.txt")).unwrap();
+    let (line, column) = parse_position(cursor.trim());
+
+    settings.set_input_file(&path);
+    settings.set_snapshot_path(&case_dir);
+    settings.set_prepend_module_to_snapshot(false);
+    settings.set_description(cursor.trim());
+
+    let _guard = settings.bind_to_scope();
+    let completions = complete(&source, offset_of(&source, line, column) as u32);
+    let snapshot: Vec<String> = completions
+        .iter()
+        .map(|completion| match &completion.detail {
+            Some(detail) => format!("{} {:?} {detail}", completion.label, completion.kind),
+            None => format!("{} {:?}", completion.label, completion.kind),
+        })
+        .collect();
+    insta::assert_snapshot!("completion", snapshot.join("\n"));
+}
+
+/// Parse a 1-based `line:column` position.
+fn parse_position(position: &str) -> (usize, usize) {
+    let (line, column) = position.split_once(':').unwrap();
+    (line.parse().unwrap(), column.parse().unwrap())
+}
+
+/// Byte offset of a 1-based line and column.
+fn offset_of(source: &str, line: usize, column: usize) -> usize {
+    source
+        .split_inclusive('\n')
+        .take(line - 1)
+        .map(str::len)
+        .sum::<usize>()
+        + column
+        - 1
+}
+
+/// Build the `src` directory of the case, The snapshot holds the written files followed by
+/// the rendered diagnostics.
+fn run_build(ctx: &Context, case_dir: PathBuf) {
+    let mut settings = ctx.settings();
+    let input = case_dir
+        .strip_prefix(&ctx.root)
+        .unwrap()
+        .join(ctx.entry_name);
+    let case_name = case_dir.file_name().unwrap();
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("build")
+        .join(case_name);
+    let _ = fs::remove_dir_all(&out_dir);
+
+    settings.set_input_file(&input);
+    settings.set_snapshot_path(&case_dir);
+    settings.set_prepend_module_to_snapshot(false);
+
+    let _guard = settings.bind_to_scope();
+    let output = build(&[input], &out_dir, CompileOptions::default())
+        .unwrap_or_else(|error| panic!("Failed to build {}: {error}", case_dir.display()));
+    insta::assert_snapshot!("build", build_snapshot(&output, &out_dir));
+}
+
+/// Build the project in the `app` directory of the case, The output directory is moved out
+/// of the case. The snapshot holds the written files and the diagnostics or the error.
+fn run_project(ctx: &Context, case_dir: PathBuf) {
+    let mut settings = ctx.settings();
+    let input = case_dir
+        .strip_prefix(&ctx.root)
+        .unwrap()
+        .join(ctx.entry_name);
+    let case_name = case_dir.file_name().unwrap();
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+        .join("project")
+        .join(case_name);
+    let _ = fs::remove_dir_all(&out_dir);
+
+    settings.set_input_file(input.join("fuse.toml"));
+    settings.set_snapshot_path(&case_dir);
+    settings.set_prepend_module_to_snapshot(false);
+
+    let _guard = settings.bind_to_scope();
+    let output = Project::load(&input)
+        .map_err(|error| error.to_string())
+        .and_then(|mut project| {
+            project.manifest.out_dir = out_dir.clone();
+            let output = build_project(&project, CompileOptions::default())
+                .map_err(|error| error.to_string())?;
+            Ok((output, project.lockfile))
+        });
+    match output {
+        Ok((output, lockfile)) => {
+            let mut snapshot = build_snapshot(&output, &out_dir);
+            if !lockfile.packages.is_empty() {
+                snapshot.push_str(&format!("\n\n-- {LOCKFILE_NAME}\n{}", lockfile.to_toml()));
+            }
+            insta::assert_snapshot!("build", snapshot.trim_end())
+        }
+        Err(error) => insta::assert_snapshot!("error", error),
+    }
+}
+
+/// The written files followed by the rendered diagnostics.
+fn build_snapshot(output: &BuildOutput, out_dir: &Path) -> String {
+    let mut written = output.written.clone();
+    written.sort();
+    let mut snapshot = String::new();
+    for path in written {
+        let name = path.strip_prefix(out_dir).unwrap();
+        let lua = fs::read_to_string(&path).unwrap();
+        snapshot.push_str(&format!("-- {}\n{lua}\n", name.display()));
+    }
+    for failure in &output.failures {
+        snapshot.push_str(&failure.render());
+        snapshot.push_str("\n\n");
+    }
+    snapshot.trim_end().to_string()
+}
+
+/// Check the `src` directory of the case, The snapshot holds the diagnostics in JSON.
+fn run_check(ctx: &Context, case_dir: PathBuf) {
+    let mut settings = ctx.settings();
+    let input = case_dir
+        .strip_prefix(&ctx.root)
+        .unwrap()
+        .join(ctx.entry_name);
+
+    settings.set_input_file(&input);
+    settings.set_snapshot_path(&case_dir);
+    settings.set_prepend_module_to_snapshot(false);
+
+    let _guard = settings.bind_to_scope();
+    let output = check(&[input])
+        .unwrap_or_else(|error| panic!("Failed to check {}: {error}", case_dir.display()));
+    insta::assert_snapshot!("check", output.to_json());
+}